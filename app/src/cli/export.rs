@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use ambient_ecs::World;
+use ambient_model_import::{
+    gltf::export::{model_crate_to_glb, GltfExporter},
+    model_crate::ModelCrate,
+};
+use ambient_terrain::{
+    heightmap::{encode_map, map_from_cells, to_unit, value_range, HeightmapFormat},
+    TerrainCells, TerrainLayers,
+};
+use anyhow::Context;
+
+/// Exports the model in the directory `asset_path`, or the prefab in the file `asset_path`,
+/// to the glTF binary file `output`. Terrain is exported to a heightmap instead.
+pub(crate) fn export(asset_path: &Path, output: &Path) -> anyhow::Result<()> {
+    if asset_path.extension().map_or(false, |ext| ext == "terrain") {
+        return export_terrain(asset_path, output);
+    }
+
+    // A model is exported through its prefab when it has one, as that's what gets spawned for it
+    let prefab_path = if asset_path.is_dir() {
+        asset_path.join("prefabs/main.json")
+    } else {
+        asset_path.to_owned()
+    };
+
+    let glb = if prefab_path.is_file() {
+        let prefab = std::fs::read(&prefab_path)
+            .with_context(|| format!("Failed to read prefab {prefab_path:?}"))?;
+        let prefab = World::from_slice(&prefab)
+            .with_context(|| format!("Failed to load prefab {prefab_path:?}"))?;
+        let prefab_dir = prefab_path.parent().context("prefab path has no parent")?;
+
+        let mut exporter = GltfExporter::new();
+        exporter.add_prefab(&prefab, |url| {
+            // Models are referenced relative to the prefab, e.g. `../models/main.json`
+            let model_path = ambient_std::path::normalize(&prefab_dir.join(url));
+            let crate_path = model_path
+                .parent()
+                .and_then(|p| p.parent())
+                .with_context(|| format!("Invalid model url {url:?}"))?;
+            ModelCrate::read_from_fs(crate_path)
+                .with_context(|| format!("Failed to read model {model_path:?}"))
+        })?;
+        exporter.to_glb()
+    } else if asset_path.is_dir() {
+        model_crate_to_glb(&ModelCrate::read_from_fs(asset_path)?)?
+    } else {
+        anyhow::bail!("{asset_path:?} is neither a built model nor a prefab");
+    };
+
+    std::fs::write(output, glb).with_context(|| format!("Failed to write {output:?}"))?;
+    Ok(())
+}
+
+/// Exports the cells of the terrain in the file `asset_path` to the heightmap `output`, and their soil
+/// to a map next to it with a `_soil` suffix, which can be imported again by the `Terrain` pipeline.
+fn export_terrain(asset_path: &Path, output: &Path) -> anyhow::Result<()> {
+    let format = output
+        .extension()
+        .and_then(|ext| HeightmapFormat::from_extension(&ext.to_string_lossy()))
+        .with_context(|| format!("Unsupported heightmap format {output:?}"))?;
+    let terrain = std::fs::read(asset_path)
+        .with_context(|| format!("Failed to read terrain {asset_path:?}"))?;
+    let terrain = TerrainCells::from_slice(&terrain)
+        .with_context(|| format!("Failed to load terrain {asset_path:?}"))?;
+    let cells = terrain
+        .cells
+        .iter()
+        .map(|(cell, state)| (*cell, state))
+        .collect::<Vec<_>>();
+
+    let maps = [
+        (
+            output.to_owned(),
+            &[TerrainLayers::Rock, TerrainLayers::Soil][..],
+        ),
+        (with_suffix(output, "_soil"), &[TerrainLayers::Soil][..]),
+    ];
+    for (path, layers) in maps {
+        let (origin_cell, map) =
+            map_from_cells(&cells, layers).context("The terrain has no cells")?;
+        let range = value_range(map.view());
+        let data = encode_map(to_unit(map.view(), range).view(), format)?;
+        std::fs::write(&path, data).with_context(|| format!("Failed to write {path:?}"))?;
+        log::info!(
+            "Wrote {path:?}, with origin_cell {origin_cell} and values from {} to {}",
+            range.0,
+            range.1
+        );
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> std::path::PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}{suffix}.{extension}"))
+}
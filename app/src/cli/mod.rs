@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use clap::{Args, Parser};
 
+pub mod export;
 pub mod new_project;
 
 #[derive(Parser, Clone)]
@@ -43,6 +44,16 @@ pub enum Cli {
         /// Relative to the project path
         asset_path: PathBuf,
     },
+    /// Export a built model or prefab to a glTF binary (`.glb`) file, or built terrain to a heightmap
+    Export {
+        #[command(flatten)]
+        project_args: ProjectCli,
+        /// Relative to the project's build directory; either the directory of a model, the JSON file of a prefab, or a `.terrain` file
+        asset_path: PathBuf,
+        /// The `.glb` file to write to, or the `.png`/`.raw` heightmap for terrain
+        #[arg(short, long)]
+        output: PathBuf,
+    },
     /// Join a multiplayer session
     Join {
         #[command(flatten)]
@@ -50,6 +61,25 @@ pub enum Cli {
         /// The server to connect to; defaults to localhost
         host: Option<String>,
     },
+    /// Play back a session recorded with `--record`
+    Replay {
+        /// The recording to play back
+        path: PathBuf,
+        /// Load the assets from this URL or `build` directory, instead of from the server that made the recording
+        #[arg(long)]
+        content: Option<String>,
+    },
+    /// Migrate the world saved by the server to the project's current component schema
+    Migrate {
+        #[command(flatten)]
+        project_args: ProjectCli,
+        /// The directory the world was saved to; defaults to `save` in the project directory
+        #[arg(long)]
+        save_dir: Option<PathBuf>,
+        /// Only report what the migration would change, without saving the result
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 #[derive(Args, Clone)]
 pub struct RunCli {
@@ -65,9 +95,17 @@ pub struct RunCli {
     #[arg(long)]
     pub golden_image_test: Option<f32>,
 
+    /// Record the audio for N seconds, compare it to the existing recording and then exit with an exit code of 1 if they are different. The audio is mixed by a fixed step every frame rather than played, so this requires `--headless`
+    #[arg(long, requires = "headless")]
+    pub golden_audio_test: Option<f32>,
+
     /// The user ID to join this server with
     #[clap(short, long)]
     pub user_id: Option<String>,
+
+    /// The token to authenticate with, if the server requires one
+    #[arg(long)]
+    pub auth_token: Option<String>,
 }
 #[derive(Args, Clone)]
 pub struct ProjectCli {
@@ -81,6 +119,13 @@ pub struct ProjectCli {
     /// Avoid building the project
     #[arg(long)]
     pub no_build: bool,
+
+    /// Discard the previous build and rebuild all assets, instead of only rebuilding what has changed
+    #[arg(long)]
+    pub clean: bool,
+    /// Watch the project for changes, and rebuild and hot-reload them into the running server and its clients
+    #[arg(long)]
+    pub watch: bool,
 }
 #[derive(Args, Clone)]
 pub struct HostCli {
@@ -109,6 +154,34 @@ pub struct HostCli {
     /// Pre-cache assets on the proxy
     #[arg(long)]
     pub proxy_pre_cache_assets: bool,
+
+    /// Require clients to provide this secret as their `--auth-token` to join
+    #[arg(long, conflicts_with = "auth_jwt_key")]
+    pub auth_shared_secret: Option<String>,
+
+    /// Require clients to provide a JWT signed (HS256) with the key in this file as their `--auth-token` to join
+    #[arg(long)]
+    pub auth_jwt_key: Option<PathBuf>,
+
+    /// Record the session to this file, so that it can be played back with `ambient replay`
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+
+    /// The directory to save the world's `Store` components to, and restore them from when the server starts
+    ///
+    /// Defaults to `save` in the project directory
+    #[arg(long)]
+    pub save_dir: Option<PathBuf>,
+
+    /// How often to save the world, in seconds; if 0, the world is only saved when the server shuts down
+    ///
+    /// Defaults to 60
+    #[arg(long)]
+    pub save_interval: Option<u64>,
+
+    /// Don't save the world, or restore it when the server starts
+    #[arg(long, conflicts_with_all = ["save_dir", "save_interval"])]
+    pub no_save: bool,
 }
 
 impl Cli {
@@ -120,7 +193,10 @@ impl Cli {
             Cli::Build { .. } => None,
             Cli::Serve { .. } => None,
             Cli::View { .. } => None,
+            Cli::Export { .. } => None,
             Cli::Join { run_args, .. } => Some(run_args),
+            Cli::Replay { .. } => None,
+            Cli::Migrate { .. } => None,
         }
     }
     /// Extract project-relevant state only
@@ -131,7 +207,10 @@ impl Cli {
             Cli::Build { project_args, .. } => Some(project_args),
             Cli::Serve { project_args, .. } => Some(project_args),
             Cli::View { project_args, .. } => Some(project_args),
+            Cli::Export { project_args, .. } => Some(project_args),
             Cli::Join { .. } => None,
+            Cli::Replay { .. } => None,
+            Cli::Migrate { project_args, .. } => Some(project_args),
         }
     }
     /// Extract host-relevant state only
@@ -142,7 +221,10 @@ impl Cli {
             Cli::Build { .. } => None,
             Cli::Serve { host_args, .. } => Some(host_args),
             Cli::View { .. } => None,
+            Cli::Export { .. } => None,
             Cli::Join { .. } => None,
+            Cli::Replay { .. } => None,
+            Cli::Migrate { .. } => None,
         }
     }
 }
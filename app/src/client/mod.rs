@@ -1,6 +1,7 @@
 use std::{collections::HashMap, net::SocketAddr, path::PathBuf, process::exit, sync::Arc, time::Duration};
 
 use ambient_app::{fps_stats, window_title, AppBuilder};
+use ambient_audio::{rms_difference, wav::read_wav};
 use ambient_cameras::UICamera;
 use ambient_core::{
     runtime,
@@ -9,11 +10,14 @@ use ambient_core::{
 use ambient_debugger::Debugger;
 use ambient_ecs::{Entity, EntityId, SystemGroup};
 use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
-use ambient_network::client::{
-    GameClient, GameClientNetworkStats, GameClientRenderTarget, GameClientServerStats, GameClientView, GameClientWorld, UseOnce,
+use ambient_network::{
+    client::{GameClient, GameClientNetworkStats, GameClientRenderTarget, GameClientServerStats, GameClientView, GameClientWorld, UseOnce},
+    recording::SessionRecording,
+    replay::SessionReplayView,
 };
 use ambient_std::{asset_cache::AssetCache, cb, friendly_id};
 use ambient_ui::{Button, Dock, FlowColumn, FocusRoot, MeasureSize, ScrollArea, StylesExt, Text, UIExt, WindowSized, STREET};
+use ambient_world_audio::offline_audio_stream;
 use glam::{uvec2, vec4, Vec2};
 
 use crate::{cli::RunCli, shared};
@@ -24,7 +28,7 @@ pub mod player;
 mod wasm;
 
 /// Construct an app and enter the main client view
-pub async fn run(assets: AssetCache, server_addr: SocketAddr, run: &RunCli, project_path: Option<PathBuf>) {
+pub async fn run(assets: AssetCache, server_addr: SocketAddr, run: &RunCli, project_path: Option<PathBuf>, auth_token: Option<String>) {
     let user_id = run.user_id.clone().unwrap_or_else(|| format!("user_{}", friendly_id()));
     let headless = if run.headless { Some(uvec2(600, 600)) } else { None };
 
@@ -37,9 +41,31 @@ pub async fn run(assets: AssetCache, server_addr: SocketAddr, run: &RunCli, proj
         .update_title_with_fps_stats(false)
         .run(move |app, _runtime| {
             *app.world.resource_mut(window_title()) = "Ambient".to_string();
-            MainApp { server_addr, user_id, show_debug: is_debug, golden_image_test: run.golden_image_test, project_path }
-                .el()
-                .spawn_interactive(&mut app.world);
+            MainApp {
+                server_addr,
+                user_id,
+                auth_token,
+                show_debug: is_debug,
+                headless: run.headless,
+                golden_image_test: run.golden_image_test,
+                golden_audio_test: run.golden_audio_test,
+                project_path,
+            }
+            .el()
+            .spawn_interactive(&mut app.world);
+        })
+        .await;
+}
+
+/// Construct an app and play back a session recording
+pub async fn replay(assets: AssetCache, recording: SessionRecording) {
+    AppBuilder::new()
+        .ui_renderer(true)
+        .with_asset_cache(assets)
+        .update_title_with_fps_stats(false)
+        .run(move |app, _runtime| {
+            *app.world.resource_mut(window_title()) = "Ambient".to_string();
+            ReplayApp { recording }.el().spawn_interactive(&mut app.world);
         })
         .await;
 }
@@ -68,8 +94,11 @@ fn MainApp(
     server_addr: SocketAddr,
     project_path: Option<PathBuf>,
     user_id: String,
+    auth_token: Option<String>,
     show_debug: bool,
+    headless: bool,
     golden_image_test: Option<f32>,
+    golden_audio_test: Option<f32>,
 ) -> Element {
     let update_network_stats = hooks.provide_context(GameClientNetworkStats::default);
     let update_server_stats = hooks.provide_context(GameClientServerStats::default);
@@ -82,9 +111,10 @@ fn MainApp(
         WindowSized::el([GameClientView {
             server_addr,
             user_id,
+            auth_token,
             on_disconnect: cb(move || {}),
             init_world: cb(UseOnce::new(Box::new(move |world, _render_target| {
-                wasm::initialize(world).unwrap();
+                wasm::initialize(world, headless).unwrap();
 
                 UICamera.el().spawn_static(world);
             }))),
@@ -113,7 +143,12 @@ fn MainApp(
             on_in_entities: None,
             inner: Dock::el(vec![
                 if let Some(seconds) = golden_image_test.filter(|_| loaded) {
-                    GoldenImageTest::el(project_path, seconds)
+                    GoldenImageTest::el(project_path.clone(), seconds)
+                } else {
+                    Element::new()
+                },
+                if let Some(seconds) = golden_audio_test.filter(|_| loaded) {
+                    GoldenAudioTest::el(project_path, seconds)
                 } else {
                     Element::new()
                 },
@@ -124,6 +159,22 @@ fn MainApp(
     ])
 }
 
+#[element_component]
+fn ReplayApp(_hooks: &mut Hooks, recording: SessionRecording) -> Element {
+    FocusRoot::el([
+        UICamera.el(),
+        TitleUpdater.el(),
+        WindowSized::el([SessionReplayView {
+            recording,
+            init_world: cb(UseOnce::new(Box::new(move |world, _render_target| {
+                UICamera.el().spawn_static(world);
+            }))),
+            systems_and_resources: cb(|| (replay_systems(), Entity::new())),
+        }
+        .el()]),
+    ])
+}
+
 #[element_component]
 fn GoldenImageTest(hooks: &mut Hooks, project_path: Option<PathBuf>, seconds: f32) -> Element {
     let (render_target, _) = hooks.consume_context::<GameClientRenderTarget>().unwrap();
@@ -177,6 +228,53 @@ fn GoldenImageTest(hooks: &mut Hooks, project_path: Option<PathBuf>, seconds: f3
     Element::new()
 }
 
+/// The largest RMS difference between two audio recordings for them to be considered the same
+const GOLDEN_AUDIO_TOLERANCE: f32 = 0.01;
+
+#[element_component]
+fn GoldenAudioTest(hooks: &mut Hooks, project_path: Option<PathBuf>, seconds: f32) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let recording_path = project_path.unwrap_or(PathBuf::new()).join("audio.wav");
+
+    hooks.use_spawn({
+        let game_client = game_client.clone();
+        move |_| {
+            let state = game_client.game_state.lock();
+            match state.world.resource_opt(offline_audio_stream()) {
+                Some(stream) => stream.lock().start_recording(),
+                None => tracing::warn!("There is no offline audio to record"),
+            }
+            Box::new(|_| {})
+        }
+    });
+    // Check every frame if enough audio has been recorded, as the audio is mixed by a fixed step every frame
+    hooks.use_frame(move |_| {
+        let state = game_client.game_state.lock();
+        let Some(stream) = state.world.resource_opt(offline_audio_stream()) else { return };
+        let stream = stream.lock();
+        if stream.recorded_duration() < Duration::from_secs_f32(seconds) {
+            return;
+        }
+
+        tracing::info!("Loading audio recording from {:?}", recording_path);
+        if let Ok(old) = read_wav(&recording_path) {
+            let dist = rms_difference(stream.recording().unwrap_or_default(), &old);
+            if dist <= GOLDEN_AUDIO_TOLERANCE {
+                tracing::info!("Audio recordings are identical, exiting");
+                exit(0);
+            } else {
+                tracing::info!("Audio recordings differ, rms difference={dist}");
+            }
+        }
+
+        tracing::info!("Saving audio recording to {:?}", recording_path);
+        stream.write_recording(&recording_path).unwrap();
+        tracing::info!("Audio recording saved");
+        exit(1);
+    });
+    Element::new()
+}
+
 #[element_component]
 fn GameView(hooks: &mut Hooks, show_debug: bool) -> Element {
     let (state, _) = hooks.consume_context::<GameClient>().unwrap();
@@ -268,6 +366,8 @@ fn systems() -> SystemGroup {
     SystemGroup::new(
         "client",
         vec![
+            // Must run before the systems that load assets
+            Box::new(shared::hot_reload::client_systems()),
             Box::new(ambient_prefab::systems()),
             Box::new(ambient_decals::client_systems()),
             Box::new(ambient_primitives::systems()),
@@ -275,7 +375,24 @@ fn systems() -> SystemGroup {
             Box::new(ambient_water::systems()),
             Box::new(ambient_physics::client_systems()),
             Box::new(wasm::systems()),
+            // Spatial audio, bus volumes, reverb zones, occlusion and the offline audio stream
+            Box::new(ambient_world_audio::systems::client_systems()),
             Box::new(player::systems_final()),
         ],
     )
 }
+
+/// The client systems used when replaying a session. Client-side WASM modules are not run, as they may depend on the server.
+fn replay_systems() -> SystemGroup {
+    SystemGroup::new(
+        "client_replay",
+        vec![
+            Box::new(ambient_prefab::systems()),
+            Box::new(ambient_decals::client_systems()),
+            Box::new(ambient_primitives::systems()),
+            Box::new(ambient_sky::systems()),
+            Box::new(ambient_water::systems()),
+            Box::new(ambient_physics::client_systems()),
+        ],
+    )
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use ambient_ecs::{EntityId, SystemGroup, World};
 use ambient_wasm::shared::{get_module_name, MessageType};
@@ -10,7 +10,12 @@ pub fn systems() -> SystemGroup {
     ambient_wasm::client::systems()
 }
 
-pub fn initialize(world: &mut World) -> anyhow::Result<()> {
+/// The sample rate sounds are mixed at when there is no audio device
+const OFFLINE_SAMPLE_RATE: u64 = 44_100;
+/// The simulated time the offline audio is advanced by every frame, one frame at 60 fps
+const OFFLINE_AUDIO_STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+pub fn initialize(world: &mut World, headless: bool) -> anyhow::Result<()> {
     let messenger = Arc::new(|world: &World, id: EntityId, type_: MessageType, message: &str| {
         let name = get_module_name(world, id);
         let (prefix, level) = match type_ {
@@ -26,19 +31,34 @@ pub fn initialize(world: &mut World) -> anyhow::Result<()> {
 
     let (tx, rx): (Sender<AudioMessage>, Receiver<AudioMessage>) = flume::unbounded();
 
+    // Headless clients have no audio device, so their sounds are mixed offline, advanced by a fixed step every frame
+    let offline_mixer = if headless {
+        Some(ambient_world_audio::systems::setup_offline_audio(world, OFFLINE_SAMPLE_RATE, OFFLINE_AUDIO_STEP)?)
+    } else {
+        None
+    };
+    let (mixer_tx, mixer_rx) = flume::bounded(1);
+
     std::thread::spawn(move || {
-        let stream = ambient_audio::AudioStream::new().unwrap();
+        let (_stream, mixer) = match offline_mixer {
+            Some(mixer) => (None, mixer),
+            None => {
+                let stream = ambient_audio::AudioStream::new().unwrap();
+                let mixer = stream.mixer().clone();
+                mixer_tx.send(mixer.clone()).ok();
+                (Some(stream), mixer)
+            }
+        };
         while let Ok(message) = rx.recv() {
             match message {
-                AudioMessage::Track(t, looping, amp) => {
+                AudioMessage::Track(t, looping, amp, control, bus) => {
+                    // The sounds are stopped through their `control`, so there's no need to keep their handles
                     match looping {
                         true => {
-                            let sound = stream.mixer().play(t.decode().repeat().gain(amp.clamp(0.0, 1.0)));
-                            sound.wait();
+                            mixer.play_on(bus, t.decode().repeat().gain(amp.clamp(0.0, 1.0)).controlled(control));
                         }
                         false => {
-                            let sound = stream.mixer().play(t.decode().gain(amp.clamp(0.0, 1.0)));
-                            sound.wait();
+                            mixer.play_on(bus, t.decode().gain(amp.clamp(0.0, 1.0)).controlled(control));
                         }
                     }
                 }
@@ -46,6 +66,13 @@ pub fn initialize(world: &mut World) -> anyhow::Result<()> {
         }
     });
     world.add_resource(audio_sender(), Arc::new(tx));
+    // The sounds are played by the audio thread, on the buses of its mixer which the world controls
+    if !headless {
+        match mixer_rx.recv() {
+            Ok(mixer) => ambient_world_audio::systems::setup_audio(world, mixer)?,
+            Err(_) => log::warn!("No audio output, so no sounds will be played"),
+        }
+    }
 
     ambient_wasm::client::initialize(world, messenger)?;
 
@@ -1,5 +1,6 @@
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
+    asset_url::AbsAssetUrl,
     download_asset::AssetsCacheOnDisk,
 };
 use clap::Parser;
@@ -134,6 +135,20 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    // If replay: play back the recording, exit once the window is closed
+    if let Cli::Replay { path, content } = &cli {
+        let mut recording = ambient_network::recording::SessionRecording::load(path)?;
+        if let Some(content) = content {
+            recording.header.content_base_url = match AbsAssetUrl::parse(content) {
+                Ok(url) => url.as_directory(),
+                Err(_) => AbsAssetUrl::from_directory_path(std::fs::canonicalize(content)?),
+            };
+        }
+        runtime.block_on(recording.check_content(&assets))?;
+        runtime.block_on(client::replay(assets, recording));
+        return Ok(());
+    }
+
     // If a project was specified, assume that assets need to be built
     let manifest = cli
         .project()
@@ -144,6 +159,16 @@ fn main() -> anyhow::Result<()> {
         })
         .transpose()?;
 
+    // If migrate: migrate the saved world to the current schema, exit without building
+    if let Cli::Migrate { save_dir, dry_run, .. } = &cli {
+        let manifest = manifest.as_ref().expect("no manifest");
+        ambient_ecs::ComponentRegistry::get_mut()
+            .add_external(ambient_project_native::all_defined_components(manifest, false).map_err(anyhow::Error::msg)?);
+        let migrations = ambient_project_native::migrations(manifest, false).map_err(anyhow::Error::msg)?;
+        let save_dir = save_dir.clone().unwrap_or_else(|| project_path.join(server::persistence::DEFAULT_SAVE_DIR));
+        return server::persistence::migrate(&save_dir, &migrations, *dry_run);
+    }
+
     if let Some(manifest) = manifest.as_ref() {
         if !cli.project().unwrap().no_build {
             let project_name = manifest.project.name.as_deref().unwrap_or("project");
@@ -154,6 +179,7 @@ fn main() -> anyhow::Result<()> {
                 project_path.clone(),
                 manifest,
                 cli.project().map(|p| p.release).unwrap_or(false),
+                cli.project().map(|p| p.clean).unwrap_or(false),
             ));
             log::info!("Done building {}", project_name);
         }
@@ -164,26 +190,37 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    // If export: write the built asset to a glTF file or heightmap, exit
+    if let Cli::Export { asset_path, output, .. } = &cli {
+        let asset_path = project_path.join("build").join(asset_path);
+        cli::export::export(&asset_path, output)?;
+        log::info!("Exported {asset_path:?} to {output:?}");
+        return Ok(());
+    }
+
     // Otherwise, either connect to a server or host one
-    let server_addr = if let Cli::Join { host, .. } = &cli {
-        if let Some(mut host) = host.clone() {
+    let (server_addr, server) = if let Cli::Join { host, .. } = &cli {
+        let server_addr = if let Some(mut host) = host.clone() {
             if !host.contains(':') {
                 host = format!("{host}:{QUIC_INTERFACE_PORT}");
             }
             runtime.block_on(tokio::net::lookup_host(&host))?.next().ok_or_else(|| anyhow::anyhow!("No address found for host {host}"))?
         } else {
             format!("127.0.0.1:{QUIC_INTERFACE_PORT}").parse()?
-        }
+        };
+        (server_addr, None)
     } else {
-        let port = server::start(&runtime, assets.clone(), cli.clone(), project_path, manifest.as_ref().expect("no manifest"));
-        format!("127.0.0.1:{port}").parse()?
+        let server = server::start(&runtime, assets.clone(), cli.clone(), project_path, manifest.as_ref().expect("no manifest"));
+        (format!("127.0.0.1:{}", server.port).parse()?, Some(server))
     };
 
     // Time to join!
     let handle = runtime.handle().clone();
     if let Some(run) = cli.run() {
         // If we have run parameters, start a client and join a server
-        runtime.block_on(client::run(assets, server_addr, run, cli.project().and_then(|p| p.path.clone())));
+        // When running locally with a shared secret, the client can use it to authenticate with its own server
+        let auth_token = run.auth_token.clone().or_else(|| cli.host().and_then(|h| h.auth_shared_secret.clone()));
+        runtime.block_on(client::run(assets, server_addr, run, cli.project().and_then(|p| p.path.clone()), auth_token));
     } else {
         // Otherwise, wait for the Ctrl+C signal
         handle.block_on(async move {
@@ -193,5 +230,8 @@ fn main() -> anyhow::Result<()> {
             }
         });
     }
+    if let Some(server) = server {
+        runtime.block_on(server.shutdown());
+    }
     Ok(())
 }
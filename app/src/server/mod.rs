@@ -3,18 +3,22 @@ use std::{
     net::SocketAddr,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
-use ambient_core::{app_start_time, asset_cache, dtime, no_sync, project_name, time};
+use ambient_core::{app_start_time, asset_cache, async_ecs::async_run, dtime, no_sync, project_name, session_start, time};
 use ambient_ecs::{
     dont_store, world_events, ComponentDesc, ComponentRegistry, Entity, Networked, SystemGroup, World, WorldEventsSystem,
     WorldStreamCompEvent,
 };
+use ambient_intent::{intent_registry, IntentRegistry};
 use ambient_network::{
+    auth::{Authenticator, JwtAuthenticator, SharedSecretAuthenticator, Unauthenticated},
     persistent_resources,
-    server::{ForkingEvent, GameServer, ShutdownEvent, ProxySettings},
-    synced_resources,
+    server::{ForkingEvent, GameServer, ProxySettings, ShutdownEvent},
+    synced_resources, ServerWorldExt,
 };
+use ambient_physics::physx::PhysicsKey;
 use ambient_prefab::PrefabFromUrl;
 use ambient_std::{
     asset_cache::{AssetCache, AsyncAssetKeyExt, SyncAssetKeyExt},
@@ -28,11 +32,34 @@ use axum::{
     routing::{get, get_service},
     Router,
 };
+use chrono::Utc;
+use tokio::{sync::Notify, task::JoinHandle};
 use tower_http::{cors::CorsLayer, services::ServeDir};
 
-use crate::{cli::Cli, shared};
+use crate::{
+    cli::{Cli, HostCli},
+    shared,
+};
 
+pub mod persistence;
 pub mod wasm;
+mod watch;
+
+/// A server started with [`start`].
+pub struct ServerHandle {
+    pub port: u16,
+    shutdown: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+impl ServerHandle {
+    /// Shuts the server down, and waits for its shutdown systems (e.g. the final save of the world) to finish.
+    pub async fn shutdown(self) {
+        self.shutdown.notify_one();
+        if let Err(err) = self.task.await {
+            log::error!("Server failed to shut down: {err:?}");
+        }
+    }
+}
 
 pub fn start(
     runtime: &tokio::runtime::Runtime,
@@ -40,7 +67,7 @@ pub fn start(
     cli: Cli,
     project_path: PathBuf,
     manifest: &ambient_project::Manifest,
-) -> u16 {
+) -> ServerHandle {
     log::info!("Creating server");
     let host_cli = cli.host().unwrap();
     let quic_interface_port = host_cli.quic_interface_port;
@@ -53,7 +80,8 @@ pub fn start(
             project_id: manifest.project.id.to_string(),
         }
     });
-    let server = runtime.block_on(async move {
+    let authenticator = create_authenticator(host_cli).unwrap();
+    let mut server = runtime.block_on(async move {
         if let Some(port) = quic_interface_port {
             GameServer::new_with_port(port, false, proxy_settings).await.context("failed to create game server with port").unwrap()
         } else {
@@ -63,7 +91,13 @@ pub fn start(
                 .unwrap()
         }
     });
+    server.authenticator = authenticator;
+    server.record_session = host_cli.record.clone();
     let port = server.port;
+    let shutdown = server.shutdown.clone();
+    let save_dir =
+        (!host_cli.no_save).then(|| host_cli.save_dir.clone().unwrap_or_else(|| project_path.join(persistence::DEFAULT_SAVE_DIR)));
+    let save_interval = Some(host_cli.save_interval.unwrap_or(60)).filter(|secs| *secs > 0).map(Duration::from_secs);
 
     let public_host = cli
         .host()
@@ -81,7 +115,7 @@ pub fn start(
     ComponentRegistry::get_mut().add_external(ambient_project_native::all_defined_components(manifest, false).unwrap());
 
     let manifest = manifest.clone();
-    runtime.spawn(async move {
+    let task = runtime.spawn(async move {
         let mut server_world = World::new_with_config("server", true);
         server_world.init_shape_change_tracking();
 
@@ -92,11 +126,33 @@ pub fn start(
         server_world.add_components(server_world.resource_entity(), Entity::new().with(project_name(), name)).unwrap();
 
         Entity::new().with(synced_resources(), ()).with(dont_store(), ()).spawn(&mut server_world);
-        // Note: this should not be reset every time the server is created. Remove this when it becomes possible to load/save worlds.
-        Entity::new().with(persistent_resources(), ()).spawn(&mut server_world);
+
+        if let Some(save_dir) = save_dir {
+            let restored = ambient_project_native::migrations(&manifest, false).map_err(anyhow::Error::msg).and_then(|migrations| {
+                persistence::restore(&mut server_world, &save_dir, &migrations)?;
+                Ok(migrations.version())
+            });
+            match restored {
+                Ok(version) => {
+                    log::info!("Saving the world to {save_dir:?}");
+                    let resources = persistence::resources(save_dir, save_interval, version);
+                    server_world.add_components(server_world.resource_entity(), resources).unwrap();
+                }
+                // Saving now would overwrite the state that couldn't be restored, so it's left alone
+                Err(err) => log::error!("Failed to restore the world, it will not be saved: {err:?}"),
+            }
+        }
+        if server_world.persisted_resource_entity().is_none() {
+            Entity::new().with(persistent_resources(), ()).spawn(&mut server_world);
+        }
 
         wasm::initialize(&mut server_world, project_path.clone(), &manifest).unwrap();
 
+        if let Some(project) = cli.project().filter(|project| project.watch) {
+            let async_run = server_world.resource(async_run()).clone();
+            tokio::spawn(watch::run(project_path.clone(), manifest.clone(), PhysicsKey.get(&assets), async_run, project.release));
+        }
+
         if let Cli::View { asset_path, .. } = cli.clone() {
             let asset_path = AbsAssetUrl::from_file_path(project_path.join("build").join(asset_path).join("prefabs/main.json"));
             log::info!("Spawning asset from {:?}", asset_path);
@@ -108,7 +164,18 @@ pub fn start(
             .run(server_world, Arc::new(systems), Arc::new(on_forking_systems), Arc::new(on_shutdown_systems), Arc::new(is_sync_component))
             .await;
     });
-    port
+    ServerHandle { port, shutdown, task }
+}
+
+fn create_authenticator(host_cli: &HostCli) -> anyhow::Result<Arc<dyn Authenticator>> {
+    Ok(if let Some(secret) = &host_cli.auth_shared_secret {
+        Arc::new(SharedSecretAuthenticator::new(secret.clone()))
+    } else if let Some(path) = &host_cli.auth_jwt_key {
+        let key = std::fs::read(path).with_context(|| format!("Failed to read JWT key from {path:?}"))?;
+        Arc::new(JwtAuthenticator::new_hs256(&key))
+    } else {
+        Arc::new(Unauthenticated)
+    })
 }
 
 fn systems(_world: &mut World) -> SystemGroup {
@@ -126,16 +193,35 @@ fn systems(_world: &mut World) -> SystemGroup {
             ambient_core::remove_at_time_system(),
             Box::new(WorldEventsSystem),
             Box::new(ambient_core::camera::camera_systems()),
+            Box::new(ambient_intent::registry_systems()),
+            Box::new(ambient_terrain::server_systems()),
             Box::new(ambient_physics::server_systems()),
+            Box::new(ambient_world_audio::systems::server_systems()),
             Box::new(wasm::systems()),
+            Box::new(persistence::systems()),
         ],
     )
 }
 fn on_forking_systems() -> SystemGroup<ForkingEvent> {
-    SystemGroup::new("on_forking_systems", vec![Box::new(ambient_physics::on_forking_systems()), Box::new(wasm::on_forking_systems())])
+    SystemGroup::new(
+        "on_forking_systems",
+        vec![
+            Box::new(ambient_physics::on_forking_systems()),
+            Box::new(wasm::on_forking_systems()),
+            Box::new(persistence::on_forking_systems()),
+        ],
+    )
 }
 fn on_shutdown_systems() -> SystemGroup<ShutdownEvent> {
-    SystemGroup::new("on_shutdown_systems", vec![Box::new(ambient_physics::on_shutdown_systems()), Box::new(wasm::on_shutdown_systems())])
+    SystemGroup::new(
+        "on_shutdown_systems",
+        vec![
+            // Saves the world before the other systems tear it down
+            Box::new(persistence::on_shutdown_systems()),
+            Box::new(ambient_physics::on_shutdown_systems()),
+            Box::new(wasm::on_shutdown_systems()),
+        ],
+    )
 }
 
 fn is_sync_component(component: ComponentDesc, _: WorldStreamCompEvent) -> bool {
@@ -152,6 +238,12 @@ fn create_resources(assets: AssetCache) -> Entity {
     server_resources.set(time(), now);
     server_resources.set(app_start_time(), now);
     server_resources.set(dtime(), 1. / 60.);
+    // Strokes made before this are already part of the restored terrain
+    server_resources.set(session_start(), Utc::now());
+
+    let mut intents = IntentRegistry::new();
+    ambient_terrain::intents::register_intents(&mut intents);
+    server_resources.set(intent_registry(), Arc::new(intents));
 
     let mut bistream_handlers = HashMap::new();
     ambient_network::server::register_rpc_bi_stream_handler(&mut bistream_handlers, shared::create_server_rpc_registry());
@@ -192,3 +284,66 @@ fn start_http_interface(runtime: &tokio::runtime::Runtime, project_path: &Path,
 async fn handle_error(_err: std::io::Error) -> impl IntoResponse {
     (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong...")
 }
+
+#[cfg(test)]
+mod tests {
+    use ambient_ecs::query;
+    use ambient_intent::{intent_applied, server_push_intent};
+    use ambient_network::server::{Player, ServerState, MAIN_INSTANCE_ID};
+    use ambient_physics::physx::physics_shape;
+    use ambient_terrain::{
+        brushes::{Brush, BrushShape, BrushSize, BrushSmoothness, BrushStrength, TerrainBrushStroke},
+        intent_terrain_stroke, stroke_server_applied, terrain_world_cell,
+    };
+    use glam::{Vec2, Vec3};
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    /// Pushes a brush stroke to a server without a GPU, like a client does with `rpc_push_intent`, and checks that its
+    /// terrain systems pick it up.
+    #[tokio::test]
+    async fn headless_server_applies_brush_intent() {
+        shared::components::init().unwrap();
+        let assets = AssetCache::new(tokio::runtime::Handle::current());
+        PhysicsKey.get(&assets);
+
+        let mut world = World::new_with_config("server", true);
+        world.init_shape_change_tracking();
+        world.add_components(world.resource_entity(), create_resources(assets)).unwrap();
+        let mut state = ServerState::new_local();
+        let instance = state.instances.get_mut(MAIN_INSTANCE_ID).unwrap();
+        instance.systems = systems(&mut world);
+        instance.world = world;
+        let user_id = "user".to_string();
+        state.players.insert(user_id.clone(), Player::new_local(MAIN_INSTANCE_ID.to_string()));
+        let state = Arc::new(Mutex::new(state));
+
+        let stroke = TerrainBrushStroke {
+            center: Vec2::ZERO,
+            layer: 0,
+            brush: Brush::Flatten,
+            brush_size: BrushSize::SMALL,
+            brush_strength: BrushStrength::MEDIUM,
+            brush_shape: BrushShape::Circle,
+            brush_smoothness: BrushSmoothness(1.),
+            start_position: Vec3::ZERO,
+            erosion: Default::default(),
+        };
+        server_push_intent(state.clone(), intent_terrain_stroke(), stroke, user_id, None).await;
+        for _ in 0..3 {
+            state.lock().step();
+        }
+
+        let guard = state.lock();
+        let world = &guard.instances[MAIN_INSTANCE_ID].world;
+        let strokes = query(()).incl(intent_terrain_stroke()).incl(intent_applied()).incl(stroke_server_applied());
+        assert_eq!(strokes.iter(world, None).count(), 1);
+        // The cells under the stroke were spawned, and given colliders by the terrain systems
+        let cells = query(()).incl(terrain_world_cell()).iter(world, None).map(|(id, _)| id).collect::<Vec<_>>();
+        assert!(!cells.is_empty());
+        for id in cells {
+            assert!(world.has_component(id, physics_shape()));
+        }
+    }
+}
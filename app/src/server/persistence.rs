@@ -0,0 +1,231 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use ambient_ecs::{
+    components, Debuggable, Description, Entity, FnSystem, MigratedWorld, Migrations, Resource, StoredWorld, SystemGroup, World,
+};
+use ambient_network::server::{ForkingEvent, ShutdownEvent};
+use ambient_sys::time::Instant;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// The directory the world is saved to by default, relative to the project.
+pub const DEFAULT_SAVE_DIR: &str = "save";
+/// The name of the file the world is saved to, within the save directory.
+pub const SAVE_FILENAME: &str = "world.json";
+
+components!("app::persistence", {
+    @[Resource, Debuggable, Description["The directory the `Store` components of the world are saved to."]]
+    save_dir: PathBuf,
+    @[Resource, Debuggable, Description["How often the world is saved. If not set, it's only saved on shutdown."]]
+    save_interval: Duration,
+    @[Resource, Debuggable, Description["The version of the project's component schema that the world is saved with."]]
+    schema_version: u32,
+});
+
+pub fn resources(dir: PathBuf, interval: Option<Duration>, version: u32) -> Entity {
+    let mut resources = Entity::new().with(save_dir(), dir).with(schema_version(), version);
+    if let Some(interval) = interval {
+        resources.set(save_interval(), interval);
+    }
+    resources
+}
+
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    schema_version: u32,
+    world: StoredWorld<'a>,
+}
+#[derive(Deserialize)]
+struct LoadedSaveFile {
+    #[serde(default)]
+    schema_version: u32,
+    world: serde_json::Value,
+}
+
+/// A world loaded with [load].
+pub struct LoadedWorld {
+    pub migrated: MigratedWorld,
+    /// The schema version the world was saved with.
+    pub schema_version: u32,
+    data: Vec<u8>,
+}
+impl LoadedWorld {
+    /// Keeps the save from before the migration around as `world.v{N}.json`, in case the migration needs to be redone.
+    /// Does nothing if the save didn't need to be migrated.
+    fn back_up(&self, dir: &Path, migrations: &Migrations) -> anyhow::Result<()> {
+        if self.schema_version == migrations.version() {
+            return Ok(());
+        }
+        let backup = dir.join(format!("world.v{}.json", self.schema_version));
+        std::fs::write(&backup, &self.data).with_context(|| format!("Failed to back up {:?} to {backup:?}", dir.join(SAVE_FILENAME)))
+    }
+}
+
+/// Loads the world saved to `dir`, and migrates it to the current schema version. Returns `None` if nothing has been saved yet.
+///
+/// The migrated world is only kept in memory, and nothing is written to `dir`.
+pub fn load(dir: &Path, migrations: &Migrations) -> anyhow::Result<Option<LoadedWorld>> {
+    let path = dir.join(SAVE_FILENAME);
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {path:?}")),
+    };
+    let save: LoadedSaveFile = serde_json::from_slice(&data).with_context(|| format!("Failed to deserialize {path:?}"))?;
+    anyhow::ensure!(
+        save.schema_version <= migrations.version(),
+        "{path:?} was saved with schema version {}, which is newer than the project's version {}",
+        save.schema_version,
+        migrations.version()
+    );
+    if save.schema_version < migrations.version() {
+        log::info!("Migrating {path:?} from schema version {} to {}", save.schema_version, migrations.version());
+    }
+
+    let migrated =
+        migrations.deserialize_world(save.schema_version, save.world).with_context(|| format!("Failed to deserialize {path:?}"))?;
+    for change in &migrated.changes {
+        log::info!("Migrated {change}");
+    }
+    migrated.warnings.log_warnings();
+    Ok(Some(LoadedWorld { migrated, schema_version: save.schema_version, data }))
+}
+
+/// Restores the world saved to `dir` into `world`, migrating it to the current schema version first.
+/// Does nothing if nothing has been saved yet.
+///
+/// The migrated world replaces the save the next time the world is saved. Saved components that no longer exist are
+/// left out, and reported as warnings.
+pub fn restore(world: &mut World, dir: &Path, migrations: &Migrations) -> anyhow::Result<()> {
+    let Some(loaded) = load(dir, migrations)? else { return Ok(()) };
+    loaded.back_up(dir, migrations)?;
+
+    let entities = loaded.migrated.world.entities();
+    log::info!("Restoring {} entities from {dir:?}", entities.len());
+    for (id, entity) in entities {
+        if world.exists(id) {
+            world.add_components(id, entity)?;
+        } else {
+            world.spawn_with_id(id, entity);
+        }
+    }
+    Ok(())
+}
+
+/// Migrates the world saved to `dir` to the current schema version, and saves it again. With `dry_run`, the changes
+/// the migration would make are only reported, and `dir` is left untouched.
+pub fn migrate(dir: &Path, migrations: &Migrations, dry_run: bool) -> anyhow::Result<()> {
+    let Some(loaded) = load(dir, migrations)? else {
+        log::info!("Nothing has been saved to {dir:?} yet");
+        return Ok(());
+    };
+    let changes = loaded.migrated.changes.len();
+    let dropped = loaded.migrated.warnings.len();
+    if dry_run {
+        log::info!("Dry run: the migration would make {changes} changes, and drop {dropped} components that can't be read");
+        return Ok(());
+    }
+    loaded.back_up(dir, migrations)?;
+    save(&loaded.migrated.world, dir, migrations.version())?;
+    log::info!("Migrated {dir:?} with {changes} changes, dropping {dropped} components that can't be read");
+    Ok(())
+}
+
+/// Saves the `Store` components of `world` to `dir`, along with the schema `version` they have. The previous save
+/// is only replaced once the new one has been written in full, so a save that is interrupted never leaves a partial world behind.
+pub fn save(world: &World, dir: &Path, version: u32) -> anyhow::Result<()> {
+    let data = serde_json::to_vec(&SaveFile { schema_version: version, world: StoredWorld(world) })?;
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create the save directory {dir:?}"))?;
+
+    let path = dir.join(SAVE_FILENAME);
+    let tmp_path = path.with_extension("json.tmp");
+    let mut file = std::fs::File::create(&tmp_path).with_context(|| format!("Failed to create {tmp_path:?}"))?;
+    file.write_all(&data)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, &path).with_context(|| format!("Failed to replace {path:?}"))?;
+    log::debug!("Saved the world to {path:?}");
+    Ok(())
+}
+
+fn save_world(world: &World) {
+    if let Some(dir) = world.resource_opt(save_dir()) {
+        if let Err(err) = save(world, dir, world.resource_opt(schema_version()).copied().unwrap_or_default()) {
+            log::error!("Failed to save the world: {err:?}");
+        }
+    }
+}
+
+/// Saves the world every `save_interval`.
+pub fn systems() -> SystemGroup {
+    let mut last_save = Instant::now();
+    SystemGroup::new(
+        "persistence",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            let Some(interval) = world.resource_opt(save_interval()) else { return };
+            if last_save.elapsed() < *interval {
+                return;
+            }
+            last_save = Instant::now();
+            save_world(world);
+        }))],
+    )
+}
+
+/// Forked instances are not saved, as they would overwrite the main instance's save.
+pub fn on_forking_systems() -> SystemGroup<ForkingEvent> {
+    SystemGroup::new(
+        "persistence/on_forking_systems",
+        vec![Box::new(FnSystem::new(|world, _| {
+            let resources = world.resource_entity();
+            world.remove_component(resources, save_dir()).ok();
+            world.remove_component(resources, save_interval()).ok();
+            world.remove_component(resources, schema_version()).ok();
+        }))],
+    )
+}
+
+pub fn on_shutdown_systems() -> SystemGroup<ShutdownEvent> {
+    SystemGroup::new("persistence/on_shutdown_systems", vec![Box::new(FnSystem::new(|world, _| save_world(world)))])
+}
+
+#[cfg(test)]
+mod tests {
+    use ambient_ecs::{Migration, MigrationStep};
+
+    use super::*;
+
+    fn dir_contents(dir: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+        let mut contents = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .map(|path| (path.clone(), std::fs::read(path).unwrap()))
+            .collect::<Vec<_>>();
+        contents.sort();
+        contents
+    }
+
+    #[test]
+    fn dry_run_leaves_save_untouched() {
+        ambient_ecs::init_components();
+        let dir = std::env::temp_dir().join("ambient_persistence_dry_run");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(SAVE_FILENAME), r#"{ "schema_version": 0, "world": {} }"#).unwrap();
+        let migrations = Migrations::new(vec![Migration {
+            version: 1,
+            steps: vec![MigrationStep::Rename { from: "core::test::old_name".to_string(), to: "core::test::new_name".to_string() }],
+        }]);
+
+        let before = dir_contents(&dir);
+        migrate(&dir, &migrations, true).unwrap();
+        assert_eq!(dir_contents(&dir), before);
+
+        // Migrating for real keeps a backup of the old save
+        migrate(&dir, &migrations, false).unwrap();
+        assert!(dir.join("world.v0.json").is_file());
+    }
+}
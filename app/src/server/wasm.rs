@@ -1,11 +1,16 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use ambient_ecs::{EntityId, SystemGroup, World};
+use ambient_ecs::{query, EntityId, SystemGroup, World};
 use ambient_project::Identifier;
 use ambient_std::asset_url::ASSETS_PROTOCOL_SCHEME;
 pub use ambient_wasm::server::{on_forking_systems, on_shutdown_systems};
 use ambient_wasm::shared::{
-    client_bytecode_from_url, get_module_name, module_bytecode, remote_paired_id, spawn_module, MessageType, ModuleBytecode,
+    client_bytecode_from_url, get_module_name, module, module_bytecode, module_limits, remote_paired_id, spawn_module, MessageType,
+    ModuleBytecode, ModuleLimits,
 };
 use anyhow::Context;
 
@@ -30,23 +35,21 @@ pub fn initialize(world: &mut World, project_path: PathBuf, manifest: &ambient_p
     ambient_wasm::server::initialize(world, messenger)?;
 
     let build_dir = project_path.join("build");
+    let limits = ModuleLimits::from_manifest(&manifest.limits);
 
     let mut modules_to_entity_ids = HashMap::new();
     for target in ["client", "server"] {
-        let wasm_component_paths: Vec<PathBuf> = std::fs::read_dir(build_dir.join(target))
-            .ok()
-            .map(|rd| rd.filter_map(Result::ok).map(|p| p.path()).filter(|p| p.extension().unwrap_or_default() == "wasm").collect())
-            .unwrap_or_default();
+        let wasm_component_paths = wasm_component_paths(&build_dir, target);
 
         let is_sole_module = wasm_component_paths.len() == 1;
         for path in wasm_component_paths {
-            let name =
-                Identifier::new(&*path.file_stem().context("no file stem for {path:?}")?.to_string_lossy()).map_err(anyhow::Error::msg)?;
+            let name = module_name(&path)?;
 
             let description = manifest.project.description.clone().unwrap_or_default();
             let description = if is_sole_module { description } else { format!("{description} ({name})") };
 
             let id = spawn_module(world, &name, description, true);
+            world.add_component(id, module_limits(), limits.clone())?;
             modules_to_entity_ids.insert(
                 (
                     target,
@@ -62,8 +65,7 @@ pub fn initialize(world: &mut World, project_path: PathBuf, manifest: &ambient_p
             );
 
             if target == "client" {
-                let relative_path = path.strip_prefix(&build_dir)?;
-                let bytecode_url = format!("{}:/{}", ASSETS_PROTOCOL_SCHEME, relative_path.to_string_lossy());
+                let bytecode_url = client_bytecode_url(&build_dir, &path, None)?;
                 world.add_component(id, client_bytecode_from_url(), bytecode_url)?;
             } else {
                 let bytecode = std::fs::read(path)?;
@@ -85,3 +87,55 @@ pub fn initialize(world: &mut World, project_path: PathBuf, manifest: &ambient_p
 
     Ok(())
 }
+
+/// Replaces the bytecode of the running modules with the modules in the project's `build` directory, which restarts them.
+///
+/// Clients reload clientside modules when their bytecode URL changes, so `generation` is added to the URL to make it unique.
+pub fn reload_modules(world: &mut World, project_path: &Path, generation: u32) -> anyhow::Result<()> {
+    let build_dir = project_path.join("build");
+    let modules = query(module())
+        .iter(world, None)
+        .map(|(id, _)| (get_module_name(world, id), world.has_component(id, client_bytecode_from_url()), id))
+        .collect::<Vec<_>>();
+
+    for target in ["client", "server"] {
+        for path in wasm_component_paths(&build_dir, target) {
+            let name = module_name(&path)?;
+            let Some((_, _, id)) =
+                modules.iter().find(|(module_name, is_client, _)| *module_name == name && *is_client == (target == "client"))
+            else {
+                log::warn!("The {target} module `{name}` is new, and will be loaded the next time the project is started");
+                continue;
+            };
+
+            if target == "client" {
+                world.set(*id, client_bytecode_from_url(), client_bytecode_url(&build_dir, &path, Some(generation))?)?;
+            } else {
+                world.set(*id, module_bytecode(), ModuleBytecode(std::fs::read(&path)?))?;
+            }
+            log::info!("Reloaded the {target} module `{name}`");
+        }
+    }
+
+    Ok(())
+}
+
+fn wasm_component_paths(build_dir: &Path, target: &str) -> Vec<PathBuf> {
+    std::fs::read_dir(build_dir.join(target))
+        .ok()
+        .map(|rd| rd.filter_map(Result::ok).map(|p| p.path()).filter(|p| p.extension().unwrap_or_default() == "wasm").collect())
+        .unwrap_or_default()
+}
+
+fn module_name(path: &Path) -> anyhow::Result<Identifier> {
+    Identifier::new(&*path.file_stem().with_context(|| format!("no file stem for {path:?}"))?.to_string_lossy()).map_err(anyhow::Error::msg)
+}
+
+fn client_bytecode_url(build_dir: &Path, path: &Path, generation: Option<u32>) -> anyhow::Result<String> {
+    let relative_path = path.strip_prefix(build_dir)?;
+    let url = format!("{}:/{}", ASSETS_PROTOCOL_SCHEME, relative_path.to_string_lossy());
+    Ok(match generation {
+        Some(generation) => format!("{url}?generation={generation}"),
+        None => url,
+    })
+}
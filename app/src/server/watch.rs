@@ -0,0 +1,155 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use ambient_core::{asset_cache, async_ecs::AsyncRun};
+use ambient_ecs::{query, Component, ComponentRegistry, World};
+use ambient_model::model_from_url;
+use ambient_network::ServerWorldExt;
+use ambient_physics::{collider::collider_from_url, physx::Physics};
+use ambient_prefab::prefab_from_url;
+use ambient_renderer::pbr_material_from_url;
+use notify::{event::EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::shared::hot_reload::{self, reloaded_assets};
+
+/// How long to wait for more changes after a change, before rebuilding
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+/// Watches the project's `assets/`, `src/`, `ambient.toml` and `Cargo.toml` for changes. When they change, the
+/// affected parts of the project are rebuilt, and the results are hot-reloaded into the server world through `async_run`.
+pub async fn run(project_path: PathBuf, mut manifest: ambient_project::Manifest, physics: Physics, async_run: AsyncRun, optimize: bool) {
+    log::info!("Watching {project_path:?} for changes");
+
+    // The watcher reports canonical paths
+    let project_path = project_path.canonicalize().unwrap_or(project_path);
+    let build_path = project_path.join("build");
+    let assets_path = project_path.join("assets");
+    let (tx, rx) = flume::unbounded();
+    let _watcher = match watch_project(&project_path, tx) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            log::error!("Failed to watch {project_path:?} for changes: {err:?}");
+            return;
+        }
+    };
+    let mut generation = 0;
+    while let Ok(paths) = rx.recv_async().await {
+        let mut changes = paths.into_iter().collect::<BTreeSet<_>>();
+        // Wait for the changes to settle, as editors and exporters often write files in several steps
+        while let Ok(Ok(paths)) = tokio::time::timeout(SETTLE_TIME, rx.recv_async()).await {
+            changes.extend(paths);
+        }
+        let changes = changes.into_iter().filter(|path| is_watched(&project_path, path)).collect::<BTreeSet<_>>();
+        if changes.is_empty() {
+            continue;
+        }
+        generation += 1;
+
+        if changes.iter().any(|path| path.starts_with(&assets_path)) {
+            log::info!("Assets changed, rebuilding");
+            let paths = ambient_build::build_assets(physics.clone(), &assets_path, &build_path, false).await;
+            if !paths.is_empty() {
+                let paths = paths.into_iter().map(|path| path.to_string()).collect::<Vec<_>>();
+                async_run.run(move |world| reload_assets(world, paths));
+            }
+        }
+
+        let manifest_path = project_path.join("ambient.toml");
+        if changes.contains(&manifest_path) {
+            match std::fs::read_to_string(&manifest_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|s| Ok(ambient_project::Manifest::parse(&s)?))
+            {
+                Ok(new_manifest) => {
+                    // Components can be added while running, but changes to existing components require a restart
+                    match ambient_project_native::all_defined_components(&new_manifest, false) {
+                        Ok(components) => ComponentRegistry::get_mut().add_external(components),
+                        Err(err) => log::error!("Failed to register the components of the changed project manifest: {err:?}"),
+                    }
+                    manifest = new_manifest;
+                }
+                Err(err) => {
+                    log::error!("Failed to load the changed project manifest: {err:?}");
+                    continue;
+                }
+            }
+        }
+
+        let rust_changed = changes
+            .iter()
+            .any(|path| path.starts_with(project_path.join("src")) || *path == manifest_path || *path == project_path.join("Cargo.toml"));
+        if rust_changed {
+            log::info!("Rust sources changed, rebuilding");
+            match ambient_build::build_rust_if_available(&project_path, &manifest, &build_path, optimize).await {
+                Ok(()) => {
+                    let project_path = project_path.clone();
+                    async_run.run(move |world| {
+                        if let Err(err) = super::wasm::reload_modules(world, &project_path, generation) {
+                            log::error!("Failed to reload modules: {err:?}");
+                        }
+                    });
+                }
+                Err(err) => log::error!("Failed to build Rust sources: {err:?}"),
+            }
+        }
+    }
+}
+
+/// Reloads the rebuilt assets at `paths` for the server and its clients.
+fn reload_assets(world: &mut World, paths: Vec<String>) {
+    let assets = world.resource(asset_cache()).clone();
+    hot_reload::invalidate_assets(&assets, &paths);
+
+    // Mark the entities using the rebuilt assets as changed, so that they are loaded again
+    for component in [model_from_url(), collider_from_url(), pbr_material_from_url()] {
+        for id in affected_entities(world, component, &paths) {
+            world.get_mut(id, component).ok();
+        }
+    }
+    // Prefabs are only loaded when they are added, so they are added again
+    for id in affected_entities(world, prefab_from_url(), &paths) {
+        let url = world.get_cloned(id, prefab_from_url()).unwrap();
+        world.remove_component(id, prefab_from_url()).unwrap();
+        world.add_component(id, prefab_from_url(), url).unwrap();
+    }
+
+    if let Some(id) = world.synced_resource_entity() {
+        world.add_component(id, reloaded_assets(), paths).unwrap();
+    }
+}
+
+fn affected_entities(world: &World, component: Component<String>, paths: &[String]) -> Vec<ambient_ecs::EntityId> {
+    query(component).iter(world, None).filter(|(_, url)| hot_reload::is_affected(url, paths)).map(|(id, _)| id).collect()
+}
+
+/// Starts watching the parts of the project at `project_path` that [is_watched] cares about. The paths of the files
+/// that are created, modified or removed are sent to `tx`.
+///
+/// The returned watcher stops watching when it's dropped.
+fn watch_project(project_path: &Path, tx: flume::Sender<Vec<PathBuf>>) -> notify::Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+            tx.send(event.paths).ok();
+        }
+        Ok(_) => {}
+        Err(err) => log::warn!("Error while watching for changes: {err:?}"),
+    })?;
+    // The project root is watched non-recursively to avoid the events from `build` and `target`
+    watcher.watch(project_path, RecursiveMode::NonRecursive)?;
+    for dir in ["assets", "src"].map(|dir| project_path.join(dir)) {
+        if dir.is_dir() {
+            watcher.watch(&dir, RecursiveMode::Recursive)?;
+        }
+    }
+    Ok(watcher)
+}
+
+/// Whether a change to `path` should trigger a rebuild: the project's `assets/` and `src/` directories,
+/// `ambient.toml` and `Cargo.toml` are watched.
+fn is_watched(project_path: &Path, path: &Path) -> bool {
+    ["assets", "src"].iter().any(|dir| path.starts_with(project_path.join(dir)))
+        || ["ambient.toml", "Cargo.toml"].iter().any(|file| path == project_path.join(file))
+}
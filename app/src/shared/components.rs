@@ -4,10 +4,14 @@ pub(crate) fn init() -> anyhow::Result<()> {
     ambient_physics::init_all_components();
     ambient_wasm::shared::init_all_components();
     ambient_decals::init_components();
+    ambient_intent::init_components();
+    ambient_terrain::init_all_components();
     ambient_world_audio::init_components();
     ambient_primitives::init_components();
     ambient_sky::init_components();
     ambient_water::init_components();
+    super::hot_reload::init_components();
+    crate::server::persistence::init_components();
 
     Ok(())
 }
@@ -0,0 +1,62 @@
+use ambient_core::asset_cache;
+use ambient_ecs::{components, query, Debuggable, Description, Networked, SystemGroup};
+use ambient_std::asset_cache::AssetCache;
+
+components!("app::hot_reload", {
+    @[Networked, Debuggable, Description["The paths of the assets (relative to the build's `assets` directory) that were changed by the latest rebuild in watch mode."]]
+    reloaded_assets: Vec<String>,
+});
+
+/// Drops the assets that were rebuilt by the server from the client's asset cache, so that they are downloaded again.
+///
+/// The server marks the entities that use these assets as changed in the same update, so this needs to run before
+/// the systems that load them.
+pub fn client_systems() -> SystemGroup {
+    SystemGroup::new(
+        "hot_reload",
+        vec![query(reloaded_assets().changed()).to_system(|q, world, qs, _| {
+            let assets = world.resource(asset_cache()).clone();
+            for (_, paths) in q.iter(world, qs) {
+                let count = invalidate_assets(&assets, paths);
+                log::debug!("Invalidated {count} cached assets after a rebuild of {} assets", paths.len());
+            }
+        })],
+    )
+}
+
+/// Expires the cached assets whose keys refer to any of the rebuilt asset `paths`. Returns the number of expired assets.
+///
+/// Assets derived from a rebuilt asset (e.g. the GPU mesh of a model) have the asset's URL in their keys, so they are
+/// expired as well.
+pub fn invalidate_assets(assets: &AssetCache, paths: &[String]) -> usize {
+    assets.invalidate(|key| paths.iter().any(|path| refers_to_asset(key, path)))
+}
+
+/// Whether the asset at `url` is one of the rebuilt asset `paths`.
+pub fn is_affected(url: &str, paths: &[String]) -> bool {
+    paths.iter().any(|path| refers_to_asset(url, path))
+}
+
+/// Whether `text` contains the URL of the asset at `path`, which is relative to the build's `assets` directory.
+/// The URL has to end after `path`, so that e.g. `a.json` doesn't match `a.json.bak`.
+fn refers_to_asset(text: &str, path: &str) -> bool {
+    let url = format!("/assets/{path}");
+    let is_path_char = |c: char| c.is_alphanumeric() || "._-/%~".contains(c);
+    text.match_indices(&url).any(|(index, _)| !text[index + url.len()..].starts_with(is_path_char))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refers_to_asset_exactly() {
+        let paths = ["main.json".to_string(), "models/a/main.json".to_string()];
+        assert!(is_affected("http://localhost:8999/content/assets/main.json", &paths));
+        assert!(is_affected("file:///project/build/assets/models/a/main.json", &paths));
+        assert!(!is_affected("file:///project/build/assets/models/a/main.json.bak", &paths));
+        assert!(!is_affected("file:///project/build/assets/models/b/main.json", &paths));
+        assert!(!is_affected("file:///project/build/assets/models/a/meshes/0.mesh", &paths));
+        assert!(refers_to_asset("ModelFromUrl(file:///project/build/assets/models/a/main.json)", "models/a/main.json"));
+    }
+}
@@ -17,6 +17,7 @@ fn pipeline() -> anyhow::Result<()> {
     let ctx = context::Context::new(&[
         Path::new("crates/physics/Cargo.toml"),
         Path::new("crates/model_import/Cargo.toml"),
+        Path::new("crates/terrain/Cargo.toml"),
         Path::new("crates/build/Cargo.toml"),
     ])?;
 
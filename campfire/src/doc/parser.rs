@@ -25,6 +25,7 @@ pub enum Type {
     String,
     AssetUrl,
     Vec2,
+    IVec2,
     Vec3,
     Vec4,
     Entity,
@@ -68,6 +69,8 @@ impl Type {
                     Type::AssetUrl
                 } else if p.name == "Vec2" {
                     Type::Vec2
+                } else if p.name == "IVec2" {
+                    Type::IVec2
                 } else if p.name == "Vec3" {
                     Type::Vec3
                 } else if p.name == "Vec4" {
@@ -124,6 +127,7 @@ impl Display for Type {
             Type::String => write!(f, "String"),
             Type::AssetUrl => write!(f, "AssetUrl"),
             Type::Vec2 => write!(f, "Vec2"),
+            Type::IVec2 => write!(f, "IVec2"),
             Type::Vec3 => write!(f, "Vec3"),
             Type::Vec4 => write!(f, "Vec4"),
             Type::Entity => write!(f, "Entity"),
@@ -18,6 +18,7 @@ pub fn generate(ty: &parser::Type, output_path: &Path) -> anyhow::Result<()> {
         ("u32", "number"),
         ("f32", "number"),
         ("Vec2", "[number, number]"),
+        ("IVec2", "[number, number]"),
         ("Vec3", "[number, number, number]"),
         ("Vec4", "[number, number, number, number]"),
         ("Entity", "{[component_id: string]: any}"),
@@ -87,6 +88,7 @@ impl GenerateTypescriptDefinition for parser::Type {
             parser::Type::String => write!(file, "string")?,
             parser::Type::AssetUrl => write!(file, "AssetUrl")?,
             parser::Type::Vec2 => write!(file, "Vec2")?,
+            parser::Type::IVec2 => write!(file, "IVec2")?,
             parser::Type::Vec3 => write!(file, "Vec3")?,
             parser::Type::Vec4 => write!(file, "Vec4")?,
             parser::Type::Entity => write!(file, "Entity")?,
@@ -1,5 +1,13 @@
-use ambient_core::{asset_cache, hierarchy::children, time};
-use ambient_ecs::{components, query, Debuggable, EntityId, MakeDefault, Networked, Store, SystemGroup};
+use ambient_core::{
+    asset_cache,
+    hierarchy::{children, parent},
+    time,
+    transform::{local_to_world, translation},
+};
+use ambient_ecs::{
+    components, query, Component, ComponentValue, Debuggable, DynSystem, EntityId, MakeDefault, Networked, Resource, Store, SystemGroup,
+    World,
+};
 use ambient_model::{animation_binder, model, model_from_url, ModelFromUrl};
 use ambient_std::{
     asset_cache::{AssetCache, AsyncAssetKeyExt},
@@ -9,14 +17,24 @@ use ambient_sys::time::SystemTime;
 use ambient_ui::Editable;
 use convert_case::{Case, Casing};
 use derive_more::Display;
+use glam::Vec3;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
 mod resources;
 mod retargeting;
+mod root_motion;
+mod state_machine;
 
 pub use resources::*;
 pub use retargeting::*;
+pub use root_motion::*;
+pub use state_machine::*;
 
 components!("animation", {
     @[Debuggable, Networked, Store]
@@ -28,10 +46,32 @@ components!("animation", {
     /// correspond
     @[Debuggable, Networked, Store]
     animation_apply_base_pose: ModelFromUrl,
+    /// Plays the states of the state machine instead of the `animation_controller`, if both are set
+    @[Debuggable, Networked, Store]
+    animation_state_machine: AnimationStateMachine,
+    /// The parameters that drive the transitions and blend spaces of the `animation_state_machine`
+    @[Debuggable, Networked, Store]
+    animation_parameters: AnimationParameters,
+    /// Where the entity is in its `animation_state_machine`. This is maintained wherever the animations are played
+    @[Debuggable]
+    animation_state: AnimationStatePlayback,
+    /// Extracts the motion of the root bone from the animations into `animation_root_motion_delta`
+    @[Debuggable, Networked, Store]
+    animation_root_motion: AnimationRootMotion,
+    /// How far the `animation_root_motion` moved in the last frame, in world space. This is only set wherever the animations are played
+    @[Debuggable]
+    animation_root_motion_delta: Vec3,
+    /// Also copies the `animation_state_machine`, `animation_parameters` and `animation_root_motion`
     @[Debuggable, Networked, Store]
     copy_animation_controller_to_children: (),
     @[Debuggable, Networked, Store]
     animation_errors: String,
+    /// The events of the animation clips that were crossed in the last frame
+    @[Resource, Debuggable]
+    animation_events: Vec<AnimationEventFired>,
+    /// The `time` the animations were last played at, which the events and root motion of the next frame are measured from
+    @[Resource, Debuggable]
+    animation_played_time: Duration,
 
     /// This is a shorthand for working directly with the animation_controller
     @[MakeDefault,  Debuggable, Networked, Store]
@@ -93,16 +133,35 @@ impl From<TypedAssetUrl<AnimationAssetType>> for AnimationClipRef {
     }
 }
 
+/// How an [AnimationAction] is combined with the other actions of an animation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimationBlendMode {
+    /// Blended with the other `Blend` actions, by their weights
+    #[default]
+    Blend,
+    /// Layered over the blended actions, replacing their pose by its weight
+    Override,
+    /// Layered over the blended actions, adding how far its clip has moved from its first frame, scaled by its weight
+    Additive,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AnimationAction {
     pub clip: AnimationClipRef,
     pub time: AnimationActionTime,
     pub looping: bool,
     pub weight: f32,
+    /// If not empty, the action only plays on the bones with these bind ids, and their descendants
+    #[serde(default)]
+    pub mask: Vec<String>,
+    /// `Override` and `Additive` actions are layered over the `Blend` actions in the order they're listed
+    #[serde(default)]
+    pub blend_mode: AnimationBlendMode,
 }
 impl AnimationAction {
-    fn time(&self, time: Duration, clip: &AnimationClip) -> f32 {
-        let anim_time = match self.time {
+    /// The time in the clip at `time`, in seconds from the start of the clip and before it's looped
+    fn unlooped_time(&self, time: Duration, clip: &AnimationClip) -> f32 {
+        match self.time {
             AnimationActionTime::Offset { start_time, speed } => {
                 if time < start_time {
                     -(start_time - time).as_secs_f32() * speed
@@ -112,7 +171,9 @@ impl AnimationAction {
             }
             AnimationActionTime::Percentage { percentage } => percentage * clip.duration(),
             AnimationActionTime::Absolute { time } => time,
-        };
+        }
+    }
+    fn clip_time(&self, anim_time: f32, clip: &AnimationClip) -> f32 {
         if self.looping {
             return anim_time % clip.duration();
         }
@@ -138,12 +199,25 @@ impl AnimationController {
                 time: AnimationActionTime::Offset { start_time: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap(), speed },
                 looping: true,
                 weight: 1.0,
+                mask: Vec::new(),
+                blend_mode: AnimationBlendMode::Blend,
             }],
             apply_base_pose: true,
         }
     }
 }
 
+/// An [AnimationEvent] that was crossed by an animation played on `entity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationEventFired {
+    pub entity: EntityId,
+    /// The url of the clip, or its id if it was not loaded from a url
+    pub clip_url: String,
+    pub name: String,
+    /// The weight of the action that played the clip
+    pub weight: f32,
+}
+
 #[derive(Debug)]
 struct AnimationBlendOutput {
     target: EntityId,
@@ -151,6 +225,17 @@ struct AnimationBlendOutput {
     weight: f32,
 }
 
+/// An output of an `Override` or `Additive` action, which is layered over the blended outputs
+#[derive(Debug)]
+struct AnimationBlendLayer {
+    key: String,
+    target: EntityId,
+    value: AnimationOutput,
+    /// The value that is subtracted from `value` before it's added, for additive layers
+    reference: Option<AnimationOutput>,
+    weight: f32,
+}
+
 pub fn animation_systems() -> SystemGroup {
     SystemGroup::new(
         "animation_systems",
@@ -160,15 +245,10 @@ pub fn animation_systems() -> SystemGroup {
                     world.add_component(id, animation_controller(), AnimationController::looping(anim.clone())).unwrap();
                 }
             }),
-            query((animation_controller().changed(), children().changed())).incl(copy_animation_controller_to_children()).to_system(
-                |q, world, qs, _| {
-                    for (_, (contr, children)) in q.collect_cloned(world, qs) {
-                        for c in children {
-                            world.set(c, animation_controller(), contr.clone()).unwrap();
-                        }
-                    }
-                },
-            ),
+            copy_to_children(animation_controller()),
+            copy_to_children(animation_state_machine()),
+            copy_to_children(animation_parameters()),
+            copy_to_children(animation_root_motion()),
             query(animation_controller().changed()).optional_changed(model()).to_system(|q, world, qs, _| {
                 for (id, ctrlr) in q.collect_cloned(world, qs) {
                     world.remove_component(id, animation_errors()).unwrap();
@@ -183,6 +263,44 @@ pub fn animation_systems() -> SystemGroup {
                     }
                 }
             }),
+            query(animation_state_machine().changed()).to_system(|q, world, qs, _| {
+                for (id, machine) in q.collect_cloned(world, qs) {
+                    world.remove_component(id, animation_state()).unwrap();
+                    world.remove_component(id, animation_errors()).unwrap();
+                    if let Err(err) = machine.validate() {
+                        world.add_component(id, animation_errors(), err).unwrap();
+                        continue;
+                    }
+                    if machine.apply_base_pose {
+                        if let Some(clip) = machine.base_pose_clip() {
+                            world
+                                .add_component(id, animation_apply_base_pose(), ModelFromUrl(clip.model_crate().unwrap().model()))
+                                .unwrap();
+                        }
+                    }
+                }
+            }),
+            query(animation_state_machine()).excl(animation_errors()).to_system(|q, world, qs, _| {
+                let time = *world.resource(time());
+                let mut changed = Vec::new();
+                for (id, machine) in q.iter(world, qs) {
+                    let parameters = world.get_ref(id, animation_parameters()).ok();
+                    let parameters = parameters.map(Cow::Borrowed).unwrap_or_default();
+                    let mut playback = match world.get_ref(id, animation_state()) {
+                        Ok(playback) => playback.clone(),
+                        Err(_) => {
+                            changed.push((id, AnimationStatePlayback::new(machine, time)));
+                            continue;
+                        }
+                    };
+                    if playback.update(machine, &parameters, time) {
+                        changed.push((id, playback));
+                    }
+                }
+                for (id, playback) in changed {
+                    world.add_component(id, animation_state(), playback).unwrap();
+                }
+            }),
             // This exists mostly because some FBX animations have pre-rotations, and to apply them to
             // character models which don't have the same pre-rotations we need to make sure they're up to sync
             query(animation_apply_base_pose()).to_system(|q, world, qs, _| {
@@ -197,25 +315,85 @@ pub fn animation_systems() -> SystemGroup {
                     }
                 }
             }),
-            query((animation_controller(), animation_binder())).excl(animation_errors()).to_system(|q, world, qs, _| {
+            query(animation_binder()).excl(animation_errors()).to_system(|q, world, qs, _| {
                 let assets = world.resource(asset_cache()).clone();
                 let time = *world.resource(time());
+                let played_time = world.resource_opt(animation_played_time()).copied().unwrap_or(time);
                 let mut outputs: HashMap<String, AnimationBlendOutput> = HashMap::new();
+                let mut layers = Vec::new();
                 let mut in_error = Vec::new();
-                for (id, (controller, binder)) in q.iter(world, qs) {
+                let mut events = Vec::new();
+                let mut root_motion_deltas = Vec::new();
+                for (id, binder) in q.iter(world, qs) {
+                    let actions: Cow<[AnimationAction]> = if let Ok(machine) = world.get_ref(id, animation_state_machine()) {
+                        let Ok(playback) = world.get_ref(id, animation_state()) else { continue };
+                        let parameters = world.get_ref(id, animation_parameters()).ok();
+                        Cow::Owned(playback.actions(machine, &parameters.map(Cow::Borrowed).unwrap_or_default(), time))
+                    } else if let Ok(controller) = world.get_ref(id, animation_controller()) {
+                        Cow::Borrowed(&controller.actions)
+                    } else {
+                        continue;
+                    };
                     let retaget = world.get(id, animation_retargeting()).unwrap_or(AnimationRetargeting::None);
                     let model = world.get_ref(id, model_from_url()).ok().and_then(|def| TypedAssetUrl::parse(def).ok());
+                    let root_motion = world.get_ref(id, animation_root_motion()).ok();
+                    let root_target = root_motion.map(|root_motion| AnimationTarget::BinderId(root_motion.bind_id.clone()));
+                    let mut root_delta = Vec3::ZERO;
+                    let mut root_weight = 0.;
                     // Calc
-                    for action in controller.actions.iter() {
+                    for action in actions.iter() {
                         match action.clip.get_clip(assets.clone(), retaget, model.clone()) {
                             Some(Err(err)) => {
                                 in_error.push((id, err));
                                 break;
                             }
                             Some(Ok(clip)) => {
-                                let anim_time = action.time(time, &clip);
+                                let anim_time = action.unlooped_time(time, &clip);
+                                let prev_anim_time = action.unlooped_time(played_time, &clip);
+                                if action.weight > 0.0 {
+                                    let clip_url = match &action.clip {
+                                        AnimationClipRef::Clip(clip) => clip.id.clone(),
+                                        AnimationClipRef::FromModelAsset(url) => url.to_string(),
+                                    };
+                                    events.extend(clip.events_crossed(prev_anim_time, anim_time, action.looping).map(|event| {
+                                        AnimationEventFired {
+                                            entity: id,
+                                            clip_url: clip_url.clone(),
+                                            name: event.name.clone(),
+                                            weight: action.weight,
+                                        }
+                                    }));
+                                }
+                                let mask = (!action.mask.is_empty()).then(|| mask_targets(world, binder, &action.mask));
                                 for track in clip.tracks.iter() {
-                                    let value = AnimationTrackInterpolator::new().value(track, anim_time);
+                                    let target = match &track.target {
+                                        AnimationTarget::BinderId(index) => match binder.get(index) {
+                                            Some(entity) => *entity,
+                                            None => continue,
+                                        },
+                                        AnimationTarget::Entity(entity) => *entity,
+                                    };
+                                    if mask.as_ref().map(|mask| !mask.contains(&target)).unwrap_or(false) {
+                                        continue;
+                                    }
+                                    let mut value = AnimationTrackInterpolator::new().value(track, action.clip_time(anim_time, &clip));
+                                    if let (Some(root_motion), AnimationOutput::Vec3 { component, value }) = (root_motion, &mut value) {
+                                        if *component == translation() && root_target.as_ref() == Some(&track.target) {
+                                            let sample = |t: f32| {
+                                                AnimationTrackInterpolator::new()
+                                                    .value(track, t + clip.start)
+                                                    .as_vec3_value()
+                                                    .copied()
+                                                    .unwrap_or_default()
+                                            };
+                                            *value = root_motion.residual(*value, sample(0.));
+                                            if action.blend_mode != AnimationBlendMode::Additive {
+                                                root_delta += action.weight
+                                                    * root_motion.delta(prev_anim_time, anim_time, clip.duration(), action.looping, sample);
+                                                root_weight += action.weight;
+                                            }
+                                        }
+                                    }
                                     let key = format!(
                                         "{}_{:?}_{}_{:?}",
                                         id,
@@ -226,33 +404,58 @@ pub fn animation_systems() -> SystemGroup {
                                     if action.weight == 0.0 {
                                         continue;
                                     }
-                                    if let Some(o) = outputs.get_mut(&key) {
-                                        o.weight += action.weight;
-                                        let p = action.weight / o.weight;
-                                        o.value = o.value.mix(value, p);
-                                    } else {
-                                        outputs.insert(
-                                            key.to_string(),
-                                            AnimationBlendOutput {
-                                                target: match &track.target {
-                                                    AnimationTarget::BinderId(index) => match binder.get(index) {
-                                                        Some(entity) => *entity,
-                                                        None => {
-                                                            continue;
-                                                        }
-                                                    },
-                                                    AnimationTarget::Entity(entity) => *entity,
-                                                },
+                                    match action.blend_mode {
+                                        AnimationBlendMode::Blend => {
+                                            if let Some(o) = outputs.get_mut(&key) {
+                                                o.weight += action.weight;
+                                                let p = action.weight / o.weight;
+                                                o.value = o.value.mix(value, p);
+                                            } else {
+                                                outputs.insert(key, AnimationBlendOutput { target, value, weight: action.weight });
+                                            }
+                                        }
+                                        AnimationBlendMode::Override => {
+                                            layers.push(AnimationBlendLayer { key, target, value, reference: None, weight: action.weight });
+                                        }
+                                        AnimationBlendMode::Additive => {
+                                            let reference = AnimationTrackInterpolator::new().value(track, clip.start);
+                                            layers.push(AnimationBlendLayer {
+                                                key,
+                                                target,
                                                 value,
+                                                reference: Some(reference),
                                                 weight: action.weight,
-                                            },
-                                        );
+                                            });
+                                        }
                                     }
                                 }
                             }
                             None => {}
                         }
                     }
+                    if let Some(root_motion) = root_motion {
+                        let mut delta = if root_weight > 0. { root_delta / root_weight } else { Vec3::ZERO };
+                        // The translation of the root bone is relative to its parent
+                        let root = binder.get(&root_motion.bind_id).and_then(|root| world.get(*root, parent()).ok());
+                        if let Some(transform) = root.and_then(|parent| world.get(parent, local_to_world()).ok()) {
+                            delta = transform.transform_vector3(delta);
+                        }
+                        root_motion_deltas.push((id, delta));
+                    }
+                }
+
+                // Layer the override and additive actions over the blended actions, in order
+                for layer in layers {
+                    match (outputs.get_mut(&layer.key), layer.reference) {
+                        (Some(o), None) => o.value = o.value.mix(layer.value, layer.weight),
+                        (Some(o), Some(reference)) => o.value = o.value.add(layer.value, reference, layer.weight),
+                        (None, None) => {
+                            outputs
+                                .insert(layer.key, AnimationBlendOutput { target: layer.target, value: layer.value, weight: layer.weight });
+                        }
+                        // There is nothing to add to
+                        (None, Some(_)) => {}
+                    }
                 }
 
                 // Apply
@@ -278,11 +481,40 @@ pub fn animation_systems() -> SystemGroup {
                 for (id, err) in in_error {
                     world.add_component(id, animation_errors(), err).unwrap();
                 }
+                for (id, delta) in root_motion_deltas {
+                    world.add_component(id, animation_root_motion_delta(), delta).unwrap();
+                }
+                world.add_resource(animation_events(), events);
+                world.add_resource(animation_played_time(), time);
             }),
         ],
     )
 }
 
+/// The bones with the bind ids in `mask`, and their descendants
+fn mask_targets(world: &World, binder: &HashMap<String, EntityId>, mask: &[String]) -> HashSet<EntityId> {
+    let mut targets = HashSet::new();
+    let mut stack = mask.iter().filter_map(|bind_id| binder.get(bind_id).copied()).collect::<Vec<_>>();
+    while let Some(id) = stack.pop() {
+        if targets.insert(id) {
+            if let Ok(children) = world.get_ref(id, children()) {
+                stack.extend(children.iter().copied());
+            }
+        }
+    }
+    targets
+}
+
+fn copy_to_children<T: ComponentValue>(component: Component<T>) -> DynSystem {
+    query((component.changed(), children().changed())).incl(copy_animation_controller_to_children()).to_system(move |q, world, qs, _| {
+        for (_, (value, children)) in q.collect_cloned(world, qs) {
+            for c in children {
+                world.set(c, component, value.clone()).unwrap();
+            }
+        }
+    })
+}
+
 pub fn animation_bind_id_from_name(name: &str) -> String {
     let name = if let Some((_a, b)) = name.split_once(':') { b.to_string() } else { name.to_string() };
     fn normalize_name(value: &str) -> String {
@@ -336,3 +568,50 @@ fn test_animation() {
     assert_eq!(1., int.value(&track, 1.).as_vec3_value().unwrap().x);
     assert_eq!(1., int.value(&track, 1.5).as_vec3_value().unwrap().x);
 }
+
+#[test]
+fn test_additive() {
+    use ambient_core::transform::{self, rotation, translation};
+    use glam::{vec3, Quat};
+
+    ambient_ecs::init_components();
+    transform::init_components();
+
+    let v = |value| AnimationOutput::Vec3 { component: translation(), value };
+    let added = v(vec3(1., 0., 0.)).add(v(vec3(0., 2., 0.)), v(vec3(0., 1., 0.)), 0.5);
+    assert_eq!(Some(&vec3(1., 0.5, 0.)), added.as_vec3_value());
+
+    let q = |value| AnimationOutput::Quat { component: rotation(), value };
+    let base = Quat::from_rotation_y(1.);
+    let AnimationOutput::Quat { value, .. } = q(base).add(q(Quat::from_rotation_x(0.5)), q(Quat::from_rotation_x(0.25)), 1.) else {
+        unreachable!()
+    };
+    assert!(value.abs_diff_eq(base * Quat::from_rotation_x(0.25), 1e-5));
+}
+
+#[test]
+fn test_events_crossed() {
+    let clip = AnimationClip {
+        id: "".to_string(),
+        tracks: Vec::new(),
+        start: 0.,
+        end: 2.,
+        events: vec![AnimationEvent { name: "a".to_string(), time: 0.5 }, AnimationEvent { name: "b".to_string(), time: 1.5 }],
+    };
+    let crossed = |from, to, looping| clip.events_crossed(from, to, looping).map(|event| event.name.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(crossed(0., 1., false), vec!["a"]);
+    // The start is exclusive and the end is inclusive, so an event isn't fired twice
+    assert_eq!(crossed(0., 0.5, false), vec!["a"]);
+    assert_eq!(crossed(0.5, 1., false), Vec::<&str>::new());
+    assert_eq!(crossed(1., 0., false), vec!["a"]);
+    assert_eq!(crossed(1., 3., false), vec!["b"]);
+
+    // Wrapping around the end of a looping clip
+    assert_eq!(crossed(1.8, 2.6, true), vec!["a"]);
+    assert_eq!(crossed(1.8, 2.6, false), Vec::<&str>::new());
+    assert_eq!(crossed(1.4, 2.6, true), vec!["a", "b"]);
+    assert_eq!(crossed(2.6, 1.8, true), vec!["a"]);
+    assert_eq!(crossed(5.9, 6.1, true), Vec::<&str>::new());
+    assert_eq!(crossed(4.4, 4.6, true), vec!["a"]);
+}
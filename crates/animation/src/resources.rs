@@ -33,6 +33,34 @@ impl AnimationOutput {
             _ => unreachable!(),
         }
     }
+    /// Adds the difference between `value` and `reference` to this output, scaled by `weight`
+    pub fn add(&self, value: AnimationOutput, reference: AnimationOutput, weight: f32) -> Self {
+        match (self, value, reference) {
+            (
+                AnimationOutput::Vec3 { value: base, .. },
+                AnimationOutput::Vec3 { value, component },
+                AnimationOutput::Vec3 { value: reference, .. },
+            ) => AnimationOutput::Vec3 { component, value: *base + (value - reference) * weight },
+
+            (
+                AnimationOutput::Quat { value: base, .. },
+                AnimationOutput::Quat { value, component },
+                AnimationOutput::Quat { value: reference, .. },
+            ) => {
+                let delta = (reference.inverse() * value).normalize();
+                let delta = if delta.w >= 0. { delta } else { delta.neg() };
+                AnimationOutput::Quat { component, value: (*base * Quat::IDENTITY.slerp(delta, weight)).normalize() }
+            }
+
+            (
+                AnimationOutput::Vec3Field { value: base, .. },
+                AnimationOutput::Vec3Field { value, field, component },
+                AnimationOutput::Vec3Field { value: reference, .. },
+            ) => AnimationOutput::Vec3Field { component, field, value: base + (value - reference) * weight },
+
+            _ => unreachable!(),
+        }
+    }
     pub fn as_vec3_value(&self) -> Option<&Vec3> {
         match self {
             AnimationOutput::Vec3 { value, .. } => Some(value),
@@ -177,21 +205,45 @@ impl AnimationTrackInterpolator {
 
 pub type AnimationClipFromUrl = BincodeFromUrl<AnimationClip>;
 
+/// A named point in an [AnimationClip], such as a footstep or the frame a hit lands on
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AnimationEvent {
+    pub name: String,
+    /// Seconds from the start of the clip
+    pub time: f32,
+}
+
+/// Stored in `.anim` files with bincode, so changing the fields requires bumping `ASSET_FORMAT_VERSION` in `ambient_build`
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AnimationClip {
     pub id: String,
     pub tracks: Vec<AnimationTrack>,
     pub start: f32,
     pub end: f32,
+    pub events: Vec<AnimationEvent>,
 }
 impl AnimationClip {
     pub fn from_tracks(tracks: Vec<AnimationTrack>) -> Self {
         let end = tracks.iter().map(|x| ordered_float::OrderedFloat::from(x.duration())).max().unwrap().into();
-        Self { id: "".to_string(), tracks, start: 0., end }
+        Self { id: "".to_string(), tracks, start: 0., end, events: Vec::new() }
     }
     pub fn duration(&self) -> f32 {
         self.end - self.start
     }
+    /// The events that are crossed when the clip is played from `from` to `to` seconds (in either direction).
+    /// When `looping`, the times may span several loops of the clip.
+    pub fn events_crossed(&self, from: f32, to: f32, looping: bool) -> impl Iterator<Item = &AnimationEvent> {
+        let (from, to) = if from <= to { (from, to) } else { (to, from) };
+        let duration = self.duration();
+        self.events.iter().filter(move |event| {
+            if looping && duration > 0. {
+                // The number of times the event occurs within (from, to]
+                ((to - event.time) / duration).floor() > ((from - event.time) / duration).floor()
+            } else {
+                from < event.time && event.time <= to
+            }
+        })
+    }
     /// Merge tracks with Vec3Field outputs into Vec3 and Quat tracks
     pub fn merge_field_tracks(&mut self) {
         let mut euler_rotation_tracks = HashMap::new();
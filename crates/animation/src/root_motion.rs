@@ -0,0 +1,52 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// Extracts the translation of the root bone from the animations, so that it can be applied to the character
+/// (e.g. with `move_character`) instead of moving the skeleton away from it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationRootMotion {
+    /// The bind id of the root bone, as returned by [animation_bind_id_from_name](crate::animation_bind_id_from_name) (e.g. `Hips`)
+    pub bind_id: String,
+    /// The axes of the root bone's translation to extract: 1 extracts the axis, and 0 keeps playing it on the bone.
+    /// For instance, `(1, 0, 1)` extracts the horizontal motion of a Y-up skeleton, but keeps it bobbing up and down
+    pub axes: Vec3,
+}
+impl AnimationRootMotion {
+    /// The translation that is left on the root bone: the extracted axes stay where they are at the start of the clip
+    pub fn residual(&self, value: Vec3, start: Vec3) -> Vec3 {
+        value * (Vec3::ONE - self.axes) + start * self.axes
+    }
+    /// How far the root bone moves along the extracted axes when the clip is played from `from` to `to` seconds.
+    /// `sample` returns the translation of the root bone at a time in `0..=duration`
+    pub fn delta(&self, from: f32, to: f32, duration: f32, looping: bool, sample: impl Fn(f32) -> Vec3) -> Vec3 {
+        let delta = if looping && duration > 0. {
+            let from_loop = (from / duration).floor();
+            let to_loop = (to / duration).floor();
+            // Every loop that is completed moves the root from where the clip starts to where it ends
+            let per_loop = sample(duration) - sample(0.);
+            sample(to - to_loop * duration) - sample(from - from_loop * duration) + per_loop * (to_loop - from_loop)
+        } else {
+            sample(to.clamp(0., duration)) - sample(from.clamp(0., duration))
+        };
+        delta * self.axes
+    }
+}
+
+#[test]
+fn test_root_motion_delta() {
+    use glam::vec3;
+
+    let root_motion = AnimationRootMotion { bind_id: "Hips".to_string(), axes: vec3(1., 0., 1.) };
+    // Walks 2 units forward per loop, while bobbing up and down
+    let sample = |t: f32| vec3(0., (t * 10.).sin(), t * 2.);
+    let delta = root_motion.delta(0.25, 0.75, 1., true, sample);
+    assert!((delta - vec3(0., 0., 1.)).length() < 1e-5);
+    // Crossing the end of the loop continues from the start of the clip
+    let delta = root_motion.delta(0.75, 2.25, 1., true, sample);
+    assert!((delta - vec3(0., 0., 3.)).length() < 1e-5);
+    // Non-looping clips stop at their end
+    let delta = root_motion.delta(0.75, 2.25, 1., false, sample);
+    assert!((delta - vec3(0., 0., 0.5)).length() < 1e-5);
+
+    assert_eq!(root_motion.residual(vec3(1., 2., 3.), vec3(0., 0., 0.5)), vec3(0., 2., 0.5));
+}
@@ -0,0 +1,307 @@
+use std::{collections::HashMap, time::Duration};
+
+use ambient_std::asset_url::{AnimationAssetType, TypedAssetUrl};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::{AnimationAction, AnimationActionTime, AnimationBlendMode, AnimationClipRef};
+
+/// The values of the parameters that drive an [`AnimationStateMachine`], by name. Parameters that aren't set are `0`.
+pub type AnimationParameters = HashMap<String, f32>;
+
+/// A declarative animation graph: the states an entity can be in, and the parameter-driven transitions between them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationStateMachine {
+    pub states: Vec<AnimationState>,
+    /// Evaluated in order every frame; the first transition that applies is taken.
+    pub transitions: Vec<AnimationTransition>,
+    pub initial_state: String,
+    /// Apply the base pose of the first clip of the initial state
+    pub apply_base_pose: bool,
+}
+impl AnimationStateMachine {
+    pub fn state(&self, name: &str) -> Option<&AnimationState> {
+        self.states.iter().find(|state| state.name == name)
+    }
+    /// Checks that every state that is referred to exists, and that every state has something to play.
+    pub fn validate(&self) -> Result<(), String> {
+        for state in &self.states {
+            if state.points.is_empty() {
+                return Err(format!("Animation state {:?} has no clips", state.name));
+            }
+        }
+        let referred = std::iter::once(&self.initial_state)
+            .chain(self.transitions.iter().flat_map(|transition| transition.from.iter().chain([&transition.to])));
+        for name in referred {
+            if self.state(name).is_none() {
+                return Err(format!("Animation state {name:?} does not exist"));
+            }
+        }
+        Ok(())
+    }
+    /// The clip whose base pose is applied when [`apply_base_pose`](Self::apply_base_pose) is set.
+    pub fn base_pose_clip(&self) -> Option<&TypedAssetUrl<AnimationAssetType>> {
+        self.state(&self.initial_state)?.points.first().map(|point| &point.clip)
+    }
+    /// The first transition out of `current` whose conditions hold for `parameters`.
+    pub fn next_transition(&self, current: &str, parameters: &AnimationParameters) -> Option<&AnimationTransition> {
+        self.transitions.iter().find(|transition| {
+            transition.to != current
+                && transition.from.as_deref().map(|from| from == current).unwrap_or(true)
+                && transition.conditions.iter().all(|condition| condition.holds(parameters))
+        })
+    }
+}
+
+/// A state of an [`AnimationStateMachine`]. A state with a single point plays a single clip; with more, it's a blend space
+/// that blends the clips based on how close their points are to the values of its parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationState {
+    pub name: String,
+    pub points: Vec<AnimationBlendPoint>,
+    /// The parameter that the x coordinate of the blend space is read from
+    pub x_parameter: Option<String>,
+    /// The parameter that the y coordinate of the blend space is read from. If not set, the blend space is 1D.
+    pub y_parameter: Option<String>,
+    pub looping: bool,
+    pub speed: f32,
+}
+impl AnimationState {
+    /// The weight of each of the [`points`](Self::points) for the current `parameters`. The weights add up to 1.
+    ///
+    /// 1D blend spaces blend linearly between the two points on either side of the parameter, and 2D blend spaces
+    /// weigh each point by its inverse squared distance to the parameters.
+    pub fn blend_weights(&self, parameters: &AnimationParameters) -> Vec<f32> {
+        let parameter = |name: &Option<String>| name.as_ref().and_then(|name| parameters.get(name)).copied().unwrap_or_default();
+        let mut weights = vec![0.; self.points.len()];
+        if self.points.is_empty() {
+            return weights;
+        }
+        let x = parameter(&self.x_parameter);
+        if self.y_parameter.is_none() {
+            let mut sorted = (0..self.points.len()).collect::<Vec<_>>();
+            sorted.sort_by(|a, b| self.points[*a].position.x.total_cmp(&self.points[*b].position.x));
+            let position = |index: usize| self.points[sorted[index]].position.x;
+            let upper = sorted.partition_point(|index| self.points[*index].position.x <= x);
+            if upper == 0 {
+                weights[sorted[0]] = 1.;
+            } else if upper == sorted.len() {
+                weights[sorted[upper - 1]] = 1.;
+            } else {
+                let (from, to) = (position(upper - 1), position(upper));
+                let p = (x - from) / (to - from);
+                weights[sorted[upper - 1]] = 1. - p;
+                weights[sorted[upper]] = p;
+            }
+        } else {
+            let at = Vec2::new(x, parameter(&self.y_parameter));
+            let distances = self.points.iter().map(|point| point.position.distance_squared(at)).collect::<Vec<_>>();
+            if let Some(exact) = distances.iter().position(|distance| *distance < f32::EPSILON) {
+                weights[exact] = 1.;
+            } else {
+                let total = distances.iter().map(|distance| 1. / distance).sum::<f32>();
+                for (weight, distance) in weights.iter_mut().zip(distances) {
+                    *weight = 1. / distance / total;
+                }
+            }
+        }
+        weights
+    }
+    /// The actions that play this state, scaled by `weight`, for a state that was entered at `entered`.
+    fn actions<'a>(
+        &'a self,
+        parameters: &AnimationParameters,
+        entered: Duration,
+        weight: f32,
+    ) -> impl Iterator<Item = AnimationAction> + 'a {
+        self.points.iter().zip(self.blend_weights(parameters)).filter(|(_, blend)| *blend > 0.).map(move |(point, blend)| AnimationAction {
+            clip: AnimationClipRef::FromModelAsset(point.clip.clone()),
+            time: AnimationActionTime::Offset { start_time: entered, speed: self.speed },
+            looping: self.looping,
+            weight: weight * blend,
+            mask: Vec::new(),
+            blend_mode: AnimationBlendMode::Blend,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationBlendPoint {
+    pub clip: TypedAssetUrl<AnimationAssetType>,
+    /// Where the clip is in the blend space. Only `x` is used in 1D blend spaces.
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationTransition {
+    /// The state to transition from. If not set, the transition can be taken from any state.
+    pub from: Option<String>,
+    pub to: String,
+    /// All of these must hold for the transition to be taken
+    pub conditions: Vec<AnimationCondition>,
+    /// How long, in seconds, the previous state is crossfaded into the next one
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationCondition {
+    pub parameter: String,
+    pub comparison: AnimationComparison,
+    pub value: f32,
+}
+impl AnimationCondition {
+    pub fn holds(&self, parameters: &AnimationParameters) -> bool {
+        let parameter = parameters.get(&self.parameter).copied().unwrap_or_default();
+        match self.comparison {
+            AnimationComparison::Less => parameter < self.value,
+            AnimationComparison::LessOrEqual => parameter <= self.value,
+            AnimationComparison::Equal => parameter == self.value,
+            AnimationComparison::NotEqual => parameter != self.value,
+            AnimationComparison::GreaterOrEqual => parameter >= self.value,
+            AnimationComparison::Greater => parameter > self.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimationComparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// Where an entity is in its [`AnimationStateMachine`]. Times are in the time of the `time` resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationStatePlayback {
+    pub state: String,
+    pub entered: Duration,
+    /// The state that is being crossfaded out, while the current state is crossfaded in
+    pub previous: Option<AnimationPreviousState>,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationPreviousState {
+    pub state: String,
+    pub entered: Duration,
+    pub fade_duration: f32,
+}
+impl AnimationStatePlayback {
+    pub fn new(machine: &AnimationStateMachine, time: Duration) -> Self {
+        Self { state: machine.initial_state.clone(), entered: time, previous: None }
+    }
+    /// Takes the next transition of `machine`, if any, and finishes the crossfade once it's done. Returns whether anything changed.
+    ///
+    /// A transition that is taken during a crossfade crossfades from the state that was being faded in.
+    pub fn update(&mut self, machine: &AnimationStateMachine, parameters: &AnimationParameters, time: Duration) -> bool {
+        if let Some(transition) = machine.next_transition(&self.state, parameters) {
+            let previous = AnimationPreviousState {
+                state: std::mem::replace(&mut self.state, transition.to.clone()),
+                entered: self.entered,
+                fade_duration: transition.duration,
+            };
+            self.previous = if transition.duration > 0. { Some(previous) } else { None };
+            self.entered = time;
+            return true;
+        }
+        if self.previous.is_some() && self.fade(time) >= 1. {
+            self.previous = None;
+            return true;
+        }
+        false
+    }
+    /// How far along the crossfade into the current state is, from 0 to 1.
+    pub fn fade(&self, time: Duration) -> f32 {
+        match &self.previous {
+            Some(previous) => ((time.saturating_sub(self.entered)).as_secs_f32() / previous.fade_duration).clamp(0., 1.),
+            None => 1.,
+        }
+    }
+    /// The actions that play the current state, and the crossfade from the previous one.
+    pub fn actions(&self, machine: &AnimationStateMachine, parameters: &AnimationParameters, time: Duration) -> Vec<AnimationAction> {
+        let fade = self.fade(time);
+        let mut actions = Vec::new();
+        if let Some(previous) = &self.previous {
+            if let Some(state) = machine.state(&previous.state) {
+                actions.extend(state.actions(parameters, previous.entered, 1. - fade));
+            }
+        }
+        if let Some(state) = machine.state(&self.state) {
+            actions.extend(state.actions(parameters, self.entered, fade));
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state(name: &str, xs: &[(f32, f32)], two_d: bool) -> AnimationState {
+        AnimationState {
+            name: name.to_string(),
+            points: xs
+                .iter()
+                .map(|(x, y)| AnimationBlendPoint {
+                    clip: TypedAssetUrl::parse(format!("https://example.com/{name}.fbx/animations/{x}_{y}.anim")).unwrap(),
+                    position: Vec2::new(*x, *y),
+                })
+                .collect(),
+            x_parameter: Some("x".to_string()),
+            y_parameter: two_d.then(|| "y".to_string()),
+            looping: true,
+            speed: 1.,
+        }
+    }
+
+    #[test]
+    fn test_blend_weights() {
+        let params = |x: f32, y: f32| AnimationParameters::from([("x".to_string(), x), ("y".to_string(), y)]);
+
+        let walk = state("walk", &[(2., 0.), (0., 0.), (1., 0.)], false);
+        assert_eq!(walk.blend_weights(&params(-1., 0.)), vec![0., 1., 0.]);
+        assert_eq!(walk.blend_weights(&params(0.25, 0.)), vec![0., 0.75, 0.25]);
+        assert_eq!(walk.blend_weights(&params(1.5, 0.)), vec![0.5, 0., 0.5]);
+        assert_eq!(walk.blend_weights(&params(3., 0.)), vec![1., 0., 0.]);
+
+        let strafe = state("strafe", &[(-1., 0.), (1., 0.), (0., 1.)], true);
+        assert_eq!(strafe.blend_weights(&params(0., 1.)), vec![0., 0., 1.]);
+        let weights = strafe.blend_weights(&params(0., 0.));
+        assert!((weights[0] - 1. / 3.).abs() < 1e-6 && (weights[0] - weights[2]).abs() < 1e-6);
+        assert!((weights.iter().sum::<f32>() - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_transitions() {
+        let machine = AnimationStateMachine {
+            states: vec![state("idle", &[(0., 0.)], false), state("run", &[(0., 0.)], false)],
+            transitions: vec![AnimationTransition {
+                from: Some("idle".to_string()),
+                to: "run".to_string(),
+                conditions: vec![AnimationCondition { parameter: "x".to_string(), comparison: AnimationComparison::Greater, value: 0.5 }],
+                duration: 2.,
+            }],
+            initial_state: "idle".to_string(),
+            apply_base_pose: false,
+        };
+        assert_eq!(machine.validate(), Ok(()));
+
+        let mut playback = AnimationStatePlayback::new(&machine, Duration::ZERO);
+        let mut params = AnimationParameters::new();
+        assert!(!playback.update(&machine, &params, Duration::from_secs(1)));
+
+        params.insert("x".to_string(), 1.);
+        assert!(playback.update(&machine, &params, Duration::from_secs(1)));
+        assert_eq!(playback.state, "run");
+        let weights = |playback: &AnimationStatePlayback, secs| {
+            playback.actions(&machine, &params, Duration::from_secs(secs)).iter().map(|action| action.weight).collect::<Vec<_>>()
+        };
+        assert_eq!(weights(&playback, 2), vec![0.5, 0.5]);
+
+        assert!(!playback.update(&machine, &params, Duration::from_secs(2)));
+        assert!(playback.update(&machine, &params, Duration::from_secs(3)));
+        assert_eq!(playback.previous, None);
+        assert_eq!(weights(&playback, 3), vec![1.]);
+    }
+}
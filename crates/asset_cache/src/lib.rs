@@ -238,6 +238,23 @@ impl AssetCache {
         cache.insert(key.clone(), SyncAssetLoc { _key: key, content: Arc::new(Mutex::new(Some(Arc::new(asset) as Arc<dyn AssetHolder>))) });
     }
 
+    /// Expires the loaded assets whose keys match `predicate`, so that they are loaded again the next time they are requested.
+    /// Existing references to the assets are not affected.
+    ///
+    /// Returns the number of assets that were expired.
+    pub fn invalidate(&self, predicate: impl Fn(&str) -> bool) -> usize {
+        let mut cache = self.async_cache.lock();
+        let mut count = 0;
+        for (key, loc) in cache.iter_mut() {
+            if matches!(loc.content, ContentState::Loaded { .. }) && predicate(key) {
+                loc.content = ContentState::Expired;
+                loc.keepalive_task = None;
+                count += 1;
+            }
+        }
+        count
+    }
+
     fn clean_up_dropped(&self) {
         let mut async_ = self.async_cache.lock();
         for (key, asset) in &mut *async_ {
@@ -186,3 +186,19 @@ async fn load_background() {
 
     assert!(Arc::ptr_eq(&a, &b));
 }
+
+#[tokio::test]
+async fn invalidate() {
+    let assets = AssetCache::new(runtime::Handle::current());
+
+    let a = TestAssetKey { name: "foo".into() }.get(&assets).await;
+    let other = TestAssetKey { name: "bar".into() }.get(&assets).await;
+
+    assert_eq!(assets.invalidate(|key| key.contains("foo")), 1);
+
+    // The invalidated asset is loaded again, while the other asset is left untouched
+    let b = TestAssetKey { name: "foo".into() }.get(&assets).await;
+    assert!(!Arc::ptr_eq(&a, &b));
+    let other_b = TestAssetKey { name: "bar".into() }.get(&assets).now_or_never().unwrap();
+    assert!(Arc::ptr_eq(&other, &other_b));
+}
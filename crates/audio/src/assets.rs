@@ -10,7 +10,7 @@ use async_trait::*;
 use crate::{
     track::{AudioFormat, Track},
     vorbis::VorbisTrack,
-    Error,
+    Error, ImpulseResponse, SampleConversion, SampleRate,
 };
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct AudioFromUrl {
@@ -45,6 +45,30 @@ impl AsyncAssetKey<Result<Arc<Track>, Arc<Error>>> for AudioFromUrl {
     }
 }
 
+/// The audio at the url as an impulse response, resampled to `sample_rate`
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ImpulseResponseFromUrl {
+    pub url: AbsAssetUrl,
+    pub sample_rate: SampleRate,
+}
+
+#[async_trait]
+impl AsyncAssetKey<Result<Arc<ImpulseResponse>, Arc<Error>>> for ImpulseResponseFromUrl {
+    async fn load(
+        self,
+        assets: ambient_std::asset_cache::AssetCache,
+    ) -> Result<Arc<ImpulseResponse>, Arc<Error>>
+    where
+        Self: 'async_trait,
+    {
+        let track = AudioFromUrl { url: self.url }.get(&assets).await?;
+        Ok(Arc::new(ImpulseResponse::new(SampleConversion::new(
+            track.decode(),
+            self.sample_rate,
+        ))))
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct VorbisFromUrl {
     pub url: AbsAssetUrl,
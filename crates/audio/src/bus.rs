@@ -0,0 +1,253 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use parking_lot::Mutex;
+use slotmap::new_key_type;
+
+use crate::{blt::Bpf, value::Constant, Frame, SampleRate, Source};
+
+new_key_type! {
+    /// Identifies a bus of an [crate::AudioMixer]
+    pub struct BusId;
+}
+
+/// Wraps the signal of a bus in another source, see [BusEffect::Custom]
+pub type CustomEffect = dyn Fn(Box<dyn Source>) -> Box<dyn Source> + Send + Sync;
+
+/// An effect applied to everything which is mixed into a bus
+#[derive(Clone)]
+pub enum BusEffect {
+    LowPass {
+        freq: f32,
+        // In dB/octave
+        bandwidth: f32,
+    },
+    HighPass {
+        freq: f32,
+        // In dB/octave
+        bandwidth: f32,
+    },
+    BandPass {
+        freq: f32,
+        bandwidth: f32,
+    },
+    /// Any other effect, which wraps the signal of the bus as a source.
+    ///
+    /// The effect must read exactly one frame of the signal for every frame it returns, as the
+    /// signal is only known one frame at a time.
+    Custom(Arc<CustomEffect>),
+}
+
+impl Debug for BusEffect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LowPass { freq, bandwidth } => f
+                .debug_struct("LowPass")
+                .field("freq", freq)
+                .field("bandwidth", bandwidth)
+                .finish(),
+            Self::HighPass { freq, bandwidth } => f
+                .debug_struct("HighPass")
+                .field("freq", freq)
+                .field("bandwidth", bandwidth)
+                .finish(),
+            Self::BandPass { freq, bandwidth } => f
+                .debug_struct("BandPass")
+                .field("freq", freq)
+                .field("bandwidth", bandwidth)
+                .finish(),
+            Self::Custom(_) => f.debug_tuple("Custom").finish(),
+        }
+    }
+}
+
+impl BusEffect {
+    fn apply(&self, source: Box<dyn Source>) -> Box<dyn Source> {
+        match self {
+            BusEffect::LowPass { freq, bandwidth } => Box::new(source.low_pass(*freq, *bandwidth)),
+            BusEffect::HighPass { freq, bandwidth } => {
+                Box::new(source.high_pass(*freq, *bandwidth))
+            }
+            BusEffect::BandPass { freq, bandwidth } => Box::new(source.blt(Constant(Bpf {
+                freq: *freq,
+                bandwidth: *bandwidth,
+            }))),
+            BusEffect::Custom(effect) => effect(source),
+        }
+    }
+}
+
+/// Lowers the volume of a bus while another bus, the sidechain, is playing. Such as lowering the
+/// music while dialogue plays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ducking {
+    pub sidechain: BusId,
+    /// The level of the sidechain above which the bus is ducked
+    pub threshold: f32,
+    /// The volume of the bus while ducked
+    pub volume: f32,
+    /// How long it takes to duck the bus
+    pub attack: Duration,
+    /// How long it takes for the bus to recover after the sidechain has fallen below the threshold
+    pub release: Duration,
+}
+
+/// How long it takes for the level of a bus to fall to a third after its signal has stopped
+const LEVEL_DECAY: Duration = Duration::from_millis(50);
+
+/// The signal of a bus, one frame at a time, as the input to its effects
+struct BusInput {
+    frame: Arc<Mutex<Frame>>,
+    sample_rate: SampleRate,
+}
+
+impl Source for BusInput {
+    fn next_sample(&mut self) -> Option<Frame> {
+        Some(*self.frame.lock())
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        None
+    }
+}
+
+type EffectChain = (Arc<Mutex<Frame>>, Box<dyn Source>);
+
+/// A node in the hierarchy of buses, which mixes the sounds played on it and its child buses,
+/// and mixes the result into its parent
+pub(crate) struct Bus {
+    pub name: String,
+    pub parent: Option<BusId>,
+    pub volume: f32,
+    pub ducking: Option<Ducking>,
+    effects: Vec<BusEffect>,
+    /// The input and output of the effects
+    chain: Option<EffectChain>,
+    duck_volume: f32,
+    /// The peak level of the output, decaying over time
+    pub level: f32,
+    /// The signal mixed into the bus during the current block
+    pub buffer: Vec<Frame>,
+}
+
+impl Bus {
+    pub fn new(name: String, parent: Option<BusId>) -> Self {
+        Self {
+            name,
+            parent,
+            volume: 1.0,
+            ducking: None,
+            effects: Vec::new(),
+            chain: None,
+            duck_volume: 1.0,
+            level: 0.0,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn effects(&self) -> &[BusEffect] {
+        &self.effects
+    }
+
+    pub fn set_effects(&mut self, effects: Vec<BusEffect>, sample_rate: SampleRate) {
+        self.chain = if effects.is_empty() {
+            None
+        } else {
+            let frame = Arc::new(Mutex::new(Frame::ZERO));
+            let input: Box<dyn Source> = Box::new(BusInput {
+                frame: frame.clone(),
+                sample_rate,
+            });
+            let chain = effects
+                .iter()
+                .fold(input, |source, effect| effect.apply(source));
+            Some((frame, chain))
+        };
+        self.effects = effects;
+    }
+
+    /// Applies the effects, volume and ducking to the buffer, given the level of the sidechain
+    pub fn process(&mut self, sidechain_level: f32, sample_rate: SampleRate) {
+        let frames = |dur: Duration| (dur.as_secs_f32() * sample_rate as f32).max(1.0);
+        let decay = (-1.0 / frames(LEVEL_DECAY)).exp();
+
+        for frame in &mut self.buffer {
+            if let Some((input, chain)) = &mut self.chain {
+                *input.lock() = *frame;
+                *frame = chain.next_sample().unwrap_or_default();
+            }
+
+            if let Some(ducking) = &self.ducking {
+                let ducked = sidechain_level > ducking.threshold;
+                let range = (1.0 - ducking.volume).abs();
+                if ducked {
+                    let step = range / frames(ducking.attack);
+                    self.duck_volume = (self.duck_volume - step).max(ducking.volume);
+                } else {
+                    let step = range / frames(ducking.release);
+                    self.duck_volume = (self.duck_volume + step).min(1.0);
+                }
+            } else {
+                self.duck_volume = 1.0;
+            }
+
+            *frame *= self.volume * self.duck_volume;
+            self.level = frame.abs().max_element().max(self.level * decay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AudioMixer, BufferedSource};
+
+    #[test]
+    fn bus_volume() {
+        let mut mixer = AudioMixer::new(10);
+        let music = mixer.add_bus("music", mixer.master_bus());
+        let quiet = mixer.add_bus("quiet", music);
+        mixer.set_bus_volume(music, 0.5);
+        mixer.set_bus_volume(quiet, 0.5);
+        mixer.play_on(music, BufferedSource::new(vec![1.0f32; 4], 1, 10));
+        mixer.play_on(quiet, BufferedSource::new(vec![1.0f32; 4], 1, 10));
+
+        assert_eq!(mixer.find_bus("quiet"), Some(quiet));
+        assert_eq!(mixer.next_sample(), Some(Frame::splat(0.5 + 0.25)));
+    }
+
+    #[test]
+    fn ducking() {
+        let mut mixer = AudioMixer::new(10);
+        let music = mixer.add_bus("music", mixer.master_bus());
+        let voice = mixer.add_bus("voice", mixer.master_bus());
+        mixer.set_bus_ducking(
+            music,
+            Some(Ducking {
+                sidechain: voice,
+                threshold: 0.1,
+                volume: 0.5,
+                attack: Duration::from_millis(200),
+                release: Duration::from_millis(200),
+            }),
+        );
+        mixer.play_on(music, BufferedSource::new(vec![1.0f32; 16], 1, 10));
+        mixer.play_on(
+            voice,
+            BufferedSource::new(vec![0.0, 0.0, 1.0, 1.0, 1.0, 1.0], 1, 10),
+        );
+
+        // The music is ducked a frame after the voice starts, and recovers once the level of the
+        // voice has decayed
+        let samples = (0..11)
+            .map(|_| mixer.next_sample().unwrap().x)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            samples,
+            [1.0, 1.0, 2.0, 1.75, 1.5, 1.5, 0.5, 0.5, 0.5, 0.75, 1.0]
+        );
+    }
+}
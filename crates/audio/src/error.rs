@@ -21,6 +21,8 @@ pub enum Error {
     PlayStreamError(#[from] PlayStreamError),
     #[error("Failed to decode wav")]
     WavError(#[from] hound::Error),
+    #[error("Failed to write wav")]
+    WavWrite(hound::Error),
     #[error("Unsupported file format: {0:?}")]
     UnsupportedFormat(String),
     #[error("Failed to open {1:?}: {0}")]
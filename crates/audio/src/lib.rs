@@ -1,11 +1,13 @@
 mod assets;
 mod error;
 mod mixer;
+mod offline;
 // mod sink;
 mod stream;
 
 mod barycentric;
 pub mod blt;
+mod bus;
 /// Fast fourier transform
 pub mod hrtf;
 pub mod signal;
@@ -18,8 +20,10 @@ pub mod vorbis;
 pub mod wav;
 
 pub use assets::*;
+pub use bus::{BusEffect, BusId, CustomEffect, Ducking};
 pub use error::*;
 pub use mixer::*;
+pub use offline::*;
 // pub use sink::*;
 pub use source::*;
 pub use spatial::*;
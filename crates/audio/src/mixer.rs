@@ -6,7 +6,7 @@ use parking_lot::Mutex;
 use slotmap::{new_key_type, SlotMap};
 
 use crate::{
-    signal::{AsyncSignal, BlockingSignal, Signal}, Frame, SampleConversion, SampleRate, Source
+    bus::{Bus, BusEffect, BusId, Ducking}, signal::{AsyncSignal, BlockingSignal, Signal}, Frame, SampleConversion, SampleRate, Source
 };
 
 new_key_type! {
@@ -19,6 +19,7 @@ struct PlayingSound {
     #[allow(dead_code)]
     cursor: usize,
     source: Box<dyn Source>,
+    bus: BusId,
 }
 
 /// Handle to a playing sound
@@ -99,6 +100,11 @@ struct AudioMixerInner {
     sample_rate: SampleRate,
     waiters: Mutex<SignalVec>,
     sources: Mutex<SlotMap<SoundId, PlayingSound>>,
+    buses: Mutex<SlotMap<BusId, Bus>>,
+    /// The buses in the order they were added. Buses are never removed, and are added after their
+    /// parents, so mixing them in reverse order mixes every bus before its parent
+    bus_order: Mutex<Vec<BusId>>,
+    master_bus: BusId,
 }
 
 impl std::fmt::Debug for AudioMixerInner {
@@ -106,17 +112,23 @@ impl std::fmt::Debug for AudioMixerInner {
         f.debug_struct("AudioMixerInner")
             .field("sample_rate", &self.sample_rate)
             .field("sources", &self.sources.lock().len())
+            .field("buses", &self.buses.lock().len())
             .finish()
     }
 }
 
 impl AudioMixer {
     pub fn new(sample_rate: SampleRate) -> Self {
+        let mut buses = SlotMap::with_key();
+        let master_bus = buses.insert(Bus::new("master".to_string(), None));
         Self {
             inner: Arc::new(AudioMixerInner {
                 sample_rate,
                 sources: Mutex::default(),
                 waiters: Default::default(),
+                buses: Mutex::new(buses),
+                bus_order: Mutex::new(vec![master_bus]),
+                master_bus,
             }),
         }
     }
@@ -127,8 +139,16 @@ impl AudioMixer {
         }
     }
 
-    /// Play a source on the mixer, returning a handle which can be used to control it
+    /// Play a source on the master bus of the mixer, returning a handle which can be used to
+    /// control it
     pub fn play<S: Source + 'static>(&self, source: S) -> Sound {
+        self.play_on(self.inner.master_bus, source)
+    }
+
+    /// Play a source on a bus of the mixer, returning a handle which can be used to control it.
+    ///
+    /// Sources played on a bus which does not exist are played on the master bus
+    pub fn play_on<S: Source + 'static>(&self, bus: BusId, source: S) -> Sound {
         let sample_rate = source.sample_rate();
 
         let source = if sample_rate == self.inner.sample_rate {
@@ -137,11 +157,11 @@ impl AudioMixer {
             Box::new(SampleConversion::new(source, self.inner.sample_rate as _)) as Box<dyn Source>
         };
 
-        let id = self
-            .inner
-            .sources
-            .lock()
-            .insert(PlayingSound { cursor: 0, source });
+        let id = self.inner.sources.lock().insert(PlayingSound {
+            cursor: 0,
+            source,
+            bus,
+        });
         Sound {
             id,
             mixer: self.clone(),
@@ -168,22 +188,11 @@ impl AudioMixer {
 
 impl Source for AudioMixer {
     fn next_sample(&mut self) -> Option<crate::Frame> {
-        let mut sources = self.inner.sources.lock();
-        let mut res = Frame::ZERO;
-        sources.retain(|id, source| {
-            let sample = match source.source.next_sample() {
-                Some(v) => v,
-                None => {
-                    self.terminate_source(id, source);
-                    return false;
-                }
-            };
-            res += sample;
-
-            true
-        });
-
-        Some(res)
+        // The buffers of the buses keep their capacity between blocks, so mixing a single frame
+        // doesn't allocate
+        let mut res = [Frame::ZERO];
+        self.sample_buffered(&mut res);
+        Some(res[0])
     }
 
     fn sample_rate(&self) -> crate::SampleRate {
@@ -191,9 +200,20 @@ impl Source for AudioMixer {
     }
 
     fn sample_buffered(&mut self, output: &mut [Frame]) -> usize {
+        let mut buses = self.inner.buses.lock();
+        for (_, bus) in buses.iter_mut() {
+            bus.buffer.clear();
+            bus.buffer.resize(output.len(), Frame::ZERO);
+        }
+
         let mut sources = self.inner.sources.lock();
         sources.retain(|id, source| {
-            let written = source.source.sample_buffered(output);
+            let bus = if buses.contains_key(source.bus) {
+                source.bus
+            } else {
+                self.inner.master_bus
+            };
+            let written = source.source.sample_buffered(&mut buses[bus].buffer);
 
             // No more samples in source
             if written != output.len() {
@@ -204,6 +224,28 @@ impl Source for AudioMixer {
             true
         });
 
+        // Mix the buses into their parents, from the leaves up to the master bus
+        let order = self.inner.bus_order.lock();
+        for &id in order.iter().rev() {
+            let sidechain_level = buses[id]
+                .ducking
+                .and_then(|ducking| Some(buses.get(ducking.sidechain)?.level))
+                .unwrap_or_default();
+
+            let bus = &mut buses[id];
+            bus.process(sidechain_level, self.inner.sample_rate);
+
+            let buffer = std::mem::take(&mut bus.buffer);
+            let dst = match bus.parent {
+                Some(parent) => &mut buses[parent].buffer[..],
+                None => &mut *output,
+            };
+            for (dst, src) in dst.iter_mut().zip(&buffer) {
+                *dst += *src;
+            }
+            buses[id].buffer = buffer;
+        }
+
         output.len()
     }
 
@@ -212,6 +254,67 @@ impl Source for AudioMixer {
     }
 }
 
+/// Buses
+impl AudioMixer {
+    /// The bus all other buses are mixed into, which is mixed into the output
+    pub fn master_bus(&self) -> BusId {
+        self.inner.master_bus
+    }
+
+    /// Adds a bus which is mixed into `parent`
+    pub fn add_bus(&self, name: impl Into<String>, parent: BusId) -> BusId {
+        let mut buses = self.inner.buses.lock();
+        let parent = if buses.contains_key(parent) {
+            parent
+        } else {
+            self.inner.master_bus
+        };
+        let id = buses.insert(Bus::new(name.into(), Some(parent)));
+        self.inner.bus_order.lock().push(id);
+        id
+    }
+
+    /// Returns the first bus with the name
+    pub fn find_bus(&self, name: &str) -> Option<BusId> {
+        let buses = self.inner.buses.lock();
+        buses
+            .iter()
+            .find(|(_, bus)| bus.name == name)
+            .map(|(id, _)| id)
+    }
+
+    pub fn bus_volume(&self, bus: BusId) -> Option<f32> {
+        Some(self.inner.buses.lock().get(bus)?.volume)
+    }
+
+    pub fn set_bus_volume(&self, bus: BusId, volume: f32) {
+        if let Some(bus) = self.inner.buses.lock().get_mut(bus) {
+            bus.volume = volume;
+        }
+    }
+
+    pub fn bus_effects(&self, bus: BusId) -> Vec<BusEffect> {
+        let buses = self.inner.buses.lock();
+        buses
+            .get(bus)
+            .map(|bus| bus.effects().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the effects of the bus, which are applied in order to everything mixed into it
+    pub fn set_bus_effects(&self, bus: BusId, effects: Vec<BusEffect>) {
+        if let Some(bus) = self.inner.buses.lock().get_mut(bus) {
+            bus.set_effects(effects, self.inner.sample_rate);
+        }
+    }
+
+    pub fn set_bus_ducking(&self, bus: BusId, ducking: Option<Ducking>) {
+        if let Some(bus) = self.inner.buses.lock().get_mut(bus) {
+            bus.ducking = ducking;
+        }
+    }
+}
+
 impl AudioMixer {
     /// Wait until all audio has stopped playing.
     /// May wait forever on infinite tracks if no timeout is given.
@@ -0,0 +1,153 @@
+use std::{path::Path, time::Duration};
+
+use crate::{wav, AudioMixer, Frame, Result, SampleRate, Source};
+
+/// An audio output without a device, for when there is none such as on CI or headless clients.
+///
+/// Instead of following the clock, the mixer is advanced by simulated time with
+/// [OfflineStream::advance]. The mix can be recorded, and written to a WAV file
+pub struct OfflineStream {
+    mixer: AudioMixer,
+    /// The simulated time, and the number of frames mixed during it
+    time: Duration,
+    frames: u64,
+    recording: Option<Vec<Frame>>,
+    buf: Vec<Frame>,
+}
+
+impl OfflineStream {
+    pub fn new(sample_rate: SampleRate) -> Self {
+        Self {
+            mixer: AudioMixer::new(sample_rate),
+            time: Duration::ZERO,
+            frames: 0,
+            recording: None,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the audio stream's mixer.
+    #[must_use]
+    pub fn mixer(&self) -> &AudioMixer {
+        &self.mixer
+    }
+
+    /// The simulated time the mixer has been advanced by
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Records the mix from now on, discarding any previous recording
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// The mix since recording was started
+    pub fn recording(&self) -> Option<&[Frame]> {
+        self.recording.as_deref()
+    }
+
+    /// The duration of the recording
+    pub fn recorded_duration(&self) -> Duration {
+        let frames = self
+            .recording
+            .as_ref()
+            .map_or(0, |recording| recording.len() as u64);
+        Duration::from_nanos(frames * 1_000_000_000 / self.mixer.sample_rate())
+    }
+
+    /// Mixes the sounds playing during the next `duration` of simulated time.
+    ///
+    /// The number of frames is rounded so that no time is lost over many small steps
+    pub fn advance(&mut self, duration: Duration) {
+        self.time += duration;
+        let frames =
+            (self.time.as_nanos() * self.mixer.sample_rate() as u128 / 1_000_000_000) as u64;
+        self.advance_frames(frames - self.frames);
+    }
+
+    fn advance_frames(&mut self, count: u64) {
+        self.frames += count;
+        self.buf.clear();
+        self.buf.resize(count as usize, Frame::ZERO);
+        self.mixer.sample_buffered(&mut self.buf);
+
+        if let Some(recording) = &mut self.recording {
+            recording.extend_from_slice(&self.buf);
+        }
+    }
+
+    /// Writes the recording to a stereo 32 bit float WAV file
+    pub fn write_recording(&self, path: impl AsRef<Path>) -> Result<()> {
+        wav::write_wav(
+            path,
+            self.recording().unwrap_or_default(),
+            self.mixer.sample_rate(),
+        )
+    }
+}
+
+impl std::fmt::Debug for OfflineStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OfflineStream")
+            .field("time", &self.time)
+            .field("frames", &self.frames)
+            .field("recording", &self.recording.as_ref().map(|v| v.len()))
+            .finish()
+    }
+}
+
+/// The root mean square of the difference between two mixes, where the shorter one is padded with
+/// silence.
+///
+/// Used to compare a mix to a golden recording
+pub fn rms_difference(a: &[Frame], b: &[Frame]) -> f32 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 0.0;
+    }
+
+    let sum: f32 = (0..len)
+        .map(|i| {
+            let diff =
+                a.get(i).copied().unwrap_or_default() - b.get(i).copied().unwrap_or_default();
+            diff.length_squared() / 2.0
+        })
+        .sum();
+
+    (sum / len as f32).sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BufferedSource;
+
+    #[test]
+    fn offline_stream() {
+        let mut stream = OfflineStream::new(10);
+        stream
+            .mixer()
+            .play(BufferedSource::new(vec![0.5f32; 20], 1, 10));
+
+        stream.advance(Duration::from_millis(50));
+        stream.start_recording();
+        // Half a frame is carried over to the next step
+        stream.advance(Duration::from_millis(250));
+        stream.advance(Duration::from_millis(250));
+        assert_eq!(stream.recording().unwrap().len(), 5);
+
+        stream.advance(Duration::from_secs(2));
+        let recording = stream.recording().unwrap();
+        assert_eq!(recording.len(), 25);
+        assert_eq!(recording[19], Frame::splat(0.5));
+        assert_eq!(recording[20], Frame::ZERO);
+
+        let path = std::env::temp_dir().join("ambient_audio_offline_stream.wav");
+        stream.write_recording(&path).unwrap();
+        assert_eq!(
+            rms_difference(&wav::read_wav(&path).unwrap(), recording),
+            0.0
+        );
+    }
+}
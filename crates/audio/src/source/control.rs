@@ -0,0 +1,233 @@
+use std::{mem, sync::Arc, time::Duration};
+
+use parking_lot::Mutex;
+
+use crate::{
+    hrtf::HrtfLib, AudioEmitter, AudioListener, BufferedSource, Frame, SampleRate, Source,
+};
+
+#[derive(Default)]
+struct ControlState {
+    stopped: bool,
+    paused: bool,
+    pitch: Option<f32>,
+    /// The volume to fade to, and how long it takes
+    fade: Option<(f32, Duration)>,
+    emitter: Option<(
+        Arc<HrtfLib>,
+        Arc<Mutex<AudioListener>>,
+        Arc<Mutex<AudioEmitter>>,
+    )>,
+}
+
+/// Controls a [Controlled] source while it is playing
+#[derive(Clone, Default)]
+pub struct SoundControl {
+    state: Arc<Mutex<ControlState>>,
+}
+
+impl std::fmt::Debug for SoundControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("SoundControl")
+            .field("stopped", &state.stopped)
+            .field("paused", &state.paused)
+            .finish()
+    }
+}
+
+impl SoundControl {
+    /// Ends the source. A stopped source can not be resumed.
+    ///
+    /// Sources are also stopped when they have no more samples
+    pub fn stop(&self) {
+        self.state.lock().stopped = true;
+    }
+
+    /// Plays silence instead of the source, without advancing it, until resumed
+    pub fn set_paused(&self, paused: bool) {
+        self.state.lock().paused = paused;
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.fade(volume, Duration::ZERO);
+    }
+
+    /// Linearly changes the volume to `volume` over `duration`
+    pub fn fade(&self, volume: f32, duration: Duration) {
+        self.state.lock().fade = Some((volume, duration));
+    }
+
+    /// Plays the source faster or slower, which also raises or lowers its pitch.
+    ///
+    /// A pitch of `2.0` plays the source twice as fast, an octave higher
+    pub fn set_pitch(&self, pitch: f32) {
+        self.state.lock().pitch = Some(pitch.max(0.0));
+    }
+
+    /// Spatializes the source from now on, emitting it from `emitter` relative to `listener`
+    pub fn attach(
+        &self,
+        hrtf_lib: Arc<HrtfLib>,
+        listener: Arc<Mutex<AudioListener>>,
+        emitter: Arc<Mutex<AudioEmitter>>,
+    ) {
+        self.state.lock().emitter = Some((hrtf_lib, listener, emitter));
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.state.lock().stopped
+    }
+}
+
+/// A source which can be stopped, paused, faded, pitched and spatialized through a
+/// [SoundControl] while it plays
+pub struct Controlled {
+    source: Box<dyn Source>,
+    control: SoundControl,
+    paused: bool,
+    volume: f32,
+    target_volume: f32,
+    /// Change of volume per frame while fading
+    fade_step: f32,
+    pitch: f32,
+    /// The two source frames the output is interpolated between, and how far between them the
+    /// output is
+    prev: Frame,
+    next: Frame,
+    t: f32,
+}
+
+impl Controlled {
+    pub fn new<S: Source + 'static>(source: S, control: SoundControl) -> Self {
+        Self {
+            source: Box::new(source),
+            control,
+            paused: false,
+            volume: 1.0,
+            target_volume: 1.0,
+            fade_step: 0.0,
+            pitch: 1.0,
+            prev: Frame::ZERO,
+            next: Frame::ZERO,
+            t: 1.0,
+        }
+    }
+
+    fn update(&mut self) -> bool {
+        let mut state = self.control.state.lock();
+        if state.stopped {
+            return false;
+        }
+
+        self.paused = state.paused;
+        if let Some(pitch) = state.pitch.take() {
+            self.pitch = pitch;
+        }
+
+        if let Some((volume, duration)) = state.fade.take() {
+            let frames = duration.as_secs_f32() * self.source.sample_rate() as f32;
+            self.target_volume = volume;
+            self.fade_step = if frames >= 1.0 {
+                (volume - self.volume).abs() / frames
+            } else {
+                f32::INFINITY
+            };
+        }
+
+        if let Some((hrtf_lib, listener, emitter)) = state.emitter.take() {
+            let source = mem::replace(
+                &mut self.source,
+                Box::new(BufferedSource::new([0.0f32; 0], 1, 1)),
+            );
+            self.source = Box::new(source.spatial(&hrtf_lib, listener, emitter));
+        }
+
+        true
+    }
+}
+
+impl Source for Controlled {
+    fn next_sample(&mut self) -> Option<Frame> {
+        if !self.update() {
+            return None;
+        }
+
+        if self.paused {
+            return Some(Frame::ZERO);
+        }
+
+        while self.t >= 1.0 {
+            self.prev = self.next;
+            let Some(next) = self.source.next_sample() else {
+                // Let the controls know that the source has ended
+                self.control.stop();
+                return None;
+            };
+            self.next = next;
+            self.t -= 1.0;
+        }
+        let sample = self.prev.lerp(self.next, self.t);
+        self.t += self.pitch;
+
+        if self.volume < self.target_volume {
+            self.volume = (self.volume + self.fade_step).min(self.target_volume);
+        } else {
+            self.volume = (self.volume - self.fade_step).max(self.target_volume);
+        }
+
+        Some(sample * self.volume)
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn controlled() {
+        let control = SoundControl::default();
+        let mut source = Controlled::new(
+            BufferedSource::new([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0], 1, 4),
+            control.clone(),
+        );
+
+        // The output lags one frame behind the source, to interpolate when pitched
+        assert_eq!(source.next_sample(), Some(Frame::ZERO));
+        assert_eq!(source.next_sample(), Some(Frame::splat(1.0)));
+
+        control.set_paused(true);
+        assert_eq!(source.next_sample(), Some(Frame::ZERO));
+        control.set_paused(false);
+
+        control.set_pitch(0.5);
+        let samples = (0..3)
+            .map(|_| source.next_sample().unwrap().x)
+            .collect_vec();
+        assert_eq!(samples, [2.0, 2.5, 3.0]);
+
+        control.set_pitch(1.0);
+        control.fade(0.0, Duration::from_millis(500));
+        let samples = (0..2)
+            .map(|_| source.next_sample().unwrap().x)
+            .collect_vec();
+        assert_eq!(samples, [3.5 * 0.5, 4.5 * 0.0]);
+
+        control.stop();
+        assert_eq!(source.next_sample(), None);
+    }
+}
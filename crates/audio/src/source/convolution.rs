@@ -0,0 +1,255 @@
+use std::{collections::VecDeque, fmt::Debug, sync::Arc};
+
+use glam::Vec2;
+use num::{complex::Complex32, Zero};
+use rustfft::{Fft, FftPlanner};
+
+use crate::{Frame, SampleRate, Source};
+
+/// The number of frames convolved at a time, which is also the latency of the convolved signal
+const BLOCK_LEN: usize = 512;
+
+/// The left and right channel
+type Spectrum = [Vec<Complex32>; 2];
+
+/// The response of a space to an impulse, such as the reverberation of a room, which sources are
+/// convolved with to sound like they are played in the space
+#[derive(Clone)]
+pub struct ImpulseResponse {
+    frames: Arc<[Frame]>,
+    sample_rate: SampleRate,
+}
+
+impl Debug for ImpulseResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImpulseResponse")
+            .field("len", &self.frames.len())
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
+}
+
+impl ImpulseResponse {
+    /// Reads the whole source as the impulse response.
+    ///
+    /// The response is normalized, so that convolving with it keeps the loudness of the source
+    pub fn new(mut source: impl Source) -> Self {
+        let sample_rate = source.sample_rate();
+        let mut frames: Vec<_> = std::iter::from_fn(|| source.next_sample()).collect();
+
+        let energy = frames.iter().fold(Vec2::ZERO, |acc, &v| acc + v * v);
+        let norm = energy.max_element().sqrt();
+        if norm > 0.0 {
+            for frame in &mut frames {
+                *frame /= norm;
+            }
+        }
+
+        Self {
+            frames: frames.into(),
+            sample_rate,
+        }
+    }
+
+    pub fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// Convolves the source with an [ImpulseResponse], and mixes the result with the unprocessed
+/// source.
+///
+/// The impulse response is split into blocks which are convolved in the frequency domain, so long
+/// responses are cheap. The convolved signal lags behind by one block.
+///
+/// The impulse response should have the same sample rate as the source
+pub struct Convolution<S> {
+    source: S,
+    wet: f32,
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+    /// The spectra of the blocks of the impulse response
+    partitions: Vec<Spectrum>,
+    /// The spectra of the latest inputs, newest first
+    history: VecDeque<Spectrum>,
+    /// The previous and the current block of input
+    input: Vec<Frame>,
+    /// The convolved signal of the previous block
+    output: Vec<Frame>,
+    cursor: usize,
+    /// The number of frames left of the tail, once the source has ended
+    tail: Option<usize>,
+    tail_len: usize,
+    scratch: Spectrum,
+}
+
+impl<S: Debug> Debug for Convolution<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Convolution")
+            .field("source", &self.source)
+            .field("wet", &self.wet)
+            .field("partitions", &self.partitions.len())
+            .finish()
+    }
+}
+
+impl<S: Source> Convolution<S> {
+    /// Mixes `wet` of the convolved signal with `1 - wet` of the source
+    pub fn new(source: S, ir: &ImpulseResponse, wet: f32) -> Self {
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(BLOCK_LEN * 2);
+        let ifft = planner.plan_fft_inverse(BLOCK_LEN * 2);
+
+        let partitions = ir
+            .frames
+            .chunks(BLOCK_LEN)
+            .map(|chunk| {
+                let mut spectrum = Self::empty_spectrum();
+                for (channel, spectrum) in spectrum.iter_mut().enumerate() {
+                    for (dst, src) in spectrum.iter_mut().zip(chunk) {
+                        *dst = Complex32::new(src[channel], 0.0);
+                    }
+                    fft.process(spectrum);
+                }
+                spectrum
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            source,
+            wet: wet.clamp(0.0, 1.0),
+            fft,
+            ifft,
+            history: VecDeque::with_capacity(partitions.len()),
+            partitions,
+            input: vec![Frame::ZERO; BLOCK_LEN * 2],
+            output: vec![Frame::ZERO; BLOCK_LEN],
+            cursor: 0,
+            tail: None,
+            tail_len: ir.len() + BLOCK_LEN,
+            scratch: Self::empty_spectrum(),
+        }
+    }
+
+    fn empty_spectrum() -> Spectrum {
+        [
+            vec![Complex32::zero(); BLOCK_LEN * 2],
+            vec![Complex32::zero(); BLOCK_LEN * 2],
+        ]
+    }
+
+    /// Convolves the input with the impulse response, using the uniformly partitioned
+    /// overlap-save method
+    fn process_block(&mut self) {
+        let mut spectrum = if self.history.len() >= self.partitions.len() {
+            self.history.pop_back().unwrap_or_else(Self::empty_spectrum)
+        } else {
+            Self::empty_spectrum()
+        };
+        for (channel, spectrum) in spectrum.iter_mut().enumerate() {
+            for (dst, src) in spectrum.iter_mut().zip(&self.input) {
+                *dst = Complex32::new(src[channel], 0.0);
+            }
+            self.fft.process(spectrum);
+        }
+        self.history.push_front(spectrum);
+
+        for (channel, acc) in self.scratch.iter_mut().enumerate() {
+            acc.fill(Complex32::zero());
+            for (input, partition) in self.history.iter().zip(&self.partitions) {
+                for ((acc, x), h) in acc.iter_mut().zip(&input[channel]).zip(&partition[channel]) {
+                    *acc += x * h;
+                }
+            }
+            self.ifft.process(acc);
+
+            // Only the second half is free of wrapped around samples
+            let scale = 1.0 / (BLOCK_LEN * 2) as f32;
+            for (dst, src) in self.output.iter_mut().zip(&acc[BLOCK_LEN..]) {
+                dst[channel] = src.re * scale;
+            }
+        }
+
+        self.input.copy_within(BLOCK_LEN.., 0);
+    }
+}
+
+impl<S: Source> Source for Convolution<S> {
+    fn next_sample(&mut self) -> Option<Frame> {
+        let dry = match self.tail {
+            None => self.source.next_sample(),
+            Some(_) => None,
+        };
+        let dry = match dry {
+            Some(dry) => dry,
+            None => {
+                // Let the reverberation ring out
+                let tail = self.tail.get_or_insert(self.tail_len);
+                if *tail == 0 {
+                    return None;
+                }
+                *tail -= 1;
+                Frame::ZERO
+            }
+        };
+
+        self.input[BLOCK_LEN + self.cursor] = dry;
+        let wet = self.output[self.cursor];
+
+        self.cursor += 1;
+        if self.cursor == BLOCK_LEN {
+            self.process_block();
+            self.cursor = 0;
+        }
+
+        Some(dry * (1.0 - self.wet) + wet * self.wet)
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        Some(self.source.sample_count()? + self.tail_len as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BufferedSource;
+
+    #[test]
+    fn convolution() {
+        let mut ir = vec![0.0f32; 700];
+        ir[0] = 1.0;
+        ir[600] = 0.5;
+        let ir = ImpulseResponse::new(BufferedSource::new(ir, 1, 100));
+
+        let mut input = vec![0.0f32; 1500];
+        input[10] = 1.0;
+        let mut source = BufferedSource::new(input, 1, 100).convolve(&ir, 1.0);
+        let output = std::iter::from_fn(|| source.next_sample()).collect::<Vec<_>>();
+        assert_eq!(output.len(), 1500 + 700 + BLOCK_LEN);
+
+        // Both echoes of the impulse are heard, a block late
+        let norm = 1.25f32.sqrt();
+        for (i, frame) in output.iter().enumerate() {
+            let expected = match i {
+                522 => 1.0 / norm,
+                1122 => 0.5 / norm,
+                _ => 0.0,
+            };
+            assert!((frame.x - expected).abs() < 1e-4, "{i}: {frame}");
+            assert_eq!(frame.x, frame.y);
+        }
+    }
+}
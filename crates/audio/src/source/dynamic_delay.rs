@@ -5,10 +5,14 @@ use glam::{vec2, Vec2};
 use crate::{Frame, Source};
 
 const BUFSIZE: usize = 1024;
+/// The longest delay of [DynamicDelay], in frames
+pub const MAX_DELAY: f32 = (BUFSIZE - 1) as f32;
 
+/// Delays the left and right channels of a source by up to `BUFSIZE - 1` (1023) frames each
 #[derive(Debug, Clone)]
-pub(crate) struct DynamicDelay<S> {
+pub struct DynamicDelay<S> {
     source: S,
+    /// The delay of each channel, in frames
     pub delay: Vec2,
     // A small buffer of samples to be able to shift
     buffer: VecDeque<Frame>,
@@ -1,7 +1,9 @@
 mod buffered;
 mod chain;
+mod control;
+mod convolution;
 mod crossfade;
-pub(crate) mod dynamic_delay;
+pub mod dynamic_delay;
 pub mod gain;
 pub mod history;
 mod mix;
@@ -22,6 +24,8 @@ use std::{
 pub use buffered::*;
 pub use chain::*;
 use circular_queue::CircularQueue;
+pub use control::*;
+pub use convolution::*;
 pub use crossfade::*;
 pub use gain::*;
 pub use mix::*;
@@ -185,6 +189,22 @@ pub trait Source: Send {
         BilinearTransform::new(self, transfer)
     }
 
+    /// Convolves the source with an impulse response, mixing `wet` of the convolved signal with the
+    /// source
+    fn convolve(self, ir: &ImpulseResponse, wet: f32) -> Convolution<Self>
+    where
+        Self: Sized,
+    {
+        Convolution::new(self, ir, wet)
+    }
+
+    fn controlled(self, control: SoundControl) -> Controlled
+    where
+        Self: Sized + 'static,
+    {
+        Controlled::new(self, control)
+    }
+
     fn history(self, freq: f32, buf: Arc<Mutex<CircularQueue<Frame>>>) -> History<Self>
     where
         Self: Sized,
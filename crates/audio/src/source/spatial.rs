@@ -3,7 +3,7 @@ use std::{ops::Deref, time::Duration};
 use glam::Vec3;
 
 use crate::{
-    hrtf::{Hrtf, HrtfContext, HrtfLib}, value::Value, AudioEmitter, AudioListener, Frame, Source, MAX_ANGULAR_SPEED, MAX_SPEED
+    hrtf::{Hrtf, HrtfContext, HrtfLib}, spatial::Occlusion, value::Value, AudioEmitter, AudioListener, Frame, Source, MAX_ANGULAR_SPEED, MAX_SPEED
 };
 
 #[derive(Debug)]
@@ -17,6 +17,7 @@ pub struct Spatial<S, L, E> {
     cur: usize,
     listener: L,
     emitter: E,
+    occlusion: Occlusion,
 }
 
 const BLOCK_DURATION: Duration = Duration::from_millis(15);
@@ -56,6 +57,7 @@ where
             len: 0,
             cur: 0,
             prev_to_source: ctx.to_source(),
+            occlusion: Occlusion::default(),
         }
     }
 
@@ -111,17 +113,20 @@ where
             self.cur += 1;
             Some(s)
         } else {
-            let ctx = {
+            let (ctx, occlusion) = {
                 let listener = self.listener.get();
                 let emitter = self.emitter.get();
 
                 let ctx = Self::calculate_hrtf_context(self.prev_to_source, &listener, &emitter);
                 self.prev_to_source = ctx.to_source();
 
-                ctx
+                (ctx, emitter.occlusion)
             };
 
             let new_len = self.hrtf.process(ctx, &mut self.output_buffer);
+            let sample_rate = self.sample_rate();
+            self.occlusion
+                .process(occlusion, &mut self.output_buffer[..new_len], sample_rate);
             self.len = new_len;
             self.cur = 1;
             if new_len == 0 {
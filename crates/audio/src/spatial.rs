@@ -1,8 +1,10 @@
-use std::f32::consts::E;
+use std::f32::consts::{E, TAU};
 
 use glam::{Mat4, Vec3};
 use serde::{Deserialize, Serialize};
 
+use crate::{Frame, SampleRate};
+
 /// The speed of sound in units/s
 pub const SPEED_OF_SOUND: f32 = 343.0;
 /// The "maximum" speed of the source relative to the listener, in
@@ -23,6 +25,10 @@ pub struct AudioEmitter {
     pub amplitude: f32,
     pub pos: Vec3,
     pub attenuation: Attenuation,
+    /// How much the emitter is blocked from the listener, from 0 for not at all to 1 for fully.
+    /// Occluded emitters sound quieter and muffled
+    #[serde(default)]
+    pub occlusion: f32,
 }
 
 impl Default for AudioEmitter {
@@ -31,6 +37,7 @@ impl Default for AudioEmitter {
             amplitude: 1.0,
             pos: Default::default(),
             attenuation: Default::default(),
+            occlusion: 0.0,
         }
     }
 }
@@ -137,3 +144,69 @@ impl Attenuation {
         }
     }
 }
+
+/// The volume of a fully occluded emitter
+const OCCLUDED_GAIN: f32 = 0.3;
+/// The cutoff frequencies of the low-pass filter of an emitter which is barely and fully occluded
+const OPEN_CUTOFF: f32 = 20_000.0;
+const OCCLUDED_CUTOFF: f32 = 600.0;
+
+/// Muffles and attenuates the signal of an emitter by its occlusion
+#[derive(Debug, Default)]
+pub(crate) struct Occlusion {
+    /// The occlusion at the end of the previous block
+    amount: f32,
+    lowpass: Frame,
+}
+
+impl Occlusion {
+    /// Processes a block, changing the occlusion linearly to `target` over the block to avoid
+    /// clicks
+    pub fn process(&mut self, target: f32, block: &mut [Frame], sample_rate: SampleRate) {
+        let start = self.amount;
+        self.amount = target.clamp(0.0, 1.0);
+
+        if start == 0.0 && self.amount == 0.0 {
+            if let Some(&last) = block.last() {
+                self.lowpass = last;
+            }
+            return;
+        }
+
+        let len = block.len() as f32;
+        for (i, frame) in block.iter_mut().enumerate() {
+            let amount = start + (self.amount - start) * (i + 1) as f32 / len;
+            // Interpolate the cutoff exponentially, as pitch is perceived logarithmically
+            let cutoff = OPEN_CUTOFF * (OCCLUDED_CUTOFF / OPEN_CUTOFF).powf(amount);
+            let alpha = 1.0 - (-TAU * cutoff / sample_rate as f32).exp();
+            self.lowpass += (*frame - self.lowpass) * alpha;
+            *frame = self.lowpass * (1.0 + (OCCLUDED_GAIN - 1.0) * amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn occlusion() {
+        let mut occlusion = Occlusion::default();
+        let signal = |i: usize| Frame::splat([1.0, -1.0][i % 2]);
+
+        let mut block = (0..64).map(signal).collect::<Vec<_>>();
+        occlusion.process(0.0, &mut block, 44100);
+        assert_eq!(block, (0..64).map(signal).collect::<Vec<_>>());
+
+        // High frequencies are filtered out, and the rest is attenuated
+        let mut block = (0..64).map(signal).collect::<Vec<_>>();
+        occlusion.process(1.0, &mut block, 44100);
+        let mut block = (0..64).map(signal).collect::<Vec<_>>();
+        occlusion.process(1.0, &mut block, 44100);
+        assert!(block[32..].iter().all(|v| v.x.abs() < 0.05 * OCCLUDED_GAIN));
+
+        let mut block = vec![Frame::ONE; 1024];
+        occlusion.process(1.0, &mut block, 44100);
+        assert!((block[1023].x - OCCLUDED_GAIN).abs() < 1e-3);
+    }
+}
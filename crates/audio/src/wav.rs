@@ -1,12 +1,12 @@
-use std::{io::Cursor, sync::Arc};
+use std::{io::Cursor, path::Path, sync::Arc};
 
 use cpal::{ChannelCount, Sample};
 use derivative::Derivative;
 use glam::{vec2, Vec2};
-use hound::{SampleFormat, WavReader, WavSpec};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
 use itertools::Itertools;
 
-use crate::{Frame, Result, SampleRate, Source};
+use crate::{Error, Frame, Result, SampleRate, Source};
 
 #[derive(Derivative, Clone)]
 #[derivative(Debug)]
@@ -182,7 +182,7 @@ impl Source for WavDecodeStream {
             self.cursor += 1;
             Some(val)
         } else {
-            let &s = self.read_next_block().unwrap().get(1)?;
+            let &s = self.read_next_block().unwrap().first()?;
             self.cursor += 1;
             Some(s)
         }
@@ -196,3 +196,30 @@ impl Source for WavDecodeStream {
         Some(self.decoded_len as _)
     }
 }
+
+/// Reads and decodes all the frames of a WAV file
+pub fn read_wav(path: impl AsRef<Path>) -> Result<Vec<Frame>> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|err| Error::Io(err, path.to_path_buf()))?;
+    let mut source = WavTrack::new(bytes.into())?.decode();
+    Ok(std::iter::from_fn(|| source.next_sample()).collect())
+}
+
+/// Writes the frames to a stereo 32 bit float WAV file
+pub fn write_wav(path: impl AsRef<Path>, frames: &[Frame], sample_rate: SampleRate) -> Result<()> {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: sample_rate as _,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+
+    let mut writer = WavWriter::create(path, spec).map_err(Error::WavWrite)?;
+    for frame in frames {
+        writer.write_sample(frame.x).map_err(Error::WavWrite)?;
+        writer.write_sample(frame.y).map_err(Error::WavWrite)?;
+    }
+    writer.finalize().map_err(Error::WavWrite)?;
+
+    Ok(())
+}
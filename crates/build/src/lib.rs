@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeSet,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -10,7 +11,11 @@ use ambient_std::asset_url::AbsAssetUrl;
 use anyhow::Context;
 use futures::FutureExt;
 use itertools::Itertools;
-use pipelines::{FileCollection, ProcessCtx, ProcessCtxKey};
+use pipelines::{
+    cache::{BuildManifest, BUILD_MANIFEST_FILENAME},
+    FileCollection, ProcessCtx, ProcessCtxKey,
+};
+use relative_path::RelativePathBuf;
 use walkdir::WalkDir;
 
 pub mod pipelines;
@@ -22,7 +27,10 @@ pub mod pipelines;
 /// src/**  This is where you store Rust source files
 /// build  This is the output directory, and is created when building
 /// ambient.toml  This is a metadata file to describe the project
-pub async fn build(physics: Physics, _assets: &AssetCache, path: PathBuf, manifest: &ProjectManifest, optimize: bool) {
+///
+/// Assets are built incrementally: only the pipelines affected by changes since the last build are re-run.
+/// Specify `clean` to discard the previous build and rebuild everything.
+pub async fn build(physics: Physics, _assets: &AssetCache, path: PathBuf, manifest: &ProjectManifest, optimize: bool, clean: bool) {
     log::info!(
         "Building project `{}` ({})",
         manifest.project.id,
@@ -35,11 +43,24 @@ pub async fn build(physics: Physics, _assets: &AssetCache, path: PathBuf, manife
     let assets_path = path.join("assets");
 
     std::fs::create_dir_all(&build_path).unwrap();
-    build_assets(physics, &assets_path, &build_path).await;
+    build_assets(physics, &assets_path, &build_path, clean).await;
     build_rust_if_available(&path, manifest, &build_path, optimize).await.unwrap();
 }
 
-async fn build_assets(physics: Physics, assets_path: &Path, build_path: &Path) {
+/// Builds the assets in `assets_path` into `build_path`, and returns the paths (relative to `build_path/assets`)
+/// of the outputs that were produced or removed by this build.
+pub async fn build_assets(physics: Physics, assets_path: &Path, build_path: &Path, clean: bool) -> BTreeSet<RelativePathBuf> {
+    let manifest_path = build_path.join(BUILD_MANIFEST_FILENAME);
+    let out_path = build_path.join("assets");
+    let previous = if clean {
+        if out_path.exists() {
+            std::fs::remove_dir_all(&out_path).unwrap();
+        }
+        BuildManifest::new()
+    } else {
+        BuildManifest::load(&manifest_path)
+    };
+
     let files = WalkDir::new(assets_path)
         .into_iter()
         .filter_map(|e| e.ok())
@@ -77,10 +98,20 @@ async fn build_assets(physics: Physics, assets_path: &Path, build_path: &Path) {
         }),
     };
     ProcessCtxKey.insert(&ctx.assets, ctx.clone());
-    pipelines::process_pipelines(&ctx).await;
+    let (_, manifest) = pipelines::process_pipelines(&ctx, &previous).await;
+    manifest.remove_stale_outputs(&previous, &out_path);
+    if let Err(err) = manifest.save(&manifest_path) {
+        log::warn!("Failed to save build manifest: {err:?}");
+    }
+    manifest.changed_outputs(&previous)
 }
 
-async fn build_rust_if_available(project_path: &Path, manifest: &ProjectManifest, build_path: &Path, optimize: bool) -> anyhow::Result<()> {
+pub async fn build_rust_if_available(
+    project_path: &Path,
+    manifest: &ProjectManifest,
+    build_path: &Path,
+    optimize: bool,
+) -> anyhow::Result<()> {
     let cargo_toml_path = project_path.join("Cargo.toml");
     if !cargo_toml_path.exists() {
         return Ok(());
@@ -0,0 +1,282 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use ambient_std::asset_url::AbsAssetUrl;
+use parking_lot::Mutex;
+use relative_path::{RelativePath, RelativePathBuf};
+use serde::{Deserialize, Serialize};
+
+/// The name of the build manifest, which is stored in the build directory.
+pub const BUILD_MANIFEST_FILENAME: &str = "build_manifest.json";
+
+/// The version of the formats of the built assets. Bump this whenever the serialized form of an output changes (e.g. a field is
+/// added to [AnimationClip](ambient_animation::AnimationClip), which is stored with bincode), so that existing builds are
+/// rebuilt instead of failing to load.
+pub const ASSET_FORMAT_VERSION: u32 = 1;
+
+/// Records what each pipeline consumed and produced in the last build, so that
+/// pipelines whose inputs haven't changed can be skipped in the next one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// The version of Ambient that produced the build. Manifests from other versions are ignored.
+    pub ambient_version: String,
+    /// The [ASSET_FORMAT_VERSION] of the build. Manifests with other versions are ignored.
+    #[serde(default)]
+    pub format_version: u32,
+    /// The pipelines that were run, keyed by their pipeline path.
+    pub pipelines: BTreeMap<String, PipelineRecord>,
+}
+impl BuildManifest {
+    pub fn new() -> Self {
+        Self { ambient_version: env!("CARGO_PKG_VERSION").to_string(), format_version: ASSET_FORMAT_VERSION, pipelines: BTreeMap::new() }
+    }
+    /// Loads the manifest at `path`. A missing, unreadable or outdated manifest results in an empty one.
+    pub fn load(path: &Path) -> Self {
+        let manifest = match std::fs::read(path) {
+            Ok(data) => match serde_json::from_slice::<Self>(&data) {
+                Ok(manifest) => manifest,
+                Err(err) => {
+                    log::warn!("Failed to parse build manifest {path:?}, rebuilding all assets: {err}");
+                    return Self::new();
+                }
+            },
+            Err(_) => return Self::new(),
+        };
+        if manifest.ambient_version != env!("CARGO_PKG_VERSION") {
+            log::info!("Build manifest was produced by Ambient {}, rebuilding all assets", manifest.ambient_version);
+            return Self::new();
+        }
+        if manifest.format_version != ASSET_FORMAT_VERSION {
+            log::info!("Build manifest has asset format version {}, rebuilding all assets", manifest.format_version);
+            return Self::new();
+        }
+        manifest
+    }
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+    pub fn outputs(&self) -> BTreeSet<&RelativePathBuf> {
+        self.pipelines.values().flat_map(|record| record.outputs.iter()).collect()
+    }
+    /// Returns the outputs that differ between this build and `previous`: the outputs of the pipelines that were re-run,
+    /// and the outputs that are no longer produced.
+    pub fn changed_outputs(&self, previous: &BuildManifest) -> BTreeSet<RelativePathBuf> {
+        let outputs = self.outputs();
+        let rerun = self.pipelines.iter().filter(|(key, record)| previous.pipelines.get(*key) != Some(*record));
+        let removed = previous.outputs().into_iter().filter(|path| !outputs.contains(path));
+        rerun.flat_map(|(_, record)| record.outputs.iter()).chain(removed).cloned().collect()
+    }
+    /// Removes the files in `out_root` that were produced by `previous`, but are no longer produced by this build.
+    pub fn remove_stale_outputs(&self, previous: &BuildManifest, out_root: &Path) {
+        let outputs = self.outputs();
+        for stale in previous.outputs().into_iter().filter(|path| !outputs.contains(path)) {
+            let path = stale.to_logical_path(out_root);
+            match std::fs::remove_file(&path) {
+                Ok(()) => log::debug!("Removed stale output {stale}"),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    log::warn!("Failed to remove stale output {path:?}: {err}");
+                    continue;
+                }
+            }
+            // Clean up any directories that the removal left empty
+            let mut dir = path.parent();
+            while let Some(parent) = dir.filter(|dir| dir.starts_with(out_root) && *dir != out_root) {
+                if std::fs::remove_dir(parent).is_err() {
+                    break;
+                }
+                dir = parent.parent();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PipelineRecord {
+    /// The hash of the pipeline's configuration.
+    pub config_hash: String,
+    /// The hashes of the files the pipeline read, relative to the input root.
+    pub inputs: BTreeMap<RelativePathBuf, String>,
+    /// The files the pipeline wrote, relative to the output root.
+    pub outputs: BTreeSet<RelativePathBuf>,
+    /// Whether the pipeline finished without errors. Failed pipelines are always re-run.
+    pub succeeded: bool,
+}
+impl PipelineRecord {
+    /// Whether this record can be reused for a pipeline with `config_hash` and `inputs`.
+    pub fn is_up_to_date(&self, config_hash: &str, inputs: &BTreeMap<RelativePathBuf, String>, out_root: &Path) -> bool {
+        self.succeeded
+            && self.config_hash == config_hash
+            && &self.inputs == inputs
+            && self.outputs.iter().all(|path| path.to_logical_path(out_root).is_file())
+    }
+}
+
+/// Tracks the files a running pipeline reads and writes, and whether it failed.
+#[derive(Debug, Default)]
+pub struct PipelineTracker {
+    inputs: Mutex<BTreeSet<RelativePathBuf>>,
+    outputs: Mutex<BTreeSet<RelativePathBuf>>,
+    failed: AtomicBool,
+}
+impl PipelineTracker {
+    pub fn add_input(&self, path: RelativePathBuf) {
+        self.inputs.lock().insert(path);
+    }
+    pub fn add_output(&self, path: RelativePathBuf) {
+        self.outputs.lock().insert(path);
+    }
+    pub fn set_failed(&self) {
+        self.failed.store(true, Ordering::SeqCst);
+    }
+    pub fn inputs(&self) -> BTreeSet<RelativePathBuf> {
+        self.inputs.lock().clone()
+    }
+    pub fn outputs(&self) -> BTreeSet<RelativePathBuf> {
+        self.outputs.lock().clone()
+    }
+    pub fn failed(&self) -> bool {
+        self.failed.load(Ordering::SeqCst)
+    }
+}
+
+pub fn hash_bytes(data: &[u8]) -> String {
+    hex::encode(ring::digest::digest(&ring::digest::SHA256, data))
+}
+
+/// Hashes the files at `paths` (relative to `in_root`). Files that can't be read are left out,
+/// which makes them count as changed.
+pub async fn hash_inputs(in_root: &AbsAssetUrl, paths: impl IntoIterator<Item = RelativePathBuf>) -> BTreeMap<RelativePathBuf, String> {
+    let mut hashes = BTreeMap::new();
+    for path in paths {
+        let Some(file_path) = input_file_path(in_root, &path) else { continue };
+        let Ok(Ok(hash)) = tokio::task::spawn_blocking(move || std::fs::read(file_path).map(|data| hash_bytes(&data))).await else {
+            continue;
+        };
+        hashes.insert(path, hash);
+    }
+    hashes
+}
+
+fn input_file_path(in_root: &AbsAssetUrl, path: &RelativePath) -> Option<PathBuf> {
+    in_root.push(path.as_str()).ok()?.to_file_path().ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BuildDir {
+        in_root: PathBuf,
+        out_root: PathBuf,
+    }
+    impl BuildDir {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("ambient_build_cache_{name}"));
+            let _ = std::fs::remove_dir_all(&root);
+            let dir = Self { in_root: root.join("assets"), out_root: root.join("build") };
+            std::fs::create_dir_all(&dir.in_root).unwrap();
+            std::fs::create_dir_all(&dir.out_root).unwrap();
+            dir
+        }
+        fn in_url(&self) -> AbsAssetUrl {
+            AbsAssetUrl::from_directory_path(&self.in_root)
+        }
+        /// Simulates a run of a pipeline with `config_hash`, which reads `input.txt` and writes `output.txt`
+        async fn build(&self, config_hash: &str) -> PipelineRecord {
+            let input = RelativePathBuf::from("input.txt");
+            let output = RelativePathBuf::from("output.txt");
+            let data = std::fs::read(input.to_logical_path(&self.in_root)).unwrap();
+            std::fs::write(output.to_logical_path(&self.out_root), data).unwrap();
+            PipelineRecord {
+                config_hash: config_hash.to_string(),
+                inputs: hash_inputs(&self.in_url(), [input]).await,
+                outputs: [output].into_iter().collect(),
+                succeeded: true,
+            }
+        }
+        async fn is_up_to_date(&self, record: &PipelineRecord, config_hash: &str) -> bool {
+            let inputs = hash_inputs(&self.in_url(), record.inputs.keys().cloned()).await;
+            record.is_up_to_date(config_hash, &inputs, &self.out_root)
+        }
+    }
+
+    #[tokio::test]
+    async fn unchanged_input_is_skipped() {
+        let dir = BuildDir::new("unchanged");
+        std::fs::write(dir.in_root.join("input.txt"), "a").unwrap();
+        let record = dir.build("config").await;
+        assert!(dir.is_up_to_date(&record, "config").await);
+    }
+
+    #[tokio::test]
+    async fn changed_input_or_config_is_rebuilt() {
+        let dir = BuildDir::new("changed");
+        std::fs::write(dir.in_root.join("input.txt"), "a").unwrap();
+        let record = dir.build("config").await;
+        assert!(!dir.is_up_to_date(&record, "other config").await);
+
+        std::fs::write(dir.in_root.join("input.txt"), "b").unwrap();
+        assert!(!dir.is_up_to_date(&record, "config").await);
+
+        std::fs::remove_file(dir.in_root.join("input.txt")).unwrap();
+        assert!(!dir.is_up_to_date(&record, "config").await);
+    }
+
+    #[tokio::test]
+    async fn missing_output_is_rebuilt() {
+        let dir = BuildDir::new("missing_output");
+        std::fs::write(dir.in_root.join("input.txt"), "a").unwrap();
+        let record = dir.build("config").await;
+        std::fs::remove_file(dir.out_root.join("output.txt")).unwrap();
+        assert!(!dir.is_up_to_date(&record, "config").await);
+    }
+
+    #[tokio::test]
+    async fn failed_build_is_rebuilt() {
+        let dir = BuildDir::new("failed");
+        std::fs::write(dir.in_root.join("input.txt"), "a").unwrap();
+        let record = PipelineRecord { succeeded: false, ..dir.build("config").await };
+        assert!(!dir.is_up_to_date(&record, "config").await);
+    }
+
+    #[test]
+    fn outdated_format_is_rebuilt() {
+        let dir = BuildDir::new("format");
+        let path = dir.out_root.join(BUILD_MANIFEST_FILENAME);
+        let mut manifest = BuildManifest::new();
+        manifest.pipelines.insert("a".to_string(), PipelineRecord::default());
+        manifest.save(&path).unwrap();
+        assert_eq!(BuildManifest::load(&path).pipelines.len(), 1);
+
+        manifest.format_version = ASSET_FORMAT_VERSION - 1;
+        manifest.save(&path).unwrap();
+        assert!(BuildManifest::load(&path).pipelines.is_empty());
+    }
+
+    #[test]
+    fn changed_outputs() {
+        let record = |config_hash: &str, outputs: &[&str]| PipelineRecord {
+            config_hash: config_hash.to_string(),
+            inputs: BTreeMap::new(),
+            outputs: outputs.iter().map(|path| RelativePathBuf::from(*path)).collect(),
+            succeeded: true,
+        };
+        let mut previous = BuildManifest::new();
+        previous.pipelines.insert("a".to_string(), record("1", &["a/out.txt"]));
+        previous.pipelines.insert("b".to_string(), record("1", &["b/out.txt", "b/removed.txt"]));
+        previous.pipelines.insert("c".to_string(), record("1", &["c/out.txt"]));
+
+        let mut current = BuildManifest::new();
+        current.pipelines.insert("a".to_string(), record("1", &["a/out.txt"]));
+        current.pipelines.insert("b".to_string(), record("2", &["b/out.txt"]));
+
+        let changed = current.changed_outputs(&previous);
+        let expected = ["b/out.txt", "b/removed.txt", "c/out.txt"].into_iter().map(RelativePathBuf::from).collect::<BTreeSet<_>>();
+        assert_eq!(changed, expected);
+    }
+}
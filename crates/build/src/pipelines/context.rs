@@ -11,7 +11,7 @@ use itertools::Itertools;
 use relative_path::{RelativePath, RelativePathBuf};
 use tokio::sync::Semaphore;
 
-use super::{out_asset::OutAsset, FileCollection, Pipeline, ProcessCtx};
+use super::{cache::PipelineTracker, out_asset::OutAsset, FileCollection, Pipeline, ProcessCtx};
 
 #[derive(Clone)]
 pub struct PipelineCtx {
@@ -21,6 +21,7 @@ pub struct PipelineCtx {
     pub root_path: RelativePathBuf,
 
     pub pipeline: Arc<Pipeline>,
+    pub tracker: Arc<PipelineTracker>,
 }
 impl PipelineCtx {
     pub fn assets(&self) -> &AssetCache {
@@ -46,7 +47,13 @@ impl PipelineCtx {
         self.out_root().push(path).unwrap().as_directory().into()
     }
     pub async fn write_file(&self, path: impl AsRef<str>, content: Vec<u8>) -> AbsAssetUrl {
-        (self.process_ctx.write_file)(self.root_path.join(path.as_ref()).to_string(), content).await
+        let url = (self.process_ctx.write_file)(self.root_path.join(path.as_ref()).to_string(), content).await;
+        self.add_output(&url);
+        url
+    }
+    /// Records `url` as an output of this pipeline. Files written with [Self::write_file] are recorded automatically.
+    pub fn add_output(&self, url: &AbsAssetUrl) {
+        self.tracker.add_output(self.process_ctx.out_root.relative_path(url.path()));
     }
     pub async fn process_single<F: Future<Output = anyhow::Result<Vec<OutAsset>>> + Send>(
         &self,
@@ -63,6 +70,7 @@ impl PipelineCtx {
             Ok(Err(err)) => err,
             Err(err) => err,
         };
+        self.tracker.set_failed();
         (self.process_ctx.on_error)(err).await;
         Vec::new()
     }
@@ -132,6 +140,7 @@ impl PipelineCtx {
                     Ok(Err(err)) => err,
                     Err(err) => err,
                 };
+                self.tracker.set_failed();
                 (self.process_ctx.on_error)(err).await;
                 Vec::new()
             }
@@ -142,7 +151,9 @@ impl PipelineCtx {
         .collect()
     }
     pub fn get_downloadable_url(&self, url: &AbsAssetUrl) -> anyhow::Result<&AbsAssetUrl> {
-        self.process_ctx.files.0.iter().find(|x| x.path() == url.path()).with_context(|| format!("No such file: {url}"))
+        let file = self.process_ctx.files.0.iter().find(|x| x.path() == url.path()).with_context(|| format!("No such file: {url}"))?;
+        self.tracker.add_input(self.process_ctx.in_root.relative_path(file.path()));
+        Ok(file)
     }
 }
 
@@ -5,7 +5,7 @@ use ambient_decals::decal;
 use ambient_ecs::Entity;
 use ambient_model_import::{
     model_crate::{cap_texture_size, ModelCrate},
-    ModelTextureSize,
+    ModelTextureSize, TextureCompression,
 };
 use ambient_physics::collider::{collider, collider_type};
 use ambient_renderer::materials::pbr_material::PbrMaterialDesc;
@@ -47,6 +47,10 @@ pub struct MaterialsPipeline {
     /// Whether or not decal prefabs should be created for each of these materials.
     #[serde(default)]
     pub output_decals: bool,
+    /// If specified, the textures of the materials are compressed for the GPU, which makes them smaller to download
+    /// and in video memory.
+    #[serde(default)]
+    pub texture_compression: Option<TextureCompression>,
 }
 
 pub async fn pipeline(ctx: &PipelineCtx, config: MaterialsPipeline) -> Vec<OutAsset> {
@@ -56,7 +60,7 @@ pub async fn pipeline(ctx: &PipelineCtx, config: MaterialsPipeline) -> Vec<OutAs
                 let name = mat.name.as_ref().or(mat.source.as_ref()).unwrap().to_string();
 
                 let mat_out_url = ctx.out_root().join(ctx.pipeline_path())?.as_directory();
-                let material = mat.to_mat(&ctx, &ctx.in_root(), &mat_out_url).await?;
+                let material = mat.to_mat(&ctx, &ctx.in_root(), &mat_out_url, config.texture_compression).await?;
                 let base_color_url = material.base_color.clone().unwrap().resolve(&mat_out_url).unwrap();
                 let base_color = ImageFromUrl { url: base_color_url }.get(ctx.assets()).await?;
                 let mat_url = ctx.write_file(ctx.pipeline_path().join("mat.json"), serde_json::to_vec(&material).unwrap()).await;
@@ -151,14 +155,25 @@ pub struct PipelinePbrMaterial {
     pub specular_exponent: Option<f32>,
 }
 impl PipelinePbrMaterial {
-    pub async fn to_mat(&self, ctx: &PipelineCtx, source_root: &AbsAssetUrl, out_root: &AbsAssetUrl) -> anyhow::Result<PbrMaterialDesc> {
-        let pipe_image = |path: &Option<AssetUrl>| -> BoxFuture<'_, anyhow::Result<Option<AssetUrl>>> {
+    pub async fn to_mat(
+        &self,
+        ctx: &PipelineCtx,
+        source_root: &AbsAssetUrl,
+        out_root: &AbsAssetUrl,
+        texture_compression: Option<TextureCompression>,
+    ) -> anyhow::Result<PbrMaterialDesc> {
+        let pipe_image = |path: &Option<AssetUrl>, compress: bool| -> BoxFuture<'_, anyhow::Result<Option<AssetUrl>>> {
             let source_root = source_root.clone();
             let path = path.clone();
             let ctx = ctx.clone();
             async move {
                 if let Some(path) = path {
-                    Ok(Some(AssetUrl::from(PipeImage::resolve(&ctx, path.resolve(&source_root).unwrap()).get(ctx.assets()).await?)))
+                    Ok(Some(AssetUrl::from(
+                        PipeImage::resolve(&ctx, path.resolve(&source_root).unwrap())
+                            .compress(texture_compression.filter(|_| compress))
+                            .get_output(&ctx)
+                            .await?,
+                    )))
                 } else {
                     Ok(None)
                 }
@@ -168,11 +183,12 @@ impl PipelinePbrMaterial {
         Ok(PbrMaterialDesc {
             name: self.name.clone(),
             source: self.source.clone(),
-            base_color: pipe_image(&self.base_color).await?,
-            opacity: pipe_image(&self.opacity).await?,
-            normalmap: pipe_image(&self.normalmap).await?,
+            base_color: pipe_image(&self.base_color, true).await?,
+            opacity: pipe_image(&self.opacity, true).await?,
+            // BC1 and BC3 would ruin the precision of normal maps, so they are left uncompressed
+            normalmap: pipe_image(&self.normalmap, false).await?,
             metallic_roughness: if let Some(url) = &self.metallic_roughness {
-                Some(PipeImage::resolve(ctx, url.resolve(source_root).unwrap()).get(ctx.assets()).await?.into())
+                Some(PipeImage::resolve(ctx, url.resolve(source_root).unwrap()).compress(texture_compression).get_output(ctx).await?.into())
             } else if let Some(specular) = &self.specular {
                 let specular_exponent = self.specular_exponent.unwrap_or(1.);
                 Some(
@@ -186,7 +202,8 @@ impl PipelinePbrMaterial {
                                 p[3] = 255;
                             }
                         })
-                        .get(ctx.assets())
+                        .compress(texture_compression)
+                        .get_output(ctx)
                         .await?
                         .into(),
                 )
@@ -245,13 +262,14 @@ pub struct PipeImage {
     second_source: Option<AbsAssetUrl>,
     transform: Option<Box<dyn ImageTransformer>>,
     cap_texture_sizes: Option<ModelTextureSize>,
+    compression: Option<TextureCompression>,
 }
 impl PipeImage {
     pub fn resolve(ctx: &PipelineCtx, source: AbsAssetUrl) -> Self {
         Self::new(ctx.get_downloadable_url(&source).unwrap().clone())
     }
     pub fn new(source: AbsAssetUrl) -> Self {
-        PipeImage { source, second_source: None, transform: None, cap_texture_sizes: None }
+        PipeImage { source, second_source: None, transform: None, cap_texture_sizes: None, compression: None }
     }
     pub fn transform<F: Fn(&mut RgbaImage, Option<&RgbaImage>) + Sync + Send + 'static>(
         mut self,
@@ -265,6 +283,17 @@ impl PipeImage {
         self.cap_texture_sizes = cap_texture_sizes;
         self
     }
+    /// Outputs the image as a KTX2 file compressed with `compression`, instead of a PNG.
+    pub fn compress(mut self, compression: Option<TextureCompression>) -> Self {
+        self.compression = compression;
+        self
+    }
+    /// Processes the image, and records the result as an output of the pipeline in `ctx`.
+    pub async fn get_output(self, ctx: &PipelineCtx) -> AssetResult<Arc<AbsAssetUrl>> {
+        let url = self.get(ctx.assets()).await?;
+        ctx.add_output(&url);
+        Ok(url)
+    }
 }
 #[async_trait]
 impl AsyncAssetKey<AssetResult<Arc<AbsAssetUrl>>> for PipeImage {
@@ -275,7 +304,8 @@ impl AsyncAssetKey<AssetResult<Arc<AbsAssetUrl>>> for PipeImage {
             .await
             .with_context(|| format!("Failed to download image {}", self.source))?)
         .clone();
-        let mut extension = "png".to_string();
+        let file_extension = if self.compression.is_some() { "ktx2" } else { "png" };
+        let mut extension = file_extension.to_string();
         let second_image = if let Some(second_source) = &self.second_source {
             Some(
                 ImageFromUrl { url: second_source.clone() }
@@ -291,12 +321,16 @@ impl AsyncAssetKey<AssetResult<Arc<AbsAssetUrl>>> for PipeImage {
         tokio::task::block_in_place(|| {
             if let Some(transform) = &self.transform {
                 transform.transform(&mut image, second_image.as_deref());
-                extension = format!("{}.png", transform.name());
+                extension = format!("{}.{file_extension}", transform.name());
             }
             if let Some(size) = self.cap_texture_sizes {
                 cap_texture_size(&mut image, size.size());
             }
-            image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
+            if let Some(compression) = self.compression {
+                data = Cursor::new(compression.compress(&image));
+            } else {
+                image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
+            }
         });
         Ok(Arc::new((ctx.write_file)(path.with_extension(extension).to_string(), data.into_inner()).await))
     }
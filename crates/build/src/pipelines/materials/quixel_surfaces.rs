@@ -16,7 +16,8 @@ use super::{
 };
 use crate::pipelines::out_asset::asset_id_from_url;
 
-pub async fn pipeline(ctx: &PipelineCtx, _config: MaterialsPipeline) -> Vec<OutAsset> {
+pub async fn pipeline(ctx: &PipelineCtx, config: MaterialsPipeline) -> Vec<OutAsset> {
+    let texture_compression = config.texture_compression;
     ctx.process_files(
         |file| {
             file.extension() == Some("json".to_string())
@@ -34,6 +35,17 @@ pub async fn pipeline(ctx: &PipelineCtx, _config: MaterialsPipeline) -> Vec<OutA
             let tags =
                 quixel_json["tags"].as_array().unwrap().iter().map(|x| x.as_str().unwrap().to_string().to_case(Case::Title)).collect_vec();
             let pack_name = quixel_json["semanticTags"]["name"].as_str().unwrap().to_string();
+            let preview = asset_crate
+                .images
+                .content
+                .get("base_color")
+                .or(asset_crate.images.content.get("opacity"))
+                .or(asset_crate.images.content.get("normal"))
+                .map(|image| OutAssetPreview::Image { image: Arc::new(image.clone()) })
+                .unwrap_or(OutAssetPreview::None);
+            if let Some(compression) = texture_compression {
+                asset_crate.compress_textures(compression);
+            }
 
             let model_crate_url = ctx.write_model_crate(&asset_crate, &ctx.in_root().relative_path(file.path())).await;
 
@@ -44,14 +56,7 @@ pub async fn pipeline(ctx: &PipelineCtx, _config: MaterialsPipeline) -> Vec<OutA
                 name: pack_name.clone(),
                 tags,
                 categories: Default::default(),
-                preview: asset_crate
-                    .images
-                    .content
-                    .get("base_color")
-                    .or(asset_crate.images.content.get("opacity"))
-                    .or(asset_crate.images.content.get("normal"))
-                    .map(|image| OutAssetPreview::Image { image: Arc::new(image.clone()) })
-                    .unwrap_or(OutAssetPreview::None),
+                preview,
                 content: OutAssetContent::Content(model_crate_url.material(ModelCrate::MAIN).abs().unwrap()),
                 source: Some(file.clone()),
             });
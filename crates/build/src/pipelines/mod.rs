@@ -1,21 +1,28 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::Arc,
+};
 
 use ambient_asset_cache::SyncAssetKey;
+use ambient_gpu::ktx2::Ktx2Texture;
 use ambient_std::{asset_cache::AssetCache, asset_url::AbsAssetUrl};
 use anyhow::Context;
+use cache::{BuildManifest, PipelineRecord};
 use context::PipelineCtx;
 use futures::{future::BoxFuture, StreamExt};
 use image::ImageFormat;
 use out_asset::{OutAsset, OutAssetContent, OutAssetPreview};
 use serde::{Deserialize, Serialize};
 
-use self::{materials::MaterialsPipeline, models::ModelsPipeline};
+use self::{materials::MaterialsPipeline, models::ModelsPipeline, terrain::TerrainPipeline};
 
 pub mod audio;
+pub mod cache;
 pub mod context;
 pub mod materials;
 pub mod models;
 pub mod out_asset;
+pub mod terrain;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -29,6 +36,9 @@ pub enum PipelineConfig {
     /// The audio asset pipeline.
     /// Will import supported audio file formats and produce Ogg Vorbis files to be used by the runtime.
     Audio,
+    /// The terrain asset pipeline.
+    /// Will import 16-bit heightmaps, and maps of the other terrain layers, and split them into terrain cells.
+    Terrain(TerrainPipeline),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +64,7 @@ impl Pipeline {
             PipelineConfig::Models(config) => models::pipeline(&ctx, config.clone()).await,
             PipelineConfig::Materials(config) => materials::pipeline(&ctx, config.clone()).await,
             PipelineConfig::Audio => audio::pipeline(&ctx).await,
+            PipelineConfig::Terrain(config) => terrain::pipeline(&ctx, config.clone()).await,
         };
         for asset in &mut assets {
             asset.tags.extend(self.tags.clone());
@@ -67,7 +78,12 @@ impl Pipeline {
     }
 }
 
-pub async fn process_pipelines(ctx: &ProcessCtx) -> Vec<OutAsset> {
+/// Processes all of the pipelines found in `ctx.files`, and returns the assets they produced
+/// alongside a manifest describing the build.
+///
+/// Pipelines whose configuration and inputs are unchanged since the build described by `previous`
+/// are skipped; they produce no assets, but their outputs are carried over to the new manifest.
+pub async fn process_pipelines(ctx: &ProcessCtx, previous: &BuildManifest) -> (Vec<OutAsset>, BuildManifest) {
     log::info!("Processing pipeline with out_root={}", ctx.out_root);
 
     #[derive(Debug, Clone, Deserialize)]
@@ -85,7 +101,8 @@ pub async fn process_pipelines(ctx: &ProcessCtx) -> Vec<OutAsset> {
         }
     }
 
-    futures::stream::iter(ctx.files.0.iter())
+    let out_root_path = ctx.out_root.to_file_path().ok().flatten();
+    let results = futures::stream::iter(ctx.files.0.iter())
         .filter_map(|file| async move {
             let pipelines: PipelineOneOrMany = if file.0.path().ends_with("pipeline.json") {
                 file.download_json(&ctx.assets).await.unwrap()
@@ -109,14 +126,55 @@ pub async fn process_pipelines(ctx: &ProcessCtx) -> Vec<OutAsset> {
                 pipeline: Arc::new(pipeline.clone()),
                 pipeline_file,
                 root_path: ctx.in_root.relative_path(root.path()),
+                tracker: Default::default(),
             };
-            tokio::spawn(async move { pipeline.process(ctx).await })
+            let previous = previous.pipelines.get(ctx.pipeline_path().as_str()).cloned();
+            let out_root_path = out_root_path.clone();
+            tokio::spawn(async move {
+                let key = ctx.pipeline_path().to_string();
+                let in_root = &ctx.process_ctx.in_root;
+                let config_hash = cache::hash_bytes(&serde_json::to_vec(&pipeline).unwrap());
+                let directory_inputs = ctx
+                    .files
+                    .0
+                    .iter()
+                    .filter(|file| !file.path().ends_with("pipeline.json"))
+                    .map(|file| in_root.relative_path(file.path()))
+                    .collect::<BTreeSet<_>>();
+
+                let previous_inputs = previous.iter().flat_map(|record| record.inputs.keys().cloned());
+                let mut inputs = cache::hash_inputs(in_root, directory_inputs.iter().cloned().chain(previous_inputs)).await;
+                if let (Some(record), Some(out_root_path)) = (previous, &out_root_path) {
+                    if record.is_up_to_date(&config_hash, &inputs, out_root_path) {
+                        log::info!("[{key}] Up to date, skipping");
+                        return (key, record, Vec::new());
+                    }
+                }
+
+                let assets = pipeline.process(ctx.clone()).await;
+
+                // Only keep the inputs that this run used, hashing any that weren't known in advance
+                let used_inputs = directory_inputs.into_iter().chain(ctx.tracker.inputs()).collect::<BTreeSet<_>>();
+                inputs.retain(|path, _| used_inputs.contains(path));
+                let unhashed = used_inputs.into_iter().filter(|path| !inputs.contains_key(path)).collect::<Vec<_>>();
+                inputs.extend(cache::hash_inputs(in_root, unhashed).await);
+
+                let record = PipelineRecord { config_hash, inputs, outputs: ctx.tracker.outputs(), succeeded: !ctx.tracker.failed() };
+                (key, record, assets)
+            })
         })
         .buffered(30)
         .map(|x| x.unwrap())
-        .flat_map(|out_assets| futures::stream::iter(out_assets.into_iter()))
         .collect::<Vec<_>>()
-        .await
+        .await;
+
+    let mut manifest = BuildManifest::new();
+    let mut out_assets = Vec::new();
+    for (key, record, assets) in results {
+        manifest.pipelines.insert(key, record);
+        out_assets.extend(assets);
+    }
+    (out_assets, manifest)
 }
 
 #[derive(Debug, Clone)]
@@ -155,6 +213,12 @@ impl FileCollection {
 
 pub async fn download_image(assets: &AssetCache, url: &AbsAssetUrl) -> anyhow::Result<image::DynamicImage> {
     let data = url.download_bytes(assets).await?;
+    if url.extension_is("ktx2") {
+        let texture = Ktx2Texture::from_bytes(&data).with_context(|| format!("Failed to load image {url}"))?;
+        return Ok(image::DynamicImage::ImageRgba8(
+            texture.decode().with_context(|| format!("Can't decode {:?} textures", texture.format))?,
+        ));
+    }
     if let Some(format) = url.extension().as_ref().and_then(ImageFormat::from_extension) {
         Ok(image::load_from_memory_with_format(&data, format).with_context(|| format!("Failed to load image {url}"))?)
     } else {
@@ -1,8 +1,11 @@
 use std::{path::PathBuf, sync::Arc};
 
+use ambient_animation::AnimationEvent;
 use ambient_core::hierarchy::children;
 use ambient_ecs::Entity;
-use ambient_model_import::{model_crate::ModelCrate, MaterialFilter, ModelTextureSize, ModelTransform, TextureResolver};
+use ambient_model_import::{
+    model_crate::ModelCrate, MaterialFilter, MeshLodLevel, ModelTextureSize, ModelTransform, TextureCompression, TextureResolver,
+};
 use ambient_physics::collider::{collider_type, ColliderType};
 use ambient_std::asset_url::AssetType;
 use futures::FutureExt;
@@ -67,6 +70,9 @@ pub struct ModelsPipeline {
     collider_type: ColliderType,
     /// Whether or not this mesh should have its texture sizes capped.
     cap_texture_sizes: Option<ModelTextureSize>,
+    /// If specified, the textures of this model are compressed for the GPU, which makes them smaller to download and in video memory.
+    #[serde(default)]
+    texture_compression: Option<TextureCompression>,
     /// Treats all assets in the pipeline as variations, and outputs a single asset which is a collection of all assets.
     /// Most useful for grass and other entities whose individual identity is not important.
     #[serde(default)]
@@ -93,6 +99,13 @@ pub struct ModelsPipeline {
     /// These will be applied in sequence.
     #[serde(default)]
     transforms: Vec<ModelTransform>,
+    /// If specified, events to add to the animations of this model, in addition to the events that are read from the model files.
+    #[serde(default)]
+    animation_events: Vec<PipelineAnimationEvents>,
+    /// If specified, simplified levels of detail to generate for the meshes of this model, from the most to the least detailed.
+    /// A collider created from the model uses the least detailed level.
+    #[serde(default)]
+    lods: Vec<MeshLodLevel>,
 }
 impl ModelsPipeline {
     pub async fn apply(
@@ -105,13 +118,26 @@ impl ModelsPipeline {
             transform.apply(model_crate);
         }
         for mat in &self.material_overrides {
-            let material =
-                mat.material.to_mat(ctx, &ctx.in_root(), &ctx.out_root().push(out_model_path.as_ref().join("materials"))?).await?;
+            let material = mat
+                .material
+                .to_mat(ctx, &ctx.in_root(), &ctx.out_root().push(out_model_path.as_ref().join("materials"))?, self.texture_compression)
+                .await?;
             model_crate.override_material(&mat.filter, material);
         }
         if let Some(max_size) = self.cap_texture_sizes {
             model_crate.cap_texture_sizes(max_size.size());
         }
+        if let Some(compression) = self.texture_compression {
+            model_crate.compress_textures(compression);
+        }
+        for declared in &self.animation_events {
+            for (id, clip) in model_crate.animations.content.iter_mut() {
+                if declared.animation.as_ref().map(|animation| animation == id).unwrap_or(true) {
+                    clip.events.extend(declared.events.iter().cloned());
+                }
+            }
+        }
+        model_crate.generate_mesh_lods(&self.lods);
         model_crate.finalize_model();
         match self.collider {
             Collider::None => {}
@@ -136,6 +162,15 @@ pub struct MaterialOverride {
     pub material: PipelinePbrMaterial,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineAnimationEvents {
+    /// The animation to add the events to, by the name of its `.anim` file in the `animations` directory of the output.
+    /// If not specified, the events are added to all of the model's animations.
+    pub animation: Option<String>,
+    /// The events to add. Their times are in seconds from the start of the animation.
+    pub events: Vec<AnimationEvent>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(tag = "type")]
 pub enum ModelImporter {
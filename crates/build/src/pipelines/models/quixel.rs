@@ -126,7 +126,13 @@ pub async fn object_pipelines_from_quixel_json(
         async move {
             let pattern = format!("{}**/*{}", in_root_url.as_directory().path(), ending);
             let file = ctx.files.find_file_res(&pattern)?.clone();
-            Ok(AssetUrl::from(PipeImage::new(file).cap_texture_size(config.cap_texture_sizes).get(ctx.assets()).await?))
+            Ok(AssetUrl::from(
+                PipeImage::new(file)
+                    .cap_texture_size(config.cap_texture_sizes)
+                    .compress(config.texture_compression)
+                    .get_output(&ctx)
+                    .await?,
+            ))
         }
         .boxed()
     };
@@ -246,7 +252,8 @@ pub async fn object_pipelines_from_quixel_json(
                             PipeImage::new(ctx.get_downloadable_url(&in_root_url.push(ending).unwrap()).unwrap().clone())
                                 .transform("mr", |img, _| rougness_to_mr(img))
                                 .cap_texture_size(config.cap_texture_sizes)
-                                .get(ctx.assets())
+                                .compress(config.texture_compression)
+                                .get_output(ctx)
                                 .await?,
                         )))
                     } else {
@@ -296,7 +296,12 @@ impl UnityMaterials {
             };
             let get_image = |image_and_file: Option<(image::RgbaImage, AbsAssetUrl)>| {
                 if let Some((mut image, file)) = image_and_file {
-                    let out_image_path = self.ctx.in_root().relative_path(file.path()).prejoin("materials").with_extension("png");
+                    let out_image_path = self
+                        .ctx
+                        .in_root()
+                        .relative_path(file.path())
+                        .prejoin("materials")
+                        .with_extension(if config.texture_compression.is_some() { "ktx2" } else { "png" });
                     let ctx = self.ctx.clone();
                     let config = config.clone();
                     async move {
@@ -305,7 +310,11 @@ impl UnityMaterials {
                             if let Some(size) = config.cap_texture_sizes {
                                 cap_texture_size(&mut image, size.size());
                             }
-                            image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
+                            if let Some(compression) = config.texture_compression {
+                                data = Cursor::new(compression.compress(&image));
+                            } else {
+                                image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
+                            }
                         });
                         Some(ctx.write_file(&out_image_path, data.into_inner()).await)
                     }
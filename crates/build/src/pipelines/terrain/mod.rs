@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use ambient_std::asset_url::{AbsAssetUrl, AssetType};
+use ambient_terrain::{
+    heightmap::{cells_from_maps, decode_map, from_unit, resample, to_unit, value_range, HeightmapFormat},
+    TerrainCells, TerrainLayers, TerrainSize,
+};
+use glam::IVec2;
+use image::{imageops::FilterType, RgbaImage};
+use ndarray::{Array2, ArrayView2};
+use serde::{Deserialize, Serialize};
+use tracing::{info_span, Instrument};
+
+use super::{
+    context::PipelineCtx,
+    out_asset::{asset_id_from_url, OutAsset, OutAssetContent, OutAssetPreview},
+};
+
+pub const TERRAIN_EXTENSION: &str = "terrain";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerrainPipeline {
+    /// The height, in meters, that the lowest value of the heightmaps corresponds to. Defaults to 0.
+    #[serde(default)]
+    pub min_height: f32,
+    /// The height, in meters, that the highest value of the heightmaps corresponds to. Defaults to 100.
+    #[serde(default = "default_max_height")]
+    pub max_height: f32,
+    /// Maps of other terrain layers, such as soil or hardness, that accompany the heightmaps.
+    #[serde(default)]
+    pub layers: Vec<TerrainLayerMap>,
+    /// The cell at which the top left corner of the heightmaps is placed. Each cell is 64 meters wide,
+    /// and each texel of the heightmaps is one meter.
+    #[serde(default)]
+    pub origin_cell: IVec2,
+}
+fn default_max_height() -> f32 {
+    100.
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerrainLayerMap {
+    /// The layer the map is imported to.
+    pub layer: TerrainLayers,
+    /// The map of a heightmap is found by appending this to its file name; for example, a suffix of `_hardness` means
+    /// that `island_hardness.png` accompanies `island.png`. Heightmaps without the map leave the layer at zero.
+    pub suffix: String,
+    /// The value that the lowest value of the map corresponds to. Defaults to 0.
+    #[serde(default)]
+    pub min: f32,
+    /// The value that the highest value of the map corresponds to. Defaults to 1.
+    #[serde(default = "default_layer_max")]
+    pub max: f32,
+}
+fn default_layer_max() -> f32 {
+    1.
+}
+
+pub async fn pipeline(ctx: &PipelineCtx, config: TerrainPipeline) -> Vec<OutAsset> {
+    let suffixes = config.layers.iter().map(|layer| layer.suffix.clone()).collect::<Vec<_>>();
+    ctx.process_files(
+        move |file| {
+            let is_map = file.extension().and_then(|ext| HeightmapFormat::from_extension(&ext)).is_some();
+            let is_layer_map = file.path().file_stem().map_or(false, |stem| suffixes.iter().any(|suffix| stem.ends_with(suffix.as_str())));
+            is_map && !is_layer_map
+        },
+        move |ctx, file| {
+            let config = config.clone();
+            async move {
+                let stem = file.path().file_stem().unwrap().to_string();
+                let heights = download_map(&ctx, &file).await?;
+                let mut layers = vec![(TerrainLayers::Rock, from_unit(heights.view(), (config.min_height, config.max_height)))];
+                for map in &config.layers {
+                    // The map can have any of the supported formats
+                    let Some(url) = ["png", "raw", "r16"]
+                        .into_iter()
+                        .find_map(|ext| ctx.get_downloadable_url(&file.join(format!("{stem}{}.{ext}", map.suffix)).ok()?).ok().cloned())
+                    else {
+                        continue;
+                    };
+                    layers.push((map.layer, from_unit(download_map(&ctx, &url).await?.view(), (map.min, map.max))));
+                }
+                // The heightmap is the total height, so the rock is what's left under the soil
+                let soil =
+                    layers.iter().find(|(layer, _)| *layer == TerrainLayers::Soil).map(|(_, soil)| resample(soil.view(), heights.dim()));
+                if let Some(soil) = soil {
+                    layers[0].1 -= &soil;
+                }
+
+                let layers = layers.iter().map(|(layer, map)| (*layer, map.view())).collect::<Vec<_>>();
+                let cells = TerrainCells { cells: cells_from_maps(&TerrainSize::new(), config.origin_cell, &layers) };
+
+                let rel_path = ctx.in_root().relative_path(file.path());
+                let content_url = ctx.write_file(rel_path.with_extension(TERRAIN_EXTENSION), cells.to_bytes()).await;
+
+                Ok(vec![OutAsset {
+                    id: asset_id_from_url(&file),
+                    type_: AssetType::Terrain,
+                    hidden: false,
+                    name: stem,
+                    tags: Vec::new(),
+                    categories: Default::default(),
+                    preview: OutAssetPreview::Image { image: Arc::new(preview(heights.view())) },
+                    content: OutAssetContent::Content(content_url),
+                    source: Some(file.clone()),
+                }])
+            }
+        },
+    )
+    .instrument(info_span!("terrain_pipeline"))
+    .await
+}
+
+async fn download_map(ctx: &PipelineCtx, url: &AbsAssetUrl) -> anyhow::Result<Array2<f32>> {
+    let format = url.extension().and_then(|ext| HeightmapFormat::from_extension(&ext)).unwrap();
+    let data = url.download_bytes(ctx.assets()).await?;
+    decode_map(&data, format)
+}
+
+/// A grayscale image of the heights, stretched to their full range.
+fn preview(heights: ArrayView2<f32>) -> RgbaImage {
+    let heights = to_unit(heights, value_range(heights));
+    let (height, width) = heights.dim();
+    let image = RgbaImage::from_fn(width as u32, height as u32, |x, y| {
+        let value = (heights[(y as usize, x as usize)] * 255.) as u8;
+        image::Rgba([value, value, value, 255])
+    });
+    let scale = 256. / width.max(height) as f32;
+    if scale < 1. {
+        image::imageops::resize(&image, (width as f32 * scale) as u32, (height as f32 * scale) as u32, FilterType::Triangle)
+    } else {
+        image
+    }
+}
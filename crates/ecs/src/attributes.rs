@@ -220,6 +220,18 @@ where
     }
 }
 
+/// Only synchronize the component to the player that owns the entity, as determined by the entity's `user_id`
+///
+/// Has no effect unless the component is also `Networked`
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OwnerOnly;
+impl ComponentAttribute for OwnerOnly {}
+impl<T: ComponentValue> AttributeConstructor<T, ()> for OwnerOnly {
+    fn construct(store: &mut AttributeStore, _: ()) {
+        store.set(Self)
+    }
+}
+
 pub(crate) struct ComponentPath(pub String);
 impl ComponentAttribute for ComponentPath {}
 
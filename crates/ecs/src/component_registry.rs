@@ -103,7 +103,8 @@ define_external_component_attribute_flags![
     (networked, Networked),
     (resource, Resource),
     (store, Store),
-    (maybe_resource, MaybeResource)
+    (maybe_resource, MaybeResource),
+    (owner_only, OwnerOnly)
 ];
 
 #[derive(Default)]
@@ -284,38 +284,10 @@ impl<'de> Deserialize<'de> for DeserEntityDataWithWarnings {
             {
                 let mut res = Entity::new();
                 while let Some((key, value)) = map.next_entry::<String, serde_json::Value>()? {
-                    let desc = with_component_registry(|r| r.get_by_path(&key));
-                    let desc = match desc {
-                        Some(desc) => desc,
-
-                        None => {
-                            self.warnings.push((EntityId::null(), key.clone(), format!("No such component: {key}")));
-                            continue;
-                        }
-                    };
-
-                    let ser: Result<_, V::Error> = desc
-                        .attribute::<Serializable>()
-                        .ok_or_else(|| de::Error::custom(format!("Component {desc:?} is not deserializable")));
-
-                    let ser = match ser {
-                        Ok(v) => v,
-                        Err(err) => {
-                            self.warnings.push((EntityId::null(), key, format!("{err:?}")));
-                            continue;
-                        }
-                    };
-
-                    let value = ser.deserializer(desc).deserialize(value);
-                    let value = match value {
-                        Ok(v) => v,
-                        Err(err) => {
-                            self.warnings.push((EntityId::null(), key, format!("{err:?}")));
-                            continue;
-                        }
-                    };
-
-                    res.set_entry(value);
+                    match deserialize_component(&key, value) {
+                        Ok(value) => res.set_entry(value),
+                        Err(err) => self.warnings.push((EntityId::null(), key, err)),
+                    }
                 }
 
                 Ok(DeserEntityDataWithWarnings { entity: res, warnings: self.warnings })
@@ -326,6 +298,13 @@ impl<'de> Deserialize<'de> for DeserEntityDataWithWarnings {
     }
 }
 
+/// Deserializes the `value` of the component at `path`. Returns a warning describing why it failed otherwise.
+pub(crate) fn deserialize_component(path: &str, value: serde_json::Value) -> Result<ComponentEntry, String> {
+    let desc = with_component_registry(|r| r.get_by_path(path)).ok_or_else(|| format!("No such component: {path}"))?;
+    let ser = desc.attribute::<Serializable>().ok_or_else(|| format!("Component {desc:?} is not deserializable"))?;
+    ser.deserializer(desc).deserialize(value).map_err(|err| format!("{err:?}"))
+}
+
 /// Use this struct while de-serializing an EntityData to also get warnings
 /// about missing/bad components. Only works with serde_json
 pub struct DeserEntityDataWithWarnings {
@@ -37,6 +37,7 @@ mod entity;
 mod events;
 mod index;
 mod location;
+mod migration;
 mod primitive_component;
 mod query;
 mod serialization;
@@ -52,6 +53,7 @@ pub use entity::*;
 pub use events::*;
 pub use index::*;
 pub use location::*;
+pub use migration::*;
 pub use primitive_component::*;
 pub use query::*;
 pub use serialization::*;
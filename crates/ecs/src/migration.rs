@@ -0,0 +1,303 @@
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use serde::{
+    de::{Error, MapAccess, Visitor},
+    Deserializer,
+};
+use serde_json::Value;
+
+use crate::{entity::deserialize_component, ECSDeserializationWarnings, Entity, EntityId, World};
+
+/// Converts the saved value of a component to its current type.
+pub type ValueConverter = Arc<dyn Fn(&Value) -> Result<Value, String> + Sync + Send>;
+
+/// A change to the components of a saved world.
+#[derive(Clone)]
+pub enum MigrationStep {
+    /// Moves the values of the component at `from` to the component at `to`. Fails if an entity already has both.
+    Rename { from: String, to: String },
+    /// Converts the values of `component` with `convert`. Values that can't be converted are left as they are.
+    Convert { component: String, convert: ValueConverter },
+    /// Adds `component` with `value` to the entities that don't have it, and replaces the values of `component` that can't be
+    /// read as its current type with `value`.
+    Default { component: String, value: Value },
+}
+impl fmt::Debug for MigrationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rename { from, to } => f.debug_struct("Rename").field("from", from).field("to", to).finish(),
+            Self::Convert { component, .. } => f.debug_struct("Convert").field("component", component).finish_non_exhaustive(),
+            Self::Default { component, value } => f.debug_struct("Default").field("component", component).field("value", value).finish(),
+        }
+    }
+}
+
+/// The steps that bring a world saved with the previous schema version up to `version`.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: u32,
+    pub steps: Vec<MigrationStep>,
+}
+
+/// A change that a [`Migration`] made to a saved world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationChange {
+    pub id: EntityId,
+    pub version: u32,
+    pub kind: MigrationChangeKind,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationChangeKind {
+    Renamed { from: String, to: String },
+    Converted { component: String, from: Value, to: Value },
+    Defaulted { component: String, from: Value, to: Value },
+    Added { component: String, value: Value },
+}
+impl fmt::Display for MigrationChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{} {}: ", self.version, self.id)?;
+        match &self.kind {
+            MigrationChangeKind::Renamed { from, to } => write!(f, "renamed {from} to {to}"),
+            MigrationChangeKind::Converted { component, from, to } => write!(f, "converted {component} from {from} to {to}"),
+            MigrationChangeKind::Defaulted { component, from, to } => write!(f, "replaced unreadable {component} {from} with {to}"),
+            MigrationChangeKind::Added { component, value } => write!(f, "added missing {component} {value}"),
+        }
+    }
+}
+
+/// The versioned schema history of the components of a world.
+///
+/// Worlds are saved along with the [`version`](Self::version) of the schema they were saved with. When they are loaded,
+/// the migrations that came after that version are applied to them, in order, before their components are deserialized.
+#[derive(Debug, Clone, Default)]
+pub struct Migrations {
+    migrations: Vec<Migration>,
+}
+impl Migrations {
+    pub fn new(mut migrations: Vec<Migration>) -> Self {
+        migrations.sort_by_key(|migration| migration.version);
+        Self { migrations }
+    }
+    /// The current schema version. Worlds that are saved now should be saved with this version.
+    pub fn version(&self) -> u32 {
+        self.migrations.last().map(|migration| migration.version).unwrap_or(0)
+    }
+    /// Deserializes a world that was saved with schema `version`, migrating it to the current version.
+    ///
+    /// Like [`DeserWorldWithWarnings`](crate::DeserWorldWithWarnings), components that can't be read after the migration are left
+    /// out and reported as warnings. Fails if a migration step can't be applied. Only works with json.
+    pub fn deserialize_world<'de, D: Deserializer<'de>>(&self, version: u32, deserializer: D) -> Result<MigratedWorld, D::Error> {
+        struct MigratingWorldVisitor<'a> {
+            migrations: Vec<&'a Migration>,
+        }
+        impl<'de, 'a> Visitor<'de> for MigratingWorldVisitor<'a> {
+            type Value = MigratedWorld;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("struct World")
+            }
+
+            fn visit_map<V>(self, mut map: V) -> Result<MigratedWorld, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                let mut res = MigratedWorld {
+                    world: World::new_with_config_internal("deserialized", false),
+                    warnings: Default::default(),
+                    changes: Vec::new(),
+                };
+                while let Some((id, mut components)) = map.next_entry::<EntityId, BTreeMap<String, Value>>()? {
+                    for migration in &self.migrations {
+                        for step in &migration.steps {
+                            let change = apply_step(step, &mut components).map_err(|err| {
+                                V::Error::custom(format!("Failed to migrate {id} to version {}: {err}", migration.version))
+                            })?;
+                            if let Some(kind) = change {
+                                res.changes.push(MigrationChange { id, version: migration.version, kind });
+                            }
+                        }
+                    }
+                    let mut entity = Entity::new();
+                    for (key, value) in components {
+                        match deserialize_component(&key, value) {
+                            Ok(value) => entity.set_entry(value),
+                            Err(err) => res.warnings.push((id, key, err)),
+                        }
+                    }
+                    res.world.spawn_with_id(id, entity);
+                }
+                Ok(res)
+            }
+        }
+
+        let migrations = self.migrations.iter().filter(|migration| migration.version > version).collect();
+        deserializer.deserialize_map(MigratingWorldVisitor { migrations })
+    }
+}
+
+fn apply_step(step: &MigrationStep, components: &mut BTreeMap<String, Value>) -> Result<Option<MigrationChangeKind>, String> {
+    match step {
+        MigrationStep::Rename { from, to } => {
+            if !components.contains_key(from) {
+                return Ok(None);
+            }
+            if components.contains_key(to) {
+                return Err(format!("can't rename {from} to {to}, as it already has {to}"));
+            }
+            let value = components.remove(from).unwrap();
+            components.insert(to.clone(), value);
+            Ok(Some(MigrationChangeKind::Renamed { from: from.clone(), to: to.clone() }))
+        }
+        MigrationStep::Convert { component, convert } => {
+            let Some(value) = components.get_mut(component) else { return Ok(None) };
+            let Some(converted) = convert(&*value).ok().filter(|converted| *converted != *value) else { return Ok(None) };
+            let from = std::mem::replace(value, converted.clone());
+            Ok(Some(MigrationChangeKind::Converted { component: component.clone(), from, to: converted }))
+        }
+        MigrationStep::Default { component, value: default } => {
+            let Some(value) = components.get_mut(component) else {
+                components.insert(component.clone(), default.clone());
+                return Ok(Some(MigrationChangeKind::Added { component: component.clone(), value: default.clone() }));
+            };
+            if deserialize_component(component, value.clone()).is_ok() {
+                return Ok(None);
+            }
+            let from = std::mem::replace(value, default.clone());
+            Ok(Some(MigrationChangeKind::Defaulted { component: component.clone(), from, to: default.clone() }))
+        }
+    }
+}
+
+/// A world that was deserialized with [`Migrations::deserialize_world`].
+pub struct MigratedWorld {
+    pub world: World,
+    pub warnings: ECSDeserializationWarnings,
+    /// What the migrations changed. The world is only changed in memory, so this can be used as a dry run.
+    pub changes: Vec<MigrationChange>,
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{components, Store};
+
+    components!("test", {
+        @[Store]
+        migration_test_name: String,
+        @[Store]
+        migration_test_count: u32,
+    });
+
+    fn init() {
+        crate::init_components();
+        init_components();
+    }
+
+    fn saved_world(id: EntityId, components: Value) -> Value {
+        Value::Object([(id.to_string(), components)].into_iter().collect())
+    }
+
+    #[test]
+    pub fn test_migrate_world() {
+        init();
+        let id = EntityId::new();
+        let saved = saved_world(id, json!({ "core::test::old_name": "hi", "core::test::migration_test_count": "3" }));
+        let migrations = Migrations::new(vec![
+            Migration {
+                version: 2,
+                steps: vec![MigrationStep::Convert {
+                    component: "core::test::migration_test_count".to_string(),
+                    convert: Arc::new(|value| {
+                        value.as_str().and_then(|s| s.parse::<u32>().ok()).map(Value::from).ok_or_else(|| "NaN".into())
+                    }),
+                }],
+            },
+            Migration {
+                version: 1,
+                steps: vec![MigrationStep::Rename {
+                    from: "core::test::old_name".to_string(),
+                    to: "core::test::migration_test_name".to_string(),
+                }],
+            },
+        ]);
+        assert_eq!(migrations.version(), 2);
+
+        let migrated = migrations.deserialize_world(0, saved.clone()).unwrap();
+        assert_eq!(migrated.world.get_ref(id, migration_test_name()).unwrap(), "hi");
+        assert_eq!(migrated.world.get(id, migration_test_count()).unwrap(), 3);
+        assert_eq!(migrated.changes.len(), 2);
+        assert!(migrated.warnings.is_empty());
+
+        // Migrations up to the saved version have already been applied
+        let migrated = migrations.deserialize_world(1, saved).unwrap();
+        assert_eq!(migrated.changes.len(), 1);
+        assert_eq!(migrated.warnings.len(), 1);
+    }
+
+    #[test]
+    pub fn test_migrate_world_default() {
+        init();
+        let id = EntityId::new();
+        let saved = saved_world(id, json!({ "core::test::migration_test_count": [1, 2] }));
+        let migrations = Migrations::new(vec![Migration {
+            version: 1,
+            steps: vec![MigrationStep::Default { component: "core::test::migration_test_count".to_string(), value: json!(0) }],
+        }]);
+
+        let migrated = migrations.deserialize_world(0, saved).unwrap();
+        assert_eq!(migrated.world.get(id, migration_test_count()).unwrap(), 0);
+        assert_eq!(
+            migrated.changes,
+            vec![MigrationChange {
+                id,
+                version: 1,
+                kind: MigrationChangeKind::Defaulted {
+                    component: "core::test::migration_test_count".to_string(),
+                    from: json!([1, 2]),
+                    to: json!(0)
+                }
+            }]
+        );
+    }
+
+    #[test]
+    pub fn test_migrate_world_default_missing() {
+        init();
+        let id = EntityId::new();
+        let saved = saved_world(id, json!({ "core::test::migration_test_name": "hi" }));
+        let migrations = Migrations::new(vec![Migration {
+            version: 1,
+            steps: vec![MigrationStep::Default { component: "core::test::migration_test_count".to_string(), value: json!(5) }],
+        }]);
+
+        let migrated = migrations.deserialize_world(0, saved).unwrap();
+        assert_eq!(migrated.world.get(id, migration_test_count()).unwrap(), 5);
+        assert_eq!(
+            migrated.changes,
+            vec![MigrationChange {
+                id,
+                version: 1,
+                kind: MigrationChangeKind::Added { component: "core::test::migration_test_count".to_string(), value: json!(5) }
+            }]
+        );
+    }
+
+    #[test]
+    pub fn test_migrate_world_rename_to_existing() {
+        init();
+        let id = EntityId::new();
+        let saved = saved_world(id, json!({ "core::test::old_name": "old", "core::test::migration_test_name": "new" }));
+        let migrations = Migrations::new(vec![Migration {
+            version: 1,
+            steps: vec![MigrationStep::Rename {
+                from: "core::test::old_name".to_string(),
+                to: "core::test::migration_test_name".to_string(),
+            }],
+        }]);
+
+        let err = migrations.deserialize_world(0, saved).err().unwrap();
+        assert!(err.to_string().contains("already has core::test::migration_test_name"), "{err}");
+    }
+}
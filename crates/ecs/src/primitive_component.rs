@@ -57,6 +57,8 @@ macro_rules! make_primitive_component {
                 $($value), *,
                 $([< Vec $value >]), *,
                 $([< Option$value >]), *,
+                /// A map, enum or struct defined in a project's `ambient.toml`; see [StructuredValue].
+                Structured,
             }
 
             impl TryFrom<&str> for PrimitiveComponentType {
@@ -78,7 +80,7 @@ macro_rules! make_primitive_component {
             }
 
             impl PrimitiveComponentType {
-                /// Not defined for the container types; use [Self::decompose_container_type].
+                /// Not defined for the container types (use [Self::decompose_container_type]) or [Self::Structured].
                 pub fn as_str(&self) -> Option<&'static str> {
                     match self {
                         $(Self::$value => Some(stringify!($value)),)*
@@ -122,6 +124,9 @@ macro_rules! make_primitive_component {
                                 build_attribute_registration!(Option<$type>, store, attributes)
                             },
                         )*
+                        PrimitiveComponentType::Structured => {
+                            build_attribute_registration!(StructuredValue, store, attributes)
+                        },
                     };
 
                     reg.register_external(path.into(), vtable, store);
@@ -138,6 +143,7 @@ macro_rules! make_primitive_component {
                     $((TypeId::of::<$type>(), PrimitiveComponentType::$value),)*
                     $((TypeId::of::<Vec<$type>>(), PrimitiveComponentType::[<Vec $value>]),)*
                     $((TypeId::of::<Option<$type>>(), PrimitiveComponentType::[<Option $value>]),)*
+                    (TypeId::of::<StructuredValue>(), PrimitiveComponentType::Structured),
                 ])
             });
         }
@@ -145,3 +151,10 @@ macro_rules! make_primitive_component {
 }
 
 primitive_component_definitions!(make_primitive_component);
+
+/// The value of a component with a map, enum or struct type defined in a project's `ambient.toml`.
+///
+/// The host does not know the layout of these types, so they are stored in the serialized form
+/// produced by the guest's generated types.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct StructuredValue(pub Vec<u8>);
@@ -7,7 +7,7 @@ use serde::{
     Deserialize, Deserializer, Serialize, Serializer,
 };
 
-use crate::{dont_store, query, DeserEntityDataWithWarnings, Entity, EntityId, Serializable, World};
+use crate::{dont_store, query, DeserEntityDataWithWarnings, Entity, EntityId, Serializable, Store, World};
 
 impl Serialize for World {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
@@ -15,18 +15,48 @@ impl Serialize for World {
 
         let mut entities = serializer.serialize_map(Some(len))?;
         for (id, _) in query(()).excl(dont_store()).iter(self, None) {
-            entities.serialize_entry(&id, &SerWorldEntity { world: self, id })?;
+            entities.serialize_entry(&id, &SerWorldEntity { world: self, id, stored_only: false })?;
         }
         entities.end()
     }
 }
+
+/// Serializes only the components of a world that have the [`Store`] attribute, leaving out
+/// entities that have none of them. Use this to persist a world to disk; the result can be
+/// deserialized as a [`World`] or [`DeserWorldWithWarnings`].
+pub struct StoredWorld<'a>(pub &'a World);
+impl<'a> Serialize for StoredWorld<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let world = self.0;
+        let ids = query(())
+            .excl(dont_store())
+            .iter(world, None)
+            .map(|(id, _)| id)
+            .filter(|id| world.get_components(*id).unwrap().iter().any(|comp| comp.has_attribute::<Store>()))
+            .collect_vec();
+
+        let mut entities = serializer.serialize_map(Some(ids.len()))?;
+        for id in ids {
+            entities.serialize_entry(&id, &SerWorldEntity { world, id, stored_only: true })?;
+        }
+        entities.end()
+    }
+}
+
 struct SerWorldEntity<'a> {
     world: &'a World,
     id: EntityId,
+    stored_only: bool,
 }
 impl<'a> Serialize for SerWorldEntity<'a> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let comps = self.world.get_components(self.id).unwrap().into_iter().filter(|x| x.has_attribute::<Serializable>()).collect_vec();
+        let comps = self
+            .world
+            .get_components(self.id)
+            .unwrap()
+            .into_iter()
+            .filter(|x| x.has_attribute::<Serializable>() && (!self.stored_only || x.has_attribute::<Store>()))
+            .collect_vec();
 
         let mut entity = serializer.serialize_map(Some(comps.len()))?;
         for comp in comps {
@@ -148,6 +178,8 @@ mod test {
         ser_test3: String,
         @[Serializable]
         ser_test4: String,
+        @[Store]
+        ser_test5: String,
     });
 
     fn init() {
@@ -205,4 +237,20 @@ mod test {
 
         assert!(serde_json::from_str::<World>(source).is_err());
     }
+
+    #[test]
+    pub fn test_serialize_stored_world() {
+        init();
+        let mut world = World::new("test");
+        world.add_resource(ser_test3(), "hi".to_string());
+        Entity::new().with(ser_test3(), "hi".to_string()).spawn(&mut world);
+        let id = Entity::new().with(ser_test3(), "hi".to_string()).with(ser_test5(), "stored".to_string()).spawn(&mut world);
+
+        let ser = serde_json::to_string(&StoredWorld(&world)).unwrap();
+        assert_eq!(&ser, &format!("{{\"{id}\":{{\"core::test::ser_test5\":\"stored\"}}}}"));
+
+        let deser: DeserWorldWithWarnings = serde_json::from_str(&ser).unwrap();
+        assert_eq!(deser.world.get_ref(id, ser_test5()).unwrap(), "stored");
+        assert!(!deser.world.has_component(id, ser_test3()));
+    }
 }
@@ -135,12 +135,11 @@ impl WorldStreamFilter {
         Self { arch_filter, component_filter }
     }
     pub fn initial_diff(&self, world: &World) -> WorldDiff {
-        WorldDiff {
-            changes: self
-                .all_entities(world)
-                .map(|id| WorldChange::Spawn(Some(id), self.read_entity_components(world, id).into()))
-                .collect_vec(),
-        }
+        WorldDiff { changes: self.all_entities(world).map(|id| self.spawn_change(world, id)).collect_vec() }
+    }
+    /// Creates a change that spawns the entity with all of its components that pass this filter
+    pub fn spawn_change(&self, world: &World, id: EntityId) -> WorldChange {
+        WorldChange::Spawn(Some(id), self.read_entity_components(world, id).into())
     }
     pub fn all_entities<'a>(&self, world: &'a World) -> impl Iterator<Item = EntityId> + 'a {
         Query::all().filter(&self.arch_filter).iter(world, None).map(|x| x.id())
@@ -0,0 +1,234 @@
+//! Encoding and decoding of the BC1 and BC3 block compressed texture formats, which store each 4x4 block
+//! of texels in 8 and 16 bytes respectively.
+
+use glam::{vec3, Vec3};
+use image::RgbaImage;
+
+pub const BC1_BLOCK_SIZE: usize = 8;
+pub const BC3_BLOCK_SIZE: usize = 16;
+
+/// Compresses `image` to BC1, ignoring its alpha channel.
+pub fn encode_bc1(image: &RgbaImage) -> Vec<u8> {
+    let mut data = Vec::with_capacity(block_count(image.width(), image.height()) * BC1_BLOCK_SIZE);
+    for_each_block(image, |block| data.extend_from_slice(&encode_color_block(block)));
+    data
+}
+
+/// Compresses `image` to BC3, which keeps its alpha channel.
+pub fn encode_bc3(image: &RgbaImage) -> Vec<u8> {
+    let mut data = Vec::with_capacity(block_count(image.width(), image.height()) * BC3_BLOCK_SIZE);
+    for_each_block(image, |block| {
+        data.extend_from_slice(&encode_alpha_block(block));
+        data.extend_from_slice(&encode_color_block(block));
+    });
+    data
+}
+
+/// Decompresses BC1 `data` of a `width` x `height` texture.
+pub fn decode_bc1(data: &[u8], width: u32, height: u32) -> RgbaImage {
+    decode_blocks(data, width, height, BC1_BLOCK_SIZE, |block| decode_color_block(block, false))
+}
+
+/// Decompresses BC3 `data` of a `width` x `height` texture.
+pub fn decode_bc3(data: &[u8], width: u32, height: u32) -> RgbaImage {
+    decode_blocks(data, width, height, BC3_BLOCK_SIZE, |block| {
+        let mut texels = decode_color_block(&block[8..], true);
+        for (texel, alpha) in texels.iter_mut().zip(decode_alpha_block(&block[..8])) {
+            texel[3] = alpha;
+        }
+        texels
+    })
+}
+
+fn block_count(width: u32, height: u32) -> usize {
+    (blocks(width) * blocks(height)) as usize
+}
+fn blocks(texels: u32) -> u32 {
+    (texels + 3) / 4
+}
+
+/// Calls `f` with the texels of each block, in row-major order. Blocks that overlap the edges of the image repeat its
+/// last row and column.
+fn for_each_block(image: &RgbaImage, mut f: impl FnMut(&[[u8; 4]; 16])) {
+    let (width, height) = image.dimensions();
+    for block_y in 0..blocks(height) {
+        for block_x in 0..blocks(width) {
+            let mut block = [[0; 4]; 16];
+            for (i, texel) in block.iter_mut().enumerate() {
+                let x = (block_x * 4 + i as u32 % 4).min(width - 1);
+                let y = (block_y * 4 + i as u32 / 4).min(height - 1);
+                *texel = image.get_pixel(x, y).0;
+            }
+            f(&block);
+        }
+    }
+}
+
+fn decode_blocks(data: &[u8], width: u32, height: u32, block_size: usize, decode: impl Fn(&[u8]) -> [[u8; 4]; 16]) -> RgbaImage {
+    let mut image = RgbaImage::new(width, height);
+    let blocks_x = blocks(width);
+    for (i, block) in data.chunks_exact(block_size).take(block_count(width, height)).enumerate() {
+        let (block_x, block_y) = (i as u32 % blocks_x, i as u32 / blocks_x);
+        for (j, texel) in decode(block).into_iter().enumerate() {
+            let (x, y) = (block_x * 4 + j as u32 % 4, block_y * 4 + j as u32 / 4);
+            if x < width && y < height {
+                image.put_pixel(x, y, image::Rgba(texel));
+            }
+        }
+    }
+    image
+}
+
+fn encode_color_block(block: &[[u8; 4]; 16]) -> [u8; 8] {
+    let colors = block.map(|texel| vec3(texel[0] as f32, texel[1] as f32, texel[2] as f32));
+    let mean = colors.iter().sum::<Vec3>() / 16.;
+
+    // The endpoints are the extremes of the colors along their principal axis
+    let mut covariance = [Vec3::ZERO; 3];
+    for color in &colors {
+        let d = *color - mean;
+        covariance[0] += d * d.x;
+        covariance[1] += d * d.y;
+        covariance[2] += d * d.z;
+    }
+    let mut axis = vec3(1., 1., 1.);
+    for _ in 0..8 {
+        axis = covariance[0] * axis.x + covariance[1] * axis.y + covariance[2] * axis.z;
+        let length = axis.abs().max_element();
+        if length < 1e-6 {
+            axis = Vec3::ZERO;
+            break;
+        }
+        axis /= length;
+    }
+    let (mut min, mut max) = (0f32, 0f32);
+    for color in &colors {
+        let t = (*color - mean).dot(axis);
+        min = min.min(t);
+        max = max.max(t);
+    }
+    let axis_length = axis.length_squared().max(1e-6);
+    let mut c0 = to_565(mean + axis * (max / axis_length));
+    let mut c1 = to_565(mean + axis * (min / axis_length));
+    if c0 < c1 {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+
+    let mut indices = 0u32;
+    if c0 != c1 {
+        let palette = color_palette(c0, c1, false);
+        for (i, color) in colors.iter().enumerate() {
+            let index = (0..4).min_by_key(|&j| (palette[j] - *color).length_squared() as u32).unwrap();
+            indices |= (index as u32) << (i * 2);
+        }
+    }
+
+    let mut res = [0; 8];
+    res[0..2].copy_from_slice(&c0.to_le_bytes());
+    res[2..4].copy_from_slice(&c1.to_le_bytes());
+    res[4..8].copy_from_slice(&indices.to_le_bytes());
+    res
+}
+
+fn decode_color_block(block: &[u8], always_opaque: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    let has_transparent = !always_opaque && c0 <= c1;
+    let palette = color_palette(c0, c1, has_transparent);
+    std::array::from_fn(|i| {
+        let index = ((indices >> (i * 2)) & 3) as usize;
+        if has_transparent && index == 3 {
+            [0, 0, 0, 0]
+        } else {
+            let color = palette[index].round();
+            [color.x as u8, color.y as u8, color.z as u8, 255]
+        }
+    })
+}
+
+fn color_palette(c0: u16, c1: u16, has_transparent: bool) -> [Vec3; 4] {
+    let (c0, c1) = (from_565(c0), from_565(c1));
+    if has_transparent {
+        [c0, c1, (c0 + c1) / 2., Vec3::ZERO]
+    } else {
+        [c0, c1, (c0 * 2. + c1) / 3., (c0 + c1 * 2.) / 3.]
+    }
+}
+
+fn to_565(color: Vec3) -> u16 {
+    let color = color.clamp(Vec3::ZERO, Vec3::splat(255.));
+    let r = (color.x * 31. / 255.).round() as u16;
+    let g = (color.y * 63. / 255.).round() as u16;
+    let b = (color.z * 31. / 255.).round() as u16;
+    (r << 11) | (g << 5) | b
+}
+
+fn from_565(color: u16) -> Vec3 {
+    let r = (color >> 11) & 31;
+    let g = (color >> 5) & 63;
+    let b = color & 31;
+    vec3(((r << 3) | (r >> 2)) as f32, ((g << 2) | (g >> 4)) as f32, ((b << 3) | (b >> 2)) as f32)
+}
+
+fn encode_alpha_block(block: &[[u8; 4]; 16]) -> [u8; 8] {
+    let a0 = block.iter().map(|texel| texel[3]).max().unwrap();
+    let a1 = block.iter().map(|texel| texel[3]).min().unwrap();
+    let mut indices = 0u64;
+    if a0 != a1 {
+        let palette = alpha_palette(a0, a1);
+        for (i, texel) in block.iter().enumerate() {
+            let index = (0..8).min_by_key(|&j| (palette[j] as i32 - texel[3] as i32).abs()).unwrap();
+            indices |= (index as u64) << (i * 3);
+        }
+    }
+    let mut res = [0; 8];
+    res[0] = a0;
+    res[1] = a1;
+    res[2..8].copy_from_slice(&indices.to_le_bytes()[0..6]);
+    res
+}
+
+fn decode_alpha_block(block: &[u8]) -> [u8; 16] {
+    let palette = alpha_palette(block[0], block[1]);
+    let mut bytes = [0; 8];
+    bytes[0..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bytes);
+    std::array::from_fn(|i| palette[((indices >> (i * 3)) & 7) as usize])
+}
+
+fn alpha_palette(a0: u8, a1: u8) -> [u8; 8] {
+    let (a0, a1) = (a0 as usize, a1 as usize);
+    std::array::from_fn(|i| match i {
+        0 => a0 as u8,
+        1 => a1 as u8,
+        _ if a0 > a1 => (((8 - i) * a0 + (i - 1) * a1 + 3) / 7) as u8,
+        6 => 0,
+        7 => 255,
+        _ => (((6 - i) * a0 + (i - 1) * a1 + 2) / 5) as u8,
+    })
+}
+
+#[test]
+fn test_bc3_roundtrip() {
+    // Gradients, whose colors in each block lie on a line
+    let image = RgbaImage::from_fn(10, 6, |x, y| {
+        let t = x + y;
+        image::Rgba([(t * 4) as u8, (t * 6) as u8, (128 - t * 2) as u8, if x < 4 { 255 } else { (y * 20) as u8 }])
+    });
+    for (data, decoded) in
+        [(encode_bc1(&image), decode_bc1(&encode_bc1(&image), 10, 6)), (encode_bc3(&image), decode_bc3(&encode_bc3(&image), 10, 6))]
+    {
+        assert_eq!(data.len() % BC1_BLOCK_SIZE, 0);
+        assert_eq!(decoded.dimensions(), (10, 6));
+        for (a, b) in image.pixels().zip(decoded.pixels()) {
+            for c in 0..3 {
+                assert!((a[c] as i32 - b[c] as i32).abs() <= 8, "{a:?} {b:?}");
+            }
+        }
+    }
+    let decoded = decode_bc3(&encode_bc3(&image), 10, 6);
+    for (a, b) in image.pixels().zip(decoded.pixels()) {
+        assert!((a[3] as i32 - b[3] as i32).abs() <= 8, "{a:?} {b:?}");
+    }
+}
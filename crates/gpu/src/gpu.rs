@@ -85,6 +85,7 @@ impl Gpu {
                     features: wgpu::Features::default()
                         | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
                         // | wgpu::Features::POLYGON_MODE_LINE
+                        | (adapter.features() & wgpu::Features::TEXTURE_COMPRESSION_BC)
                         | features,
                     limits: wgpu::Limits {
                         max_bind_groups: 8,
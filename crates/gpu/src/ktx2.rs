@@ -0,0 +1,298 @@
+//! Reading and writing of [KTX2](https://registry.khronos.org/KTX/specs/2.0/ktxspec.v2.html) texture files, which hold
+//! textures with all of their mip levels in a format the GPU can sample from directly.
+//!
+//! Basis Universal (supercompressed) files are not supported.
+
+use image::{imageops::FilterType, RgbaImage};
+use thiserror::Error;
+
+use crate::bcn::{decode_bc1, decode_bc3, encode_bc1, encode_bc3};
+
+const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const HEADER_SIZE: usize = 80;
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+#[derive(Debug, Error)]
+pub enum Ktx2Error {
+    #[error("Not a KTX2 file")]
+    InvalidIdentifier,
+    #[error("The KTX2 file is truncated")]
+    Truncated,
+    #[error("Unsupported KTX2 format (VkFormat {0})")]
+    UnsupportedFormat(u32),
+    #[error("Unsupported KTX2 supercompression scheme {0}")]
+    UnsupportedSupercompression(u32),
+    #[error("Only 2D KTX2 textures are supported")]
+    UnsupportedDimensions,
+}
+
+/// The texel formats of KTX2 textures that can be loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ktx2Format {
+    Rgba8,
+    /// Color, without alpha, in 8 bytes per block of 4x4 texels.
+    Bc1,
+    /// Color and alpha in 16 bytes per block of 4x4 texels.
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc7,
+}
+impl Ktx2Format {
+    /// The `VkFormat` of this format. Textures are written as linear; whether they hold sRGB colors is decided by the
+    /// texture format they are loaded as, like for other images.
+    fn vk_format(&self) -> u32 {
+        match self {
+            Ktx2Format::Rgba8 => 37,
+            Ktx2Format::Bc1 => 131,
+            Ktx2Format::Bc3 => 137,
+            Ktx2Format::Bc4 => 139,
+            Ktx2Format::Bc5 => 141,
+            Ktx2Format::Bc7 => 145,
+        }
+    }
+    fn from_vk_format(vk_format: u32) -> Option<Self> {
+        Some(match vk_format {
+            37 | 43 => Ktx2Format::Rgba8,
+            131..=134 => Ktx2Format::Bc1,
+            137 | 138 => Ktx2Format::Bc3,
+            139 => Ktx2Format::Bc4,
+            141 => Ktx2Format::Bc5,
+            145 | 146 => Ktx2Format::Bc7,
+            _ => return None,
+        })
+    }
+    /// The wgpu texture format with these texels, which is sRGB if `srgb` is set and the format has an sRGB variant.
+    pub fn texture_format(&self, srgb: bool) -> wgpu::TextureFormat {
+        match (self, srgb) {
+            (Ktx2Format::Rgba8, false) => wgpu::TextureFormat::Rgba8Unorm,
+            (Ktx2Format::Rgba8, true) => wgpu::TextureFormat::Rgba8UnormSrgb,
+            (Ktx2Format::Bc1, false) => wgpu::TextureFormat::Bc1RgbaUnorm,
+            (Ktx2Format::Bc1, true) => wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+            (Ktx2Format::Bc3, false) => wgpu::TextureFormat::Bc3RgbaUnorm,
+            (Ktx2Format::Bc3, true) => wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+            (Ktx2Format::Bc4, _) => wgpu::TextureFormat::Bc4RUnorm,
+            (Ktx2Format::Bc5, _) => wgpu::TextureFormat::Bc5RgUnorm,
+            (Ktx2Format::Bc7, false) => wgpu::TextureFormat::Bc7RgbaUnorm,
+            (Ktx2Format::Bc7, true) => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+        }
+    }
+    /// The size of the texels of a `width` x `height` level, in bytes.
+    fn level_size(&self, width: u32, height: u32) -> usize {
+        let (width, height) = (width as usize, height as usize);
+        let blocks = ((width + 3) / 4) * ((height + 3) / 4);
+        match self {
+            Ktx2Format::Rgba8 => width * height * 4,
+            Ktx2Format::Bc1 | Ktx2Format::Bc4 => blocks * 8,
+            Ktx2Format::Bc3 | Ktx2Format::Bc5 | Ktx2Format::Bc7 => blocks * 16,
+        }
+    }
+    fn block_size(&self) -> usize {
+        match self {
+            Ktx2Format::Rgba8 => 4,
+            Ktx2Format::Bc1 | Ktx2Format::Bc4 => 8,
+            Ktx2Format::Bc3 | Ktx2Format::Bc5 | Ktx2Format::Bc7 => 16,
+        }
+    }
+    /// The data format descriptor of this format, as a basic descriptor block.
+    fn data_format_descriptor(&self) -> Vec<u8> {
+        // (color model, [(bit offset, bit length, channel id)])
+        let (color_model, samples): (u8, &[(u16, u8, u8)]) = match self {
+            Ktx2Format::Rgba8 => (1, &[(0, 8, 0), (8, 8, 1), (16, 8, 2), (24, 8, 15)]),
+            Ktx2Format::Bc1 => (128, &[(0, 64, 0)]),
+            Ktx2Format::Bc3 => (130, &[(0, 64, 15), (64, 64, 0)]),
+            Ktx2Format::Bc4 => (131, &[(0, 64, 0)]),
+            Ktx2Format::Bc5 => (132, &[(0, 64, 0), (64, 64, 1)]),
+            Ktx2Format::Bc7 => (134, &[(0, 128, 0)]),
+        };
+        let block_dimension = if *self == Ktx2Format::Rgba8 { 0 } else { 3 };
+        let block_size = 24 + 16 * samples.len();
+        let mut res = Vec::with_capacity(4 + block_size);
+        res.extend_from_slice(&(4 + block_size as u32).to_le_bytes());
+        res.extend_from_slice(&0u32.to_le_bytes()); // Khronos vendor, basic descriptor type
+        res.extend_from_slice(&2u16.to_le_bytes()); // Version
+        res.extend_from_slice(&(block_size as u16).to_le_bytes());
+        res.extend_from_slice(&[color_model, 1, 1, 0]); // BT.709 primaries, linear transfer function, straight alpha
+        res.extend_from_slice(&[block_dimension, block_dimension, 0, 0]);
+        res.extend_from_slice(&[self.block_size() as u8, 0, 0, 0, 0, 0, 0, 0]);
+        for &(bit_offset, bit_length, channel) in samples {
+            res.extend_from_slice(&bit_offset.to_le_bytes());
+            res.extend_from_slice(&[bit_length - 1, channel]);
+            res.extend_from_slice(&[0, 0, 0, 0]);
+            res.extend_from_slice(&0u32.to_le_bytes());
+            res.extend_from_slice(&if bit_length == 8 { 255u32 } else { u32::MAX }.to_le_bytes());
+        }
+        res
+    }
+}
+
+/// A 2D texture with its mip levels, as stored in a KTX2 file.
+#[derive(Debug, Clone)]
+pub struct Ktx2Texture {
+    pub format: Ktx2Format,
+    pub width: u32,
+    pub height: u32,
+    /// The texels of each mip level, starting with the full size texture.
+    pub levels: Vec<Vec<u8>>,
+}
+impl Ktx2Texture {
+    /// Compresses `image` and its mipmaps, using BC1 if it's opaque and BC3 otherwise. The size of the image is rounded to
+    /// a multiple of 4, which is required by block compressed textures.
+    pub fn compress_bc(image: &RgbaImage) -> Self {
+        let opaque = image.pixels().all(|p| p[3] == 255);
+        let (format, encode): (_, fn(&RgbaImage) -> Vec<u8>) =
+            if opaque { (Ktx2Format::Bc1, encode_bc1) } else { (Ktx2Format::Bc3, encode_bc3) };
+        let (width, height) = ((image.width() + 3) / 4 * 4, (image.height() + 3) / 4 * 4);
+        let mut level = if image.dimensions() == (width, height) {
+            image.clone()
+        } else {
+            image::imageops::resize(image, width, height, FilterType::Triangle)
+        };
+        let mut levels = vec![encode(&level)];
+        while level.width() > 1 || level.height() > 1 {
+            level = image::imageops::resize(&level, (level.width() / 2).max(1), (level.height() / 2).max(1), FilterType::Triangle);
+            levels.push(encode(&level));
+        }
+        Self { format, width, height, levels }
+    }
+    /// The size of mip level `level`.
+    pub fn level_dimensions(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+    /// Decodes the full size texture, for when the GPU can't sample from this format. Only [Ktx2Format::Rgba8],
+    /// [Ktx2Format::Bc1] and [Ktx2Format::Bc3] can be decoded.
+    pub fn decode(&self) -> Option<RgbaImage> {
+        let data = self.levels.first()?;
+        match self.format {
+            Ktx2Format::Rgba8 => RgbaImage::from_raw(self.width, self.height, data.clone()),
+            Ktx2Format::Bc1 => Some(decode_bc1(data, self.width, self.height)),
+            Ktx2Format::Bc3 => Some(decode_bc3(data, self.width, self.height)),
+            _ => None,
+        }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Ktx2Error> {
+        if data.len() < HEADER_SIZE {
+            return Err(Ktx2Error::Truncated);
+        }
+        if data[0..12] != IDENTIFIER {
+            return Err(Ktx2Error::InvalidIdentifier);
+        }
+        let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap()) as usize;
+        let vk_format = u32_at(12);
+        let (width, height, depth, layers, faces, level_count) = (u32_at(20), u32_at(24), u32_at(28), u32_at(32), u32_at(36), u32_at(40));
+        let supercompression = u32_at(44);
+        if supercompression != 0 {
+            return Err(Ktx2Error::UnsupportedSupercompression(supercompression));
+        }
+        let format = Ktx2Format::from_vk_format(vk_format).ok_or(Ktx2Error::UnsupportedFormat(vk_format))?;
+        if width == 0 || height == 0 || depth > 1 || layers > 1 || faces != 1 {
+            return Err(Ktx2Error::UnsupportedDimensions);
+        }
+        // The GPU can only sample block compressed textures whose size is a multiple of the blocks
+        if format != Ktx2Format::Rgba8 && (width % 4 != 0 || height % 4 != 0) {
+            return Err(Ktx2Error::UnsupportedDimensions);
+        }
+        let level_count = level_count.max(1) as usize;
+        if data.len() < HEADER_SIZE + level_count * LEVEL_INDEX_ENTRY_SIZE {
+            return Err(Ktx2Error::Truncated);
+        }
+        let levels = (0..level_count)
+            .map(|level| {
+                let entry = HEADER_SIZE + level * LEVEL_INDEX_ENTRY_SIZE;
+                let (offset, length) = (u64_at(entry), u64_at(entry + 8));
+                // A file can claim more levels than a texture of its size can have
+                let level_size = |size: u32| size.checked_shr(level as u32).map(|size| size.max(1)).ok_or(Ktx2Error::UnsupportedDimensions);
+                let (level_width, level_height) = (level_size(width)?, level_size(height)?);
+                let size = format.level_size(level_width, level_height);
+                if length < size {
+                    return Err(Ktx2Error::Truncated);
+                }
+                // Only the texels are kept, as any padding after them would misalign the levels when they are uploaded together
+                let end = offset.checked_add(size).ok_or(Ktx2Error::Truncated)?;
+                data.get(offset..end).map(|level| level.to_vec()).ok_or(Ktx2Error::Truncated)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { format, width, height, levels })
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let dfd = self.format.data_format_descriptor();
+        let dfd_offset = HEADER_SIZE + self.levels.len() * LEVEL_INDEX_ENTRY_SIZE;
+
+        // The levels are stored from the smallest to the largest, each aligned to the size of a block
+        let alignment = self.format.block_size().max(4);
+        let mut offset = dfd_offset + dfd.len();
+        let mut level_offsets = vec![0; self.levels.len()];
+        for (level, data) in self.levels.iter().enumerate().rev() {
+            offset = (offset + alignment - 1) / alignment * alignment;
+            level_offsets[level] = offset;
+            offset += data.len();
+        }
+
+        let mut res = Vec::with_capacity(offset);
+        res.extend_from_slice(&IDENTIFIER);
+        for value in [self.format.vk_format(), 1, self.width, self.height, 0, 0, 1, self.levels.len() as u32, 0] {
+            res.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [dfd_offset as u32, dfd.len() as u32, 0, 0] {
+            res.extend_from_slice(&value.to_le_bytes());
+        }
+        res.extend_from_slice(&0u64.to_le_bytes());
+        res.extend_from_slice(&0u64.to_le_bytes());
+        for (level, data) in self.levels.iter().enumerate() {
+            for value in [level_offsets[level], data.len(), data.len()] {
+                res.extend_from_slice(&(value as u64).to_le_bytes());
+            }
+        }
+        res.extend_from_slice(&dfd);
+        for (level, data) in self.levels.iter().enumerate().rev() {
+            res.resize(level_offsets[level], 0);
+            res.extend_from_slice(data);
+        }
+        res
+    }
+}
+
+#[test]
+fn test_ktx2_roundtrip() {
+    let image = RgbaImage::from_fn(13, 6, |x, y| image::Rgba([(x * 10) as u8, (y * 20) as u8, 0, 255]));
+    let texture = Ktx2Texture::compress_bc(&image);
+    assert_eq!(texture.format, Ktx2Format::Bc1);
+    assert_eq!((texture.width, texture.height), (16, 8));
+    assert_eq!(texture.levels.len(), 5);
+
+    let read = Ktx2Texture::from_bytes(&texture.to_bytes()).unwrap();
+    assert_eq!(read.format, texture.format);
+    assert_eq!((read.width, read.height), (texture.width, texture.height));
+    assert_eq!(read.levels, texture.levels);
+    for (level, data) in read.levels.iter().enumerate() {
+        let (width, height) = read.level_dimensions(level);
+        assert_eq!(data.len(), read.format.level_size(width, height));
+    }
+}
+
+#[test]
+fn test_ktx2_malformed() {
+    let texture = Ktx2Texture { format: Ktx2Format::Rgba8, width: 1, height: 1, levels: vec![vec![0; 4]] };
+    assert!(Ktx2Texture::from_bytes(&texture.to_bytes()).is_ok());
+
+    // The offset of the level would overflow
+    let mut data = texture.to_bytes();
+    data[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(matches!(Ktx2Texture::from_bytes(&data), Err(Ktx2Error::Truncated)));
+
+    // Padding after the texels of a level is not kept
+    let mut data = texture.to_bytes();
+    data[HEADER_SIZE + 8..HEADER_SIZE + 16].copy_from_slice(&8u64.to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    assert_eq!(Ktx2Texture::from_bytes(&data).unwrap().levels, vec![vec![0; 4]]);
+
+    // Block compressed textures must be a whole number of blocks
+    let bc = Ktx2Texture { format: Ktx2Format::Bc1, width: 6, height: 4, levels: vec![vec![0; 16]] };
+    assert!(matches!(Ktx2Texture::from_bytes(&bc.to_bytes()), Err(Ktx2Error::UnsupportedDimensions)));
+
+    // More levels than a 32 bit size can be halved into
+    let texture = Ktx2Texture { levels: vec![vec![0; 4]; 33], ..texture };
+    assert!(matches!(Ktx2Texture::from_bytes(&texture.to_bytes()), Err(Ktx2Error::UnsupportedDimensions)));
+}
@@ -10,6 +10,7 @@ use std::{
 };
 
 use ambient_std::asset_cache::{AssetCache, SyncAssetKeyExt};
+use anyhow::Context;
 use bytemuck::Pod;
 use byteorder::{ByteOrder, LittleEndian};
 use glam::{uvec2, UVec4, Vec4};
@@ -24,6 +25,7 @@ use crate::shader_module::DEPTH_FORMAT;
 use super::{
     fill::FillerKey,
     gpu::{Gpu, GpuKey},
+    ktx2::Ktx2Texture,
     mipmap::generate_mipmaps,
 };
 
@@ -53,8 +55,10 @@ impl Texture {
     }
 
     fn size_in_bytes_from_desc(descriptor: &wgpu::TextureDescriptor) -> u64 {
-        let mut mip_size = (descriptor.size.width as u64 * descriptor.size.height as u64 * descriptor.size.depth_or_array_layers as u64)
-            * descriptor.format.describe().block_size as u64;
+        let info = descriptor.format.describe();
+        let (block_width, block_height) = info.block_dimensions;
+        let blocks = (descriptor.size.width / block_width as u32) as u64 * (descriptor.size.height / block_height as u32) as u64;
+        let mut mip_size = blocks * descriptor.size.depth_or_array_layers as u64 * info.block_size as u64;
         let mut size_in_bytes = mip_size;
         for _ in 1..descriptor.mip_level_count {
             mip_size /= 2;
@@ -134,6 +138,30 @@ impl Texture {
         gpu.queue.submit(Some(encoder.finish()));
         texture
     }
+    /// Creates a texture with the mip levels of `texture`, which is sRGB if `format` is. If the GPU can't sample from the
+    /// format of `texture`, it's decoded and mipmapped as a `format` texture instead.
+    pub fn from_ktx2(assets: AssetCache, texture: &Ktx2Texture, format: wgpu::TextureFormat, label: wgpu::Label) -> anyhow::Result<Self> {
+        let gpu = GpuKey.get(&assets);
+        let ktx2_format = texture.format.texture_format(format.describe().srgb);
+        if gpu.device.features().contains(ktx2_format.describe().required_features) {
+            Ok(Self::new_with_data(
+                gpu,
+                &wgpu::TextureDescriptor {
+                    size: wgpu::Extent3d { width: texture.width, height: texture.height, depth_or_array_layers: 1 },
+                    mip_level_count: texture.levels.len() as u32,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: ktx2_format,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                    label,
+                },
+                &texture.levels.concat(),
+            ))
+        } else {
+            let image = texture.decode().with_context(|| format!("The GPU doesn't support {ktx2_format:?} textures"))?;
+            Ok(Self::from_rgba8_image_mipmapped(assets, &image, format, label))
+        }
+    }
     pub fn from_image(gpu: Arc<Gpu>, image: DynamicImage, format: wgpu::TextureFormat, label: wgpu::Label) -> Self {
         let img = image.into_rgba8();
 
@@ -11,7 +11,7 @@ use async_trait::async_trait;
 use futures::future::join_all;
 use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
 
-use crate::texture::Texture;
+use crate::{ktx2::Ktx2Texture, texture::Texture};
 
 #[derive(Debug, Clone)]
 pub struct ImageFromUrl {
@@ -40,6 +40,10 @@ async fn image_from_url(assets: AssetCache, url: AbsAssetUrl) -> Result<DynamicI
 
     let extension = url.extension().context("No extension")?;
     Ok(task::block_in_place(move || -> anyhow::Result<DynamicImage> {
+        if extension == "ktx2" {
+            let texture = Ktx2Texture::from_bytes(&data)?;
+            return Ok(DynamicImage::ImageRgba8(texture.decode().with_context(|| format!("Can't decode {:?} textures", texture.format))?));
+        }
         let format = ImageFormat::from_extension(extension).context("Invalid extension")?;
         Ok(image::io::Reader::with_format(Cursor::new(&*data), format).decode()?)
     })
@@ -59,6 +63,14 @@ impl AsyncAssetKey<Result<Arc<Texture>, AssetError>> for TextureFromUrl {
     }
     #[tracing::instrument(level = "info", name = "texture_from_url")]
     async fn load(self, assets: AssetCache) -> Result<Arc<Texture>, AssetError> {
+        if self.url.extension_is("ktx2") {
+            let data = BytesFromUrl::new(self.url.clone(), true).get(&assets).await?;
+            return task::block_in_place(|| -> anyhow::Result<Arc<Texture>> {
+                let texture = Ktx2Texture::from_bytes(&data).with_context(|| format!("Failed to load texture {}", self.url))?;
+                Ok(Arc::new(Texture::from_ktx2(assets, &texture, self.format, Some(&self.url.to_string()))?))
+            })
+            .map_err(Into::into);
+        }
         let image = image_from_url(assets.clone(), self.url.clone()).await?;
         task::block_in_place(|| Ok(Arc::new(Texture::from_image_mipmapped(assets, image, self.format, Some(&self.url.to_string())))))
     }
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use ambient_animation::{animation_bind_id_from_name, AnimationClip, AnimationOutputs, AnimationTarget, AnimationTrack, Vec3Field};
+use ambient_animation::{
+    animation_bind_id_from_name, AnimationClip, AnimationEvent, AnimationOutputs, AnimationTarget, AnimationTrack, Vec3Field,
+};
 use ambient_core::transform::{euler_rotation, scale, translation};
 use fbxcel::tree::v7400::NodeHandle;
 use itertools::Itertools;
@@ -66,6 +68,7 @@ pub fn get_animations(doc: &FbxDoc) -> HashMap<String, AnimationClip> {
                     .collect(),
 
                 start: stack.local_start.map(|x| x as f32 / FBX_TIME).unwrap_or(0.),
+                events: stack.events.clone(),
                 end: match stack.local_stop {
                     Some(x) => x as f32 / FBX_TIME,
                     None => {
@@ -110,6 +113,8 @@ pub struct FbxAnimationStack {
     pub local_start: Option<i64>,
     pub local_stop: Option<i64>,
     pub layers: Vec<i64>,
+    /// Read from the user defined `events` property of the stack
+    pub events: Vec<AnimationEvent>,
 }
 impl FbxAnimationStack {
     pub fn from_node(node: NodeHandle) -> Self {
@@ -119,6 +124,7 @@ impl FbxAnimationStack {
         let props = node.children().find(|node| node.name() == "Properties70");
         let mut local_stop = None;
         let mut local_start = None;
+        let mut events = Vec::new();
         if let Some(props) = props {
             if let Some(local_stop_node) = props.children().find(|node| node.attributes()[0].get_string().unwrap() == "LocalStop") {
                 local_stop = Some(local_stop_node.attributes()[4].get_i64().unwrap());
@@ -126,8 +132,13 @@ impl FbxAnimationStack {
             if let Some(local_start_node) = props.children().find(|node| node.attributes()[0].get_string().unwrap() == "LocalStart") {
                 local_start = Some(local_start_node.attributes()[4].get_i64().unwrap());
             }
+            if let Some(events_node) = props.children().find(|node| node.attributes()[0].get_string().unwrap() == "events") {
+                if let Some(json) = events_node.attributes().get(4).and_then(|value| value.get_string()) {
+                    events = crate::parse_animation_events(&name, json);
+                }
+            }
         }
-        Self { id, name, local_stop, local_start, layers: Vec::new() }
+        Self { id, name, local_stop, local_start, layers: Vec::new(), events }
     }
 }
 #[derive(Debug)]
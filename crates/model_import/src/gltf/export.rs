@@ -0,0 +1,859 @@
+//! Exports model crates, and the prefabs composed of them, to glTF 2.0 binary (`.glb`) files.
+
+use std::{collections::HashMap, io::Cursor};
+
+use ambient_animation::{animation_bind_id_from_name, AnimationClip, AnimationOutputs, AnimationTarget, Vec3Field};
+use ambient_core::{
+    hierarchy::children,
+    name,
+    transform::{euler_rotation, local_to_parent, mesh_to_local, rotation, scale, translation},
+};
+use ambient_ecs::{EntityId, World};
+use ambient_gpu::ktx2::Ktx2Texture;
+use ambient_model::{animation_bind_id, model_from_url, model_skin_ix, model_skins, pbr_renderer_primitives_from_url};
+use ambient_renderer::materials::pbr_material::PbrMaterialDesc;
+use ambient_std::{asset_url::AssetUrl, mesh::Mesh};
+use anyhow::{anyhow, Context};
+use glam::{vec3, EulerRot, Mat4, Quat, Vec3, Vec4};
+use image::{imageops::FilterType, DynamicImage, ImageOutputFormat, RgbaImage};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use serde_json::{json, Value};
+
+use crate::model_crate::{AssetMap, ModelCrate};
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Exports the main model of `model_crate` to a `.glb` file.
+pub fn model_crate_to_glb(model_crate: &ModelCrate) -> anyhow::Result<Vec<u8>> {
+    let mut exporter = GltfExporter::new();
+    exporter.add_model(model_crate, None, Mat4::IDENTITY)?;
+    Ok(exporter.to_glb())
+}
+
+/// Builds a glTF scene out of one or more models.
+///
+/// Each model is added under a node of its own, and brings along its meshes, materials, skins and animations.
+/// Only the most detailed LOD of each mesh is exported, and assets that are referenced by the model but are not
+/// part of its crate are skipped.
+#[derive(Default)]
+pub struct GltfExporter {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    skins: Vec<Value>,
+    animations: Vec<Value>,
+    scene_nodes: Vec<usize>,
+
+    // The assets of the model being added, keyed by their paths within its crate
+    material_cache: HashMap<String, Option<usize>>,
+    mesh_cache: HashMap<String, Option<usize>>,
+    texture_cache: HashMap<String, Option<usize>>,
+}
+impl GltfExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the main model of `model_crate` to the scene, under a node with the given `name` and `transform`.
+    /// Returns the index of that node.
+    pub fn add_model(&mut self, model_crate: &ModelCrate, name_: Option<&str>, transform: Mat4) -> anyhow::Result<usize> {
+        let root = self.add_model_node(model_crate, name_, transform)?;
+        self.scene_nodes.push(root);
+        Ok(root)
+    }
+
+    /// Adds the main model of `model_crate` under a node with the given `name` and `transform`, which is not added to the scene.
+    /// Returns the index of that node.
+    fn add_model_node(&mut self, model_crate: &ModelCrate, name_: Option<&str>, transform: Mat4) -> anyhow::Result<usize> {
+        self.material_cache.clear();
+        self.mesh_cache.clear();
+        self.texture_cache.clear();
+
+        let world = &model_crate.models.content.get(ModelCrate::MAIN).context("The model crate has no main model")?.0;
+        let roots = world.resource_opt(children()).cloned().unwrap_or_default();
+
+        // The nodes of the model are numbered in depth first order after its root node, followed by the nodes that
+        // offset meshes from their parents
+        let mut entities = Vec::new();
+        let mut stack = roots.iter().rev().copied().collect_vec();
+        while let Some(id) = stack.pop() {
+            entities.push(id);
+            if let Ok(c) = world.get_ref(id, children()) {
+                stack.extend(c.iter().rev());
+            }
+        }
+        let root = self.nodes.len();
+        let node_ix: HashMap<EntityId, usize> = entities.iter().enumerate().map(|(i, id)| (*id, root + 1 + i)).collect();
+        let mut mesh_nodes = Vec::new();
+
+        let mut root_node = transform_json(transform * world.resource_opt(local_to_parent()).copied().unwrap_or_default());
+        if let Some(name_) = name_.or_else(|| world.resource_opt(name()).map(|x| x.as_str())) {
+            root_node["name"] = json!(name_);
+        }
+        if !roots.is_empty() {
+            root_node["children"] = json!(roots.iter().map(|id| node_ix[id]).collect_vec());
+        }
+        self.nodes.push(root_node);
+
+        let skin_offset = self.skins.len();
+        for &id in &entities {
+            let mut node = json!({});
+            if let Ok(name_) = world.get_ref(id, name()) {
+                node["name"] = json!(name_);
+            }
+            if world.has_component(id, translation()) || world.has_component(id, rotation()) || world.has_component(id, scale()) {
+                node["translation"] = json!(world.get(id, translation()).unwrap_or_default().to_array());
+                node["rotation"] = json!(world.get(id, rotation()).unwrap_or_default().to_array());
+                node["scale"] = json!(world.get(id, scale()).unwrap_or(Vec3::ONE).to_array());
+            } else if let Ok(local_to_parent) = world.get(id, local_to_parent()) {
+                extend(&mut node, transform_json(local_to_parent));
+            }
+            let mut node_children = world.get_ref(id, children()).map(|c| c.iter().map(|id| node_ix[id]).collect_vec()).unwrap_or_default();
+
+            if let Ok(primitives) = world.get_ref(id, pbr_renderer_primitives_from_url()) {
+                if let Some(mesh) =
+                    self.add_mesh(model_crate, primitives.iter().filter(|p| p.lod == 0).map(|p| (&p.mesh, p.material.as_ref())))?
+                {
+                    let mut mesh_node = json!({ "mesh": mesh });
+                    if let Ok(skin) = world.get(id, model_skin_ix()) {
+                        mesh_node["skin"] = json!(skin_offset + skin);
+                    }
+                    // glTF can't offset a mesh from its node, so the mesh gets a child node of its own instead
+                    match world.get(id, mesh_to_local()) {
+                        Ok(mesh_to_local) if mesh_to_local != Mat4::IDENTITY => {
+                            extend(&mut mesh_node, transform_json(mesh_to_local));
+                            node_children.push(root + 1 + entities.len() + mesh_nodes.len());
+                            mesh_nodes.push(mesh_node);
+                        }
+                        _ => extend(&mut node, mesh_node),
+                    }
+                }
+            }
+            if !node_children.is_empty() {
+                node["children"] = json!(node_children);
+            }
+            self.nodes.push(node);
+        }
+        self.nodes.extend(mesh_nodes);
+
+        for skin in world.resource_opt(model_skins()).cloned().unwrap_or_default() {
+            let joints = skin
+                .joints
+                .iter()
+                .map(|id| node_ix.get(id).copied())
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| anyhow!("A skin of the model has joints outside of its hierarchy"))?;
+            let mut gltf_skin = json!({ "joints": joints });
+            if !skin.inverse_bind_matrices.is_empty() {
+                let data = f32_bytes(skin.inverse_bind_matrices.iter().flat_map(|m| m.to_cols_array()));
+                gltf_skin["inverseBindMatrices"] = json!(self.add_accessor(&data, FLOAT, "MAT4", skin.inverse_bind_matrices.len(), None));
+            }
+            self.skins.push(gltf_skin);
+        }
+
+        let bind_ids: HashMap<String, EntityId> = entities
+            .iter()
+            .filter_map(|&id| {
+                let bind_id = world
+                    .get_ref(id, animation_bind_id())
+                    .cloned()
+                    .ok()
+                    .or_else(|| Some(animation_bind_id_from_name(world.get_ref(id, name()).ok()?)))?;
+                Some((bind_id, id))
+            })
+            .collect();
+        for (_, clip) in model_crate.animations.content.iter().sorted_by_key(|(id, _)| *id) {
+            self.add_animation(world, clip, &bind_ids, &node_ix);
+        }
+        Ok(root)
+    }
+
+    /// Adds the hierarchy of `prefab` to the scene, with a node for each entity that has the entity's name and local transform.
+    /// The nodes of entities with a `model_from_url` contain that model; `load_model_crate` is given the url to load it.
+    pub fn add_prefab(
+        &mut self,
+        prefab: &World,
+        mut load_model_crate: impl FnMut(&str) -> anyhow::Result<ModelCrate>,
+    ) -> anyhow::Result<()> {
+        for id in prefab.resource_opt(children()).cloned().unwrap_or_default() {
+            let node = self.add_prefab_node(prefab, id, &mut load_model_crate)?;
+            self.scene_nodes.push(node);
+        }
+        Ok(())
+    }
+
+    fn add_prefab_node(
+        &mut self,
+        prefab: &World,
+        id: EntityId,
+        load_model_crate: &mut impl FnMut(&str) -> anyhow::Result<ModelCrate>,
+    ) -> anyhow::Result<usize> {
+        let name_ = prefab.get_ref(id, name()).ok().map(|x| x.as_str());
+        let transform = local_transform(prefab, id);
+        let node = match prefab.get_ref(id, model_from_url()) {
+            Ok(url) => {
+                let model_crate = load_model_crate(url)?;
+                self.add_model_node(&model_crate, name_, transform)?
+            }
+            Err(_) => {
+                let mut node = transform_json(transform);
+                if let Some(name_) = name_ {
+                    node["name"] = json!(name_);
+                }
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        for &child in prefab.get_ref(id, children()).map(|c| c.as_slice()).unwrap_or_default() {
+            let child = self.add_prefab_node(prefab, child, load_model_crate)?;
+            match self.nodes[node]["children"].as_array_mut() {
+                Some(node_children) => node_children.push(json!(child)),
+                None => self.nodes[node]["children"] = json!([child]),
+            }
+        }
+        Ok(node)
+    }
+
+    /// Writes the scene as a binary glTF file.
+    pub fn to_glb(mut self) -> Vec<u8> {
+        pad(&mut self.buffer, 0);
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "Ambient" },
+            "scene": 0,
+            "scenes": [{ "nodes": self.scene_nodes }],
+        });
+        for (key, values) in [
+            ("nodes", self.nodes),
+            ("meshes", self.meshes),
+            ("materials", self.materials),
+            ("images", self.images),
+            ("skins", self.skins),
+            ("animations", self.animations),
+            ("accessors", self.accessors),
+            ("bufferViews", self.buffer_views),
+        ] {
+            if !values.is_empty() {
+                document[key] = json!(values);
+            }
+        }
+        if !self.textures.is_empty() {
+            document["textures"] = json!(self.textures);
+            // Linear filtering with mipmaps, and repeating texture coordinates
+            document["samplers"] = json!([{ "magFilter": 9729, "minFilter": 9987, "wrapS": 10497, "wrapT": 10497 }]);
+        }
+        if !self.buffer.is_empty() {
+            document["buffers"] = json!([{ "byteLength": self.buffer.len() }]);
+        }
+        let mut json = serde_json::to_vec(&document).unwrap();
+        pad(&mut json, b' ');
+
+        let mut chunks = vec![(*b"JSON", json)];
+        if !self.buffer.is_empty() {
+            chunks.push((*b"BIN\0", self.buffer));
+        }
+        let length = 12 + chunks.iter().map(|(_, data)| 8 + data.len()).sum::<usize>();
+        let mut glb = Vec::with_capacity(length);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(length as u32).to_le_bytes());
+        for (ty, data) in chunks {
+            glb.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            glb.extend_from_slice(&ty);
+            glb.extend_from_slice(&data);
+        }
+        glb
+    }
+
+    fn add_mesh<'a>(
+        &mut self,
+        model_crate: &ModelCrate,
+        primitives: impl Iterator<Item = (&'a AssetUrl, Option<&'a AssetUrl>)>,
+    ) -> anyhow::Result<Option<usize>> {
+        let primitives = primitives.collect_vec();
+        let key =
+            primitives.iter().map(|(mesh, material)| format!("{mesh}:{}", material.map(|x| x.to_string()).unwrap_or_default())).join(";");
+        if let Some(mesh) = self.mesh_cache.get(&key) {
+            return Ok(*mesh);
+        }
+        let mut gltf_primitives = Vec::new();
+        for (mesh, material) in primitives {
+            let Some(mesh) = crate_asset(&model_crate.meshes, mesh) else {
+                log::warn!("Skipping the mesh {mesh}, which is not part of the model crate");
+                continue;
+            };
+            let material = match material {
+                Some(url) => self.add_material(model_crate, url)?,
+                None => None,
+            };
+            gltf_primitives.extend(self.add_primitive(mesh, material));
+        }
+        let mesh = if gltf_primitives.is_empty() {
+            None
+        } else {
+            self.meshes.push(json!({ "primitives": gltf_primitives }));
+            Some(self.meshes.len() - 1)
+        };
+        self.mesh_cache.insert(key, mesh);
+        Ok(mesh)
+    }
+
+    fn add_primitive(&mut self, mesh: &Mesh, material: Option<usize>) -> Option<Value> {
+        let Some(positions) = &mesh.positions else {
+            log::warn!("Skipping the mesh {:?}, which has no positions", mesh.name);
+            return None;
+        };
+        let position =
+            self.add_accessor(&f32_bytes(positions.iter().flat_map(|p| p.to_array())), FLOAT, "VEC3", positions.len(), Some(ARRAY_BUFFER));
+        if !positions.is_empty() {
+            let (min, max) =
+                positions.iter().fold((Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)), |(min, max), p| (min.min(*p), max.max(*p)));
+            self.accessors[position]["min"] = json!(min.to_array());
+            self.accessors[position]["max"] = json!(max.to_array());
+        }
+        let mut attributes = json!({ "POSITION": position });
+        if let Some(normals) = &mesh.normals {
+            attributes["NORMAL"] = json!(self.add_accessor(
+                &f32_bytes(normals.iter().flat_map(|n| n.to_array())),
+                FLOAT,
+                "VEC3",
+                normals.len(),
+                Some(ARRAY_BUFFER)
+            ));
+        }
+        if let Some(tangents) = &mesh.tangents {
+            let data = f32_bytes(tangents.iter().flat_map(|t| t.extend(1.).to_array()));
+            attributes["TANGENT"] = json!(self.add_accessor(&data, FLOAT, "VEC4", tangents.len(), Some(ARRAY_BUFFER)));
+        }
+        for (i, texcoords) in mesh.texcoords.iter().enumerate() {
+            let data = f32_bytes(texcoords.iter().flat_map(|t| t.to_array()));
+            attributes[format!("TEXCOORD_{i}")] = json!(self.add_accessor(&data, FLOAT, "VEC2", texcoords.len(), Some(ARRAY_BUFFER)));
+        }
+        if let Some(colors) = &mesh.colors {
+            attributes["COLOR_0"] = json!(self.add_accessor(
+                &f32_bytes(colors.iter().flat_map(|c| c.to_array())),
+                FLOAT,
+                "VEC4",
+                colors.len(),
+                Some(ARRAY_BUFFER)
+            ));
+        }
+        if let Some(joints) = &mesh.joint_indices {
+            let data = joints.iter().flat_map(|j| j.to_array()).flat_map(|j| (j as u16).to_le_bytes()).collect_vec();
+            attributes["JOINTS_0"] = json!(self.add_accessor(&data, UNSIGNED_SHORT, "VEC4", joints.len(), Some(ARRAY_BUFFER)));
+        }
+        if let Some(weights) = &mesh.joint_weights {
+            let data = f32_bytes(weights.iter().flat_map(|w| w.to_array()));
+            attributes["WEIGHTS_0"] = json!(self.add_accessor(&data, FLOAT, "VEC4", weights.len(), Some(ARRAY_BUFFER)));
+        }
+
+        let mut primitive = json!({ "attributes": attributes });
+        if let Some(indices) = &mesh.indices {
+            // The glTF importer reverses the winding of the triangles, so they're reversed back here
+            let data = indices
+                .chunks(3)
+                .flat_map(|triangle| match *triangle {
+                    [a, b, c] => vec![a, c, b],
+                    _ => triangle.to_vec(),
+                })
+                .flat_map(u32::to_le_bytes)
+                .collect_vec();
+            primitive["indices"] = json!(self.add_accessor(&data, UNSIGNED_INT, "SCALAR", indices.len(), Some(ELEMENT_ARRAY_BUFFER)));
+        }
+        if let Some(material) = material {
+            primitive["material"] = json!(material);
+        }
+        Some(primitive)
+    }
+
+    fn add_material(&mut self, model_crate: &ModelCrate, url: &AssetUrl) -> anyhow::Result<Option<usize>> {
+        let key = url.to_string();
+        if let Some(material) = self.material_cache.get(&key) {
+            return Ok(*material);
+        }
+        let material = match crate_asset(&model_crate.materials, url) {
+            Some(material) => Some(self.add_material_desc(model_crate, material)?),
+            None => {
+                log::warn!("Skipping the material {url}, which is not part of the model crate");
+                None
+            }
+        };
+        self.material_cache.insert(key, material);
+        Ok(material)
+    }
+
+    fn add_material_desc(&mut self, model_crate: &ModelCrate, material: &PbrMaterialDesc) -> anyhow::Result<usize> {
+        let mut pbr = json!({
+            "baseColorFactor": material.base_color_factor.unwrap_or(Vec4::ONE).to_array(),
+            "metallicFactor": material.metallic,
+            "roughnessFactor": material.roughness,
+        });
+
+        // glTF keeps the opacity in the alpha channel of the base color
+        let base_color_key =
+            format!("{:?}:{:?}", material.base_color.as_ref().map(|x| x.to_string()), material.opacity.as_ref().map(|x| x.to_string()));
+        let base_color = self.add_texture(base_color_key, || {
+            let base_color = material.base_color.as_ref().map(|url| crate_image(model_crate, url)).transpose()?.flatten();
+            let Some(opacity) = material.opacity.as_ref().map(|url| crate_image(model_crate, url)).transpose()?.flatten() else {
+                return Ok(base_color);
+            };
+            let mut base_color =
+                base_color.unwrap_or_else(|| RgbaImage::from_pixel(opacity.width(), opacity.height(), image::Rgba([255; 4])));
+            let opacity = image::imageops::resize(
+                &DynamicImage::ImageRgba8(opacity).into_luma8(),
+                base_color.width(),
+                base_color.height(),
+                FilterType::Triangle,
+            );
+            for (texel, alpha) in base_color.pixels_mut().zip(opacity.pixels()) {
+                texel[3] = alpha[0];
+            }
+            Ok(Some(base_color))
+        })?;
+        if let Some(index) = base_color {
+            pbr["baseColorTexture"] = json!({ "index": index });
+        }
+
+        if let Some(url) = &material.metallic_roughness {
+            // The reverse of the channel swizzle of the glTF importer; glTF keeps the metallic factor in the blue channel
+            let metallic_roughness = self.add_texture(format!("mr:{url}"), || {
+                Ok(crate_image(model_crate, url)?.map(|mut image| {
+                    for texel in image.pixels_mut() {
+                        texel[2] = texel[0];
+                        texel[0] = 0;
+                        texel[3] = 255;
+                    }
+                    image
+                }))
+            })?;
+            if let Some(index) = metallic_roughness {
+                pbr["metallicRoughnessTexture"] = json!({ "index": index });
+            }
+        }
+
+        let mut gltf_material = json!({ "pbrMetallicRoughness": pbr });
+        if let Some(name_) = &material.name {
+            gltf_material["name"] = json!(name_);
+        }
+        if let Some(url) = &material.normalmap {
+            if let Some(index) = self.add_texture(url.to_string(), || crate_image(model_crate, url))? {
+                gltf_material["normalTexture"] = json!({ "index": index });
+            }
+        }
+        if let Some(emissive) = material.emissive_factor.map(|x| x.truncate().clamp(Vec3::ZERO, Vec3::ONE)).filter(|x| *x != Vec3::ZERO) {
+            gltf_material["emissiveFactor"] = json!(emissive.to_array());
+        }
+        if material.transparent == Some(true) {
+            gltf_material["alphaMode"] = json!("BLEND");
+        } else if let Some(cutoff) = material.alpha_cutoff {
+            gltf_material["alphaMode"] = json!("MASK");
+            gltf_material["alphaCutoff"] = json!(cutoff);
+        }
+        if material.double_sided == Some(true) {
+            gltf_material["doubleSided"] = json!(true);
+        }
+        self.materials.push(gltf_material);
+        Ok(self.materials.len() - 1)
+    }
+
+    fn add_texture(&mut self, key: String, image: impl FnOnce() -> anyhow::Result<Option<RgbaImage>>) -> anyhow::Result<Option<usize>> {
+        if let Some(texture) = self.texture_cache.get(&key) {
+            return Ok(*texture);
+        }
+        let texture = match image()? {
+            Some(image) => {
+                let mut data = Cursor::new(Vec::new());
+                image.write_to(&mut data, ImageOutputFormat::Png)?;
+                let view = self.add_buffer_view(&data.into_inner(), None);
+                self.images.push(json!({ "bufferView": view, "mimeType": "image/png" }));
+                self.textures.push(json!({ "sampler": 0, "source": self.images.len() - 1 }));
+                Some(self.textures.len() - 1)
+            }
+            None => None,
+        };
+        self.texture_cache.insert(key, texture);
+        Ok(texture)
+    }
+
+    fn add_animation(
+        &mut self,
+        world: &World,
+        clip: &AnimationClip,
+        bind_ids: &HashMap<String, EntityId>,
+        node_ix: &HashMap<EntityId, usize>,
+    ) {
+        let mut vec3_tracks = Vec::new();
+        let mut quat_tracks = Vec::new();
+        let mut field_tracks: IndexMap<(EntityId, &str), Vec<(Vec3Field, &[f32], &[f32])>> = IndexMap::new();
+        for track in &clip.tracks {
+            let AnimationTarget::BinderId(bind_id) = &track.target else { continue };
+            let Some(&id) = bind_ids.get(bind_id) else { continue };
+            let component = track.outputs.component();
+            let property = if component == translation() {
+                "translation"
+            } else if component == scale() {
+                "scale"
+            } else if component == rotation() {
+                "rotation"
+            } else if component == euler_rotation() {
+                "euler_rotation"
+            } else {
+                continue;
+            };
+            match &track.outputs {
+                AnimationOutputs::Vec3 { data, .. } => vec3_tracks.push((id, property, track.inputs.clone(), data.clone())),
+                AnimationOutputs::Quat { data, .. } => quat_tracks.push((id, track.inputs.clone(), data.clone())),
+                AnimationOutputs::Vec3Field { field, data, .. } => {
+                    field_tracks.entry((id, property)).or_default().push((*field, track.inputs.as_slice(), data.as_slice()));
+                }
+            }
+        }
+
+        // glTF animates whole vectors, so the tracks of separate fields are resampled together
+        for ((id, property), fields) in field_tracks {
+            let inputs = fields.iter().flat_map(|(_, inputs, _)| inputs.iter().copied()).sorted_by(f32::total_cmp).dedup().collect_vec();
+            let rest = match property {
+                "translation" => world.get(id, translation()).unwrap_or_default(),
+                "scale" => world.get(id, scale()).unwrap_or(Vec3::ONE),
+                _ => world.get(id, euler_rotation()).unwrap_or_else(|_| quat_to_euler(world.get(id, rotation()).unwrap_or_default())),
+            };
+            let outputs = inputs
+                .iter()
+                .map(|&time| {
+                    let mut value = rest;
+                    for (field, inputs, data) in &fields {
+                        let Some(sample) = sample(inputs, data, time) else { continue };
+                        match field {
+                            Vec3Field::X => value.x = sample,
+                            Vec3Field::Y => value.y = sample,
+                            Vec3Field::Z => value.z = sample,
+                        }
+                    }
+                    value
+                })
+                .collect_vec();
+            vec3_tracks.push((id, property, inputs, outputs));
+        }
+
+        let mut channels = Vec::new();
+        let mut samplers = Vec::new();
+        let mut add_channel = |exporter: &mut Self, id: EntityId, path: &str, inputs: &[f32], outputs: Vec<f32>, ty: &str| {
+            let stride = if ty == "VEC4" { 4 } else { 3 };
+            let count = inputs.len().min(outputs.len() / stride);
+            if count == 0 {
+                return;
+            }
+            let inputs = &inputs[..count];
+            let input = exporter.add_accessor(&f32_bytes(inputs.iter().copied()), FLOAT, "SCALAR", count, None);
+            exporter.accessors[input]["min"] = json!([inputs.iter().copied().fold(f32::MAX, f32::min)]);
+            exporter.accessors[input]["max"] = json!([inputs.iter().copied().fold(f32::MIN, f32::max)]);
+            let output = exporter.add_accessor(&f32_bytes(outputs.into_iter().take(count * stride)), FLOAT, ty, count, None);
+            samplers.push(json!({ "input": input, "output": output, "interpolation": "LINEAR" }));
+            channels.push(json!({ "sampler": samplers.len() - 1, "target": { "node": node_ix[&id], "path": path } }));
+        };
+        for (id, property, inputs, outputs) in vec3_tracks {
+            if property == "euler_rotation" {
+                let outputs = outputs.into_iter().flat_map(|x| euler_to_quat(x).to_array()).collect_vec();
+                add_channel(self, id, "rotation", &inputs, outputs, "VEC4");
+            } else {
+                add_channel(self, id, property, &inputs, outputs.into_iter().flat_map(|x| x.to_array()).collect_vec(), "VEC3");
+            }
+        }
+        for (id, inputs, outputs) in quat_tracks {
+            add_channel(self, id, "rotation", &inputs, outputs.into_iter().flat_map(|x| x.to_array()).collect_vec(), "VEC4");
+        }
+
+        if channels.is_empty() {
+            log::warn!("Skipping the animation {:?}, which doesn't animate any node of the model", clip.id);
+            return;
+        }
+        let mut animation = json!({ "channels": channels, "samplers": samplers });
+        if !clip.id.is_empty() {
+            animation["name"] = json!(clip.id);
+        }
+        // The same extras that the glTF importer reads events from
+        if !clip.events.is_empty() {
+            animation["extras"] = json!({ "events": clip.events });
+        }
+        self.animations.push(animation);
+    }
+
+    fn add_buffer_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        pad(&mut self.buffer, 0);
+        let mut view = json!({ "buffer": 0, "byteOffset": self.buffer.len(), "byteLength": data.len() });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.buffer.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn add_accessor(&mut self, data: &[u8], component_type: u32, ty: &str, count: usize, target: Option<u32>) -> usize {
+        let view = self.add_buffer_view(data, target);
+        self.accessors.push(json!({ "bufferView": view, "componentType": component_type, "type": ty, "count": count }));
+        self.accessors.len() - 1
+    }
+}
+
+/// Looks up an asset of the model crate by a url relative to one of its files
+fn crate_asset<'a, T: Send + 'static>(map: &'a AssetMap<T>, url: &AssetUrl) -> Option<&'a T> {
+    match url {
+        AssetUrl::Relative(path) => map.get_by_path(path.clone()),
+        AssetUrl::Absolute(_) => None,
+    }
+}
+
+fn crate_image(model_crate: &ModelCrate, url: &AssetUrl) -> anyhow::Result<Option<RgbaImage>> {
+    if let Some(image) = crate_asset(&model_crate.images, url) {
+        Ok(Some(image.clone()))
+    } else if let Some(data) = crate_asset(&model_crate.compressed_images, url) {
+        Ok(Some(Ktx2Texture::from_bytes(data)?.decode().ok_or_else(|| anyhow!("The format of the texture {url} can't be decoded"))?))
+    } else {
+        log::warn!("Skipping the texture {url}, which is not part of the model crate");
+        Ok(None)
+    }
+}
+
+/// The transform of `id` relative to its parent
+fn local_transform(world: &World, id: EntityId) -> Mat4 {
+    if world.has_component(id, translation()) || world.has_component(id, rotation()) || world.has_component(id, scale()) {
+        Mat4::from_scale_rotation_translation(
+            world.get(id, scale()).unwrap_or(Vec3::ONE),
+            world.get(id, rotation()).unwrap_or_default(),
+            world.get(id, translation()).unwrap_or_default(),
+        )
+    } else {
+        world.get(id, local_to_parent()).unwrap_or_default()
+    }
+}
+
+fn transform_json(transform: Mat4) -> Value {
+    let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+    let mut node = json!({});
+    if translation != Vec3::ZERO {
+        node["translation"] = json!(translation.to_array());
+    }
+    if rotation != Quat::IDENTITY {
+        node["rotation"] = json!(rotation.to_array());
+    }
+    if scale != Vec3::ONE {
+        node["scale"] = json!(scale.to_array());
+    }
+    node
+}
+
+fn extend(node: &mut Value, other: Value) {
+    if let (Some(node), Value::Object(other)) = (node.as_object_mut(), other) {
+        node.extend(other);
+    }
+}
+
+fn euler_to_quat(euler: Vec3) -> Quat {
+    Quat::from_euler(EulerRot::ZYX, euler.z, euler.y, euler.x)
+}
+fn quat_to_euler(quat: Quat) -> Vec3 {
+    let (z, y, x) = quat.to_euler(EulerRot::ZYX);
+    vec3(x, y, z)
+}
+
+/// Linearly interpolates the keyframes of a track at `time`
+fn sample(inputs: &[f32], data: &[f32], time: f32) -> Option<f32> {
+    let count = inputs.len().min(data.len());
+    let (inputs, data) = (&inputs[..count], &data[..count]);
+    match inputs.partition_point(|&t| t <= time) {
+        0 => data.first().copied(),
+        i if i == count => data.last().copied(),
+        i => {
+            let p = (time - inputs[i - 1]) / (inputs[i] - inputs[i - 1]);
+            Some(data[i - 1] + (data[i] - data[i - 1]) * p)
+        }
+    }
+}
+
+fn f32_bytes(values: impl IntoIterator<Item = f32>) -> Vec<u8> {
+    values.into_iter().flat_map(f32::to_le_bytes).collect()
+}
+
+/// Pads `data` to a multiple of 4 bytes, which glTF aligns its buffers and chunks to
+fn pad(data: &mut Vec<u8>, value: u8) {
+    data.resize((data.len() + 3) / 4 * 4, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ambient_animation::AnimationTrack;
+    use ambient_core::hierarchy::parent;
+    use ambient_ecs::{query, Entity};
+    use ambient_model::{Model, ModelSkin, PbrRenderPrimitiveFromUrl};
+    use glam::{uvec4, vec2, vec4};
+
+    use super::{super::gltf_import::GltfImport, *};
+    use crate::dotdot_path;
+
+    fn init() {
+        ambient_ecs::init_components();
+        ambient_core::init_all_components();
+        ambient_model::init_components();
+    }
+
+    fn test_mesh() -> Mesh {
+        Mesh {
+            name: "quad".to_string(),
+            positions: Some(vec![vec3(0., 0., 0.), vec3(1., 0., 0.), vec3(1., 1., 0.), vec3(0., 1., 0.)]),
+            colors: None,
+            normals: Some(vec![Vec3::Z; 4]),
+            tangents: None,
+            texcoords: vec![vec![vec2(0., 0.), vec2(1., 0.), vec2(1., 1.), vec2(0., 1.)]],
+            joint_indices: Some(vec![uvec4(0, 0, 0, 0), uvec4(0, 0, 0, 0), uvec4(1, 0, 0, 0), uvec4(1, 0, 0, 0)]),
+            joint_weights: Some(vec![vec4(1., 0., 0., 0.); 4]),
+            indices: Some(vec![0, 1, 2, 0, 2, 3]),
+        }
+    }
+
+    /// A skinned quad with a `root` and a `joint` node, and an animation of both
+    fn test_model_crate() -> ModelCrate {
+        let mut model_crate = ModelCrate::new();
+        let mesh = model_crate.meshes.insert("quad", test_mesh()).path;
+        let material =
+            PbrMaterialDesc { name: Some("red".to_string()), base_color_factor: Some(vec4(1., 0., 0., 1.)), ..Default::default() };
+        let material = model_crate.materials.insert("red", material).path;
+
+        let mut world = World::new("model");
+        let joint = Entity::new()
+            .with(name(), "joint".to_string())
+            .with(translation(), vec3(0., 1., 0.))
+            .with(rotation(), Quat::from_rotation_z(0.5))
+            .with(scale(), Vec3::ONE)
+            .spawn(&mut world);
+        let root = Entity::new()
+            .with(name(), "root".to_string())
+            .with(translation(), vec3(1., 2., 3.))
+            .with(rotation(), Quat::IDENTITY)
+            .with(scale(), Vec3::splat(2.))
+            .with(children(), vec![joint])
+            .with(
+                pbr_renderer_primitives_from_url(),
+                vec![PbrRenderPrimitiveFromUrl { mesh: dotdot_path(&mesh).into(), material: Some(dotdot_path(&material).into()), lod: 0 }],
+            )
+            .with(model_skin_ix(), 0)
+            .spawn(&mut world);
+        world.add_component(joint, parent(), root).unwrap();
+        world.add_resource(children(), vec![root]);
+        world.add_resource(
+            model_skins(),
+            vec![ModelSkin {
+                inverse_bind_matrices: Arc::new(vec![Mat4::IDENTITY, Mat4::from_translation(vec3(0., -1., 0.))]),
+                joints: vec![root, joint],
+            }],
+        );
+        model_crate.models.insert(ModelCrate::MAIN, Model(world));
+
+        let mut clip = AnimationClip::from_tracks(vec![
+            AnimationTrack {
+                target: AnimationTarget::BinderId(animation_bind_id_from_name("joint")),
+                inputs: vec![0., 1.],
+                outputs: AnimationOutputs::Quat { component: rotation(), data: vec![Quat::IDENTITY, Quat::from_rotation_z(1.)] },
+            },
+            AnimationTrack {
+                target: AnimationTarget::BinderId(animation_bind_id_from_name("root")),
+                inputs: vec![0., 0.5, 1.],
+                outputs: AnimationOutputs::Vec3 { component: translation(), data: vec![Vec3::ZERO, Vec3::X, Vec3::ZERO] },
+            },
+        ]);
+        clip.id = "wave".to_string();
+        model_crate.animations.insert("wave", clip);
+        model_crate
+    }
+
+    async fn import_glb(glb: &[u8]) -> ModelCrate {
+        let import = GltfImport::from_slice("test".to_string(), true, glb).unwrap();
+        let mut model_crate = ModelCrate::new();
+        super::super::import(&import, &mut model_crate).await.unwrap();
+        model_crate
+    }
+
+    fn find_node(world: &World, name_: &str) -> EntityId {
+        query(name()).iter(world, None).find(|(_, x)| x.as_str() == name_).map(|(id, _)| id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        init();
+        let original = test_model_crate();
+        let imported = import_glb(&model_crate_to_glb(&original).unwrap()).await;
+
+        let meshes = imported.meshes.content.values().collect_vec();
+        assert_eq!(meshes.len(), 1);
+        let (mesh, original_mesh) = (meshes[0], test_mesh());
+        assert_eq!(mesh.positions.as_ref().unwrap().len(), 4);
+        assert_eq!(mesh.indices, original_mesh.indices);
+        assert_eq!(mesh.joint_indices, original_mesh.joint_indices);
+
+        let material = imported.materials.content.values().next().unwrap();
+        assert_eq!(material.base_color_factor, Some(vec4(1., 0., 0., 1.)));
+
+        let world = &imported.models.content[ModelCrate::MAIN].0;
+        let (root, joint) = (find_node(world, "root"), find_node(world, "joint"));
+        assert_eq!(world.get(root, translation()).unwrap(), vec3(1., 2., 3.));
+        assert_eq!(world.get(root, scale()).unwrap(), Vec3::splat(2.));
+        assert_eq!(world.get_ref(root, children()).unwrap(), &vec![joint]);
+        assert_eq!(world.get(joint, translation()).unwrap(), vec3(0., 1., 0.));
+        assert!(world.get(joint, rotation()).unwrap().abs_diff_eq(Quat::from_rotation_z(0.5), 1e-6));
+        assert!(world.has_component(root, pbr_renderer_primitives_from_url()));
+
+        let skins = world.resource(model_skins());
+        assert_eq!(skins.len(), 1);
+        assert_eq!(skins[0].joints, vec![root, joint]);
+        assert_eq!(*skins[0].inverse_bind_matrices, vec![Mat4::IDENTITY, Mat4::from_translation(vec3(0., -1., 0.))]);
+
+        let clips = imported.animations.content.values().collect_vec();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].id, "wave");
+        assert_eq!(clips[0].tracks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn prefab_hierarchy() {
+        init();
+        let mut prefab = World::new("prefab");
+        let character = Entity::new()
+            .with(name(), "character".to_string())
+            .with(translation(), vec3(0., 0., 1.))
+            .with(model_from_url(), "character/models/main.json".to_string())
+            .spawn(&mut prefab);
+        let marker = Entity::new().with(name(), "marker".to_string()).with(translation(), vec3(5., 0., 0.)).spawn(&mut prefab);
+        let group = Entity::new()
+            .with(name(), "group".to_string())
+            .with(translation(), vec3(10., 0., 0.))
+            .with(children(), vec![character, marker])
+            .spawn(&mut prefab);
+        prefab.add_resource(children(), vec![group]);
+
+        let mut exporter = GltfExporter::new();
+        exporter.add_prefab(&prefab, |_| Ok(test_model_crate())).unwrap();
+        let imported = import_glb(&exporter.to_glb()).await;
+
+        let world = &imported.models.content[ModelCrate::MAIN].0;
+        let (group, character, marker, root) =
+            (find_node(world, "group"), find_node(world, "character"), find_node(world, "marker"), find_node(world, "root"));
+        assert_eq!(world.resource(children()), &vec![group]);
+        assert_eq!(world.get_ref(group, children()).unwrap(), &vec![character, marker]);
+        assert_eq!(world.get_ref(character, children()).unwrap(), &vec![root]);
+        // Transforms stay relative to the parent
+        assert_eq!(world.get(group, translation()).unwrap(), vec3(10., 0., 0.));
+        assert_eq!(world.get(character, translation()).unwrap(), vec3(0., 0., 1.));
+        assert_eq!(world.get(marker, translation()).unwrap(), vec3(5., 0., 0.));
+    }
+}
@@ -19,6 +19,7 @@ use relative_path::RelativePathBuf;
 use self::gltf_import::GltfImport;
 use crate::{dotdot_path, model_crate::ModelCrate};
 
+pub mod export;
 mod gltf_import;
 
 pub async fn import_url(assets: &AssetCache, url: &AbsAssetUrl, asset_crate: &mut ModelCrate) -> anyhow::Result<RelativePathBuf> {
@@ -112,6 +113,15 @@ pub async fn import(import: &GltfImport, asset_crate: &mut ModelCrate) -> anyhow
             .collect();
         let mut animation_clip = AnimationClip::from_tracks(tracks);
         animation_clip.id = animation.name().unwrap_or("").to_string();
+        // Events are read from the animation's extras, e.g. `{ "events": [{ "name": "footstep", "time": 0.3 }] }`
+        if let Some(events) = animation
+            .extras()
+            .as_ref()
+            .and_then(|extras| serde_json::from_str::<serde_json::Value>(extras.get()).ok())
+            .and_then(|extras| extras.get("events").map(|events| events.to_string()))
+        {
+            animation_clip.events = crate::parse_animation_events(&animation_clip.id, &events);
+        }
         asset_crate.animations.insert(&format!("{}{}", name_(animation.name()), index), animation_clip);
     }
 
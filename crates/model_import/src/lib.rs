@@ -1,8 +1,9 @@
 use std::{f32::consts::PI, path::PathBuf, sync::Arc};
 
-use ambient_animation::AnimationOutputs;
+use ambient_animation::{AnimationEvent, AnimationOutputs};
 use ambient_core::{bounding::local_bounding_aabb, transform::translation};
 use ambient_editor_derive::ElementEditor;
+use ambient_gpu::ktx2::Ktx2Texture;
 use ambient_renderer::materials::pbr_material::PbrMaterialDesc;
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
@@ -115,6 +116,8 @@ pub enum ModelImportTransform {
     Transform(ModelTransform),
     OverrideMaterial { filter: MaterialFilter, material: Box<PbrMaterialDesc> },
     CapTextureSizes { max_size: ModelTextureSize },
+    CompressTextures { compression: TextureCompression },
+    GenerateMeshLods { levels: Vec<MeshLodLevel> },
     // RemoveAllMaterials,
     // SetAnimatable { animatable: bool },
     CreatePrefab,
@@ -151,6 +154,12 @@ impl ModelImportTransform {
             ModelImportTransform::CapTextureSizes { max_size } => {
                 model_crate.cap_texture_sizes(max_size.size());
             }
+            ModelImportTransform::CompressTextures { compression } => {
+                model_crate.compress_textures(*compression);
+            }
+            ModelImportTransform::GenerateMeshLods { levels } => {
+                model_crate.generate_mesh_lods(levels);
+            }
             // AssetTransform::RemoveAllMaterials => {
             //     model.cpu_materials.clear();
             //     model.gpu_materials.clear();
@@ -284,6 +293,16 @@ impl ModelTransform {
     }
 }
 
+/// A simplified level of detail of a mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MeshLodLevel {
+    /// The fraction of the triangles of the original mesh to keep, e.g. `0.5` for half of them.
+    pub triangle_ratio: f32,
+    /// This level of detail is used once the radius of the model's bounding sphere on screen is smaller than this,
+    /// where `1` is half the height of the screen.
+    pub screen_size: f32,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ElementEditor)]
 pub enum ModelTextureSize {
     /// Cap this model's textures to 128x128.
@@ -321,6 +340,22 @@ impl Default for ModelTextureSize {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextureCompression {
+    /// Compress opaque textures to BC1 and textures with transparency to BC3, in KTX2 files with mipmaps.
+    /// These are supported by desktop GPUs; on other GPUs, they are decompressed when they are loaded.
+    /// Normal maps are left uncompressed.
+    Bc,
+}
+impl TextureCompression {
+    /// Compresses `image`, and returns it as a KTX2 file.
+    pub fn compress(&self, image: &RgbaImage) -> Vec<u8> {
+        match self {
+            TextureCompression::Bc => Ktx2Texture::compress_bc(image).to_bytes(),
+        }
+    }
+}
+
 // #[derive(Debug, Clone)]
 // pub struct ModelFromAssetPipeline(pub ModelImportPipeline);
 // impl ModelFromAssetPipeline {
@@ -341,6 +376,17 @@ impl Default for ModelTextureSize {
 
 pub const MODEL_EXTENSIONS: &[&str] = &["glb", "fbx", "obj"];
 
+/// Parses the events of the animation `clip` from the JSON in a model file; a list like `[{ "name": "footstep", "time": 0.3 }]`.
+pub(crate) fn parse_animation_events(clip: &str, json: &str) -> Vec<AnimationEvent> {
+    match serde_json::from_str(json) {
+        Ok(events) => events,
+        Err(err) => {
+            log::warn!("Failed to parse the animation events of {clip:?}: {err}");
+            Vec::new()
+        }
+    }
+}
+
 /// ../[path]
 pub fn dotdot_path(path: impl Into<RelativePathBuf>) -> RelativePathBuf {
     RelativePathBuf::from("..").join(path.into())
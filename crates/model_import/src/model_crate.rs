@@ -1,4 +1,9 @@
-use std::{collections::HashMap, io::Cursor, path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use ambient_animation::{animation_bind_id_from_name, AnimationClip};
 use ambient_core::{
@@ -37,7 +42,7 @@ use ordered_float::Float;
 use physxx::{PxConvexFlag, PxConvexMeshDesc, PxDefaultMemoryOutputStream, PxMeshFlag, PxTriangleMeshDesc};
 use relative_path::RelativePathBuf;
 
-use crate::{dotdot_path, MaterialFilter, TextureResolver};
+use crate::{dotdot_path, MaterialFilter, MeshLodLevel, TextureCompression, TextureResolver};
 
 #[derive(Debug, Clone)]
 pub struct AssetLoc {
@@ -88,6 +93,18 @@ impl<T: Send + 'static> AssetMap<T> {
     pub fn to_items(&self) -> Vec<AssetItem> {
         self.content.iter().map(|(id, content)| AssetItem { path: self.loc.path(id), data: Arc::new((self.serialize)(content)) }).collect()
     }
+    fn read_from_fs(&mut self, path: &Path, deserialize: impl Fn(&[u8]) -> anyhow::Result<T>) -> anyhow::Result<()> {
+        let Ok(entries) = std::fs::read_dir(path.join(&self.loc.store)) else { return Ok(()) };
+        for entry in entries {
+            let file = entry?.path();
+            if file.extension().map_or(false, |x| x == self.loc.extension.as_str()) {
+                let id = file.file_stem().unwrap().to_string_lossy().to_string();
+                let data = std::fs::read(&file).with_context(|| format!("Failed to read file: {file:?}"))?;
+                self.content.insert(id, deserialize(&data).with_context(|| format!("Failed to load {file:?}"))?);
+            }
+        }
+        Ok(())
+    }
 }
 
 pub struct ModelCrate {
@@ -96,6 +113,8 @@ pub struct ModelCrate {
     pub meshes: AssetMap<Mesh>,
     pub animations: AssetMap<AnimationClip>,
     pub images: AssetMap<image::RgbaImage>,
+    /// Images that have been compressed for the GPU, as KTX2 files
+    pub compressed_images: AssetMap<Vec<u8>>,
     pub materials: AssetMap<PbrMaterialDesc>,
     pub px_triangle_meshes: AssetMap<Vec<u8>>,
     pub px_convex_meshes: AssetMap<Vec<u8>>,
@@ -113,6 +132,7 @@ impl ModelCrate {
                 v.write_to(&mut data, ImageOutputFormat::Png).unwrap();
                 data.into_inner()
             }),
+            compressed_images: AssetMap::new("images", "ktx2", |v| v.clone()),
             materials: AssetMap::new("materials", "json", |v| serde_json::to_vec(v).unwrap()),
             px_triangle_meshes: AssetMap::new("px_triangle_meshes", "pxtm", |v| v.clone()),
             px_convex_meshes: AssetMap::new("px_convex_meshes", "pxcm", |v| v.clone()),
@@ -152,6 +172,21 @@ impl ModelCrate {
             tokio::fs::write(&item_path, &*item.data).await.context(format!("Failed to write file: {item_path:?}")).unwrap();
         }
     }
+    /// Reads a model crate that was written with [Self::write_to_fs]
+    pub fn read_from_fs(path: &Path) -> anyhow::Result<Self> {
+        let mut res = Self::new();
+        res.models.read_from_fs(path, Model::from_slice)?;
+        res.prefabs.read_from_fs(path, World::from_slice)?;
+        res.meshes.read_from_fs(path, |data| Ok(bincode::deserialize(data)?))?;
+        res.animations.read_from_fs(path, |data| Ok(bincode::deserialize(data)?))?;
+        res.images.read_from_fs(path, |data| Ok(image::load_from_memory(data)?.into_rgba8()))?;
+        res.compressed_images.read_from_fs(path, |data| Ok(data.to_vec()))?;
+        res.materials.read_from_fs(path, |data| Ok(serde_json::from_slice(data)?))?;
+        res.px_triangle_meshes.read_from_fs(path, |data| Ok(data.to_vec()))?;
+        res.px_convex_meshes.read_from_fs(path, |data| Ok(data.to_vec()))?;
+        res.colliders.read_from_fs(path, |data| Ok(serde_json::from_slice(data)?))?;
+        Ok(res)
+    }
     pub fn to_items(&self) -> Vec<AssetItem> {
         [
             self.models.to_items().into_iter(),
@@ -159,6 +194,7 @@ impl ModelCrate {
             self.meshes.to_items().into_iter(),
             self.animations.to_items().into_iter(),
             self.images.to_items().into_iter(),
+            self.compressed_images.to_items().into_iter(),
             self.materials.to_items().into_iter(),
             self.px_triangle_meshes.to_items().into_iter(),
             self.px_convex_meshes.to_items().into_iter(),
@@ -303,6 +339,49 @@ impl ModelCrate {
         });
         self.merge_mesh_lods(cutoffs, lods.into_iter().map(|id| ModelNodeRef { model: source, root: Some(id) }).collect())
     }
+    /// Adds simplified versions of the meshes of each node as its lower levels of detail, which are used once the model is smaller
+    /// than their `screen_size` on screen. Nodes that already have levels of detail are left as they are.
+    pub fn generate_mesh_lods(&mut self, levels: &[MeshLodLevel]) {
+        if levels.is_empty() {
+            return;
+        }
+        let cutoffs = levels.iter().map(|level| level.screen_size).chain([0.]).collect_vec();
+        let world = &mut self.models.content.get_mut(ModelCrate::MAIN).unwrap().0;
+        let mut simplified = HashMap::new();
+        for (node, primitives) in query(pbr_renderer_primitives_from_url()).excl(lod_cutoffs()).collect_cloned(world, None) {
+            if primitives.is_empty() || primitives.iter().any(|primitive| primitive.lod != 0) {
+                continue;
+            }
+            let mut lod_primitives = primitives.clone();
+            for primitive in &primitives {
+                let Some(mesh_id) = self.meshes.loc.id_from_path(primitive.mesh.path()) else { continue };
+                for (i, level) in levels.iter().enumerate() {
+                    let lod_mesh_id = format!("{mesh_id}_lod{}", i + 1);
+                    if !simplified.contains_key(&lod_mesh_id) {
+                        let Some(mesh) = self.meshes.content.get(&mesh_id).map(|mesh| mesh.simplify(level.triangle_ratio)) else {
+                            continue;
+                        };
+                        let path = self.meshes.insert(lod_mesh_id.clone(), mesh).path;
+                        simplified.insert(lod_mesh_id.clone(), path);
+                    }
+                    lod_primitives.push(PbrRenderPrimitiveFromUrl {
+                        mesh: dotdot_path(simplified[&lod_mesh_id].clone()).into(),
+                        material: primitive.material.clone(),
+                        lod: i + 1,
+                    });
+                }
+            }
+            world
+                .add_components(
+                    node,
+                    Entity::new()
+                        .with(pbr_renderer_primitives_from_url(), lod_primitives)
+                        .with(lod_cutoffs(), LodCutoffs::new(&cutoffs))
+                        .with_default(gpu_lod()),
+                )
+                .unwrap();
+        }
+    }
     pub fn set_all_material(&mut self, material: PbrMaterialDesc) {
         self.materials.content.clear();
         let mat_path = dotdot_path(self.materials.insert("main".to_string(), material).path);
@@ -423,6 +502,32 @@ impl ModelCrate {
             cap_texture_size(image, max_size);
         }
     }
+    /// Compresses the images of the model for the GPU, and makes its materials use the compressed images.
+    ///
+    /// Normal maps are left uncompressed, as BC1 and BC3 would ruin their precision.
+    pub fn compress_textures(&mut self, compression: TextureCompression) {
+        let normalmaps = self
+            .materials
+            .content
+            .values()
+            .filter_map(|material| material.normalmap.as_ref())
+            .filter_map(|url| self.images.loc.id_from_path(url.path()))
+            .collect::<HashSet<_>>();
+        let ids = self.images.content.keys().filter(|id| !normalmaps.contains(*id)).cloned().collect_vec();
+        for id in ids {
+            let image = self.images.content.remove(&id).unwrap();
+            self.compressed_images.insert(id, compression.compress(&image));
+        }
+        for material in self.materials.content.values_mut() {
+            for url in [&mut material.base_color, &mut material.opacity, &mut material.metallic_roughness].into_iter().flatten() {
+                if let Some(id) = self.images.loc.id_from_path(url.path()) {
+                    if self.compressed_images.content.contains_key(&id) {
+                        *url = dotdot_path(self.compressed_images.loc.path(id)).into();
+                    }
+                }
+            }
+        }
+    }
     pub fn update_transforms(&mut self) {
         TransformSystem::new().run(self.model_world_mut(), &FrameEvent);
     }
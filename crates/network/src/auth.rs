@@ -0,0 +1,190 @@
+use ambient_sys::time::SystemTime;
+use async_trait::async_trait;
+use ring::{constant_time::verify_slices_are_equal, hmac};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The credentials the client presents to the server during the handshake
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClientCredentials {
+    /// The user ID the client claims to be
+    pub user_id: String,
+    /// A token proving the client's identity, if any. Its meaning depends on the server's [`Authenticator`]
+    pub token: Option<String>,
+}
+
+impl std::fmt::Debug for ClientCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientCredentials").field("user_id", &self.user_id).field("has_token", &self.token.is_some()).finish()
+    }
+}
+
+/// The identity of a client, as verified by an [`Authenticator`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedIdentity {
+    pub user_id: String,
+    /// The name to show to other players
+    pub display_name: String,
+}
+
+/// The reason a client was not allowed to connect. Sent to the client so that it can be shown to the user.
+#[derive(Debug, Clone, Error, Serialize, Deserialize, PartialEq, Eq)]
+#[error("{reason}")]
+pub struct AuthError {
+    pub reason: String,
+}
+impl AuthError {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self { reason: reason.into() }
+    }
+}
+
+/// Decides whether a connecting client is allowed to join the server, and who they are.
+///
+/// Runs during the handshake, before the player is spawned.
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    async fn authenticate(&self, credentials: &ClientCredentials) -> Result<VerifiedIdentity, AuthError>;
+}
+
+/// Accepts every client as the user they claim to be
+#[derive(Debug, Clone, Default)]
+pub struct Unauthenticated;
+
+#[async_trait]
+impl Authenticator for Unauthenticated {
+    async fn authenticate(&self, credentials: &ClientCredentials) -> Result<VerifiedIdentity, AuthError> {
+        Ok(VerifiedIdentity { user_id: credentials.user_id.clone(), display_name: credentials.user_id.clone() })
+    }
+}
+
+/// Accepts clients whose token is the shared secret
+pub struct SharedSecretAuthenticator {
+    secret: String,
+}
+impl SharedSecretAuthenticator {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self { secret: secret.into() }
+    }
+}
+
+#[async_trait]
+impl Authenticator for SharedSecretAuthenticator {
+    async fn authenticate(&self, credentials: &ClientCredentials) -> Result<VerifiedIdentity, AuthError> {
+        let token = credentials.token.as_deref().ok_or_else(|| AuthError::new("This server requires a token to join"))?;
+        verify_slices_are_equal(token.as_bytes(), self.secret.as_bytes()).map_err(|_| AuthError::new("Invalid token"))?;
+
+        Ok(VerifiedIdentity { user_id: credentials.user_id.clone(), display_name: credentials.user_id.clone() })
+    }
+}
+
+/// Accepts clients whose token is a JWT signed with HS256 using the server's key.
+///
+/// The token's `sub` claim must match the user ID of the client. The `name` claim is used as the display name if present,
+/// and the `exp` and `nbf` claims are checked if present.
+pub struct JwtAuthenticator {
+    key: hmac::Key,
+}
+impl JwtAuthenticator {
+    pub fn new_hs256(key: &[u8]) -> Self {
+        Self { key: hmac::Key::new(hmac::HMAC_SHA256, key) }
+    }
+
+    fn verify(&self, token: &str) -> Result<JwtClaims, AuthError> {
+        let invalid = || AuthError::new("Invalid token");
+        let decode = |part: &str| base64::decode_config(part, base64::URL_SAFE_NO_PAD).map_err(|_| invalid());
+
+        let mut parts = token.split('.');
+        let (Some(header), Some(claims), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+
+        let header: JwtHeader = serde_json::from_slice(&decode(header)?).map_err(|_| invalid())?;
+        if header.alg != "HS256" {
+            return Err(AuthError::new(format!("Unsupported token algorithm: {}", header.alg)));
+        }
+
+        // The signature is computed over the `header.claims` part of the token
+        let signing_input = &token[..token.rfind('.').unwrap()];
+        hmac::verify(&self.key, signing_input.as_bytes(), &decode(signature)?).map_err(|_| invalid())?;
+
+        let claims: JwtClaims = serde_json::from_slice(&decode(claims)?).map_err(|_| invalid())?;
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        if claims.exp.map(|exp| now >= exp).unwrap_or(false) {
+            return Err(AuthError::new("Token has expired"));
+        }
+        if claims.nbf.map(|nbf| now < nbf).unwrap_or(false) {
+            return Err(AuthError::new("Token is not valid yet"));
+        }
+
+        Ok(claims)
+    }
+}
+
+#[async_trait]
+impl Authenticator for JwtAuthenticator {
+    async fn authenticate(&self, credentials: &ClientCredentials) -> Result<VerifiedIdentity, AuthError> {
+        let token = credentials.token.as_deref().ok_or_else(|| AuthError::new("This server requires a token to join"))?;
+        let claims = self.verify(token)?;
+        if claims.sub != credentials.user_id {
+            return Err(AuthError::new("Token was issued for a different user"));
+        }
+
+        Ok(VerifiedIdentity { display_name: claims.name.unwrap_or_else(|| claims.sub.clone()), user_id: claims.sub })
+    }
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct JwtClaims {
+    sub: String,
+    name: Option<String>,
+    exp: Option<u64>,
+    nbf: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(key: &[u8], header: &str, claims: &str) -> String {
+        let encode = |part: &[u8]| base64::encode_config(part, base64::URL_SAFE_NO_PAD);
+        let signing_input = format!("{}.{}", encode(header.as_bytes()), encode(claims.as_bytes()));
+        let signature = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), signing_input.as_bytes());
+        format!("{signing_input}.{}", encode(signature.as_ref()))
+    }
+
+    #[test]
+    fn jwt_valid() {
+        let auth = JwtAuthenticator::new_hs256(b"secret");
+        let token = sign(b"secret", r#"{"alg":"HS256","typ":"JWT"}"#, r#"{"sub":"user_1","name":"Player One"}"#);
+        let claims = auth.verify(&token).unwrap();
+        assert_eq!(claims.sub, "user_1");
+        assert_eq!(claims.name.as_deref(), Some("Player One"));
+    }
+
+    #[test]
+    fn jwt_wrong_key() {
+        let auth = JwtAuthenticator::new_hs256(b"secret");
+        let token = sign(b"not the secret", r#"{"alg":"HS256"}"#, r#"{"sub":"user_1"}"#);
+        assert!(auth.verify(&token).is_err());
+    }
+
+    #[test]
+    fn jwt_expired() {
+        let auth = JwtAuthenticator::new_hs256(b"secret");
+        let token = sign(b"secret", r#"{"alg":"HS256"}"#, r#"{"sub":"user_1","exp":1}"#);
+        assert_eq!(auth.verify(&token).err(), Some(AuthError::new("Token has expired")));
+    }
+
+    #[test]
+    fn jwt_unsupported_algorithm() {
+        let auth = JwtAuthenticator::new_hs256(b"secret");
+        let token = sign(b"secret", r#"{"alg":"none"}"#, r#"{"sub":"user_1"}"#);
+        assert!(auth.verify(&token).is_err());
+    }
+}
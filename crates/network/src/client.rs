@@ -31,6 +31,7 @@ use tokio::io::AsyncReadExt;
 use tracing::{debug_span, Instrument};
 
 use crate::{
+    auth::ClientCredentials,
     client_game_state::ClientGameState,
     create_client_endpoint_random_port, is_remote_entity, log_network_result,
     protocol::{ClientInfo, ClientProtocol, ServerInfo},
@@ -136,6 +137,8 @@ pub type InitCallback = Box<dyn FnOnce(&mut World, Arc<RenderTarget>) + Send + S
 pub struct GameClientView {
     pub server_addr: SocketAddr,
     pub user_id: String,
+    /// Sent to the server to prove the identity of the user
+    pub auth_token: Option<String>,
     pub systems_and_resources: Cb<dyn Fn() -> (SystemGroup, Entity) + Sync + Send>,
     pub init_world: Cb<UseOnce<InitCallback>>,
     pub error_view: Cb<dyn Fn(String) -> Element + Sync + Send>,
@@ -153,6 +156,7 @@ impl Clone for GameClientView {
         Self {
             server_addr: self.server_addr,
             user_id: self.user_id.clone(),
+            auth_token: self.auth_token.clone(),
             systems_and_resources: self.systems_and_resources.clone(),
             init_world: self.init_world.clone(),
             error_view: self.error_view.clone(),
@@ -172,6 +176,7 @@ impl ElementComponent for GameClientView {
         let Self {
             server_addr,
             user_id,
+            auth_token,
             init_world,
             error_view,
             systems_and_resources,
@@ -316,7 +321,7 @@ impl ElementComponent for GameClientView {
                     let client_loop = ClientInstance {
                         set_connection_status,
                         server_addr,
-                        user_id,
+                        credentials: ClientCredentials { user_id, token: auth_token },
                         on_init: &mut on_init,
                         on_diff: &mut on_diff,
                         on_bi_stream: &on_bi_stream,
@@ -333,6 +338,11 @@ impl ElementComponent for GameClientView {
                             if let Some(err) = err.downcast_ref::<NetworkError>() {
                                 if let NetworkError::ConnectionClosed = err {
                                     log::info!("Connection closed by peer");
+                                } else if let NetworkError::HandshakeRejected(rejection) = err {
+                                    log::warn!("Server refused the connection: {rejection}");
+                                    // Show the reason by itself, as it is meant to be read by the user
+                                    set_error(Some(rejection.to_string()));
+                                    return;
                                 } else {
                                     log::error!("Network error: {:?}", err);
                                 }
@@ -395,7 +405,7 @@ pub fn GameClientWorld(hooks: &mut Hooks) -> Element {
 struct ClientInstance<'a> {
     set_connection_status: CallbackFn<String>,
     server_addr: SocketAddr,
-    user_id: String,
+    credentials: ClientCredentials,
 
     /// Called when the client connected and received the world.
     on_init: &'a mut (dyn FnMut(Connection, ClientInfo, ServerInfo) -> anyhow::Result<Box<dyn FnOnce() + Sync + Send>> + Send + Sync),
@@ -429,7 +439,7 @@ impl<'a> ClientInstance<'a> {
         (self.set_connection_status)("Waiting for server to respond".to_string());
 
         // Set up the protocol.
-        let mut protocol = ClientProtocol::new(conn, self.user_id.clone()).await?;
+        let mut protocol = ClientProtocol::new(conn, self.credentials.clone()).await?;
 
         let stats_interval = 5;
         let mut stats_timer = tokio::time::interval(Duration::from_secs_f32(stats_interval as f32));
@@ -11,6 +11,7 @@ use ambient_std::log_error;
 use bytes::Bytes;
 use connection::Connection;
 use futures::{Future, SinkExt, StreamExt};
+use protocol::HandshakeRejection;
 use quinn::{ClientConfig, ConnectionClose, ConnectionError::ConnectionClosed, Endpoint, ServerConfig, TransportConfig};
 use rand::Rng;
 use rustls::{Certificate, PrivateKey, RootCertStore};
@@ -22,12 +23,16 @@ use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 pub use ambient_ecs::generated::components::core::network::{is_remote_entity, persistent_resources, synced_resources};
 
 pub type AsyncMutex<T> = tokio::sync::Mutex<T>;
+pub mod auth;
 pub mod client;
 pub mod client_connection;
 pub mod client_game_state;
 pub mod connection;
 pub mod hooks;
 pub mod protocol;
+pub mod recording;
+pub mod relevance;
+pub mod replay;
 pub mod rpc;
 pub mod server;
 
@@ -143,6 +148,8 @@ pub enum NetworkError {
     RpcError(#[from] RpcError),
     #[error(transparent)]
     ProxyError(#[from] ambient_proxy::Error),
+    #[error("The server refused the connection. {0}")]
+    HandshakeRejected(#[from] HandshakeRejection),
 }
 
 impl NetworkError {
@@ -237,6 +244,13 @@ impl OutgoingStream {
         let bytes = bincode::serialize(value)?;
         self.send_bytes(bytes).await
     }
+
+    /// Closes the stream once all sent data has been received by the peer
+    pub async fn finish(&mut self) -> Result<(), NetworkError> {
+        self.stream.close().await?;
+
+        Ok(())
+    }
 }
 
 /// Are you sure you don't want [open_bincode_bi_stream_with_id] instead?
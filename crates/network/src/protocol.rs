@@ -3,13 +3,39 @@ use ambient_std::asset_url::AbsAssetUrl;
 use anyhow::{Context, Result};
 use futures::io::BufReader;
 use quinn::{Connection, RecvStream};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::{
-    client_connection::ClientConnection, next_bincode_bi_stream, open_bincode_bi_stream, IncomingStream, NetworkError, OutgoingStream,
+    auth::{AuthError, Authenticator, ClientCredentials},
+    client_connection::ClientConnection,
+    next_bincode_bi_stream, open_bincode_bi_stream, IncomingStream, NetworkError, OutgoingStream,
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The version of the network protocol, following semver.
+///
+/// The messages are encoded with bincode, which can't skip unknown fields or tolerate reordered ones, so adding, removing
+/// or reordering a field of any message needs a major bump. Bump the minor version when adding behavior that is guarded by
+/// a capability, and therefore only used once both peers have announced it. Clients and servers connect as long as their
+/// protocol versions are semver-compatible, regardless of the version of Ambient they are running.
+pub const PROTOCOL_VERSION: &str = "1.0.0";
+
+/// The optional protocol features supported by this build. Only the capabilities supported by both the client and the
+/// server are enabled for a connection; see [`ClientInfo::has_capability`].
+pub const CAPABILITIES: &[&str] = &[];
+
+/// Returns true if peers using the protocol versions `a` and `b` can talk to each other
+pub fn is_protocol_compatible(a: &str, b: &str) -> bool {
+    let (Ok(a), Ok(b)) = (Version::parse(a), Version::parse(b)) else {
+        return false;
+    };
+    let (older, newer) = if a <= b { (a, b) } else { (b, a) };
+    VersionReq::parse(&format!("^{older}")).map(|req| req.matches(&newer)).unwrap_or(false)
+}
+
 #[derive(Debug)]
 pub struct ClientProtocol {
     pub(crate) conn: Connection,
@@ -21,20 +47,20 @@ pub struct ClientProtocol {
 }
 
 impl ClientProtocol {
-    pub async fn new(conn: Connection, player_id: String) -> Result<Self> {
-        // Say who we are
-        // The server will respond appropriately and return things such as
-        // username (TODO)
+    pub async fn new(conn: Connection, credentials: ClientCredentials) -> Result<Self> {
+        // Say who we are, and which protocol we speak
+        // The server will verify our credentials and respond with our identity, or the reason we were rejected
         let (mut tx, mut rx) = open_bincode_bi_stream(&conn).await?;
-        tx.send(&player_id).await?;
+        tx.send(&ClientHello::new(credentials)).await?;
 
         // The server will acknowledge and send the credentials back
-        let client_info: ClientInfo = rx.next().await?;
+        let client_info: Result<ClientInfo, HandshakeRejection> = rx.next().await?;
+        let client_info = client_info.map_err(NetworkError::HandshakeRejected)?;
         ComponentRegistry::get_mut().add_external(client_info.external_components.clone());
 
         let server_info: ServerInfo = rx.next().await?;
         if server_info.version != VERSION {
-            anyhow::bail!("Server version mismatch: expected {}, got {}", VERSION, server_info.version);
+            log::info!("Connected to a server running version {} (client is running {VERSION})", server_info.version);
         }
 
         // Great, the server knows who we are.
@@ -80,20 +106,42 @@ pub struct ServerProtocol {
 }
 
 impl ServerProtocol {
-    pub async fn new(conn: ClientConnection, server_info: ServerInfo) -> Result<Self, NetworkError> {
-        // The client now sends the player id
+    pub async fn new(conn: ClientConnection, server_info: ServerInfo, authenticator: &dyn Authenticator) -> Result<Self, NetworkError> {
+        // The client now sends its protocol version and credentials
         let (mut tx, mut rx) = next_bincode_bi_stream(&conn).await?;
 
-        let user_id: String = rx.next().await?;
-
-        log::debug!("Received handshake from {user_id:?}");
+        let hello: ClientHello = rx.next().await?;
+
+        log::debug!("Received handshake from {hello:?}");
+
+        let identity = if !is_protocol_compatible(&hello.protocol_version, &server_info.protocol_version) {
+            Err(HandshakeRejection::IncompatibleProtocol {
+                client_protocol_version: hello.protocol_version.clone(),
+                server_protocol_version: server_info.protocol_version.clone(),
+                server_version: server_info.version.clone(),
+            })
+        } else {
+            authenticator.authenticate(&hello.credentials).await.map_err(HandshakeRejection::Unauthenticated)
+        };
+
+        let identity = match identity {
+            Ok(identity) => identity,
+            Err(err) => {
+                log::debug!("Rejecting {:?}: {err}", hello.credentials.user_id);
+                tx.send(&Err::<ClientInfo, _>(err.clone())).await?;
+                // Make sure the client receives the reason before the connection is dropped
+                tx.finish().await?;
+                return Err(err.into());
+            }
+        };
 
         let external_components = ComponentRegistry::get().all_external().map(|x| x.0).collect();
+        let capabilities = hello.capabilities.into_iter().filter(|capability| server_info.capabilities.contains(capability)).collect();
 
         // Respond
-        let client_info = ClientInfo { user_id, external_components };
+        let client_info = ClientInfo { user_id: identity.user_id, display_name: identity.display_name, external_components, capabilities };
         log::debug!("Responding with {client_info:?}");
-        tx.send(&client_info).await?;
+        tx.send(&Ok::<_, HandshakeRejection>(client_info.clone())).await?;
 
         // Send the project name to the client so it can title its window correctly
         tx.send(&server_info).await?;
@@ -113,21 +161,62 @@ impl ServerProtocol {
     }
 }
 
-/// Contains things such as username (TODO) and user_id
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+/// The first message of the handshake, sent by the client.
+///
+/// Servers can only tell incompatible clients why they were rejected while they can still deserialize this. Changing its
+/// fields is a major protocol bump, and servers using the old layout will drop such clients without a reason.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ClientHello {
+    protocol_version: String,
+    capabilities: Vec<String>,
+    credentials: ClientCredentials,
+}
+impl ClientHello {
+    fn new(credentials: ClientCredentials) -> Self {
+        Self { protocol_version: PROTOCOL_VERSION.into(), capabilities: CAPABILITIES.iter().map(|x| x.to_string()).collect(), credentials }
+    }
+}
+
+/// The reason the server refused a client during the handshake. Sent to the client so that it can be shown to the user.
+#[derive(Debug, Clone, Error, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HandshakeRejection {
+    #[error("Authentication failed: {0}")]
+    Unauthenticated(AuthError),
+    #[error(
+        "The server uses network protocol version {server_protocol_version} (Ambient {server_version}), which is not compatible with \
+         this client's protocol version {client_protocol_version}. Please use a version of Ambient compatible with the server."
+    )]
+    IncompatibleProtocol { client_protocol_version: String, server_protocol_version: String, server_version: String },
+}
+
+/// Contains the verified identity of the client
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ClientInfo {
     pub user_id: String,
+    /// The name to show to other players
+    pub display_name: String,
     pub external_components: Vec<ExternalComponentDesc>,
+    /// The protocol capabilities supported by both the client and the server
+    pub capabilities: Vec<String>,
+}
+impl ClientInfo {
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|x| x == capability)
+    }
 }
 
 impl std::fmt::Debug for ClientInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ClientInfo").field("user_id", &self.user_id).finish_non_exhaustive()
+        f.debug_struct("ClientInfo")
+            .field("user_id", &self.user_id)
+            .field("display_name", &self.display_name)
+            .field("capabilities", &self.capabilities)
+            .finish_non_exhaustive()
     }
 }
 
 /// Miscellaneous information about the server that needs to be sent to the client during the handshake.
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ServerInfo {
     /// The name of the project. Used by the client to figure out what to title its window. Defaults to "Ambient".
     pub project_name: String,
@@ -135,9 +224,15 @@ pub struct ServerInfo {
     // Base url of the content server.
     pub content_base_url: AbsAssetUrl,
 
-    /// The version of the server. Used by the client to determine whether or not to keep connecting.
-    /// Defaults to the version of the crate.
+    /// The version of Ambient the server is running. Defaults to the version of the crate.
     pub version: String,
+
+    /// The network protocol version of the server. Used by the server to determine whether or not the client can connect.
+    /// Defaults to [`PROTOCOL_VERSION`].
+    pub protocol_version: String,
+
+    /// The optional protocol features supported by the server. Defaults to [`CAPABILITIES`].
+    pub capabilities: Vec<String>,
 }
 
 impl Default for ServerInfo {
@@ -146,6 +241,24 @@ impl Default for ServerInfo {
             project_name: "Ambient".into(),
             content_base_url: AbsAssetUrl::parse("http://localhost:8999/content/").unwrap(),
             version: VERSION.into(),
+            protocol_version: PROTOCOL_VERSION.into(),
+            capabilities: CAPABILITIES.iter().map(|x| x.to_string()).collect(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protocol_compatibility() {
+        assert!(is_protocol_compatible("1.0.0", "1.0.0"));
+        assert!(is_protocol_compatible("1.0.0", "1.3.2"));
+        assert!(is_protocol_compatible("1.3.2", "1.0.0"));
+        assert!(!is_protocol_compatible("1.3.2", "2.0.0"));
+        assert!(!is_protocol_compatible("0.2.0", "0.3.0"));
+        assert!(is_protocol_compatible("0.2.0", "0.2.5"));
+        assert!(!is_protocol_compatible("1.0.0", "not a version"));
+    }
+}
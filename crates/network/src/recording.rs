@@ -0,0 +1,260 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_ecs::{ComponentRegistry, EntityId, ExternalComponentDesc, World, WorldChange, WorldDiff, WorldStreamFilter};
+use ambient_std::{asset_cache::AssetCache, asset_url::AbsAssetUrl};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::PROTOCOL_VERSION;
+
+/// Identifies a session recording file
+const MAGIC: [u8; 8] = *b"AMBREC01";
+
+/// A file at the root of the content of every built project, used to check that the content is still available
+const CONTENT_PROBE: &str = "build_manifest.json";
+
+/// Describes the session a recording was made of. Written at the start of the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHeader {
+    /// The network protocol version of the server that made the recording
+    pub protocol_version: String,
+    pub project_name: String,
+    /// Where the assets of the project can be loaded from.
+    ///
+    /// Recordings only contain the world diffs, so playback loads the assets from here, and needs the recorded server to
+    /// still serve them (or a copy of its content to be used instead)
+    pub content_base_url: AbsAssetUrl,
+    /// The external components of the project, which need to be registered before the diffs can be read
+    pub external_components: Vec<ExternalComponentDesc>,
+}
+
+/// A single diff of the recorded world, and the time at which it was produced, relative to the start of the recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub time: Duration,
+    pub diff: WorldDiff,
+}
+
+/// Writes the diffs produced by a server world instance to a file, so that the session can be replayed later.
+///
+/// The file consists of a [`SessionHeader`], followed by a [`RecordedFrame`] for every recorded diff. The first frame
+/// spawns the entire world as it was when the recording started.
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    start_time: Option<Duration>,
+    /// The entities spawned by the recording so far
+    known: HashSet<EntityId>,
+}
+impl SessionRecorder {
+    pub fn create(path: &Path, project_name: String, content_base_url: AbsAssetUrl) -> anyhow::Result<Self> {
+        let file = File::create(path).with_context(|| format!("Failed to create session recording at {path:?}"))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&MAGIC)?;
+
+        let header = SessionHeader {
+            protocol_version: PROTOCOL_VERSION.into(),
+            project_name,
+            content_base_url,
+            external_components: ComponentRegistry::get().all_external().map(|x| x.0).collect(),
+        };
+        bincode::serialize_into(&mut writer, &header)?;
+        writer.flush()?;
+
+        Ok(Self { writer, start_time: None, known: HashSet::new() })
+    }
+
+    /// Records the diff produced by the world stream of `world` at `time`
+    pub fn record(&mut self, world: &World, filter: &WorldStreamFilter, time: Duration, diff: &WorldDiff) -> anyhow::Result<()> {
+        let diff = match self.start_time {
+            Some(_) => diff.clone(),
+            None => {
+                // The stream may not contain the entities that existed before the recording started, so spawn the world in full
+                self.start_time = Some(time);
+                filter.initial_diff(world)
+            }
+        };
+        let diff = self.track_entities(diff);
+        let time = time.saturating_sub(self.start_time.unwrap());
+        if diff.is_empty() && time > Duration::ZERO {
+            return Ok(());
+        }
+
+        bincode::serialize_into(&mut self.writer, &RecordedFrame { time, diff })?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Drops spawns of entities that have already been recorded, and keeps track of which entities exist
+    fn track_entities(&mut self, diff: WorldDiff) -> WorldDiff {
+        let changes = diff
+            .changes
+            .into_iter()
+            .filter(|change| match change {
+                WorldChange::Spawn(Some(id), _) => self.known.insert(*id),
+                WorldChange::Despawn(id) => {
+                    self.known.remove(id);
+                    true
+                }
+                _ => true,
+            })
+            .collect();
+        WorldDiff { changes }
+    }
+}
+
+/// A session recording loaded from disk
+#[derive(Debug, Clone)]
+pub struct SessionRecording {
+    pub header: SessionHeader,
+    pub frames: Arc<Vec<RecordedFrame>>,
+}
+impl SessionRecording {
+    /// Loads a recording, registering the external components it uses
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open session recording at {path:?}"))?;
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0; MAGIC.len()];
+        std::io::Read::read_exact(&mut reader, &mut magic).context("Failed to read session recording")?;
+        anyhow::ensure!(magic == MAGIC, "{path:?} is not a session recording");
+
+        let header: SessionHeader = bincode::deserialize_from(&mut reader).context("Failed to read session header")?;
+        ComponentRegistry::get_mut().add_external(header.external_components.clone());
+
+        let mut frames = Vec::new();
+        loop {
+            match bincode::deserialize_from::<_, RecordedFrame>(&mut reader) {
+                Ok(frame) => frames.push(frame),
+                Err(err) => match *err {
+                    bincode::ErrorKind::Io(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                    _ => return Err(err).context("Failed to read session recording"),
+                },
+            }
+        }
+
+        Ok(Self { header, frames: Arc::new(frames) })
+    }
+
+    /// Checks that the assets of the recorded project can still be loaded from [`SessionHeader::content_base_url`]
+    pub async fn check_content(&self, assets: &AssetCache) -> anyhow::Result<()> {
+        let base_url = &self.header.content_base_url;
+        base_url.join(CONTENT_PROBE)?.download_bytes(assets).await.with_context(|| {
+            format!(
+                "The content of the recorded project is no longer available at {base_url}. Pass `--content` with the project's `build` \
+                 directory to replay the session with a copy of its assets"
+            )
+        })?;
+        Ok(())
+    }
+
+    /// The time of the last frame of the recording
+    pub fn duration(&self) -> Duration {
+        self.frames.last().map(|frame| frame.time).unwrap_or_default()
+    }
+}
+
+/// The changes to make to a world to bring it to the current time of a [`SessionPlayback`]
+#[derive(Debug, Default)]
+pub struct PlaybackStep {
+    /// If true, everything spawned by the playback so far must be despawned before applying `diff`
+    pub reset: bool,
+    pub diff: WorldDiff,
+}
+
+/// Plays back the frames of a [`SessionRecording`]
+#[derive(Debug, Clone)]
+pub struct SessionPlayback {
+    frames: Arc<Vec<RecordedFrame>>,
+    /// The index of the next frame to apply
+    next_frame: usize,
+    time: Duration,
+    pub paused: bool,
+    /// Playback speed, where 1 is real time
+    pub speed: f32,
+}
+impl SessionPlayback {
+    pub fn new(recording: &SessionRecording) -> Self {
+        Self { frames: recording.frames.clone(), next_frame: 0, time: Duration::ZERO, paused: false, speed: 1. }
+    }
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+    pub fn duration(&self) -> Duration {
+        self.frames.last().map(|frame| frame.time).unwrap_or_default()
+    }
+    pub fn is_finished(&self) -> bool {
+        self.next_frame >= self.frames.len()
+    }
+
+    /// Advances the playback by `dtime` seconds of real time, scaled by the playback speed. Does nothing while paused.
+    pub fn advance(&mut self, dtime: f32) -> PlaybackStep {
+        if self.paused {
+            return PlaybackStep::default();
+        }
+        let time = (self.time + Duration::from_secs_f32((dtime * self.speed).max(0.))).min(self.duration());
+        self.seek(time)
+    }
+
+    /// Moves the playback to `time`. Seeking backwards replays the recording from the start.
+    pub fn seek(&mut self, time: Duration) -> PlaybackStep {
+        let time = time.min(self.duration());
+        let reset = time < self.time;
+        if reset {
+            self.next_frame = 0;
+        }
+        self.time = time;
+
+        let end = self.next_frame + self.frames[self.next_frame..].iter().take_while(|frame| frame.time <= time).count();
+        let changes = self.frames[self.next_frame..end].iter().flat_map(|frame| frame.diff.changes.iter().cloned()).collect();
+        self.next_frame = end;
+
+        PlaybackStep { reset, diff: WorldDiff { changes } }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playback(times: &[u64]) -> SessionPlayback {
+        let frames = times
+            .iter()
+            .map(|&time| RecordedFrame { time: Duration::from_secs(time), diff: WorldDiff::new().despawn(vec![EntityId::new()]) })
+            .collect();
+        SessionPlayback { frames: Arc::new(frames), next_frame: 0, time: Duration::ZERO, paused: false, speed: 1. }
+    }
+
+    #[test]
+    fn playback_advance() {
+        let mut playback = playback(&[0, 1, 2, 3]);
+        assert_eq!(playback.advance(0.).diff.changes.len(), 1);
+        assert_eq!(playback.advance(1.5).diff.changes.len(), 1);
+        playback.speed = 2.;
+        assert_eq!(playback.advance(1.).diff.changes.len(), 2);
+        assert!(playback.is_finished());
+    }
+
+    #[test]
+    fn playback_paused() {
+        let mut playback = playback(&[0, 1]);
+        playback.paused = true;
+        assert!(playback.advance(5.).diff.is_empty());
+        assert_eq!(playback.time(), Duration::ZERO);
+    }
+
+    #[test]
+    fn playback_seek_backwards() {
+        let mut playback = playback(&[0, 1, 2, 3]);
+        playback.seek(Duration::from_secs(3));
+        let step = playback.seek(Duration::from_secs(1));
+        assert!(step.reset);
+        assert_eq!(step.diff.changes.len(), 2);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use ambient_core::{player::user_id, transform::get_world_position};
+use ambient_ecs::{
+    generated::components::core::network::{
+        always_relevant, player_visibility_groups, relevance_origin, relevance_radius, visibility_groups,
+    },
+    EntityId, OwnerOnly, World, WorldChange, WorldDiff, WorldStreamFilter,
+};
+use glam::Vec3;
+
+/// Determines which entities are relevant to a specific player, based on the player's position,
+/// visibility groups and ownership.
+#[derive(Debug, Clone)]
+pub struct PlayerInterest {
+    user_id: String,
+    origin: Option<Vec3>,
+    radius: Option<f32>,
+    groups: Vec<String>,
+}
+impl PlayerInterest {
+    pub fn new(world: &World, player: EntityId) -> Self {
+        let origin_entity = world.get(player, relevance_origin()).unwrap_or(player);
+        Self {
+            user_id: world.get_cloned(player, user_id()).unwrap_or_default(),
+            origin: get_world_position(world, origin_entity).ok(),
+            radius: world.get(player, relevance_radius()).ok(),
+            groups: world.get_cloned(player, player_visibility_groups()).unwrap_or_default(),
+        }
+    }
+    /// Returns true if the entity is owned by this player
+    pub fn is_owner(&self, world: &World, id: EntityId) -> bool {
+        world.get_ref(id, user_id()).map(|owner| owner == &self.user_id).unwrap_or(false)
+    }
+    pub fn is_relevant(&self, world: &World, id: EntityId) -> bool {
+        if self.is_owner(world, id) {
+            return true;
+        }
+        if let Ok(groups) = world.get_ref(id, visibility_groups()) {
+            if !groups.iter().any(|group| self.groups.contains(group)) {
+                return false;
+            }
+        }
+        if world.has_component(id, always_relevant()) {
+            return true;
+        }
+        match (self.origin, self.radius) {
+            (Some(origin), Some(radius)) => match get_world_position(world, id) {
+                Ok(position) => position.distance_squared(origin) <= radius * radius,
+                // Entities without a position (e.g. resource entities) are always relevant
+                Err(_) => true,
+            },
+            _ => true,
+        }
+    }
+    /// Removes all `OwnerOnly` components from the change, unless this player owns the entity
+    fn strip_owner_only(&self, world: &World, change: WorldChange) -> Option<WorldChange> {
+        let is_shared = |desc: ambient_ecs::ComponentDesc| !desc.has_attribute::<OwnerOnly>();
+        match change {
+            WorldChange::Spawn(Some(id), _)
+            | WorldChange::AddComponents(id, _)
+            | WorldChange::RemoveComponents(id, _)
+            | WorldChange::Set(id, _)
+                if self.is_owner(world, id) =>
+            {
+                Some(change)
+            }
+            WorldChange::Spawn(id, mut data) => {
+                data.filter(&is_shared);
+                Some(WorldChange::Spawn(id, data))
+            }
+            WorldChange::AddComponents(id, mut data) => {
+                data.filter(&is_shared);
+                (!data.is_empty()).then_some(WorldChange::AddComponents(id, data))
+            }
+            WorldChange::RemoveComponents(id, mut comps) => {
+                comps.retain(|&comp| is_shared(comp));
+                (!comps.is_empty()).then_some(WorldChange::RemoveComponents(id, comps))
+            }
+            WorldChange::Set(_, ref entry) if !is_shared(entry.desc()) => None,
+            change => Some(change),
+        }
+    }
+}
+
+/// Keeps track of the entities a player's client currently knows about
+#[derive(Debug, Clone, Default)]
+pub struct PlayerRelevance {
+    known: HashSet<EntityId>,
+}
+impl PlayerRelevance {
+    /// Forgets all entities known by the client, and creates a diff which spawns all entities relevant to the player
+    pub fn initial_diff(&mut self, world: &World, filter: &WorldStreamFilter, interest: &PlayerInterest) -> WorldDiff {
+        self.known.clear();
+        self.filter_diff(world, filter, interest, &WorldDiff::new())
+    }
+    /// Despawns all entities known by the client, and spawns all entities relevant to the player in `world` instead.
+    /// Used when the player moves to another world instance.
+    pub fn switch_world_diff(&mut self, world: &World, filter: &WorldStreamFilter, interest: &PlayerInterest) -> WorldDiff {
+        let despawns = WorldDiff::new().despawn(self.known.drain().collect());
+        let spawns = self.initial_diff(world, filter, interest);
+        WorldDiff { changes: despawns.changes.into_iter().chain(spawns.changes).collect() }
+    }
+    /// Converts a diff from the shared world stream into a diff for this player.
+    ///
+    /// Entities that became relevant since the last diff are spawned in full, entities that are no
+    /// longer relevant are despawned, and changes to entities the client doesn't know about are dropped.
+    pub fn filter_diff(&mut self, world: &World, filter: &WorldStreamFilter, interest: &PlayerInterest, diff: &WorldDiff) -> WorldDiff {
+        let relevant: HashSet<EntityId> = filter.all_entities(world).filter(|&id| interest.is_relevant(world, id)).collect();
+
+        let mut changes = self
+            .known
+            .iter()
+            .filter(|&&id| !relevant.contains(&id) && world.exists(id))
+            .map(|&id| WorldChange::Despawn(id))
+            .collect::<Vec<_>>();
+
+        for change in &diff.changes {
+            let change = match change {
+                WorldChange::Despawn(id) if self.known.contains(id) => change.clone(),
+                // Spawns are handled below, as the entity may have been spawned in full already
+                WorldChange::Despawn(_) | WorldChange::Spawn(_, _) => continue,
+                WorldChange::AddComponents(id, _) | WorldChange::RemoveComponents(id, _) | WorldChange::Set(id, _) => {
+                    if !self.known.contains(id) || !relevant.contains(id) {
+                        continue;
+                    }
+                    change.clone()
+                }
+            };
+            changes.extend(interest.strip_owner_only(world, change));
+        }
+
+        for &id in relevant.difference(&self.known) {
+            changes.extend(interest.strip_owner_only(world, filter.spawn_change(world, id)));
+        }
+
+        self.known = relevant;
+        WorldDiff { changes }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ambient_core::transform::translation;
+    use ambient_ecs::Entity;
+    use glam::vec3;
+
+    use super::*;
+
+    fn spawned(diff: &WorldDiff) -> HashSet<EntityId> {
+        diff.changes.iter().filter_map(|change| if let WorldChange::Spawn(id, _) = change { *id } else { None }).collect()
+    }
+
+    fn despawned(diff: &WorldDiff) -> HashSet<EntityId> {
+        diff.changes.iter().filter_map(|change| if let WorldChange::Despawn(id) = change { Some(*id) } else { None }).collect()
+    }
+
+    #[test]
+    fn relevance_radius() {
+        ambient_ecs::init_components();
+        let mut world = World::new("test");
+        let player = Entity::new()
+            .with(user_id(), "player".to_string())
+            .with(translation(), Vec3::ZERO)
+            .with(relevance_radius(), 10.)
+            .spawn(&mut world);
+        let near = Entity::new().with(translation(), vec3(5., 0., 0.)).spawn(&mut world);
+        let far = Entity::new().with(translation(), vec3(50., 0., 0.)).spawn(&mut world);
+        let always = Entity::new().with(translation(), vec3(50., 0., 0.)).with(always_relevant(), ()).spawn(&mut world);
+        let owned =
+            Entity::new().with(translation(), vec3(50., 0., 0.)).with(user_id(), "player".to_string()).spawn(&mut world);
+
+        let filter = WorldStreamFilter::default();
+        let mut relevance = PlayerRelevance::default();
+        let diff = relevance.initial_diff(&world, &filter, &PlayerInterest::new(&world, player));
+        assert_eq!(spawned(&diff), HashSet::from([player, near, always, owned]));
+
+        // Entering and leaving the radius spawns and despawns the entities
+        world.set(near, translation(), vec3(20., 0., 0.)).unwrap();
+        world.set(far, translation(), vec3(0., 5., 0.)).unwrap();
+        let diff = relevance.filter_diff(&world, &filter, &PlayerInterest::new(&world, player), &WorldDiff::new());
+        assert_eq!(spawned(&diff), HashSet::from([far]));
+        assert_eq!(despawned(&diff), HashSet::from([near]));
+
+        // Changes to entities the client doesn't know about are dropped
+        let changes = WorldDiff::new().set(near, translation(), vec3(21., 0., 0.)).set(far, translation(), vec3(0., 6., 0.));
+        let diff = relevance.filter_diff(&world, &filter, &PlayerInterest::new(&world, player), &changes);
+        assert_eq!(diff.changes.len(), 1);
+        assert!(matches!(diff.changes[0], WorldChange::Set(id, _) if id == far));
+    }
+}
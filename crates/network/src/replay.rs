@@ -0,0 +1,152 @@
+use std::{sync::Arc, time::Duration};
+
+use ambient_app::window_title;
+use ambient_core::{
+    asset_cache, dtime, gpu,
+    window::{cursor_position, window_logical_size, window_physical_size, window_scale_factor},
+};
+use ambient_ecs::{query, world_events, Entity, EntityId, SystemGroup, World};
+use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_renderer::RenderTarget;
+use ambient_std::{asset_cache::SyncAssetKeyExt, asset_url::ContentBaseUrlKey, cb, Cb};
+use ambient_ui::{
+    layout::{docking, space_between_items, Docking},
+    Button, ButtonStyle, Dock, FlowRow, Slider, Text, UIExt, STREET,
+};
+use glam::{uvec2, vec4};
+use parking_lot::Mutex;
+
+use crate::{
+    client::{GameClientRenderTarget, GameClientWorld, InitCallback, UseOnce},
+    client_game_state::ClientGameState,
+    is_remote_entity,
+    recording::{PlaybackStep, SessionPlayback, SessionRecording},
+};
+
+const SPEEDS: [f32; 5] = [0.25, 0.5, 1., 2., 4.];
+
+/// Plays back a [`SessionRecording`] in a client world, with controls to pause, seek and change the playback speed
+#[allow(clippy::type_complexity)]
+#[derive(Debug, Clone)]
+pub struct SessionReplayView {
+    pub recording: SessionRecording,
+    pub systems_and_resources: Cb<dyn Fn() -> (SystemGroup, Entity) + Sync + Send>,
+    pub init_world: Cb<UseOnce<InitCallback>>,
+}
+
+impl ElementComponent for SessionReplayView {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let Self { recording, systems_and_resources, init_world } = *self;
+
+        let gpu = hooks.world.resource(gpu()).clone();
+        hooks.provide_context(|| GameClientRenderTarget(Arc::new(RenderTarget::new(gpu.clone(), uvec2(1, 1), None))));
+        let (render_target, _) = hooks.consume_context::<GameClientRenderTarget>().unwrap();
+
+        *hooks.world.resource_mut(window_title()) = format!("{} (replay)", recording.header.project_name);
+
+        let game_state = hooks.use_ref_with(|world| {
+            let assets = world.resource(asset_cache()).clone();
+            ContentBaseUrlKey.insert(&assets, recording.header.content_base_url.clone());
+
+            let (systems, resources) = systems_and_resources();
+            let mut state = ClientGameState::new(world, assets, "replay".to_string(), render_target.0.clone(), systems, resources);
+
+            (init_world.take().expect("Init called twice"))(&mut state.world, render_target.0.clone());
+
+            state
+        });
+        let playback = hooks.use_ref_with(|_| SessionPlayback::new(&recording));
+
+        {
+            let game_state = game_state.clone();
+            let playback = playback.clone();
+            let render_target = render_target.clone();
+            let world_event_reader = Mutex::new(hooks.world.resource(world_events()).reader());
+            hooks.use_frame(move |app_world| {
+                let mut game_state = game_state.lock();
+
+                let step = playback.lock().advance(*app_world.resource(dtime()));
+                apply_step(&mut game_state.world, step);
+
+                // Pipe events and window state from the app world to the game world
+                for (_, event) in world_event_reader.lock().iter(app_world.resource(world_events())) {
+                    game_state.world.resource_mut(world_events()).add_event(event.clone());
+                }
+                let scale_factor = *app_world.resource(window_scale_factor());
+                let size = uvec2(render_target.0.color_buffer.size.width, render_target.0.color_buffer.size.height);
+                let world = &mut game_state.world;
+                world.set_if_changed(EntityId::resources(), cursor_position(), *app_world.resource(cursor_position())).unwrap();
+                world
+                    .set_if_changed(EntityId::resources(), window_logical_size(), (size.as_vec2() / scale_factor as f32).as_uvec2())
+                    .unwrap();
+                world.set_if_changed(EntityId::resources(), window_physical_size(), size).unwrap();
+                world.set_if_changed(EntityId::resources(), window_scale_factor(), scale_factor).unwrap();
+
+                game_state.on_frame(&render_target.0);
+            });
+        }
+
+        // Keep the controls up to date with the playback
+        let rerender = hooks.use_rerender_signal();
+        hooks.use_interval(0.1, move || rerender());
+
+        let (time, duration, paused, speed) = {
+            let playback = playback.lock();
+            (playback.time(), playback.duration(), playback.paused, playback.speed)
+        };
+
+        let controls = FlowRow::el([
+            Button::new(if paused { "Play" } else { "Pause" }, {
+                let playback = playback.clone();
+                move |_| {
+                    let mut playback = playback.lock();
+                    playback.paused = !playback.paused;
+                }
+            })
+            .el(),
+            Slider {
+                value: time.as_secs_f32(),
+                on_change: Some(cb({
+                    let playback = playback.clone();
+                    move |value: f32| {
+                        let step = playback.lock().seek(Duration::from_secs_f32(value.max(0.)));
+                        apply_step(&mut game_state.lock().world, step);
+                    }
+                })),
+                min: 0.,
+                max: duration.as_secs_f32(),
+                width: 400.,
+                logarithmic: false,
+                round: None,
+                suffix: Some("s"),
+            }
+            .el(),
+            Text::el(format!("/ {:.1}s", duration.as_secs_f32())),
+            FlowRow::el(SPEEDS.map(|value| {
+                let playback = playback.clone();
+                Button::new(format!("{value}x"), move |_| playback.lock().speed = value)
+                    .style(ButtonStyle::Flat)
+                    .toggled(speed == value)
+                    .el()
+            })),
+        ])
+        .with(docking(), Docking::Bottom)
+        .with_background(vec4(0., 0., 0., 1.))
+        .with_padding_even(STREET)
+        .with(space_between_items(), STREET);
+
+        Dock::el([controls, GameClientWorld.el()])
+    }
+}
+
+/// Applies a step of the playback to the client world
+fn apply_step(world: &mut World, step: PlaybackStep) {
+    if step.reset {
+        for (id, _) in query(is_remote_entity()).collect_cloned(world, None) {
+            world.despawn(id);
+        }
+    }
+    if !step.diff.is_empty() {
+        step.diff.apply(world, Entity::new().with(is_remote_entity(), ()), false);
+    }
+}
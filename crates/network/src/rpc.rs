@@ -7,8 +7,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     server::{
-        create_player_entity_data, player_entity_stream, player_stats_stream, ForkingEvent, RpcArgs as ServerRpcArgs, WorldInstance,
-        MAIN_INSTANCE_ID,
+        create_player_entity_data, player_entity_stream, player_relevance, player_stats_stream, ForkingEvent, RpcArgs as ServerRpcArgs,
+        WorldInstance, MAIN_INSTANCE_ID,
     },
     ServerWorldExt,
 };
@@ -52,7 +52,12 @@ pub async fn rpc_fork_instance(args: ServerRpcArgs, RpcForkInstance { resources,
 
             world.reset_events();
 
-            WorldInstance { systems: (state.create_server_systems)(&mut world), world, world_stream: instance.world_stream.clone() }
+            WorldInstance {
+                systems: (state.create_server_systems)(&mut world),
+                world,
+                world_stream: instance.world_stream.clone(),
+                recorder: None,
+            }
         };
         state.instances.insert(id.clone(), new_instance);
     }
@@ -70,24 +75,25 @@ pub async fn rpc_join_instance(args: ServerRpcArgs, new_instance_id: String) {
     // Borrow the new world mutably to broadcast its diffs.
     instances.get_mut(&new_instance_id).unwrap().broadcast_diffs();
 
-    // Borrow both worlds immutably to extract the old world's player count and the diff between the two, and
-    // to broadcast the latest diffs for the new instance.
-    let (old_player_count, diff) = {
-        let (old_instance, new_instance) = instances.get(&old_instance_id).zip(instances.get(&new_instance_id)).unwrap();
+    let old_player_count = instances.get(&old_instance_id).unwrap().player_count();
+
+    // Borrow the old world mutably to remove the player, their streams and the entities their client knows about.
+    let (entities_tx, stats_tx, relevance) = {
+        let mut ed = instances.get_mut(&old_instance_id).unwrap().despawn_player(&args.user_id).unwrap();
         (
-            old_instance.player_count(),
-            WorldDiff::from_a_to_b(old_instance.world_stream.filter().clone(), &old_instance.world, &new_instance.world),
+            ed.remove_self(player_entity_stream()).unwrap(),
+            ed.remove_self(player_stats_stream()).unwrap(),
+            ed.remove_self(player_relevance()).unwrap(),
         )
     };
 
-    // Borrow the old world mutably to remove the player and their streams.
-    let (entities_tx, stats_tx) = {
-        let mut ed = instances.get_mut(&old_instance_id).unwrap().despawn_player(&args.user_id).unwrap();
-        (ed.remove_self(player_entity_stream()).unwrap(), ed.remove_self(player_stats_stream()).unwrap())
+    // Borrow the new world mutably to spawn the player in with their old streams, and move their client over to the new world.
+    let diff = {
+        let new_instance = instances.get_mut(&new_instance_id).unwrap();
+        let player = new_instance
+            .spawn_player(create_player_entity_data(&args.user_id, entities_tx.clone(), stats_tx).with(player_relevance(), relevance));
+        new_instance.switch_player_diff(player)
     };
-
-    // Borrow the new world mutably to spawn the player in with their old streams.
-    instances.get_mut(&new_instance_id).unwrap().spawn_player(create_player_entity_data(&args.user_id, entities_tx.clone(), stats_tx));
     state.players.get_mut(&args.user_id).unwrap().instance = new_instance_id.to_string();
 
     let msg = bincode::serialize(&diff).unwrap();
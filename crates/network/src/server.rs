@@ -14,7 +14,7 @@ use ambient_core::{
 };
 use ambient_ecs::{
     components, dont_store, query, ArchetypeFilter, ComponentDesc, Entity, EntityId, FrameEvent, Resource, System, SystemGroup, World,
-    WorldStream, WorldStreamCompEvent, WorldStreamFilter,
+    WorldDiff, WorldStream, WorldStreamCompEvent, WorldStreamFilter,
 };
 use ambient_proxy::client::AllocatedEndpoint;
 use ambient_rpc::RpcRegistry;
@@ -34,15 +34,19 @@ use parking_lot::{Mutex, RwLock};
 use quinn::{Endpoint, RecvStream, SendStream};
 use tokio::{
     io::AsyncReadExt,
+    sync::Notify,
     time::{interval, MissedTickBehavior},
 };
 use tracing::{debug_span, Instrument};
 
 use crate::{
+    auth::{Authenticator, Unauthenticated},
     client_connection::ClientConnection,
     connection::Connection,
     create_server,
     protocol::{ClientInfo, ServerInfo, ServerProtocol},
+    recording::SessionRecorder,
+    relevance::{PlayerInterest, PlayerRelevance},
     NetworkError, RPC_BISTREAM_ID,
 };
 use colored::Colorize;
@@ -58,6 +62,7 @@ components!("network::server", {
     player_entity_stream: Sender<Vec<u8>>,
     player_stats_stream: Sender<FpsSample>,
     player_connection: ClientConnection,
+    player_relevance: Arc<Mutex<PlayerRelevance>>,
 });
 
 pub type BiStreamHandlers = HashMap<u32, Arc<dyn Fn(SharedServerState, AssetCache, &String, SendStream, RecvStream) + Sync + Send>>;
@@ -77,6 +82,8 @@ pub struct WorldInstance {
     pub world: World,
     pub world_stream: WorldStream,
     pub systems: SystemGroup,
+    /// Records the diffs of this instance to disk, if enabled
+    pub recorder: Option<SessionRecorder>,
 }
 
 #[derive(Clone)]
@@ -96,6 +103,7 @@ pub fn create_player_entity_data(user_id: &str, entities_tx: Sender<Vec<u8>>, st
         .with(ambient_core::player::user_id(), user_id.to_string())
         .with(player_entity_stream(), entities_tx)
         .with(player_stats_stream(), stats_tx)
+        .with_default(player_relevance())
         .with_default(dont_store())
 }
 
@@ -129,21 +137,45 @@ impl WorldInstance {
     pub fn despawn_player(&mut self, user_id: &str) -> Option<Entity> {
         self.world.despawn(get_by_user_id(&self.world, user_id)?)
     }
+    /// Sends each player the changes to the entities that are relevant to them
     pub fn broadcast_diffs(&mut self) {
         let diff = self.world_stream.next_diff(&self.world);
-        if diff.is_empty() {
-            return;
+        let filter = self.world_stream.filter();
+
+        if let Some(recorder) = &mut self.recorder {
+            let time = *self.world.resource(ambient_core::time());
+            let result = recorder.record(&self.world, filter, time, &diff);
+            if let Err(err) = result {
+                log::error!("Failed to record session, stopping the recording: {err:?}");
+                self.recorder = None;
+            }
         }
-        let msg = bincode::serialize(&diff).unwrap();
 
         profiling::scope!("Send MsgEntities");
-        for (_, (entity_stream,)) in query((player_entity_stream(),)).iter(&self.world, None) {
-            let msg = msg.clone();
+        for (id, (entity_stream, relevance)) in query((player_entity_stream(), player_relevance())).iter(&self.world, None) {
+            let interest = PlayerInterest::new(&self.world, id);
+            let diff = relevance.lock().filter_diff(&self.world, filter, &interest, &diff);
+            if diff.is_empty() {
+                continue;
+            }
+            let msg = bincode::serialize(&diff).unwrap();
             if let Err(_err) = entity_stream.send(msg) {
                 log::warn!("Failed to broadcast diff to player");
             }
         }
     }
+    /// Creates the diff that initializes the world of a newly connected player
+    pub fn initial_player_diff(&self, player: EntityId) -> WorldDiff {
+        let interest = PlayerInterest::new(&self.world, player);
+        let relevance = self.world.get_ref(player, player_relevance()).expect("Player is missing relevance state");
+        relevance.lock().initial_diff(&self.world, self.world_stream.filter(), &interest)
+    }
+    /// Creates the diff that moves the client of a player that just joined this instance over from their previous instance
+    pub fn switch_player_diff(&self, player: EntityId) -> WorldDiff {
+        let interest = PlayerInterest::new(&self.world, player);
+        let relevance = self.world.get_ref(player, player_relevance()).expect("Player is missing relevance state");
+        relevance.lock().switch_world_diff(&self.world, self.world_stream.filter(), &interest)
+    }
     pub fn player_count(&self) -> usize {
         query((player(),)).iter(&self.world, None).count()
     }
@@ -190,6 +222,7 @@ impl ServerState {
                     world: World::new("main_server"),
                     world_stream: WorldStream::new(world_stream_filter),
                     systems: SystemGroup::new("", vec![]),
+                    recorder: None,
                 },
             )]
             .into(),
@@ -257,6 +290,12 @@ pub struct GameServer {
     /// Shuts down the server if there are no players
     pub use_inactivity_shutdown: bool,
     proxy_settings: Option<ProxySettings>,
+    /// Verifies the identity of connecting clients. Defaults to accepting every client as who they claim to be
+    pub authenticator: Arc<dyn Authenticator>,
+    /// If set, the diffs of the main instance are recorded to this file, so that the session can be replayed later
+    pub record_session: Option<PathBuf>,
+    /// Notify this to shut the server down. The shutdown systems are run for every instance before `run` returns
+    pub shutdown: Arc<Notify>,
 }
 impl GameServer {
     pub async fn new_with_port(port: u16, use_inactivity_shutdown: bool, proxy_settings: Option<ProxySettings>) -> anyhow::Result<Self> {
@@ -265,7 +304,15 @@ impl GameServer {
         let endpoint = create_server(server_addr)?;
 
         log::debug!("GameServer listening on port {}", port);
-        Ok(Self { endpoint, port, use_inactivity_shutdown, proxy_settings })
+        Ok(Self {
+            endpoint,
+            port,
+            use_inactivity_shutdown,
+            proxy_settings,
+            authenticator: Arc::new(Unauthenticated),
+            record_session: None,
+            shutdown: Arc::new(Notify::new()),
+        })
     }
     pub async fn new_with_port_in_range(
         port_range: Range<u16>,
@@ -293,16 +340,29 @@ impl GameServer {
         create_shutdown_systems: Arc<dyn Fn() -> SystemGroup<ShutdownEvent> + Sync + Send>,
         is_sync_component: Arc<dyn Fn(ComponentDesc, WorldStreamCompEvent) -> bool + Sync + Send>,
     ) -> SharedServerState {
-        let Self { endpoint, proxy_settings, .. } = self;
+        let Self { endpoint, proxy_settings, authenticator, record_session, shutdown, .. } = self;
         let assets = world.resource(asset_cache()).clone();
         let world_stream_filter = WorldStreamFilter::new(ArchetypeFilter::new().excl(no_sync()), is_sync_component);
+        let recorder = record_session.and_then(|path| {
+            match SessionRecorder::create(&path, world.resource(project_name()).clone(), ServerBaseUrlKey.get(&assets)) {
+                Ok(recorder) => {
+                    log::info!("Recording session to {path:?}");
+                    Some(recorder)
+                }
+                Err(err) => {
+                    log::error!("Failed to start recording the session: {err:?}");
+                    None
+                }
+            }
+        });
         let state = Arc::new(Mutex::new(ServerState::new(
             [(
                 MAIN_INSTANCE_ID.to_string(),
                 WorldInstance {
                     systems: create_server_systems(&mut world),
                     world,
-                    world_stream: WorldStream::new(world_stream_filter.clone()),
+                    world_stream: WorldStream::new(world_stream_filter),
+                    recorder,
                 },
             )]
             .into_iter()
@@ -322,10 +382,10 @@ impl GameServer {
         if let Some(proxy_settings) = proxy_settings {
             let endpoint = endpoint.clone();
             let state = state.clone();
-            let world_stream_filter = world_stream_filter.clone();
             let assets = assets.clone();
+            let authenticator = authenticator.clone();
             tokio::spawn(async move {
-                start_proxy_connection(endpoint.clone(), proxy_settings, state.clone(), world_stream_filter.clone(), assets.clone()).await;
+                start_proxy_connection(endpoint.clone(), proxy_settings, state.clone(), assets.clone(), authenticator).await;
             });
         }
 
@@ -345,7 +405,7 @@ impl GameServer {
 
 
                     log::debug!("Accepted connection");
-                    run_connection(conn.into(), state.clone(), world_stream_filter.clone(), assets.clone(), ServerBaseUrlKey.get(&assets));
+                    run_connection(conn.into(), state.clone(), assets.clone(), ServerBaseUrlKey.get(&assets), authenticator.clone());
                 }
                 _ = sim_interval.tick() => {
                     fps_counter.frame_start();
@@ -374,6 +434,10 @@ impl GameServer {
                         last_active = Instant::now();
                     }
                 }
+                _ = shutdown.notified() => {
+                    log::info!("[{}] Shutting down", self.port);
+                    break;
+                }
                 else => {
                     log::info!("No more connections. Shutting down.");
                     break
@@ -398,8 +462,8 @@ async fn start_proxy_connection(
     endpoint: Endpoint,
     settings: ProxySettings,
     state: Arc<Mutex<ServerState>>,
-    world_stream_filter: WorldStreamFilter,
     assets: AssetCache,
+    authenticator: Arc<dyn Authenticator>,
 ) {
     // start with content base url being the same as for direct connections
     let content_base_url = Arc::new(RwLock::new(ServerBaseUrlKey.get(&assets)));
@@ -427,7 +491,7 @@ async fn start_proxy_connection(
         let content_base_url = content_base_url.clone();
         Arc::new(move |_player_id, conn: ambient_proxy::client::ProxiedConnection| {
             log::debug!("Accepted connection via proxy");
-            run_connection(conn.into(), state.clone(), world_stream_filter.clone(), assets.clone(), content_base_url.read().clone());
+            run_connection(conn.into(), state.clone(), assets.clone(), content_base_url.read().clone(), authenticator.clone());
         })
     };
 
@@ -472,9 +536,9 @@ async fn start_proxy_connection(
 fn run_connection(
     connection: ClientConnection,
     state: SharedServerState,
-    world_stream_filter: WorldStreamFilter,
     assets: AssetCache,
     content_base_url: AbsAssetUrl,
+    authenticator: Arc<dyn Authenticator>,
 ) {
     let connection_id = friendly_id();
     let handle = Arc::new(OnceCell::new());
@@ -517,27 +581,30 @@ fn run_connection(
                     // Bring world stream up to the current time
                     log::debug!("[{}] Broadcasting diffs", user_id);
                     instance.broadcast_diffs();
-                    log::debug!("[{}] Creating init diff", user_id);
-
-                    let diff = world_stream_filter.initial_diff(&instance.world);
-                    let diff = bincode::serialize(&diff).unwrap();
-
-                    log_result!(diffs_tx.send(diff));
-                    log::debug!("[{}] Init diff sent", user_id);
 
-                    if !reconnecting {
-                        instance.spawn_player(
+                    // The player entity has to exist before the init diff is created, as it determines what the player can see
+                    let entity = if !reconnecting {
+                        let entity = instance.spawn_player(
                             create_player_entity_data(user_id, diffs_tx.clone(), stats_tx.clone())
                                 .with(player_connection(), new_player_connection.clone()),
                         );
                         log::info!("[{}] Player spawned", user_id);
+                        entity
                     } else {
                         let entity = get_by_user_id(&instance.world, user_id).unwrap();
                         instance.world.set(entity, player_entity_stream(), diffs_tx.clone()).unwrap();
                         instance.world.set(entity, player_stats_stream(), stats_tx.clone()).unwrap();
                         instance.world.set(entity, player_connection(), new_player_connection.clone()).unwrap();
                         log::info!("[{}] Player reconnected", user_id);
-                    }
+                        entity
+                    };
+
+                    log::debug!("[{}] Creating init diff", user_id);
+                    let diff = instance.initial_player_diff(entity);
+                    let diff = bincode::serialize(&diff).unwrap();
+
+                    log_result!(diffs_tx.send(diff));
+                    log::debug!("[{}] Init diff sent", user_id);
                 };
 
                 let on_disconnect = |user_id: &Option<String>| {
@@ -639,8 +706,11 @@ fn run_connection(
                     ServerInfo { project_name: world.resource(project_name()).clone(), content_base_url, ..Default::default() }
                 };
 
-                match client.run(connection, server_info).await {
+                match client.run(connection, server_info, authenticator.as_ref()).await {
                     Ok(()) => {}
+                    Err(NetworkError::HandshakeRejected(err)) => {
+                        log::info!("Rejected client: {err}");
+                    }
                     Err(err) if err.is_closed() => {
                         log::info!("Connection closed by client");
                     }
@@ -683,9 +753,14 @@ impl<'a> Drop for ClientInstance<'a> {
 
 impl<'a> ClientInstance<'a> {
     #[tracing::instrument(skip_all)]
-    pub async fn run(mut self, conn: ClientConnection, server_info: ServerInfo) -> Result<(), NetworkError> {
+    pub async fn run(
+        mut self,
+        conn: ClientConnection,
+        server_info: ServerInfo,
+        authenticator: &dyn Authenticator,
+    ) -> Result<(), NetworkError> {
         log::debug!("Connecting to client");
-        let mut proto = ServerProtocol::new(conn, server_info).await?;
+        let mut proto = ServerProtocol::new(conn, server_info, authenticator).await?;
 
         log::debug!("Client loop starting");
         let mut entities_rx = self.diffs_rx.stream();
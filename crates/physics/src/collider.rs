@@ -32,6 +32,8 @@ use physxx::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    helpers::{set_shape_reporting, update_shape_layers},
+    intersection::DEFAULT_COLLISION_LAYERS,
     main_controller_manager, make_physics_static,
     mesh::{PhysxGeometry, PhysxGeometryFromUrl},
     physx::{
@@ -158,6 +160,7 @@ pub fn server_systems() -> SystemGroup {
                             actor.as_actor().set_user_data(id);
                             actor.get_shapes()[0].set_user_data(PxShapeUserData { entity: id, density: 1., ..Default::default() });
                             world.add_component(id, character_controller(), controller).unwrap();
+                            update_shape_layers(world, id);
                         } else {
                             world.remove_component(id, character_controller()).unwrap();
                         }
@@ -260,6 +263,9 @@ pub fn server_systems() -> SystemGroup {
                             let shapes = if is_dynamic && !is_kinematic { &mut shapes_convex } else { &mut shapes_concave };
                             let coff = world.get(id, contact_offset()).ok();
                             let roff = world.get(id, rest_offset()).ok();
+                            let is_trigger = world.has_component(id, trigger());
+                            let report_contacts = world.has_component(id, contact_reporting());
+                            let layers = world.get(id, collision_layers()).unwrap_or(DEFAULT_COLLISION_LAYERS);
                             for shape in shapes.iter_mut() {
                                 if !actor.attach_shape(shape) {
                                     log::error!("Failed to attach shape to entity {}", id);
@@ -274,6 +280,8 @@ pub fn server_systems() -> SystemGroup {
                                 if let Some(roff) = roff {
                                     shape.set_rest_offset(roff);
                                 }
+                                set_shape_reporting(shape, is_trigger, report_contacts);
+                                shape.set_query_filter_data([layers, 0, 0, 0]);
                                 shape.update_user_data::<PxShapeUserData>(&|ud| ud.entity = id);
                             }
                             if let Some(actor) = actor.to_rigid_dynamic() {
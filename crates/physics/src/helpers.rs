@@ -6,11 +6,19 @@ use anyhow::{bail, Context};
 use glam::{vec3, Vec3};
 use itertools::Itertools;
 use physxx::{
-    AsPxActor, AsPxRigidActor, PxActor, PxActorTypeFlag, PxBase, PxBoxGeometry, PxConvexMeshGeometry, PxJoint, PxMeshScale, PxOverlapCallback, PxQueryFilterData, PxQueryFlag, PxRevoluteJointRef, PxRigidActor, PxRigidActorRef, PxRigidBody, PxRigidBodyFlag, PxRigidDynamicRef, PxRigidStaticRef, PxSceneRef, PxShape, PxSphereGeometry, PxTransform, PxTriangleMeshGeometry, PxUserData, PxForceMode
+    AsPxActor, AsPxRigidActor, PxActor, PxActorTypeFlag, PxBase, PxBoxGeometry, PxConvexMeshGeometry, PxForceMode, PxJoint, PxMeshScale,
+    PxOverlapCallback, PxQueryFilterData, PxQueryFlag, PxRevoluteJointRef, PxRigidActor, PxRigidActorRef, PxRigidBody, PxRigidBodyFlag,
+    PxRigidDynamicRef, PxRigidStaticRef, PxSceneRef, PxShape, PxShapeFlag, PxSphereGeometry, PxTransform, PxTriangleMeshGeometry,
+    PxUserData,
 };
 
 use crate::{
-    collider::{collider_shapes_convex, collider_type, kinematic}, main_physics_scene, physx::{physics, physics_controlled, physics_shape, revolute_joint, rigid_dynamic}, unit_mass, unit_velocity, ColliderScene, PxActorUserData, PxShapeUserData
+    collider::{collider_shapes_convex, collider_type, kinematic},
+    collision_layers, contact_reporting,
+    intersection::DEFAULT_COLLISION_LAYERS,
+    main_physics_scene,
+    physx::{character_controller, physics, physics_controlled, physics_shape, revolute_joint, rigid_dynamic},
+    trigger, unit_mass, unit_velocity, ColliderScene, PxActorUserData, PxShapeUserData, CONTACT_REPORTING_FILTER,
 };
 
 pub fn convert_rigid_static_to_dynamic(world: &mut World, id: EntityId) {
@@ -115,6 +123,48 @@ pub fn get_shapes(world: &World, id: EntityId) -> impl Iterator<Item = PxShape>
         .chain(world.get_ref(id, collider_shapes_convex()).into_iter().flatten().cloned())
 }
 
+/// Updates the query filter data of the shapes of an entity to match its [collision_layers], so that layer-filtered scene
+/// queries only hit it when it's in one of their layers
+pub fn update_shape_layers(world: &World, id: EntityId) {
+    let layers = world.get(id, collision_layers()).unwrap_or(DEFAULT_COLLISION_LAYERS);
+    let controller_shapes =
+        world.get_ref(id, character_controller()).into_iter().flat_map(|controller| controller.get_actor().get_shapes());
+    for shape in get_shapes(world, id).chain(controller_shapes) {
+        shape.set_query_filter_data([layers, 0, 0, 0]);
+    }
+}
+
+/// Updates the shapes of an entity to match its [trigger](crate::trigger) and [contact_reporting](crate::contact_reporting) components
+pub fn update_shape_reporting(world: &World, id: EntityId) {
+    let is_trigger = world.has_component(id, trigger());
+    let report_contacts = world.has_component(id, contact_reporting());
+    for shape in get_shapes(world, id) {
+        set_shape_reporting(&shape, is_trigger, report_contacts);
+    }
+    // PhysX only re-filters existing pairs when asked to, so without this the old flags would stay in effect for
+    // shapes that are already touching
+    if let Some(actor) = world.get_ref(id, physics_shape()).ok().and_then(|shape| shape.get_actor()) {
+        if let Some(scene) = actor.get_scene() {
+            scene.reset_filtering(&actor);
+        }
+    }
+}
+
+pub fn set_shape_reporting(shape: &PxShape, is_trigger: bool, report_contacts: bool) {
+    // PhysX does not allow a shape to be both a simulation shape and a trigger
+    if is_trigger {
+        shape.set_flag(PxShapeFlag::SIMULATION_SHAPE, false);
+        shape.set_flag(PxShapeFlag::TRIGGER_SHAPE, true);
+    } else {
+        shape.set_flag(PxShapeFlag::TRIGGER_SHAPE, false);
+        shape.set_flag(PxShapeFlag::SIMULATION_SHAPE, true);
+    }
+
+    let mut filter_data = shape.get_simulation_filter_data();
+    filter_data[0] = if report_contacts { filter_data[0] | CONTACT_REPORTING_FILTER } else { filter_data[0] & !CONTACT_REPORTING_FILTER };
+    shape.set_simulation_filter_data(filter_data);
+}
+
 pub fn scale_shape(shape: PxShape, scale: Vec3) {
     tracing::debug!("Scaling shape");
     let geo = shape.get_geometry();
@@ -9,8 +9,8 @@ use glam::Vec3;
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use physxx::{
-    PxConvexFlag, PxConvexMesh, PxConvexMeshDesc, PxConvexMeshGeometry, PxOverlapCallback, PxQueryFilterData, PxRaycastCallback,
-    PxRigidActor, PxShape, PxTransform, PxUserData,
+    PxConvexFlag, PxConvexMesh, PxConvexMeshDesc, PxConvexMeshGeometry, PxGeometry, PxOverlapCallback, PxQueryFilterData, PxQueryFlag,
+    PxRaycastCallback, PxRigidActor, PxShape, PxTransform, PxUserData,
 };
 use serde::{Deserialize, Serialize};
 
@@ -135,3 +135,151 @@ pub struct RaycastFilter {
     pub entities: Option<ArchetypeFilter>,
     pub collider_type: Option<ColliderScene>,
 }
+
+/// A collision layer mask that matches every layer.
+pub const ALL_COLLISION_LAYERS: u32 = u32::MAX;
+/// The layers of entities without [collision_layers](crate::collision_layers).
+pub const DEFAULT_COLLISION_LAYERS: u32 = 1;
+
+/// Casts a ray against the physics scene, and returns the hits within `max_distance` that are in any of the `layers`,
+/// sorted by distance.
+pub fn raycast_layers(world: &World, ray: Ray, max_distance: f32, layers: u32) -> Vec<(EntityId, f32)> {
+    let mut hit = PxRaycastCallback::new(100);
+    let scene = ColliderScene::Physics.get_scene(world);
+    if !scene.raycast(ray.origin, ray.dir, max_distance, &mut hit, None, &layers_filter_data(layers)) {
+        return Vec::new();
+    }
+    hit.touches()
+        .into_iter()
+        .filter_map(|hit| Some((hit.shape?.get_user_data::<PxShapeUserData>()?.entity, hit.distance)))
+        .sorted_by_key(|x| OrderedFloat(x.1))
+        .collect_vec()
+}
+
+/// A hit from a [sweep].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    pub entity: EntityId,
+    pub distance: f32,
+    pub position: Vec3,
+    pub normal: Vec3,
+}
+
+/// Sweeps `geometry` from `pose` along `direction` against the physics scene, and returns the hits within `max_distance`
+/// that are in any of the `layers`, sorted by distance.
+pub fn sweep(
+    world: &World,
+    geometry: &dyn PxGeometry,
+    pose: &PxTransform,
+    direction: Vec3,
+    max_distance: f32,
+    layers: u32,
+) -> Vec<SweepHit> {
+    let scene = ColliderScene::Physics.get_scene(world);
+    scene
+        .sweep(geometry, pose, direction, max_distance, layers_filter_data(layers))
+        .touches()
+        .into_iter()
+        .filter_map(|hit| {
+            let entity = hit.shape?.get_user_data::<PxShapeUserData>()?.entity;
+            Some(SweepHit { entity, distance: hit.distance, position: hit.position, normal: hit.normal })
+        })
+        .sorted_by_key(|hit| OrderedFloat(hit.distance))
+        .collect_vec()
+}
+
+/// Returns the entities in any of the `layers` that overlap `geometry` at `pose` in the physics scene.
+pub fn overlap(world: &World, geometry: &dyn PxGeometry, pose: PxTransform, layers: u32) -> Vec<EntityId> {
+    let mut hit_call = PxOverlapCallback::new(1000);
+    let scene = ColliderScene::Physics.get_scene(world);
+    if !scene.overlap(geometry, pose, &mut hit_call, &layers_filter_data(layers)) {
+        return Vec::new();
+    }
+    hit_call.touches().into_iter().filter_map(|hit| Some(hit.shape.get_user_data::<PxShapeUserData>()?.entity)).unique().collect_vec()
+}
+
+/// Filter data that reports every hit in any of the `layers` as a touch, instead of stopping at the first blocking hit.
+///
+/// The layers are filtered by PhysX against the query filter data of the shapes (see
+/// [update_shape_layers](crate::helpers::update_shape_layers)), so that hits in other layers don't take up the hit buffer
+fn layers_filter_data(layers: u32) -> PxQueryFilterData {
+    let mut filter_data = PxQueryFilterData::new();
+    filter_data.set_flags(PxQueryFlag::STATIC | PxQueryFlag::DYNAMIC | PxQueryFlag::NO_BLOCK);
+    // Empty filter data hits every shape, including the ones that aren't in any layer
+    if layers != ALL_COLLISION_LAYERS {
+        filter_data.set_data([layers, 0, 0, 0]);
+    }
+    filter_data
+}
+
+#[cfg(test)]
+mod test {
+    use ambient_ecs::Entity;
+    use ambient_std::asset_cache::AssetCache;
+    use glam::vec3;
+    use physxx::{AsPxRigidActor, PxRigidStaticRef, PxSphereGeometry};
+
+    use super::*;
+    use crate::{
+        collision_layers, create_server_resources,
+        helpers::update_shape_layers,
+        physx::{physics, physics_shape},
+        wood_physics_material,
+    };
+
+    #[test]
+    fn raycast_layers_filter() {
+        ambient_ecs::init_components();
+        crate::init_all_components();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut resources = Entity::new();
+        create_server_resources(&AssetCache::new(runtime.handle().clone()), &mut resources);
+        let mut world = World::new("test");
+        world.add_components(world.resource_entity(), resources).unwrap();
+
+        // A row of spheres along the ray, alternating between the first two layers. There are more of
+        // them than fit in the hit buffer
+        let physics = world.resource(physics()).clone();
+        let material = world.resource(wood_physics_material()).clone();
+        let ids = (0..200)
+            .map(|i| {
+                let id = Entity::new().with(collision_layers(), 1 << (i % 2)).spawn(&mut world);
+                let shape = PxShape::new(physics.physics, &PxSphereGeometry::new(0.25), &[&material], Some(true), None);
+                shape.set_user_data(PxShapeUserData { entity: id, ..Default::default() });
+                let actor = PxRigidStaticRef::new(physics.physics, &PxTransform::from_translation(vec3(i as f32 + 1., 0., 0.)));
+                actor.as_rigid_actor().attach_shape(&shape);
+                world.resource(main_physics_scene()).add_actor(&actor);
+                world.add_component(id, physics_shape(), shape).unwrap();
+                update_shape_layers(&world, id);
+                id
+            })
+            .collect_vec();
+
+        let ray = Ray::new(Vec3::ZERO, Vec3::X);
+        let hits = raycast_layers(&world, ray, 1000., 2);
+        assert_eq!(hits.len(), 100);
+        assert!(hits.iter().all(|(id, _)| world.get(*id, collision_layers()).unwrap() == 2));
+        assert_eq!(hits[0].0, ids[1]);
+
+        assert_eq!(raycast_layers(&world, ray, 1000., 4), vec![]);
+        assert_eq!(raycast_layers(&world, ray, 2.5, ALL_COLLISION_LAYERS).len(), 2);
+
+        // Changing the layers of an entity changes which queries hit it
+        world.set(ids[0], collision_layers(), 2).unwrap();
+        update_shape_layers(&world, ids[0]);
+        assert_eq!(raycast_layers(&world, ray, 1000., 2)[0].0, ids[0]);
+
+        // Entities without layers are in the first layer
+        let id = Entity::new().spawn(&mut world);
+        let shape = PxShape::new(physics.physics, &PxSphereGeometry::new(0.25), &[&material], Some(true), None);
+        shape.set_user_data(PxShapeUserData { entity: id, ..Default::default() });
+        let actor = PxRigidStaticRef::new(physics.physics, &PxTransform::from_translation(vec3(0., 1., 0.)));
+        actor.as_rigid_actor().attach_shape(&shape);
+        world.resource(main_physics_scene()).add_actor(&actor);
+        world.add_component(id, physics_shape(), shape).unwrap();
+        update_shape_layers(&world, id);
+        let ray = Ray::new(Vec3::ZERO, Vec3::Y);
+        assert_eq!(raycast_layers(&world, ray, 1000., 1).into_iter().map(|(hit, _)| hit).collect_vec(), vec![id]);
+        assert_eq!(raycast_layers(&world, ray, 1000., 2), vec![]);
+    }
+}
@@ -5,7 +5,7 @@ use ambient_ecs::{components, query, Debuggable, DynSystem, Entity, EntityId, Fn
 use ambient_network::server::{ForkingEvent, ShutdownEvent};
 use ambient_std::asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt};
 use collider::{collider_shapes, collider_shapes_convex};
-use glam::{vec3, Mat4};
+use glam::{vec3, Mat4, Vec3};
 use helpers::release_px_scene;
 use parking_lot::Mutex;
 use physx::{
@@ -13,8 +13,8 @@ use physx::{
     physics_shape, revolute_joint, rigid_actor, rigid_dynamic, rigid_static,
 };
 use physxx::{
-    AsPxActor, PxContactPairHeader, PxControllerManagerRef, PxMaterial, PxPvdSceneFlag, PxRigidActor, PxSceneDesc, PxSceneFlags,
-    PxSceneRef, PxSimulationEventCallback, PxUserData,
+    AsPxActor, PxContactPairHeader, PxControllerManagerRef, PxMaterial, PxPairFlag, PxPvdSceneFlag, PxRigidActor, PxSceneDesc,
+    PxSceneFlags, PxSceneRef, PxShape, PxSimulationEventCallback, PxTriggerPair, PxUserData,
 };
 use serde::{Deserialize, Serialize};
 
@@ -43,6 +43,10 @@ components!("physics", {
     wood_physics_material: PxMaterial,
     @[Debuggable, Resource]
     collisions: Arc<Mutex<Vec<(EntityId, EntityId)>>>,
+    @[Debuggable, Resource]
+    contact_events: Arc<Mutex<Vec<ContactEvent>>>,
+    @[Debuggable, Resource]
+    trigger_events: Arc<Mutex<Vec<TriggerEvent>>>,
 });
 pub fn init_all_components() {
     init_components();
@@ -62,22 +66,62 @@ pub fn create_server_resources(assets: &AssetCache, server_resources: &mut Entit
     main_scene_desc.update_flags(|flags| flags | PxSceneFlags::ENABLE_CCD);
     main_scene_desc.set_filter_shader(main_physx_scene_filter_shader, true);
     let collisions = Arc::new(Mutex::new(Vec::new()));
+    let contact_events = Arc::new(Mutex::new(Vec::new()));
+    let trigger_events = Arc::new(Mutex::new(Vec::new()));
     {
         let collisions = collisions.clone();
+        let contact_events = contact_events.clone();
+        let trigger_events = trigger_events.clone();
         main_scene_desc.set_simulation_event_callbacks(PxSimulationEventCallback {
             collision_callback: Some(Box::new(move |header: &PxContactPairHeader| {
                 if let (Some(a), Some(b)) = (header.actors[0], header.actors[1]) {
                     let a = a.borrow_shapes().get(0).and_then(|s| s.get_user_data::<PxShapeUserData>()).map(|ud| ud.entity);
                     let b = b.borrow_shapes().get(0).and_then(|s| s.get_user_data::<PxShapeUserData>()).map(|ud| ud.entity);
                     if let (Some(a), Some(b)) = (a, b) {
-                        collisions.lock().push((a, b));
+                        if header.pairs.iter().any(|pair| pair.events.contains(PxPairFlag::NOTIFY_TOUCH_FOUND)) {
+                            collisions.lock().push((a, b));
+                        }
+                    }
+                }
+
+                let mut contact_events = contact_events.lock();
+                for pair in &header.pairs {
+                    // Shapes that have been removed from the scene can't be traced back to their entity
+                    let [Some(shape_a), Some(shape_b)] = &pair.shapes else { continue };
+                    if (shape_a.get_simulation_filter_data()[0] | shape_b.get_simulation_filter_data()[0]) & CONTACT_REPORTING_FILTER == 0 {
+                        continue;
                     }
+                    let entity = |shape: &PxShape| shape.get_user_data::<PxShapeUserData>().map(|ud| ud.entity);
+                    let (Some(a), Some(b), Some(phase)) = (entity(shape_a), entity(shape_b), ContactPhase::from_events(pair.events)) else {
+                        continue;
+                    };
+                    contact_events.push(ContactEvent {
+                        phase,
+                        entities: [a, b],
+                        points: pair
+                            .points
+                            .iter()
+                            .map(|p| ContactPoint { position: p.position, normal: p.normal, impulse: p.impulse, separation: p.separation })
+                            .collect(),
+                    });
+                }
+            })),
+            trigger_callback: Some(Box::new(move |pairs: &[PxTriggerPair]| {
+                let mut trigger_events = trigger_events.lock();
+                for pair in pairs {
+                    let entity = |shape: &Option<PxShape>| shape.as_ref()?.get_user_data::<PxShapeUserData>().map(|ud| ud.entity);
+                    let (Some(trigger), Some(other)) = (entity(&pair.trigger_shape), entity(&pair.other_shape)) else {
+                        continue;
+                    };
+                    trigger_events.push(TriggerEvent { trigger, other, entered: pair.status.contains(PxPairFlag::NOTIFY_TOUCH_FOUND) });
                 }
             })),
         });
     }
     let main_scene = PxSceneRef::new(&physics.physics, &main_scene_desc);
     server_resources.set(self::collisions(), collisions);
+    server_resources.set(self::contact_events(), contact_events);
+    server_resources.set(self::trigger_events(), trigger_events);
     server_resources.set(self::collider_loads(), vec![]);
 
     main_scene.get_scene_pvd_client().set_scene_pvd_flags(
@@ -115,6 +159,55 @@ impl Default for PxShapeUserData {
     }
 }
 
+/// The bit of a shape's simulation filter data that enables detailed contact reporting for it
+pub const CONTACT_REPORTING_FILTER: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactPhase {
+    Begin,
+    Persist,
+    End,
+}
+impl ContactPhase {
+    fn from_events(events: PxPairFlag) -> Option<Self> {
+        if events.contains(PxPairFlag::NOTIFY_TOUCH_FOUND) {
+            Some(Self::Begin)
+        } else if events.contains(PxPairFlag::NOTIFY_TOUCH_PERSISTS) {
+            Some(Self::Persist)
+        } else if events.contains(PxPairFlag::NOTIFY_TOUCH_LOST) {
+            Some(Self::End)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactPoint {
+    pub position: Vec3,
+    /// Points from the second entity to the first entity
+    pub normal: Vec3,
+    pub impulse: Vec3,
+    pub separation: f32,
+}
+
+/// A contact between two entities, at least one of which has [contact_reporting]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactEvent {
+    pub phase: ContactPhase,
+    pub entities: [EntityId; 2],
+    /// Empty when the contact ends
+    pub points: Vec<ContactPoint>,
+}
+
+/// An entity entering or leaving a [trigger]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEvent {
+    pub trigger: EntityId,
+    pub other: EntityId,
+    pub entered: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct PxActorUserData {
     pub serialize: bool,
@@ -161,11 +254,21 @@ impl SyncAssetKey<PxMaterial> for PxWoodMaterialKey {
 }
 
 unsafe extern "C" fn main_physx_scene_filter_shader(mut info: *mut physxx::sys::FilterShaderCallbackInfo) -> u16 {
-    (*(*info).pairFlags).mBits |= (physxx::sys::PxPairFlag::eSOLVE_CONTACT
-        | physxx::sys::PxPairFlag::eDETECT_DISCRETE_CONTACT
-        | physxx::sys::PxPairFlag::eDETECT_CCD_CONTACT
-        | physxx::sys::PxPairFlag::eCONTACT_DEFAULT
-        | physxx::sys::PxPairFlag::eNOTIFY_TOUCH_FOUND) as u16;
+    let is_trigger = |attributes: u32| attributes & physxx::sys::PxFilterObjectFlag::eTRIGGER as u32 != 0;
+    if is_trigger((*info).attributes0) || is_trigger((*info).attributes1) {
+        (*(*info).pairFlags).mBits = PxPairFlag::TRIGGER_DEFAULT.bits();
+        return (physxx::sys::PxFilterFlag::eDEFAULT) as u16;
+    }
+
+    let mut flags = PxPairFlag::SOLVE_CONTACT
+        | PxPairFlag::DETECT_DISCRETE_CONTACT
+        | PxPairFlag::DETECT_CCD_CONTACT
+        | PxPairFlag::CONTACT_DEFAULT
+        | PxPairFlag::NOTIFY_TOUCH_FOUND;
+    if ((*info).filterData0.word0 | (*info).filterData1.word0) & CONTACT_REPORTING_FILTER != 0 {
+        flags |= PxPairFlag::NOTIFY_TOUCH_PERSISTS | PxPairFlag::NOTIFY_TOUCH_LOST | PxPairFlag::NOTIFY_CONTACT_POINTS;
+    }
+    (*(*info).pairFlags).mBits |= flags.bits();
     (physxx::sys::PxFilterFlag::eDEFAULT) as u16
 }
 
@@ -224,6 +327,8 @@ pub fn fetch_simulation_system() -> DynSystem {
         profiling::scope!("fetch_simulation_system");
 
         world.resource(collisions()).lock().clear();
+        world.resource(contact_events()).lock().clear();
+        world.resource(trigger_events()).lock().clear();
         world.resource_mut(collider_loads()).clear();
         let scene = world.resource(main_physics_scene());
         // Ensure the previous simulation has completed
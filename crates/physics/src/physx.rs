@@ -10,7 +10,7 @@ use glam::{EulerRot, Quat, Vec3};
 use parking_lot::Mutex;
 use physxx::{articulation_reduced_coordinate::*, *};
 
-use crate::helpers::{get_shapes, scale_shape};
+use crate::helpers::{get_shapes, scale_shape, update_shape_layers, update_shape_reporting};
 
 pub use ambient_ecs::generated::components::core::physics::*;
 
@@ -248,6 +248,36 @@ pub fn sync_ecs_physics() -> SystemGroup {
                     }
                 }
             }),
+            query(()).incl(trigger()).spawned().to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    update_shape_reporting(world, id);
+                }
+            }),
+            query(()).incl(trigger()).despawned().to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    update_shape_reporting(world, id);
+                }
+            }),
+            query(()).incl(contact_reporting()).spawned().to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    update_shape_reporting(world, id);
+                }
+            }),
+            query(()).incl(contact_reporting()).despawned().to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    update_shape_reporting(world, id);
+                }
+            }),
+            query(collision_layers().changed()).to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    update_shape_layers(world, id);
+                }
+            }),
+            query(()).incl(collision_layers()).despawned().to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    update_shape_layers(world, id);
+                }
+            }),
             // Sync PhysX changes to ECS.
             query((rigid_dynamic(), translation(), rotation())).incl(physics_controlled()).to_system(|q, world, qs, _| {
                 for (id, (rigid_dynamic, pos, rot)) in q.collect_cloned(world, qs) {
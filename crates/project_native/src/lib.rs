@@ -1,25 +1,18 @@
+use std::sync::Arc;
+
 use ambient_ecs::{
-    ExternalComponentAttributes, ExternalComponentDesc, ExternalComponentFlagAttributes,
-    PrimitiveComponentType,
+    ExternalComponentAttributes, ExternalComponentDesc, ExternalComponentFlagAttributes, Migration,
+    MigrationStep, Migrations, PrimitiveComponentContainerType, PrimitiveComponentType,
 };
 
-use ambient_project::{ComponentType, IdentifierPathBuf, Manifest};
+use ambient_project::{ComponentType, Identifier, IdentifierPathBuf, Manifest};
+use serde_json::Value;
 
 pub fn all_defined_components(
     manifest: &Manifest,
     global_namespace: bool,
 ) -> Result<Vec<ExternalComponentDesc>, &'static str> {
-    let project_path: Vec<_> = if global_namespace {
-        vec![]
-    } else {
-        manifest
-            .project
-            .organization
-            .iter()
-            .chain(std::iter::once(&manifest.project.id))
-            .cloned()
-            .collect()
-    };
+    let project_path = project_path(manifest, global_namespace);
 
     manifest
         .components
@@ -44,9 +37,161 @@ pub fn all_defined_components(
         .collect::<Result<Vec<_>, _>>()
 }
 
+/// Returns the migrations declared in the manifest, which bring the `Store` components of
+/// saved worlds up to date with the components the manifest defines now.
+pub fn migrations(manifest: &Manifest, global_namespace: bool) -> Result<Migrations, String> {
+    let project_path = project_path(manifest, global_namespace);
+    let full_path = |id: &IdentifierPathBuf| {
+        IdentifierPathBuf::from_iter(project_path.iter().chain(id.as_path().iter()).cloned())
+            .to_string()
+    };
+
+    manifest
+        .migrations
+        .iter()
+        .map(|migration| {
+            let renames = migration
+                .rename
+                .iter()
+                .map(|(from, to)| MigrationStep::Rename {
+                    from: full_path(from),
+                    to: full_path(to),
+                });
+            let conversions = migration.convert.iter().map(|id| {
+                let ty = manifest
+                    .components
+                    .get(id)
+                    .and_then(|component| component.other())
+                    .ok_or_else(|| {
+                        format!("Migration {}: no such component {id}", migration.version)
+                    })
+                    .and_then(|component| {
+                        component_type_to_primitive(&component.type_).map_err(String::from)
+                    })?;
+                Ok(MigrationStep::Convert {
+                    component: full_path(id),
+                    convert: Arc::new(move |value| convert_value(value, ty)),
+                })
+            });
+            let defaults = migration.default.iter().map(|(id, value)| {
+                Ok(MigrationStep::Default {
+                    component: full_path(id),
+                    value: serde_json::to_value(value).map_err(|err| err.to_string())?,
+                })
+            });
+
+            Ok(Migration {
+                version: migration.version,
+                steps: renames
+                    .map(Ok)
+                    .chain(conversions)
+                    .chain(defaults)
+                    .collect::<Result<_, String>>()?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map(Migrations::new)
+}
+
+/// Converts a saved `value` to the json representation of `ty`, if there's an obvious way to.
+fn convert_value(value: &Value, ty: PrimitiveComponentType) -> Result<Value, String> {
+    use PrimitiveComponentType as PCT;
+
+    if let Some((container, element_ty)) = ty.decompose_container_type() {
+        return match (container, value) {
+            (PrimitiveComponentContainerType::Option, Value::Null) => Ok(Value::Null),
+            (PrimitiveComponentContainerType::Option, value) => convert_value(value, element_ty),
+            (PrimitiveComponentContainerType::Vec, Value::Array(values)) => values
+                .iter()
+                .map(|value| convert_value(value, element_ty))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            (PrimitiveComponentContainerType::Vec, value) => {
+                Ok(Value::Array(vec![convert_value(value, element_ty)?]))
+            }
+        };
+    }
+
+    let error = || format!("Can't convert {value} to {ty:?}");
+    let number = || match value {
+        Value::Number(number) => number.as_f64(),
+        Value::Bool(value) => Some(if *value { 1. } else { 0. }),
+        Value::String(value) => value.trim().parse().ok(),
+        Value::Array(values) if values.len() == 1 => values[0].as_f64(),
+        _ => None,
+    };
+    let integer = |min: f64, max: f64| {
+        number()
+            .map(f64::round)
+            .filter(|number| (min..=max).contains(number))
+            .ok_or_else(error)
+    };
+    let vector = |len: usize, integer: bool| {
+        let mut values: Vec<_> = match value {
+            Value::Array(values) => values.iter().map(|value| value.as_f64()).collect(),
+            _ => vec![Some(number()?); len],
+        };
+        values.resize(len, Some(0.));
+        values
+            .into_iter()
+            .map(|value| {
+                value.filter(|value| !integer || *value >= 0.).map(|value| {
+                    if integer {
+                        Value::from(value.round() as u32)
+                    } else {
+                        Value::from(value)
+                    }
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array)
+    };
+
+    match ty {
+        PCT::Bool => match value {
+            Value::Bool(value) => Some(Value::Bool(*value)),
+            Value::String(value) => value.trim().parse().ok().map(Value::Bool),
+            _ => number().map(|number| Value::Bool(number != 0.)),
+        }
+        .ok_or_else(error),
+        PCT::F32 | PCT::F64 => number().map(Value::from).ok_or_else(error),
+        PCT::I32 => integer(i32::MIN as f64, i32::MAX as f64).map(|n| Value::from(n as i32)),
+        PCT::U8 => integer(0., u8::MAX as f64).map(|n| Value::from(n as u8)),
+        PCT::U32 => integer(0., u32::MAX as f64).map(|n| Value::from(n as u32)),
+        PCT::U64 => integer(0., u64::MAX as f64).map(|n| Value::from(n as u64)),
+        PCT::String => match value {
+            Value::String(value) => Ok(Value::String(value.clone())),
+            Value::Number(_) | Value::Bool(_) => Ok(Value::String(value.to_string())),
+            _ => Err(error()),
+        },
+        PCT::Vec2 => vector(2, false).ok_or_else(error),
+        PCT::Vec3 => vector(3, false).ok_or_else(error),
+        PCT::Vec4 => vector(4, false).ok_or_else(error),
+        PCT::Uvec2 => vector(2, true).ok_or_else(error),
+        PCT::Uvec3 => vector(3, true).ok_or_else(error),
+        PCT::Uvec4 => vector(4, true).ok_or_else(error),
+        _ => Err(error()),
+    }
+}
+
+fn project_path(manifest: &Manifest, global_namespace: bool) -> Vec<Identifier> {
+    if global_namespace {
+        vec![]
+    } else {
+        manifest
+            .project
+            .organization
+            .iter()
+            .chain(std::iter::once(&manifest.project.id))
+            .cloned()
+            .collect()
+    }
+}
+
 fn component_type_to_primitive(ty: &ComponentType) -> Result<PrimitiveComponentType, &'static str> {
     match ty {
         ComponentType::String(ty) => PrimitiveComponentType::try_from(ty.as_str()),
+        ComponentType::Structured(_) => Ok(PrimitiveComponentType::Structured),
         ComponentType::ContainerType {
             type_,
             element_type,
@@ -75,7 +220,7 @@ fn component_type_to_primitive(ty: &ComponentType) -> Result<PrimitiveComponentT
 mod tests {
     use ambient_shared_types::primitive_component_definitions;
 
-    use crate::{component_type_to_primitive, ComponentType};
+    use crate::{component_type_to_primitive, convert_value, ComponentType};
 
     #[test]
     fn can_convert_component_types() {
@@ -125,4 +270,42 @@ mod tests {
 
         primitive_component_definitions!(make_test_cases);
     }
+
+    #[test]
+    fn can_convert_structured_component_types() {
+        use ambient_ecs::PrimitiveComponentType as PCT;
+        use ambient_project::StructuredType as ST;
+        use ComponentType as CT;
+
+        let map = CT::Structured(ST::Map {
+            element_type: Box::new(CT::String("F32".to_string())),
+        });
+        assert_eq!(component_type_to_primitive(&map), Ok(PCT::Structured));
+
+        let enum_ = CT::Structured(ST::Enum { variants: vec![] });
+        assert_eq!(component_type_to_primitive(&enum_), Ok(PCT::Structured));
+    }
+
+    #[test]
+    fn can_convert_values() {
+        use ambient_ecs::PrimitiveComponentType as PCT;
+        use serde_json::json;
+
+        assert_eq!(convert_value(&json!("3"), PCT::U32), Ok(json!(3)));
+        assert_eq!(convert_value(&json!(2.6), PCT::I32), Ok(json!(3)));
+        assert!(convert_value(&json!(-1), PCT::U8).is_err());
+        assert_eq!(convert_value(&json!(1), PCT::Bool), Ok(json!(true)));
+        assert_eq!(convert_value(&json!(5), PCT::String), Ok(json!("5")));
+        assert_eq!(
+            convert_value(&json!(2), PCT::Vec3),
+            Ok(json!([2.0, 2.0, 2.0]))
+        );
+        assert_eq!(
+            convert_value(&json!([1, 2]), PCT::Vec3),
+            Ok(json!([1.0, 2.0, 0.0]))
+        );
+        assert_eq!(convert_value(&json!(1), PCT::VecU32), Ok(json!([1])));
+        assert_eq!(convert_value(&json!(null), PCT::OptionF32), Ok(json!(null)));
+        assert!(convert_value(&json!({}), PCT::Quat).is_err());
+    }
 }
@@ -510,6 +510,9 @@ pub enum AssetType {
     Atmosphere,
     Biomes,
 
+    /// Terrain cells imported from heightmaps
+    Terrain,
+
     /// Represents a vorbis backed file
     VorbisTrack,
     SoundGraph,
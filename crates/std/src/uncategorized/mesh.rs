@@ -154,6 +154,10 @@ impl Mesh {
             .normals
             .as_mut()
             .map(|normals| normals.drain(..).enumerate().filter_map(|(i, v)| if used[i] { Some(v) } else { None }).collect());
+        self.tangents = self
+            .tangents
+            .as_mut()
+            .map(|tangents| tangents.drain(..).enumerate().filter_map(|(i, v)| if used[i] { Some(v) } else { None }).collect());
         self.joint_indices = self
             .joint_indices
             .as_mut()
@@ -215,6 +219,60 @@ impl Mesh {
         }
         self.tangents = Some(tangents);
     }
+    /// Creates a simplified version of this mesh, with about `target_ratio` of its triangles, by collapsing the edges that change its
+    /// shape the least. Only the indices change, so all vertex attributes are kept.
+    ///
+    /// Vertices that share a position are collapsed together, so attribute seams (such as UV seams or the edges of flat shading) are
+    /// only moved along the seam. The vertices on the borders of the mesh are not moved, so the mesh may keep more triangles than the
+    /// target.
+    #[profiling::function]
+    pub fn simplify(&self, target_ratio: f32) -> Mesh {
+        let (Some(positions), Some(indices)) = (&self.positions, &self.indices) else { return self.clone() };
+        let target = (indices.len() as f32 / 3. * target_ratio.clamp(0., 1.)).ceil() as usize;
+        // Vertices which only differ by index, such as those of flat shaded faces that lie in the same plane, are the same vertex
+        let mut first = std::collections::HashMap::new();
+        let same = (0..positions.len() as u32).map(|i| *first.entry(self.vertex_bits(i as usize)).or_insert(i)).collect_vec();
+        let indices = indices.iter().map(|&i| same[i as usize]).collect_vec();
+        let mut simplifier = Simplifier::new(positions, &indices);
+        simplifier.run(target);
+        if simplifier.alive_count > target * 2 {
+            tracing::warn!(
+                "Simplified mesh {:?} to {} triangles instead of the targeted {target}, as its borders and seams can't be collapsed",
+                self.name,
+                simplifier.alive_count
+            );
+        }
+        let mut mesh = Mesh { indices: Some(simplifier.indices()), ..self.clone() };
+        mesh.remove_unused_vertices();
+        mesh
+    }
+    /// The bits of all the attributes of a vertex
+    fn vertex_bits(&self, i: usize) -> Vec<u32> {
+        let mut bits = Vec::new();
+        let floats = |values: &[f32]| values.iter().map(|x| x.to_bits()).collect_vec();
+        if let Some(positions) = &self.positions {
+            bits.extend(floats(&positions[i].to_array()));
+        }
+        if let Some(colors) = &self.colors {
+            bits.extend(floats(&colors[i].to_array()));
+        }
+        if let Some(normals) = &self.normals {
+            bits.extend(floats(&normals[i].to_array()));
+        }
+        if let Some(tangents) = &self.tangents {
+            bits.extend(floats(&tangents[i].to_array()));
+        }
+        for texcoords in &self.texcoords {
+            bits.extend(floats(&texcoords[i].to_array()));
+        }
+        if let Some(joint_indices) = &self.joint_indices {
+            bits.extend(joint_indices[i].to_array());
+        }
+        if let Some(joint_weights) = &self.joint_weights {
+            bits.extend(floats(&joint_weights[i].to_array()));
+        }
+        bits
+    }
     pub fn size_in_bytes(&self) -> usize {
         self.positions.as_ref().map(|x| std::mem::size_of_val(&**x)).unwrap_or(0)
             + self.colors.as_ref().map(|x| std::mem::size_of_val(&**x)).unwrap_or(0)
@@ -226,3 +284,292 @@ impl Mesh {
             + self.texcoords.iter().map(|x| std::mem::size_of_val(&**x)).sum::<usize>()
     }
 }
+
+/// The error quadric of a vertex; the sum of the squared distances to the planes of its triangles, as a symmetric 4x4 matrix
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+impl Quadric {
+    fn from_plane(normal: DVec3, d: f64, weight: f64) -> Self {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        Self([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|x| x * weight))
+    }
+    fn add(&self, other: &Quadric) -> Self {
+        let mut res = *self;
+        for (x, y) in res.0.iter_mut().zip(other.0) {
+            *x += y;
+        }
+        res
+    }
+    fn error(&self, p: DVec3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        q[0] * x * x
+            + 2. * q[1] * x * y
+            + 2. * q[2] * x * z
+            + 2. * q[3] * x
+            + q[4] * y * y
+            + 2. * q[5] * y * z
+            + 2. * q[6] * y
+            + q[7] * z * z
+            + 2. * q[8] * z
+            + q[9]
+    }
+}
+
+/// Collapses of one vertex into another, by their error, the vertices and the versions of the vertices when they were queued
+type CollapseQueue = std::collections::BinaryHeap<std::cmp::Reverse<(ordered_float::OrderedFloat<f64>, u32, u32, u32, u32)>>;
+
+/// Collapses vertices into their neighbours, cheapest first.
+///
+/// The vertices are welded by position, so the collapses work on the shape of the mesh rather than on the split vertices of its
+/// attribute seams. Each of the split vertices is moved to the split vertex of the target which is on the same side of the seam
+struct Simplifier<'a> {
+    positions: &'a [Vec3],
+    /// The first vertex with the same position as each vertex, which the welded vertices below are indexed by
+    welded: Vec<u32>,
+    triangles: Vec<[u32; 3]>,
+    alive: Vec<bool>,
+    alive_count: usize,
+    vertex_triangles: Vec<Vec<usize>>,
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    removed: Vec<bool>,
+    /// Bumped when the quadric of a vertex changes, which makes its queued collapses stale
+    versions: Vec<u32>,
+    queue: CollapseQueue,
+}
+impl<'a> Simplifier<'a> {
+    fn new(positions: &'a [Vec3], indices: &[u32]) -> Self {
+        let mut first = std::collections::HashMap::new();
+        let welded =
+            positions.iter().enumerate().map(|(i, p)| *first.entry(p.to_array().map(f32::to_bits)).or_insert(i as u32)).collect_vec();
+        let triangles = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect_vec();
+        let mut vertex_triangles = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut edges = std::collections::HashMap::<(u32, u32), u32>::new();
+        for (i, t) in triangles.iter().enumerate() {
+            let t = t.map(|v| welded[v as usize]);
+            let [a, b, c] = t.map(|v| positions[v as usize].as_dvec3());
+            let cross = (b - a).cross(c - a);
+            let area = cross.length();
+            if area > 0. {
+                let normal = cross / area;
+                let quadric = Quadric::from_plane(normal, -normal.dot(a), area);
+                for &v in t.iter().unique() {
+                    quadrics[v as usize] = quadrics[v as usize].add(&quadric);
+                }
+            }
+            for j in 0..3 {
+                if !vertex_triangles[t[j] as usize].contains(&i) {
+                    vertex_triangles[t[j] as usize].push(i);
+                }
+                let (a, b) = (t[j], t[(j + 1) % 3]);
+                if a != b {
+                    *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+        }
+        // Vertices on the borders of the mesh can't be moved
+        let mut locked = vec![false; positions.len()];
+        for ((a, b), count) in edges.iter() {
+            if *count == 1 {
+                locked[*a as usize] = true;
+                locked[*b as usize] = true;
+            }
+        }
+        let alive_count = triangles.len();
+        let mut simplifier = Self {
+            positions,
+            welded,
+            alive: vec![true; triangles.len()],
+            alive_count,
+            triangles,
+            vertex_triangles,
+            quadrics,
+            locked,
+            removed: vec![false; positions.len()],
+            versions: vec![0; positions.len()],
+            queue: Default::default(),
+        };
+        for (a, b) in edges.into_keys() {
+            simplifier.push(a, b);
+            simplifier.push(b, a);
+        }
+        simplifier
+    }
+    /// The triangle with its vertices replaced by their welded vertices
+    fn welded_triangle(&self, i: usize) -> [u32; 3] {
+        self.triangles[i].map(|v| self.welded[v as usize])
+    }
+    /// Queues collapsing `from` into `to`
+    fn push(&mut self, from: u32, to: u32) {
+        if self.locked[from as usize] {
+            return;
+        }
+        let quadric = self.quadrics[from as usize].add(&self.quadrics[to as usize]);
+        let error = quadric.error(self.positions[to as usize].as_dvec3());
+        self.queue.push(std::cmp::Reverse((error.into(), from, to, self.versions[from as usize], self.versions[to as usize])));
+    }
+    fn run(&mut self, target_triangles: usize) {
+        while self.alive_count > target_triangles {
+            let Some(std::cmp::Reverse((_, from, to, from_version, to_version))) = self.queue.pop() else { break };
+            let (f, t) = (from as usize, to as usize);
+            if self.removed[f] || self.removed[t] || self.versions[f] != from_version || self.versions[t] != to_version {
+                continue;
+            }
+            if !self.keeps_orientation(from, to) {
+                continue;
+            }
+            let Some(split_targets) = self.split_targets(from, to) else { continue };
+            self.collapse(from, to, &split_targets);
+        }
+    }
+    /// Whether collapsing `from` into `to` keeps the triangles around `from` facing the same way
+    fn keeps_orientation(&self, from: u32, to: u32) -> bool {
+        self.vertex_triangles[from as usize].iter().filter(|i| self.alive[**i]).all(|&i| {
+            let t = self.welded_triangle(i);
+            if t.contains(&to) {
+                return true;
+            }
+            let [a, b, c] = t.map(|v| self.positions[v as usize]);
+            let before = (b - a).cross(c - a);
+            let [a, b, c] = t.map(|v| self.positions[if v == from { to } else { v } as usize]);
+            let after = (b - a).cross(c - a);
+            after.dot(before) > 0. && after.length_squared() > before.length_squared() * 1e-6
+        })
+    }
+    /// The split vertex of `to` that each split vertex of `from` moves to, taken from the triangles on the collapsed edge.
+    ///
+    /// Returns `None` if a split vertex of `from` has no such triangle, or several with different targets, which happens when the
+    /// edge crosses an attribute seam instead of following it
+    fn split_targets(&self, from: u32, to: u32) -> Option<Vec<(u32, u32)>> {
+        let mut targets = Vec::<(u32, u32)>::new();
+        let triangles = self.vertex_triangles[from as usize].iter().copied().filter(|i| self.alive[*i]).collect_vec();
+        for &i in &triangles {
+            let t = self.triangles[i];
+            let (Some(&v), Some(&u)) =
+                (t.iter().find(|v| self.welded[**v as usize] == from), t.iter().find(|v| self.welded[**v as usize] == to))
+            else {
+                continue;
+            };
+            match targets.iter().find(|(split, _)| *split == v) {
+                Some(&(_, target)) if target != u => return None,
+                Some(_) => {}
+                None => targets.push((v, u)),
+            }
+        }
+        let all_targeted = triangles
+            .iter()
+            .flat_map(|i| self.triangles[*i])
+            .filter(|v| self.welded[*v as usize] == from)
+            .all(|v| targets.iter().any(|(split, _)| *split == v));
+        all_targeted.then_some(targets)
+    }
+    fn collapse(&mut self, from: u32, to: u32, split_targets: &[(u32, u32)]) {
+        let (f, t) = (from as usize, to as usize);
+        for i in std::mem::take(&mut self.vertex_triangles[f]) {
+            if !self.alive[i] {
+                continue;
+            }
+            if self.welded_triangle(i).contains(&to) {
+                self.alive[i] = false;
+                self.alive_count -= 1;
+            } else {
+                for v in self.triangles[i].iter_mut() {
+                    if let Some(&(_, target)) = split_targets.iter().find(|(split, _)| split == v) {
+                        *v = target;
+                    }
+                }
+                self.vertex_triangles[t].push(i);
+            }
+        }
+        self.removed[f] = true;
+        self.quadrics[t] = self.quadrics[t].add(&self.quadrics[f]);
+        self.versions[t] += 1;
+        self.vertex_triangles[t].retain(|i| self.alive[*i]);
+        let neighbours = self.vertex_triangles[t].iter().flat_map(|i| self.welded_triangle(*i)).filter(|v| *v != to).unique().collect_vec();
+        for v in neighbours {
+            self.push(to, v);
+            self.push(v, to);
+        }
+    }
+    fn indices(&self) -> Vec<u32> {
+        self.triangles.iter().zip(&self.alive).filter(|(_, alive)| **alive).flat_map(|(t, _)| *t).collect()
+    }
+}
+
+/// The positions and indices of a flat grid of 10x10 quads, which can be simplified down to the two triangles of its border
+#[cfg(test)]
+fn test_grid() -> (Vec<Vec3>, Vec<u32>) {
+    let size = 11;
+    let positions = (0..size * size).map(|i| vec3((i % size) as f32, (i / size) as f32, 0.)).collect_vec();
+    let mut indices = Vec::new();
+    for y in 0..size - 1 {
+        for x in 0..size - 1 {
+            let i = y * size + x;
+            indices.extend([i, i + 1, i + size, i + 1, i + size + 1, i + size]);
+        }
+    }
+    (positions, indices)
+}
+
+#[cfg(test)]
+fn assert_simplified(mesh: &Mesh, max_triangles: usize) {
+    let triangles = mesh.indices.as_ref().unwrap().len() / 3;
+    assert!(triangles <= max_triangles, "{triangles}");
+    assert_eq!(mesh.aabb().unwrap().max, vec3(10., 10., 0.));
+    assert_eq!(mesh.positions.as_ref().unwrap().len(), mesh.indices.as_ref().unwrap().iter().unique().count());
+}
+
+#[test]
+fn test_simplify() {
+    let (positions, indices) = test_grid();
+    let mesh = Mesh { positions: Some(positions), indices: Some(indices), ..Default::default() };
+    assert_simplified(&mesh.simplify(0.25), 50);
+}
+
+#[test]
+fn test_simplify_flat_shaded() {
+    // Every triangle has its own vertices, which have the same normal as the grid is flat
+    let (positions, indices) = test_grid();
+    let positions = indices.iter().map(|&i| positions[i as usize]).collect_vec();
+    let mesh = Mesh {
+        normals: Some(vec![Vec3::Z; positions.len()]),
+        indices: Some((0..positions.len() as u32).collect()),
+        positions: Some(positions),
+        ..Default::default()
+    };
+    assert_simplified(&mesh.simplify(0.25), 50);
+}
+
+#[test]
+fn test_simplify_uv_seam() {
+    // The right half of the grid has its own vertices on the middle column, with different texture coordinates
+    let (mut positions, mut indices) = test_grid();
+    let mut texcoords = positions.iter().map(|p| p.xy() / 10.).collect_vec();
+    let seam = positions.iter().map(|p| p.x == 5.).collect_vec();
+    let right = indices.chunks_exact(3).map(|t| t.iter().any(|&i| positions[i as usize].x > 5.)).collect_vec();
+    let mut duplicates = std::collections::HashMap::new();
+    for (t, right) in indices.chunks_exact_mut(3).zip(right) {
+        for i in t.iter_mut().filter(|i| right && seam[**i as usize]) {
+            *i = *duplicates.entry(*i).or_insert_with(|| {
+                positions.push(positions[*i as usize]);
+                texcoords.push(vec2(1., positions[*i as usize].y));
+                positions.len() as u32 - 1
+            });
+        }
+    }
+    let mesh = Mesh { positions: Some(positions), texcoords: vec![texcoords], indices: Some(indices), ..Default::default() };
+    let simplified = mesh.simplify(0.25);
+    assert_simplified(&simplified, 50);
+
+    // The triangles stay on their side of the seam, with the texture coordinates of their side
+    let (positions, texcoords) = (simplified.positions.as_ref().unwrap(), &simplified.texcoords[0]);
+    for t in simplified.indices.as_ref().unwrap().chunks_exact(3) {
+        let right = t.iter().any(|&i| positions[i as usize].x > 5.);
+        for &i in t {
+            let (p, uv) = (positions[i as usize], texcoords[i as usize]);
+            assert!(if right { p.x >= 5. && (p.x > 5. || uv.x == 1.) } else { p.x <= 5. && uv.x == p.x / 10. }, "{p} {uv}");
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! The terrain brushes on the CPU, for when there's no GPU to run the compute shaders on, such as on a headless server.
+//! They mirror the shaders, reading zero outside of the heightmap and skipping writes outside of it like storage
+//! textures do, and operate on the [TerrainStateCpu] of the cells.
+
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+};
+
+use ambient_core::{frame_index, map_seed};
+use ambient_ecs::{EntityId, World};
+use ambient_gpu::texture_loaders::Rgba8ImageFromUrl;
+use ambient_network::ServerWorldExt;
+use ambient_std::asset_cache::{AssetCache, AsyncAssetKeyExt};
+use glam::{vec3, IVec2, Vec3, Vec3Swizzles};
+use image::RgbaImage;
+use ndarray::Array3;
+use parking_lot::Mutex;
+
+use super::{
+    flatten_cpu, hydraulic_erosion_cpu, normalmap_cpu, raise_cpu, raise_noise_url, thermal_erosion_cpu, water_sim_cpu, Brush,
+    FlattenBrushParams, RaiseBrushConfig, RaiseBrushParams, TerrainBrushStroke, ThermalErosionParams, WaterSimConfig,
+};
+use crate::{
+    gather_terrain_cells_cpu, get_terrain_cell, spread_terrain_cells_cpu, terrain_cell_version, terrain_state_cpu, TerrainLayers,
+    TerrainSize, TERRAIN_LAYERS,
+};
+
+#[derive(Clone, Debug)]
+pub struct CpuTerrainBrush {
+    assets: AssetCache,
+    /// Downloaded by [Self::prepare] the first time the raise or lower brush is used.
+    raise_noise: Arc<Mutex<Option<Arc<RgbaImage>>>>,
+    frame: Arc<AtomicI32>,
+}
+impl CpuTerrainBrush {
+    pub fn new(assets: AssetCache) -> Self {
+        Self { assets, raise_noise: Default::default(), frame: Arc::new(AtomicI32::new(0)) }
+    }
+    /// Loads what's needed to [apply](Self::apply) `strokes`.
+    pub async fn prepare(&self, strokes: &[TerrainBrushStroke]) {
+        if self.raise_noise.lock().is_some() || !strokes.iter().any(|stroke| matches!(stroke.brush, Brush::Raise | Brush::Lower)) {
+            return;
+        }
+        match (Rgba8ImageFromUrl { url: raise_noise_url() }).get(&self.assets).await {
+            Ok(noise) => *self.raise_noise.lock() = Some(noise),
+            Err(err) => tracing::error!("Failed to load the noise of the raise brush: {err:?}"),
+        }
+    }
+    #[profiling::function]
+    pub fn apply(&self, world: &mut World, stroke: TerrainBrushStroke) -> Vec<EntityId> {
+        let map_globals = world.persisted_resource_entity().unwrap();
+        let seed = world.get(map_globals, map_seed()).unwrap();
+
+        let area = stroke.get_brush_area();
+        let mut heightmap = gather_terrain_cells_cpu(world, area.top_left_cell, area.cells_size);
+        match stroke.brush {
+            Brush::Raise | Brush::Lower => {
+                let Some(noise) = self.raise_noise.lock().clone() else {
+                    tracing::error!("Can't apply {:?} stroke, as the noise of the brush isn't loaded", stroke.brush);
+                    return Vec::new();
+                };
+                let amount = stroke.brush_strength.strength();
+                let params = RaiseBrushParams {
+                    heightmap_world_position: area.world_position,
+                    heightmap_world_texel_size: area.world_texel_size,
+                    brush: stroke.brush_wgsl(if stroke.brush == Brush::Raise { amount } else { -amount }),
+                    layer: stroke.layer as i32,
+                    ..Default::default()
+                };
+                raise_cpu(&mut heightmap, &noise, &RaiseBrushConfig { params, seed });
+            }
+            Brush::Flatten => {
+                let (start_cell, start_texel) = TerrainSize::new().cell_and_texel_from_position(stroke.start_position.xy());
+                let start_heightmap = get_terrain_cell(world, start_cell).and_then(|id| world.get_ref(id, terrain_state_cpu()).ok());
+                // A single white texel, like the texture the GPU brush falls back to
+                let white = Array3::ones((TERRAIN_LAYERS as usize, 1, 1));
+                let params = FlattenBrushParams {
+                    heightmap_world_position: area.world_position,
+                    heightmap_world_texel_size: area.world_texel_size,
+                    brush: stroke.brush_wgsl(stroke.brush_strength.strength()),
+                    start_texel,
+                    _padding: Default::default(),
+                };
+                flatten_cpu(&mut heightmap, start_heightmap.map_or(&white, |state| &state.heightmap), &params);
+            }
+            Brush::Erode => {
+                let config = stroke.erosion_config(&area);
+                hydraulic_erosion_cpu(&mut heightmap, &config);
+            }
+            Brush::Erode2 => {
+                let mut config = WaterSimConfig::default();
+                config.params.frame = world.resource_opt(frame_index()).copied().unwrap_or_default() as i32;
+                water_sim_cpu(&mut heightmap, &config);
+            }
+            Brush::Thermal => {
+                let params = ThermalErosionParams {
+                    heightmap_world_position: area.world_position,
+                    heightmap_world_size: area.world_size,
+                    heightmap_texture_size: area.texture_size.as_ivec2(),
+                    brush_position: stroke.center,
+                    brush_radius: stroke.brush_size.radius(),
+                    frame: self.frame.fetch_add(1, Ordering::SeqCst),
+                    _padding: Default::default(),
+                };
+                thermal_erosion_cpu(&mut heightmap, &params);
+            }
+        }
+        let normalmap = normalmap_cpu(&heightmap);
+        let changed_cells = spread_terrain_cells_cpu(world, &heightmap, &normalmap, area.top_left_cell, area.cells_size);
+
+        for id in &changed_cells {
+            if let Ok(version) = world.get_mut(*id, terrain_cell_version()) {
+                *version += 1;
+            }
+        }
+
+        changed_cells
+    }
+}
+
+/// Reads a texel like `textureLoad` does from a storage texture; texels outside of `heightmap` are zero.
+pub(crate) fn load(heightmap: &Array3<f32>, texel: IVec2, layer: usize) -> f32 {
+    // Negative coordinates wrap around to large indices, which are out of bounds too
+    heightmap.get((layer, texel.y as usize, texel.x as usize)).copied().unwrap_or(0.)
+}
+
+/// Writes a texel like `textureStore` does to a storage texture; texels outside of `heightmap` are skipped.
+pub(crate) fn store(heightmap: &mut Array3<f32>, texel: IVec2, layer: usize, value: f32) {
+    if let Some(v) = heightmap.get_mut((layer, texel.y as usize, texel.x as usize)) {
+        *v = value;
+    }
+}
+
+/// All texels of `heightmap`, row by row.
+pub(crate) fn texels(heightmap: &Array3<f32>) -> impl Iterator<Item = IVec2> {
+    let (_, height, width) = heightmap.dim();
+    (0..height as i32).flat_map(move |y| (0..width as i32).map(move |x| IVec2::new(x, y)))
+}
+
+pub(crate) fn smoothstep(e0: f32, e1: f32, x: f32) -> f32 {
+    let t = ((x - e0) / (e1 - e0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+pub(crate) fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a * (1. - t) + b * t
+}
+
+/// Like `smoothstep_power` in brush.wgsl.
+#[allow(clippy::excessive_precision)]
+pub(crate) fn smoothstep_power(e0: f32, e1: f32, x: f32, smoothness: f32) -> f32 {
+    let r = 6.643856189774724;
+    let z = 1. + (1. - smoothness).powf(r) * 1000.;
+    1. - (1. - smoothstep(e0, e1, x)).powf(z)
+}
+
+/// Like `get_hardness` in get_hardness.wgsl.
+#[allow(clippy::approx_constant)]
+pub(crate) fn get_hardness(heightmap: &Array3<f32>, texel: IVec2, height: f32) -> f32 {
+    let hardness = load(heightmap, texel, TerrainLayers::Hardness as usize);
+    let amount = load(heightmap, texel, TerrainLayers::HardnessStrataAmount as usize);
+    let wavelength = load(heightmap, texel, TerrainLayers::HardnessStrataWavelength as usize);
+    let pi = 3.14159;
+    smoothstep(0.4, 0.6, hardness * mix(1., (1. + (height * 2. * pi / wavelength).sin()) * 0.5, amount))
+}
+
+/// The height of the ground at `texel`, which is the rock and the soil on top of it.
+pub(crate) fn terrain_height(heightmap: &Array3<f32>, texel: IVec2) -> f32 {
+    load(heightmap, texel, TerrainLayers::Rock as usize) + load(heightmap, texel, TerrainLayers::Soil as usize)
+}
+
+/// Like `get_normal` in normalmap.wgsl.
+pub(crate) fn terrain_normal(heightmap: &Array3<f32>, texel: IVec2) -> Vec3 {
+    let height = terrain_height(heightmap, texel);
+    let up = vec3(0., -1., terrain_height(heightmap, texel + IVec2::new(0, -1)) - height);
+    let down = vec3(0., 1., terrain_height(heightmap, texel + IVec2::new(0, 1)) - height);
+    let left = vec3(-1., 0., terrain_height(heightmap, texel + IVec2::new(-1, 0)) - height);
+    let right = vec3(1., 0., terrain_height(heightmap, texel + IVec2::new(1, 0)) - height);
+    (up.cross(right) + down.cross(left)).normalize()
+}
@@ -3,12 +3,13 @@ use std::borrow::Cow;
 use ambient_gpu::{gpu::Gpu, texture::TextureView, wgsl_utils::wgsl_interpolate};
 use ambient_std::include_file;
 use glam::{UVec2, Vec2};
+use ndarray::Array3;
 use wgpu::{
     util::DeviceExt, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BufferBindingType, ShaderStages, TextureFormat,
     TextureViewDimension,
 };
 
-use super::BrushWGSL;
+use super::{load, store, texels, BrushWGSL};
 use crate::{wgsl_terrain_preprocess, TERRAIN_LAYERS};
 
 #[repr(C)]
@@ -121,3 +122,16 @@ impl FlattenBrush {
         cpass.dispatch_workgroups(size.x, size.y, TERRAIN_LAYERS);
     }
 }
+
+/// Runs [FlattenBrush] on `heightmap`, moving each layer towards its height at `params.start_texel` of `start_heightmap`.
+pub fn flatten_cpu(heightmap: &mut Array3<f32>, start_heightmap: &Array3<f32>, params: &FlattenBrushParams) {
+    for texel in texels(heightmap) {
+        let p = texel.as_vec2() * params.heightmap_world_texel_size + params.heightmap_world_position;
+        let brush_strength = params.brush.strength_at(p) * 0.04;
+        for layer in 0..TERRAIN_LAYERS as usize {
+            let our_height = load(heightmap, texel, layer);
+            let delta = load(start_heightmap, params.start_texel.as_ivec2(), layer) - our_height;
+            store(heightmap, texel, layer, our_height + (delta * brush_strength).clamp(-10., 10.));
+        }
+    }
+}
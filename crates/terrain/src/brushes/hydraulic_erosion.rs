@@ -5,14 +5,16 @@ use std::{borrow::Cow, f32::consts::PI};
 use ambient_editor_derive::ElementEditor;
 use ambient_gpu::{gpu::Gpu, texture::TextureView};
 use ambient_std::include_file;
-use glam::{ivec2, vec2, IVec2, IVec3, UVec2, Vec2};
+use glam::{ivec2, uvec2, vec2, IVec2, IVec3, UVec2, Vec2};
 use itertools::Itertools;
+use ndarray::Array3;
 use rand::prelude::*;
 use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
 use wgpu::util::DeviceExt;
 
-use crate::wgsl_terrain_preprocess;
+use super::{get_hardness, load, mix, store, terrain_height};
+use crate::{wgsl_terrain_preprocess, TerrainLayers, TERRAIN_BASE};
 
 #[derive(Clone, Debug, Serialize, Deserialize, ElementEditor)]
 pub struct HydraulicErosionConfig {
@@ -160,42 +162,14 @@ impl HydraulicErosionCompute {
         Self { pipeline }
     }
     pub fn run(&self, gpu: &Gpu, encoder: &mut wgpu::CommandEncoder, texture: &TextureView, size: UVec2, config: &HydraulicErosionConfig) {
-        let drops = (((size.x * size.y) as f32 * config.drops_per_m2) as usize).max(1);
-
         let param_buffer = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Simulation Parameter Buffer"),
             contents: bytemuck::cast_slice(&[config.params]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        // Create brush
-        let mut brush_positions = Vec::new();
-        let mut brush_weights = Vec::new();
-
-        let mut weight_sum = 0.;
-        for brush_y in (-config.drop_radius)..(config.drop_radius + 1) {
-            for brush_x in (-config.drop_radius)..(config.drop_radius + 1) {
-                let sqr_dst = brush_x * brush_x + brush_y * brush_y;
-                if sqr_dst < config.drop_radius * config.drop_radius {
-                    brush_positions.push(ivec2(brush_x, brush_y));
-                    let brush_weight = 1. - (sqr_dst as f32).sqrt() / config.drop_radius as f32;
-                    weight_sum += brush_weight;
-                    brush_weights.push(brush_weight);
-                }
-            }
-        }
-        for brush_weight in &mut brush_weights {
-            *brush_weight /= weight_sum;
-        }
-        let mut rng = Pcg64::seed_from_u64(config.seed);
-        let random_positions = (0..drops)
-            .map(|_| {
-                let r = config.brush_radius * rng.gen::<f32>().sqrt();
-                let theta = rng.gen::<f32>() * 2. * PI;
-                (vec2(theta.cos(), theta.sin()) * r + config.brush_position).as_ivec2()
-                // ivec2(rng.gen_range(0..(size.x as i32 - 1)), rng.gen_range(0..(size.y as i32 - 1)))
-            })
-            .collect_vec();
+        let ErosionInputs { brush_positions, brush_weights, random_positions } = ErosionInputs::new(size, config);
+        let drops = random_positions.len();
 
         let brush_positions = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Simulation Parameter Buffer"),
@@ -237,3 +211,162 @@ impl HydraulicErosionCompute {
         }
     }
 }
+
+/// The drops of a [HydraulicErosionConfig], and the brush they erode the terrain with.
+struct ErosionInputs {
+    brush_positions: Vec<IVec2>,
+    brush_weights: Vec<f32>,
+    random_positions: Vec<IVec2>,
+}
+impl ErosionInputs {
+    fn new(size: UVec2, config: &HydraulicErosionConfig) -> Self {
+        let drops = (((size.x * size.y) as f32 * config.drops_per_m2) as usize).max(1);
+
+        // Create brush
+        let mut brush_positions = Vec::new();
+        let mut brush_weights = Vec::new();
+
+        let mut weight_sum = 0.;
+        for brush_y in (-config.drop_radius)..(config.drop_radius + 1) {
+            for brush_x in (-config.drop_radius)..(config.drop_radius + 1) {
+                let sqr_dst = brush_x * brush_x + brush_y * brush_y;
+                if sqr_dst < config.drop_radius * config.drop_radius {
+                    brush_positions.push(ivec2(brush_x, brush_y));
+                    let brush_weight = 1. - (sqr_dst as f32).sqrt() / config.drop_radius as f32;
+                    weight_sum += brush_weight;
+                    brush_weights.push(brush_weight);
+                }
+            }
+        }
+        for brush_weight in &mut brush_weights {
+            *brush_weight /= weight_sum;
+        }
+        let mut rng = Pcg64::seed_from_u64(config.seed);
+        let random_positions = (0..drops)
+            .map(|_| {
+                let r = config.brush_radius * rng.gen::<f32>().sqrt();
+                let theta = rng.gen::<f32>() * 2. * PI;
+                (vec2(theta.cos(), theta.sin()) * r + config.brush_position).as_ivec2()
+                // ivec2(rng.gen_range(0..(size.x as i32 - 1)), rng.gen_range(0..(size.y as i32 - 1)))
+            })
+            .collect_vec();
+        Self { brush_positions, brush_weights, random_positions }
+    }
+}
+
+/// Runs [HydraulicErosionCompute] on `heightmap`. The drops run one after another, rather than at the same time like
+/// they do on the GPU, so the results differ slightly where their paths cross.
+pub fn hydraulic_erosion_cpu(heightmap: &mut Array3<f32>, config: &HydraulicErosionConfig) {
+    let (_, height, width) = heightmap.dim();
+    let ErosionInputs { brush_positions, brush_weights, random_positions } = ErosionInputs::new(uvec2(width as u32, height as u32), config);
+    let params = &config.params;
+    let size = params.heightmap_size;
+    let map_size = size.x;
+    let soil = TerrainLayers::Soil as usize;
+    let rock = TerrainLayers::Rock as usize;
+
+    for (id, start) in random_positions.iter().enumerate() {
+        let mut pos = start.as_vec2();
+        let mut dir = Vec2::ZERO;
+        let mut vel = params.start_velocity;
+        let mut water = params.start_water;
+        let mut sediment = 0.;
+
+        for lifetime in 0..params.max_lifetime {
+            let node = pos.as_ivec2();
+
+            // Calculate droplet's offset inside the cell (0,0) = at NW node, (1,1) = at SE node
+            let cell_offset = pos - pos.floor();
+
+            // Calculate droplet's height and direction of flow with bilinear interpolation of surrounding heights
+            let (gradient, drop_height) = gradient_and_height(heightmap, pos);
+
+            // Update the droplet's direction and position (move position 1 unit regardless of speed)
+            dir = dir * (1. - params.inertia) - gradient * params.inertia;
+
+            if dir.length() < 0.00001 {
+                dir = (random_positions[(id + lifetime as usize) % random_positions.len()] - size / 2).as_vec2().normalize();
+            }
+
+            if node.x < params.border_size
+                || node.x > map_size - params.border_size
+                || node.y < params.border_size
+                || node.y > map_size - params.border_size
+            {
+                break;
+            }
+
+            dir = dir.normalize();
+            pos += dir;
+
+            // Find the droplet's new height and calculate the delta_height
+            let new_height = gradient_and_height(heightmap, pos).1;
+            let delta_height = new_height - drop_height;
+
+            // Calculate the droplet's sediment capacity (higher when moving fast down a slope and contains lots of water)
+            let capacity = (-delta_height).max(params.min_slope) * vel * water * params.capacity;
+
+            let hardness = get_hardness(heightmap, node, new_height + TERRAIN_BASE);
+
+            // If carrying more sediment than capacity, or if flowing uphill:
+            if sediment > capacity || delta_height > 0. {
+                // If moving uphill (delta_height > 0) try fill up to the current height, otherwise deposit a fraction of the excess sediment
+                let amount_to_deposit =
+                    if delta_height > 0. { delta_height.min(sediment) } else { (sediment - capacity) * params.deposition };
+                sediment -= amount_to_deposit;
+
+                // Add the sediment to the four nodes of the current cell using bilinear interpolation
+                // Deposition is not distributed over a radius (like erosion) so that it can fill small pits
+                let (cx, cy) = (cell_offset.x, cell_offset.y);
+                for (offset, weight) in [
+                    (ivec2(0, 0), (1. - cx) * (1. - cy)),
+                    (ivec2(1, 0), cx * (1. - cy)),
+                    (ivec2(0, 1), (1. - cx) * cy),
+                    (ivec2(1, 1), cx * cy),
+                ] {
+                    let texel = node + offset;
+                    store(heightmap, texel, soil, (load(heightmap, texel, soil) + amount_to_deposit * weight).max(0.));
+                }
+            } else {
+                // Erode a fraction of the droplet's current carry capacity.
+                // Clamp the erosion to the change in height so that it doesn't dig a hole in the terrain behind the droplet
+                let amount_to_erode = ((capacity - sediment) * params.erosion * mix(1., 0.2, hardness)).min(-delta_height);
+
+                for (offset, weight) in brush_positions.iter().zip(&brush_weights) {
+                    let erode_pos = node + *offset;
+                    let weighted_erode_amount = amount_to_erode * weight;
+                    let current_soil = load(heightmap, erode_pos, soil);
+                    if current_soil > 0. {
+                        store(heightmap, erode_pos, soil, (current_soil - weighted_erode_amount).max(0.));
+                    } else {
+                        let current_rock = load(heightmap, erode_pos, rock);
+                        store(heightmap, erode_pos, rock, (current_rock - weighted_erode_amount).max(0.));
+                    }
+                    sediment += weighted_erode_amount;
+                }
+            }
+
+            // Update droplet's speed and water content
+            let gravity = mix(0.1, 4., hardness);
+            vel = (vel * vel + delta_height * gravity).max(0.).sqrt();
+            water *= 1. - params.evaporation;
+        }
+    }
+}
+
+/// The gradient and height of the terrain at `pos`, interpolated bilinearly between the texels around it.
+fn gradient_and_height(heightmap: &Array3<f32>, pos: Vec2) -> (Vec2, f32) {
+    let coord = pos.as_ivec2();
+    let p = pos - pos.floor();
+
+    let height_nw = terrain_height(heightmap, coord);
+    let height_ne = terrain_height(heightmap, coord + ivec2(1, 0));
+    let height_sw = terrain_height(heightmap, coord + ivec2(0, 1));
+    let height_se = terrain_height(heightmap, coord + ivec2(1, 1));
+
+    let gradient_x = (height_ne - height_nw) * (1. - p.y) + (height_se - height_sw) * p.y;
+    let gradient_y = (height_sw - height_nw) * (1. - p.x) + (height_se - height_ne) * p.x;
+    let height = height_nw * (1. - p.x) * (1. - p.y) + height_ne * p.x * (1. - p.y) + height_sw * (1. - p.x) * p.y + height_se * p.x * p.y;
+
+    (vec2(gradient_x, gradient_y), height)
+}
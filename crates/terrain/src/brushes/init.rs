@@ -2,16 +2,17 @@ use std::{borrow::Cow, sync::Arc};
 
 use ambient_gpu::{gpu::Gpu, texture::Texture, wgsl_utils::wgsl_interpolate};
 use ambient_std::include_file;
-use glam::{vec2, Vec2};
-use itertools::Itertools;
-use rand::prelude::*;
-use rand_pcg::Pcg64;
+use glam::{vec2, vec3, vec4, Vec2, Vec3};
+use ndarray::Array3;
 use wgpu::{
     util::DeviceExt, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BufferBindingType, ShaderStages, TextureFormat,
     TextureViewDimension,
 };
 
-use crate::wgsl_terrain_preprocess;
+use super::{load, mix, noise_offsets, store, texels};
+use crate::{wgsl_terrain_preprocess, TerrainLayers};
+
+const INIT_GROUND_OCTAVES: usize = 9;
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct InitGroundConfig {
@@ -91,9 +92,7 @@ impl InitGroundBrush {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let mut rng = Pcg64::seed_from_u64(config.seed);
-        let octaves = 9;
-        let offsets = (0..octaves).map(|_| vec2(rng.gen::<f32>() * 1000., rng.gen::<f32>() * 1000.)).collect_vec();
+        let offsets = noise_offsets(config.seed, INIT_GROUND_OCTAVES);
 
         let offsets = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Offsets"),
@@ -121,3 +120,56 @@ impl InitGroundBrush {
         cpass.dispatch_workgroups(heightmap.size.width, heightmap.size.height, 1);
     }
 }
+
+/// Runs [InitGroundBrush] on `heightmap`. Nothing runs either of them automatically; they're for tools that generate
+/// the ground procedurally.
+pub fn init_ground_cpu(heightmap: &mut Array3<f32>, config: &InitGroundConfig) {
+    let offsets = noise_offsets(config.seed, INIT_GROUND_OCTAVES);
+    let (_, height, width) = heightmap.dim();
+    let size = vec2(width as f32, height as f32);
+    for texel in texels(heightmap) {
+        let p = texel.as_vec2() * config.params.heightmap_world_size / (size - 1.) + config.params.heightmap_world_position;
+        for (layer, p) in [(TerrainLayers::Rock, p), (TerrainLayers::Soil, p + vec2(2000., 2000.))] {
+            let mut height = load(heightmap, texel, layer as usize);
+            let mut max_height = 0.;
+            let mut amplitude = 1.;
+            let mut scale = 5. / 256.;
+            for offset in &offsets {
+                height += amplitude * snoise2d(p * scale + *offset);
+                max_height += amplitude;
+                scale *= 2.;
+                amplitude *= 0.4;
+            }
+            let height = mix(0., 1., (height + max_height) / (2. * max_height)) + 20.;
+            store(heightmap, texel, layer as usize, height);
+        }
+    }
+}
+
+/// Simplex 2D noise, like `snoise2d` in snoise.wgsl.
+#[allow(clippy::excessive_precision)]
+fn snoise2d(v: Vec2) -> f32 {
+    fn permute(x: Vec3) -> Vec3 {
+        ((x * 34. + 1.) * x) % 289.
+    }
+    fn fract(x: Vec3) -> Vec3 {
+        x - x.floor()
+    }
+    let c = vec4(0.211324865405187, 0.366025403784439, -0.577350269189626, 0.024390243902439);
+    let i = (v + v.dot(Vec2::splat(c.y))).floor();
+    let x0 = v - i + i.dot(Vec2::splat(c.x));
+    let i1 = if x0.x > x0.y { vec2(1., 0.) } else { vec2(0., 1.) };
+    let x12 = vec4(x0.x + c.x - i1.x, x0.y + c.x - i1.y, x0.x + c.z, x0.y + c.z);
+    let i = i % 289.;
+    let p = permute(permute(Vec3::splat(i.y) + vec3(0., i1.y, 1.)) + i.x + vec3(0., i1.x, 1.));
+    let mut m = (Vec3::splat(0.5) - vec3(x0.dot(x0), x12.x * x12.x + x12.y * x12.y, x12.z * x12.z + x12.w * x12.w)).max(Vec3::ZERO);
+    m = m * m;
+    m = m * m;
+    let x = 2. * fract(p * c.w) - 1.;
+    let h = x.abs() - 0.5;
+    let ox = (x + 0.5).floor();
+    let a0 = x - ox;
+    m *= 1.79284291400159 - 0.85373472095314 * (a0 * a0 + h * h);
+    let g = vec3(a0.x * x0.x + h.x * x0.y, a0.y * x12.x + h.y * x12.y, a0.z * x12.z + h.z * x12.w);
+    130. * m.dot(g)
+}
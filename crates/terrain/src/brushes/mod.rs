@@ -6,12 +6,15 @@ use ambient_ecs::{EntityId, World};
 use ambient_gpu::{gpu::GpuKey, std_assets::PixelTextureViewKey, texture::Texture};
 use ambient_network::ServerWorldExt;
 use ambient_std::asset_cache::{AssetCache, AsyncAssetKey, SyncAssetKeyExt};
-use glam::{ivec2, IVec2, UVec2, Vec2, Vec3, Vec3Swizzles};
+use glam::{ivec2, vec2, IVec2, UVec2, Vec2, Vec3, Vec3Swizzles};
+use rand::prelude::*;
+use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
 
 use super::{gather_terrain_cells, spread_terrain_cells, TerrainSize, TerrainStateCpu, TERRAIN_LAYERS};
 use crate::{get_terrain_cell, spawn_terrain, terrain_cell_needs_cpu_download, terrain_cell_version, terrain_state};
 
+mod cpu;
 mod flatten;
 mod hydraulic_erosion;
 mod init;
@@ -21,6 +24,8 @@ mod thermal_erosion;
 mod water_sim;
 
 use async_trait::async_trait;
+pub use cpu::CpuTerrainBrush;
+pub(crate) use cpu::*;
 pub use flatten::*;
 pub use hydraulic_erosion::*;
 pub use init::*;
@@ -94,6 +99,35 @@ impl Default for BrushWGSL {
         Self { center: Vec2::ZERO, radius: 1., shape: BrushShape::Circle, smoothness: 1., amplitude: 0., _padding: Default::default() }
     }
 }
+impl BrushWGSL {
+    /// The strength of the brush at `world_position`, like `get_brush_strength` in brush.wgsl.
+    pub fn strength_at(&self, world_position: Vec2) -> f32 {
+        let d = match self.shape {
+            BrushShape::Circle => (self.center - world_position).length(),
+            BrushShape::Square => {
+                let d = (world_position - self.center).abs();
+                d.x.max(d.y)
+            }
+        };
+        smoothstep_power(self.radius, 0., d.min(self.radius), self.smoothness) * self.amplitude
+    }
+}
+
+/// Random offsets into the noise of the brushes, one per octave.
+fn noise_offsets(seed: u64, count: usize) -> Vec<Vec2> {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    (0..count).map(|_| vec2(rng.gen::<f32>() * 1000., rng.gen::<f32>() * 1000.)).collect()
+}
+
+/// The cells a stroke changes, and the heightmap they're gathered into.
+struct BrushArea {
+    top_left_cell: IVec2,
+    cells_size: UVec2,
+    texture_size: UVec2,
+    world_position: Vec2,
+    world_size: Vec2,
+    world_texel_size: f32,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TerrainBrushStroke {
@@ -115,6 +149,43 @@ impl TerrainBrushStroke {
         let bottom_right_cell = ((self.center + radius) / terrain.size_in_meters()).ceil().as_ivec2();
         (top_left_cell, bottom_right_cell)
     }
+    fn get_brush_area(&self) -> BrushArea {
+        let terrain = TerrainSize::new();
+        let (top_left_cell, bottom_right_cell) = self.get_brush_cells();
+        let cells_size = (bottom_right_cell - top_left_cell).max(IVec2::ONE).as_uvec2();
+        let texture_size = cells_size * (terrain.texture_size() - 1) as u32 + 1;
+        let world_size = cells_size.as_vec2() * terrain.size_in_meters();
+        BrushArea {
+            top_left_cell,
+            cells_size,
+            texture_size,
+            world_position: top_left_cell.as_vec2() * terrain.size_in_meters(),
+            world_size,
+            world_texel_size: world_size.x / (texture_size.x as f32 - 1.),
+        }
+    }
+    fn brush_wgsl(&self, amplitude: f32) -> BrushWGSL {
+        BrushWGSL {
+            center: self.center,
+            radius: self.brush_size.radius(),
+            shape: self.brush_shape,
+            amplitude,
+            smoothness: self.brush_smoothness.0,
+            _padding: Default::default(),
+        }
+    }
+    fn erosion_config(&self, area: &BrushArea) -> HydraulicErosionConfig {
+        let mut config = self.erosion.clone();
+        // config.drops_per_m2 = match brush_strength {
+        //     BrushStrength::Small => 0.01,
+        //     BrushStrength::Medium => 0.1,
+        //     BrushStrength::Large => 1.4,
+        // };
+        config.params.heightmap_size = area.texture_size.as_ivec2();
+        config.brush_radius = self.brush_size.radius();
+        config.brush_position = self.center - area.world_position;
+        config
+    }
     pub fn ensure_cells_exist(&self, world: &mut World) {
         let (top_left_cell, bottom_right_cell) = self.get_brush_cells();
         for y in top_left_cell.y..bottom_right_cell.y {
@@ -160,12 +231,37 @@ pub struct TerrainBrushKey;
 #[async_trait]
 impl AsyncAssetKey<Arc<TerrainBrush>> for TerrainBrushKey {
     async fn load(self, assets: AssetCache) -> Arc<TerrainBrush> {
-        Arc::new(TerrainBrush::new(assets).await)
+        // Headless servers have no GPU to run the compute shaders on
+        if GpuKey.exists(&assets) {
+            Arc::new(TerrainBrush::Gpu(GpuTerrainBrush::new(assets).await))
+        } else {
+            Arc::new(TerrainBrush::Cpu(CpuTerrainBrush::new(assets)))
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum TerrainBrush {
+    Gpu(GpuTerrainBrush),
+    Cpu(CpuTerrainBrush),
+}
+impl TerrainBrush {
+    /// Loads what's needed to [apply](Self::apply) `strokes`.
+    pub async fn prepare(&self, strokes: &[TerrainBrushStroke]) {
+        if let TerrainBrush::Cpu(brush) = self {
+            brush.prepare(strokes).await;
+        }
+    }
+    pub fn apply(&self, world: &mut World, stroke: TerrainBrushStroke) -> Vec<EntityId> {
+        match self {
+            TerrainBrush::Gpu(brush) => brush.apply(world, stroke),
+            TerrainBrush::Cpu(brush) => brush.apply(world, stroke),
+        }
     }
 }
 
 #[derive(Clone, Debug)]
-pub struct TerrainBrush {
+pub struct GpuTerrainBrush {
     brush_raise_lower: Arc<RaiseBrush>,
     normals: Arc<NormalmapFromHeightmapCompute>,
     frame: Arc<AtomicI32>,
@@ -173,7 +269,7 @@ pub struct TerrainBrush {
     intermediate_normalmap: Arc<Texture>,
 }
 
-impl TerrainBrush {
+impl GpuTerrainBrush {
     pub async fn new(assets: AssetCache) -> Self {
         let max_brush_size = 3000;
         let gpu = GpuKey.get(&assets);
@@ -218,35 +314,19 @@ impl TerrainBrush {
         let map_globals = world.persisted_resource_entity().unwrap();
         let seed = world.get(map_globals, map_seed()).unwrap();
 
-        let (top_left_cell, bottom_right_cell) = stroke.get_brush_cells();
-        let TerrainBrushStroke { center, layer, brush, brush_size, brush_strength, brush_smoothness, brush_shape, start_position, erosion } =
-            stroke;
+        let area = stroke.get_brush_area();
+        let BrushArea { top_left_cell, cells_size, texture_size, .. } = area;
         let gpu = world.resource(gpu()).clone();
         let mut encoder = gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        let terrain = TerrainSize::new();
-        let mut cells_size = bottom_right_cell - top_left_cell;
-        cells_size.x = cells_size.x.max(1);
-        cells_size.y = cells_size.y.max(1);
-        let cells_size = cells_size.as_uvec2();
-        let texture_size = cells_size * (terrain.texture_size() - 1) as u32 + 1;
-        let heightmap_world_size = cells_size.as_vec2() * terrain.size_in_meters();
-        let heightmap_world_texel_size = heightmap_world_size.x / (texture_size.x as f32 - 1.);
         gather_terrain_cells(world, &mut encoder, &self.intermediate_heightmap, top_left_cell, cells_size);
-        match brush {
+        match stroke.brush {
             Brush::Raise | Brush::Lower => {
-                let amount = brush_strength.strength();
+                let amount = stroke.brush_strength.strength();
                 let params = RaiseBrushParams {
-                    heightmap_world_position: top_left_cell.as_vec2() * terrain.size_in_meters(),
-                    heightmap_world_texel_size,
-                    brush: BrushWGSL {
-                        center,
-                        radius: brush_size.radius(),
-                        shape: brush_shape,
-                        amplitude: if brush == Brush::Raise { amount } else { -amount },
-                        smoothness: brush_smoothness.0,
-                        _padding: Default::default(),
-                    },
-                    layer: layer as i32,
+                    heightmap_world_position: area.world_position,
+                    heightmap_world_texel_size: area.world_texel_size,
+                    brush: stroke.brush_wgsl(if stroke.brush == Brush::Raise { amount } else { -amount }),
+                    layer: stroke.layer as i32,
                     ..Default::default()
                 };
                 self.brush_raise_lower.run(
@@ -260,23 +340,16 @@ impl TerrainBrush {
             Brush::Flatten => {
                 let brush = FlattenBrush::new(&gpu);
                 let mut start_heightmap = PixelTextureViewKey::white().get(world.resource(asset_cache()));
-                let (start_cell, start_texel) = TerrainSize::new().cell_and_texel_from_position(start_position.xy());
+                let (start_cell, start_texel) = TerrainSize::new().cell_and_texel_from_position(stroke.start_position.xy());
                 if let Some(id) = get_terrain_cell(world, start_cell) {
                     if let Ok(state) = world.get_ref(id, terrain_state()) {
                         start_heightmap = Arc::new(state.heightmap.create_view(&Default::default()));
                     }
                 }
                 let params = FlattenBrushParams {
-                    heightmap_world_position: top_left_cell.as_vec2() * terrain.size_in_meters(),
-                    heightmap_world_texel_size,
-                    brush: BrushWGSL {
-                        center,
-                        radius: brush_size.radius(),
-                        shape: brush_shape,
-                        amplitude: brush_strength.strength(),
-                        smoothness: brush_smoothness.0,
-                        _padding: Default::default(),
-                    },
+                    heightmap_world_position: area.world_position,
+                    heightmap_world_texel_size: area.world_texel_size,
+                    brush: stroke.brush_wgsl(stroke.brush_strength.strength()),
                     start_texel,
                     _padding: Default::default(),
                 };
@@ -290,15 +363,7 @@ impl TerrainBrush {
                 );
             }
             Brush::Erode => {
-                let mut config = erosion;
-                // config.drops_per_m2 = match brush_strength {
-                //     BrushStrength::Small => 0.01,
-                //     BrushStrength::Medium => 0.1,
-                //     BrushStrength::Large => 1.4,
-                // };
-                config.params.heightmap_size = texture_size.as_ivec2();
-                config.brush_radius = brush_size.radius();
-                config.brush_position = center - top_left_cell.as_vec2() * terrain.size_in_meters();
+                let config = stroke.erosion_config(&area);
                 let brush = HydraulicErosionCompute::new(&gpu);
                 brush.run(&gpu, &mut encoder, &self.intermediate_heightmap.create_view(&Default::default()), texture_size, &config);
 
@@ -327,11 +392,11 @@ impl TerrainBrush {
                 let brush = ThermalErosionCompute::new(&gpu);
                 let config = ThermalErosionConfig {
                     params: ThermalErosionParams {
-                        heightmap_world_position: top_left_cell.as_vec2() * terrain.size_in_meters(),
-                        heightmap_world_size: cells_size.as_vec2() * terrain.size_in_meters(),
+                        heightmap_world_position: area.world_position,
+                        heightmap_world_size: area.world_size,
                         heightmap_texture_size: texture_size.as_ivec2(),
-                        brush_position: center,
-                        brush_radius: brush_size.radius(),
+                        brush_position: stroke.center,
+                        brush_radius: stroke.brush_size.radius(),
                         frame: self.frame.fetch_add(1, std::sync::atomic::Ordering::SeqCst),
                         _padding: Default::default(),
                     },
@@ -3,7 +3,9 @@ use std::borrow::Cow;
 use ambient_gpu::{gpu::Gpu, texture::TextureView};
 use ambient_std::include_file;
 use glam::UVec2;
+use ndarray::Array3;
 
+use super::{terrain_normal, texels};
 use crate::wgsl_terrain_preprocess;
 
 #[derive(Debug)]
@@ -70,3 +72,16 @@ impl NormalmapFromHeightmapCompute {
         cpass.dispatch_workgroups(size.x, size.y, 1);
     }
 }
+
+/// Runs [NormalmapFromHeightmapCompute] on `heightmap`. The normalmap is indexed by `[y, x, component]`.
+pub fn normalmap_cpu(heightmap: &Array3<f32>) -> Array3<f32> {
+    let (_, height, width) = heightmap.dim();
+    let mut normalmap = Array3::zeros((height, width, 4));
+    for texel in texels(heightmap) {
+        let normal = terrain_normal(heightmap, texel);
+        for (c, value) in normal.to_array().into_iter().enumerate() {
+            normalmap[(texel.y as usize, texel.x as usize, c)] = value;
+        }
+    }
+    normalmap
+}
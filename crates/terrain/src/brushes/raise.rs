@@ -13,16 +13,26 @@ use ambient_std::{
     include_file,
 };
 use glam::{vec2, UVec2, Vec2};
-use itertools::Itertools;
-use rand::prelude::*;
-use rand_pcg::Pcg64;
+use image::RgbaImage;
+use ndarray::Array3;
 use wgpu::{
     util::DeviceExt, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BufferBindingType, ShaderStages, TextureFormat,
     TextureViewDimension,
 };
 
-use super::BrushWGSL;
-use crate::{wgsl_terrain_preprocess, OLD_CONTENT_SERVER_URL};
+use super::{load, mix, noise_offsets, smoothstep, store, texels, BrushWGSL};
+use crate::{wgsl_terrain_preprocess, TerrainLayers, OLD_CONTENT_SERVER_URL};
+
+const RAISE_NOISE_OFFSETS: usize = 12;
+
+/// The noise the raise brush varies its strength and the hardness of the terrain with.
+pub(crate) fn raise_noise_url() -> AbsAssetUrl {
+    AbsAssetUrl::parse(format!(
+        "{OLD_CONTENT_SERVER_URL}assets/models/{}",
+        "ArtStationSurfaces/VFX-HQ-Seamless-Noise-Pack-Vol1/Noise_002.png"
+    ))
+    .unwrap()
+}
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct RaiseBrushConfig {
@@ -132,17 +142,7 @@ impl RaiseBrush {
         });
         Self {
             pipeline,
-            noise_texture: TextureFromUrl {
-                url: AbsAssetUrl::parse(format!(
-                    "{OLD_CONTENT_SERVER_URL}assets/models/{}",
-                    "ArtStationSurfaces/VFX-HQ-Seamless-Noise-Pack-Vol1/Noise_002.png"
-                ))
-                .unwrap(),
-                format: wgpu::TextureFormat::Rgba8Unorm,
-            }
-            .get(&assets)
-            .await
-            .unwrap(),
+            noise_texture: TextureFromUrl { url: raise_noise_url(), format: wgpu::TextureFormat::Rgba8Unorm }.get(&assets).await.unwrap(),
             noise_sampler: DefaultSamplerKey.get(&assets),
         }
     }
@@ -153,8 +153,7 @@ impl RaiseBrush {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let mut rng = Pcg64::seed_from_u64(config.seed);
-        let offsets = (0..12).map(|_| vec2(rng.gen::<f32>() * 1000., rng.gen::<f32>() * 1000.)).collect_vec();
+        let offsets = noise_offsets(config.seed, RAISE_NOISE_OFFSETS);
 
         let offsets = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Offsets"),
@@ -184,3 +183,33 @@ impl RaiseBrush {
         cpass.dispatch_workgroups(size.x, size.y, 1);
     }
 }
+
+/// Runs [RaiseBrush] on `heightmap`, with `noise` being the image at [raise_noise_url].
+pub fn raise_cpu(heightmap: &mut Array3<f32>, noise: &RgbaImage, config: &RaiseBrushConfig) {
+    let params = &config.params;
+    let offsets = noise_offsets(config.seed, RAISE_NOISE_OFFSETS);
+    let sample = |i: usize, p: Vec2| sample_noise(noise, offsets[i] + p);
+    for texel in texels(heightmap) {
+        let p = texel.as_vec2() * params.heightmap_world_texel_size + params.heightmap_world_position;
+        let mut height = load(heightmap, texel, params.layer as usize);
+        let brush_strength = params.brush.strength_at(p);
+
+        let p = p * 0.01;
+        height = (height + brush_strength * mix(0.5, 1., sample(0, p * 0.1))).max(0.);
+        store(heightmap, texel, params.layer as usize, height);
+
+        store(heightmap, texel, TerrainLayers::Hardness as usize, sample(1, p * 0.02));
+        store(heightmap, texel, TerrainLayers::HardnessStrataAmount as usize, smoothstep(0.4, 0.7, sample(2, p * 0.01)));
+        store(heightmap, texel, TerrainLayers::HardnessStrataWavelength as usize, mix(60., 200., sample(3, p * 0.05)));
+    }
+}
+
+/// Samples the red channel of `noise` at `uv` with linear filtering, repeating the image.
+fn sample_noise(noise: &RgbaImage, uv: Vec2) -> f32 {
+    let (width, height) = noise.dimensions();
+    let p = uv * vec2(width as f32, height as f32) - 0.5;
+    let t = p - p.floor();
+    let (x, y) = (p.x.floor() as i64, p.y.floor() as i64);
+    let texel = |x: i64, y: i64| noise.get_pixel(x.rem_euclid(width as i64) as u32, y.rem_euclid(height as i64) as u32)[0] as f32 / 255.;
+    mix(mix(texel(x, y), texel(x + 1, y), t.x), mix(texel(x, y + 1), texel(x + 1, y + 1), t.x), t.y)
+}
@@ -2,11 +2,13 @@ use std::borrow::Cow;
 
 use ambient_gpu::{gpu::Gpu, texture::TextureView};
 use ambient_std::include_file;
-use glam::{IVec2, UVec2, Vec2};
+use glam::{ivec2, IVec2, UVec2, Vec2};
+use ndarray::Array3;
 use serde::{Deserialize, Serialize};
 use wgpu::util::DeviceExt;
 
-use crate::wgsl_terrain_preprocess;
+use super::{get_hardness, load, mix, smoothstep, store, texels};
+use crate::{wgsl_terrain_preprocess, TerrainLayers, TERRAIN_BASE};
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct ThermalErosionConfig {
@@ -96,3 +98,63 @@ impl ThermalErosionCompute {
         cpass.dispatch_workgroups(size.x, size.y, 1);
     }
 }
+
+/// Runs [ThermalErosionCompute] on `heightmap`. All texels read the heightmap from before the brush ran, whereas on the
+/// GPU some of them see the changes of their neighbours.
+pub fn thermal_erosion_cpu(heightmap: &mut Array3<f32>, params: &ThermalErosionParams) {
+    let source = heightmap.clone();
+    let size = params.heightmap_texture_size.as_vec2();
+    let dir = erosion_direction(params.frame);
+    let (rock, soil) = (TerrainLayers::Rock as usize, TerrainLayers::Soil as usize);
+    let sample = |texel: IVec2| (load(&source, texel, rock), load(&source, texel, soil));
+
+    for texel in texels(&source) {
+        let t = texel.as_vec2();
+        let border_dist = t.x.min(t.y).min((size.x - 1. - t.x).min(size.y - 1. - t.y));
+        let brush_strength = smoothstep(0., 50., (border_dist - 1.).max(0.));
+
+        let (local_rock, local_soil) = sample(texel);
+        let (neighbor_rock, neighbor_soil) = sample(texel + dir);
+        let (local_depth, neighbor_depth) = (local_rock + local_soil, neighbor_rock + neighbor_soil);
+
+        if local_soil > 0.1 {
+            let value = thermal_erode(local_depth, neighbor_depth, local_soil, neighbor_soil, deg_to_rad(10.), brush_strength);
+            store(heightmap, texel, soil, value);
+        } else {
+            let hardness = get_hardness(&source, texel, local_depth + TERRAIN_BASE);
+            let viscosity = mix(deg_to_rad(89.), deg_to_rad(45.), hardness);
+            let value = thermal_erode(local_depth, neighbor_depth, local_rock, neighbor_rock, viscosity, brush_strength);
+            store(heightmap, texel, rock, value);
+        }
+    }
+}
+
+/// The neighbour that material moves to or from in `frame`, like in thermal_erosion.wgsl.
+pub(crate) fn erosion_direction(frame: i32) -> IVec2 {
+    match frame % 4 {
+        0 => ivec2(-1, 0),
+        1 => ivec2(1, 0),
+        2 => ivec2(0, -1),
+        3 => ivec2(0, 1),
+        _ => IVec2::ZERO,
+    }
+}
+
+fn thermal_erode(local_depth: f32, neighbor_depth: f32, local: f32, neighbor: f32, viscosity: f32, brush_strength: f32) -> f32 {
+    let d = local_depth - neighbor_depth;
+    if d.abs().atan() > viscosity {
+        if d > 0. {
+            local - (brush_strength * d / 4.).min(local)
+        } else {
+            local + (brush_strength * -d / 4.).min(neighbor)
+        }
+    } else {
+        local
+    }
+}
+
+// The same approximation of pi as the shader
+#[allow(clippy::approx_constant)]
+fn deg_to_rad(deg: f32) -> f32 {
+    deg * 3.14159 / 180.
+}
@@ -3,11 +3,13 @@ use std::borrow::Cow;
 use ambient_editor_derive::ElementEditor;
 use ambient_gpu::{gpu::Gpu, texture::TextureView};
 use ambient_std::include_file;
-use glam::UVec2;
+use glam::{ivec2, vec2, IVec2, UVec2, Vec2};
+use ndarray::Array3;
 use serde::{Deserialize, Serialize};
 use wgpu::util::DeviceExt;
 
-use crate::wgsl_terrain_preprocess;
+use super::{erosion_direction, load, store, terrain_normal, texels};
+use crate::{wgsl_terrain_preprocess, TerrainLayers};
 
 #[derive(Clone, Debug, Serialize, Deserialize, ElementEditor, Default)]
 pub struct WaterSimConfig {
@@ -112,3 +114,116 @@ impl WaterSimCompute {
         }
     }
 }
+
+/// The time step of [WaterSimCompute].
+const D_T: f32 = 0.02;
+
+/// Runs the passes of [WaterSimCompute] on `heightmap`. Each pass reads the heightmap from before it ran, whereas on the
+/// GPU some texels see the changes of their neighbours.
+pub fn water_sim_cpu(heightmap: &mut Array3<f32>, config: &WaterSimConfig) {
+    use TerrainLayers::*;
+    let params = &config.params;
+    let water_height = |heightmap: &Array3<f32>, texel: IVec2| {
+        load(heightmap, texel, Rock as usize) + load(heightmap, texel, Soil as usize) + load(heightmap, texel, Water as usize)
+    };
+    let velocity = |heightmap: &Array3<f32>, texel: IVec2| {
+        vec2(load(heightmap, texel, WaterVelocityX as usize), load(heightmap, texel, WaterVelocityY as usize))
+    };
+
+    // Rain
+    for texel in texels(heightmap) {
+        let water = load(heightmap, texel, Water as usize) + D_T * 0.012;
+        store(heightmap, texel, Water as usize, water);
+    }
+
+    // Flux
+    let source = heightmap.clone();
+    for texel in texels(&source) {
+        let cell_height = water_height(&source, texel);
+        let water = load(&source, texel, Water as usize);
+        let flux =
+            [(ivec2(-1, 0), WaterOutflowL), (ivec2(1, 0), WaterOutflowR), (ivec2(0, -1), WaterOutflowT), (ivec2(0, 1), WaterOutflowB)].map(
+                |(delta, layer)| {
+                    let h = (cell_height - water_height(&source, texel + delta)).clamp(-2., 2.);
+                    (layer, (load(&source, texel, layer as usize) + D_T * params.gravity * h).max(0.))
+                },
+            );
+        let total = flux.iter().map(|(_, f)| f).sum::<f32>();
+        let k = if total == 0. { 1. } else { (water / (total * D_T)).max(1.) };
+        for (layer, f) in flux {
+            store(heightmap, texel, layer as usize, k * f);
+        }
+    }
+
+    // Update water
+    let source = heightmap.clone();
+    for texel in texels(&source) {
+        let f_l_to_r = load(&source, texel + ivec2(-1, 0), WaterOutflowR as usize);
+        let f_r_to_l = load(&source, texel + ivec2(1, 0), WaterOutflowL as usize);
+        let f_t_to_b = load(&source, texel + ivec2(0, -1), WaterOutflowB as usize);
+        let f_b_to_t = load(&source, texel + ivec2(0, 1), WaterOutflowT as usize);
+
+        let f_l = load(&source, texel, WaterOutflowL as usize);
+        let f_r = load(&source, texel, WaterOutflowR as usize);
+        let f_b = load(&source, texel, WaterOutflowB as usize);
+        let f_t = load(&source, texel, WaterOutflowT as usize);
+
+        let water_change = D_T * (f_l_to_r + f_r_to_l + f_t_to_b + f_b_to_t - (f_l + f_r + f_t + f_b));
+        let water = (load(&source, texel, Water as usize) + water_change).max(0.);
+        let evaporation = 0.015;
+        store(heightmap, texel, Water as usize, water * (1. - evaporation * D_T));
+
+        store(heightmap, texel, WaterVelocityX as usize, 0.5 * (f_l_to_r - f_l + f_r - f_r_to_l));
+        store(heightmap, texel, WaterVelocityY as usize, 0.5 * (f_t_to_b - f_t + f_b - f_b_to_t));
+    }
+
+    // Water erosion
+    let source = heightmap.clone();
+    for texel in texels(&source) {
+        let mut rock = load(&source, texel, Rock as usize);
+        let mut sediment = load(&source, texel, Sediment as usize);
+        let carrying_capacity = (1. - terrain_normal(&source, texel).z) * velocity(&source, texel).length();
+        if sediment < carrying_capacity {
+            let rock_to_sediment = D_T * 0.01 * (carrying_capacity - sediment);
+            rock -= rock_to_sediment;
+            sediment += rock_to_sediment;
+        } else {
+            let deposit = D_T * (sediment - carrying_capacity);
+            rock += deposit;
+            sediment -= deposit;
+        }
+        store(heightmap, texel, Rock as usize, rock);
+        store(heightmap, texel, Sediment as usize, sediment);
+    }
+
+    // Sediment movement
+    let source = heightmap.clone();
+    for texel in texels(&source) {
+        let sediment = bilinear_sample(&source, texel.as_vec2() - D_T * velocity(&source, texel), Sediment as usize);
+        store(heightmap, texel, Sediment as usize, sediment);
+    }
+
+    // Thermal erosion
+    let source = heightmap.clone();
+    let dir = erosion_direction(params.frame);
+    for texel in texels(&source) {
+        let mut rock = load(&source, texel, Rock as usize);
+        let d = rock - load(&source, texel + dir, Rock as usize);
+        if d.abs() > 0.1 {
+            rock -= d / 4.;
+        }
+        store(heightmap, texel, Rock as usize, rock);
+    }
+}
+
+fn bilinear_sample(heightmap: &Array3<f32>, pos: Vec2, layer: usize) -> f32 {
+    let coord = pos.as_ivec2();
+    let p = pos - pos.floor();
+
+    let height_nw = load(heightmap, coord, layer);
+    let height_ne = load(heightmap, coord + ivec2(1, 0), layer);
+    let height_sw = load(heightmap, coord + ivec2(0, 1), layer);
+    let height_se = load(heightmap, coord + ivec2(1, 1), layer);
+
+    height_nw * (1. - p.x) * (1. - p.y) + height_ne * p.x * (1. - p.y) + height_sw * (1. - p.x) * p.y + height_se * p.x * p.y
+}
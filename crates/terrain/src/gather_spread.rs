@@ -1,10 +1,13 @@
+use std::sync::Arc;
+
 use ambient_app::gpu;
 use ambient_core::asset_cache;
 use ambient_ecs::{EntityId, World};
 use ambient_gpu::texture::Texture;
 use glam::{ivec2, IVec2, UVec2};
+use ndarray::{s, Array3};
 
-use crate::{get_terrain_cell, terrain_state};
+use crate::{get_terrain_cell, terrain_state, terrain_state_cpu, TerrainSize, TerrainStateCpu, TERRAIN_LAYERS};
 
 pub fn gather_terrain_cells(world: &World, encoder: &mut wgpu::CommandEncoder, terrain_map: &Texture, top_left_cell: IVec2, cells: UVec2) {
     for y in 0..cells.y as i32 {
@@ -107,3 +110,61 @@ pub fn spread_terrain_cells(
     }
     changed_cells
 }
+
+/// Gathers the heightmaps of the cells into a single heightmap, like [gather_terrain_cells] does on the GPU. Texels of
+/// missing cells are zero.
+pub fn gather_terrain_cells_cpu(world: &World, top_left_cell: IVec2, cells: UVec2) -> Array3<f32> {
+    let stride = TerrainSize::new().texture_size() - 1;
+    let mut heightmap = Array3::zeros((TERRAIN_LAYERS as usize, cells.y as usize * stride + 1, cells.x as usize * stride + 1));
+    for y in 0..cells.y as usize {
+        for x in 0..cells.x as usize {
+            if let Some(id) = get_terrain_cell(world, top_left_cell + ivec2(x as i32, y as i32)) {
+                if let Ok(state) = world.get_ref(id, terrain_state_cpu()) {
+                    let (oy, ox) = (y * stride, x * stride);
+                    heightmap.slice_mut(s![.., oy..oy + stride + 1, ox..ox + stride + 1]).assign(&state.heightmap);
+                }
+            }
+        }
+    }
+    heightmap
+}
+
+/// Spreads the heightmap and normalmap onto the [TerrainStateCpu] of each cell, like [spread_terrain_cells] does on the
+/// GPU. The state on the GPU of the cells, if any, is removed so that it's created again.
+pub fn spread_terrain_cells_cpu(
+    world: &mut World,
+    heightmap: &Array3<f32>,
+    normalmap: &Array3<f32>,
+    top_left_cell: IVec2,
+    cells: UVec2,
+) -> Vec<EntityId> {
+    let mut changed_cells = Vec::new();
+    for y in 0..cells.y as usize {
+        for x in 0..cells.x as usize {
+            if let Some(id) = get_terrain_cell(world, top_left_cell + ivec2(x as i32, y as i32)) {
+                if let Ok(state) = world.get_ref(id, terrain_state_cpu()) {
+                    let stride = state.size.texture_size() - 1;
+                    let (oy, ox) = (y * stride, x * stride);
+                    let mut state = TerrainStateCpu::clone(state);
+                    state.heightmap.assign(&heightmap.slice(s![.., oy..oy + stride + 1, ox..ox + stride + 1]));
+
+                    // Note: we're not copying the border cells, because those normals will be wrong
+                    let lx = if x == 0 { 1 } else { 0 };
+                    let ly = if y == 0 { 1 } else { 0 };
+                    let rx = if x >= cells.x as usize - 1 { 1 } else { 0 };
+                    let ry = if y >= cells.y as usize - 1 { 1 } else { 0 };
+                    state.normalmap.slice_mut(s![ly..stride + 1 - ry, lx..stride + 1 - rx, ..]).assign(&normalmap.slice(s![
+                        oy + ly..oy + stride + 1 - ry,
+                        ox + lx..ox + stride + 1 - rx,
+                        ..
+                    ]));
+
+                    world.set(id, terrain_state_cpu(), Arc::new(state)).unwrap();
+                    world.remove_component(id, terrain_state()).ok();
+                    changed_cells.push(id);
+                }
+            }
+        }
+    }
+    changed_cells
+}
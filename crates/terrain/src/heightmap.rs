@@ -0,0 +1,224 @@
+//! Conversion between terrain cells and 16-bit heightmaps (and maps of the other terrain layers), as authored in
+//! external terrain tools.
+
+use std::io::Cursor;
+
+use anyhow::Context;
+use glam::{vec3, IVec2};
+use image::{DynamicImage, ImageBuffer, ImageFormat, ImageOutputFormat, Luma};
+use ndarray::{s, Array2, Array3, ArrayView2, Axis};
+
+use crate::{TerrainLayers, TerrainSize, TerrainStateCpu, TERRAIN_LAYERS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightmapFormat {
+    /// A 16-bit grayscale PNG.
+    Png,
+    /// Headerless, square, little-endian 16-bit values, as exported by most terrain tools.
+    Raw16,
+}
+impl HeightmapFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "raw" | "r16" => Some(Self::Raw16),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes a map to an array indexed by `[y, x]`, with values from 0 to 1.
+pub fn decode_map(data: &[u8], format: HeightmapFormat) -> anyhow::Result<Array2<f32>> {
+    let map = match format {
+        HeightmapFormat::Png => {
+            let image = image::load_from_memory_with_format(data, ImageFormat::Png).context("Failed to decode png")?.into_luma16();
+            let (width, height) = image.dimensions();
+            Array2::from_shape_vec((height as usize, width as usize), image.into_raw().into_iter().map(from_u16).collect())?
+        }
+        HeightmapFormat::Raw16 => {
+            let texels = data.len() / 2;
+            let size = (texels as f64).sqrt() as usize;
+            if data.len() % 2 != 0 || size * size != texels {
+                anyhow::bail!("Raw heightmaps must be square, but got {} bytes", data.len());
+            }
+            let values = data.chunks_exact(2).map(|v| from_u16(u16::from_le_bytes([v[0], v[1]]))).collect();
+            Array2::from_shape_vec((size, size), values)?
+        }
+    };
+    if map.is_empty() {
+        anyhow::bail!("The map is empty");
+    }
+    Ok(map)
+}
+
+/// Encodes a map indexed by `[y, x]`. Values are clamped to the range 0 to 1.
+pub fn encode_map(map: ArrayView2<f32>, format: HeightmapFormat) -> anyhow::Result<Vec<u8>> {
+    let values = map.iter().map(|&v| to_u16(v)).collect::<Vec<_>>();
+    match format {
+        HeightmapFormat::Png => {
+            let (height, width) = map.dim();
+            let image = ImageBuffer::<Luma<u16>, _>::from_raw(width as u32, height as u32, values).unwrap();
+            let mut data = Vec::new();
+            DynamicImage::ImageLuma16(image).write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png)?;
+            Ok(data)
+        }
+        HeightmapFormat::Raw16 => {
+            if map.nrows() != map.ncols() {
+                anyhow::bail!("Raw heightmaps must be square, but got {:?}", map.dim());
+            }
+            Ok(values.into_iter().flat_map(|v| v.to_le_bytes()).collect())
+        }
+    }
+}
+
+fn from_u16(value: u16) -> f32 {
+    value as f32 / u16::MAX as f32
+}
+fn to_u16(value: f32) -> u16 {
+    (value.clamp(0., 1.) * u16::MAX as f32).round() as u16
+}
+
+/// Maps values from 0 to 1 to the range `(min, max)`.
+pub fn from_unit(map: ArrayView2<f32>, (min, max): (f32, f32)) -> Array2<f32> {
+    map.mapv(|v| min + v * (max - min))
+}
+/// Maps values in the range `(min, max)` to 0 to 1.
+pub fn to_unit(map: ArrayView2<f32>, (min, max): (f32, f32)) -> Array2<f32> {
+    let extent = (max - min).max(f32::EPSILON);
+    map.mapv(|v| (v - min) / extent)
+}
+/// The smallest and largest values of `map`.
+pub fn value_range(map: ArrayView2<f32>) -> (f32, f32) {
+    map.iter().fold((f32::MAX, f32::MIN), |(min, max), &v| (min.min(v), max.max(v)))
+}
+
+/// Splits maps of terrain layers into cells, with the top left texel of the maps at the origin of `origin_cell`. The
+/// maps are indexed by `[y, x]`, one texel per meter, and are resampled to the size of the first one; layers without
+/// a map are left at zero.
+pub fn cells_from_maps(
+    size: &TerrainSize,
+    origin_cell: IVec2,
+    layers: &[(TerrainLayers, ArrayView2<f32>)],
+) -> Vec<(IVec2, TerrainStateCpu)> {
+    let Some((_, first)) = layers.first() else { return Vec::new() };
+    let dim = first.dim();
+    let mut heightmap = Array3::zeros((TERRAIN_LAYERS as usize, dim.0, dim.1));
+    for (layer, map) in layers {
+        heightmap.index_axis_mut(Axis(0), *layer as usize).assign(&resample(*map, dim));
+    }
+    let heights =
+        &heightmap.index_axis(Axis(0), TerrainLayers::Rock as usize) + &heightmap.index_axis(Axis(0), TerrainLayers::Soil as usize);
+    let normalmap = normalmap_from_heights(heights.view());
+
+    // Neighbouring cells share their edge texels
+    let texture_size = size.texture_size();
+    let stride = texture_size - 1;
+    let cells = |texels: usize| ((texels.max(2) - 1 + stride - 1) / stride) as i32;
+    let clamp = |i: usize, texels: usize| i.min(texels - 1);
+
+    let mut res = Vec::new();
+    for y in 0..cells(dim.0) {
+        for x in 0..cells(dim.1) {
+            let texel = |ty: usize, tx: usize| (clamp(y as usize * stride + ty, dim.0), clamp(x as usize * stride + tx, dim.1));
+            let cell = TerrainStateCpu {
+                size: size.clone(),
+                heightmap: Array3::from_shape_fn((TERRAIN_LAYERS as usize, texture_size, texture_size), |(layer, ty, tx)| {
+                    let (y, x) = texel(ty, tx);
+                    heightmap[(layer, y, x)]
+                }),
+                normalmap: Array3::from_shape_fn((texture_size, texture_size, 4), |(ty, tx, c)| {
+                    let (y, x) = texel(ty, tx);
+                    normalmap[(y, x, c)]
+                }),
+            };
+            res.push((origin_cell + IVec2::new(x, y), cell));
+        }
+    }
+    res
+}
+
+/// Combines the sum of `layers` of `cells` into a single map indexed by `[y, x]`, and returns it along with the cell at
+/// its top left. Texels of missing cells are zero.
+pub fn map_from_cells(cells: &[(IVec2, &TerrainStateCpu)], layers: &[TerrainLayers]) -> Option<(IVec2, Array2<f32>)> {
+    let min = cells.iter().map(|(cell, _)| *cell).reduce(IVec2::min)?;
+    let max = cells.iter().map(|(cell, _)| *cell).reduce(IVec2::max)?;
+    let stride = cells[0].1.size.texture_size() - 1;
+    let count = (max - min + IVec2::ONE).as_uvec2();
+    let mut map = Array2::zeros((count.y as usize * stride + 1, count.x as usize * stride + 1));
+    for (cell, state) in cells {
+        let offset = (*cell - min).as_uvec2() * stride as u32;
+        let (y, x) = (offset.y as usize, offset.x as usize);
+        let mut target = map.slice_mut(s![y..y + stride + 1, x..x + stride + 1]);
+        target.fill(0.);
+        for layer in layers {
+            target += &state.heightmap.slice(s![*layer as usize, ..stride + 1, ..stride + 1]);
+        }
+    }
+    Some((min, map))
+}
+
+/// Computes the normals of a map of terrain heights the same way as the terrain brushes do on the GPU, clamping at the
+/// edges of the map. The result is indexed by `[y, x, component]`.
+pub fn normalmap_from_heights(heights: ArrayView2<f32>) -> Array3<f32> {
+    let (height, width) = heights.dim();
+    let h = |x: i32, y: i32| heights[(y.clamp(0, height as i32 - 1) as usize, x.clamp(0, width as i32 - 1) as usize)];
+    let mut normalmap = Array3::zeros((height, width, 4));
+    for y in 0..height {
+        for x in 0..width {
+            let (tx, ty) = (x as i32, y as i32);
+            let center = h(tx, ty);
+            let up = vec3(0., -1., h(tx, ty - 1) - center);
+            let down = vec3(0., 1., h(tx, ty + 1) - center);
+            let left = vec3(-1., 0., h(tx - 1, ty) - center);
+            let right = vec3(1., 0., h(tx + 1, ty) - center);
+            let normal = (up.cross(right) + down.cross(left)).normalize();
+            for (c, value) in normal.to_array().into_iter().enumerate() {
+                normalmap[(y, x, c)] = value;
+            }
+        }
+    }
+    normalmap
+}
+
+/// Bilinearly resamples `map` to `dim`, aligning the corner texels.
+pub fn resample(map: ArrayView2<f32>, dim: (usize, usize)) -> Array2<f32> {
+    if map.dim() == dim {
+        return map.to_owned();
+    }
+    let (height, width) = map.dim();
+    let scale = |i: usize, to: usize, from: usize| if to > 1 { i as f32 * (from - 1) as f32 / (to - 1) as f32 } else { 0. };
+    Array2::from_shape_fn(dim, |(y, x)| {
+        let (fy, fx) = (scale(y, dim.0, height), scale(x, dim.1, width));
+        let (y0, x0) = (fy.floor() as usize, fx.floor() as usize);
+        let (y1, x1) = ((y0 + 1).min(height - 1), (x0 + 1).min(width - 1));
+        let (ty, tx) = (fy - y0 as f32, fx - x0 as f32);
+        let top = map[(y0, x0)] * (1. - tx) + map[(y0, x1)] * tx;
+        let bottom = map[(y1, x0)] * (1. - tx) + map[(y1, x1)] * tx;
+        top * (1. - ty) + bottom * ty
+    })
+}
+
+#[test]
+fn test_cells_roundtrip() {
+    let heights = Array2::from_shape_fn((100, 150), |(y, x)| (x as f32 * 0.1).sin() * 10. + y as f32 * 0.2);
+    let raw = encode_map(to_unit(heights.slice(s![..100, ..100]), (-10., 30.)).view(), HeightmapFormat::Raw16).unwrap();
+    let decoded = from_unit(decode_map(&raw, HeightmapFormat::Raw16).unwrap().view(), (-10., 30.));
+    assert!(decoded.iter().zip(heights.slice(s![..100, ..100])).all(|(a, b)| (a - b).abs() < 1e-3));
+
+    let cells = cells_from_maps(&TerrainSize::new(), IVec2::new(-1, 2), &[(TerrainLayers::Rock, heights.view())]);
+    assert_eq!(cells.len(), 6);
+    assert_eq!(cells[0].0, IVec2::new(-1, 2));
+    assert_eq!(cells[0].1.heightmap[(TerrainLayers::Rock as usize, 10, 64)], cells[1].1.heightmap[(TerrainLayers::Rock as usize, 10, 0)]);
+
+    let cells = cells.iter().map(|(cell, state)| (*cell, state)).collect::<Vec<_>>();
+    let (origin, map) = map_from_cells(&cells, &[TerrainLayers::Rock, TerrainLayers::Soil]).unwrap();
+    assert_eq!(origin, IVec2::new(-1, 2));
+    assert_eq!(map.slice(s![..100, ..150]), heights);
+}
+
+#[test]
+fn test_decode_empty_map() {
+    assert!(decode_map(&[], HeightmapFormat::Raw16).is_err());
+    let png = encode_map(Array2::zeros((1, 1)).view(), HeightmapFormat::Png).unwrap();
+    assert_eq!(decode_map(&png, HeightmapFormat::Png).unwrap().dim(), (1, 1));
+}
@@ -1,7 +1,9 @@
 use ambient_core::{asset_cache, async_ecs::async_run, runtime, session_start};
-use ambient_ecs::{components, query, SystemGroup};
+use ambient_ecs::{components, query, Component, EntityId, SystemGroup, World};
+use ambient_gpu::gpu::GpuKey;
 use ambient_intent::{intent_applied, intent_reverted, intent_timestamp, use_old_state, IntentRegistry};
-use ambient_std::asset_cache::AsyncAssetKeyExt;
+use ambient_std::asset_cache::{AsyncAssetKeyExt, SyncAssetKeyExt};
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
 
 use crate::brushes::{TerrainBrushKey, TerrainBrushStroke};
@@ -10,6 +12,7 @@ components!("terrain", {
     intent_terrain_stroke: TerrainBrushStroke,
     intent_terrain_stroke_state: (),
     stroke_client_applied: (),
+    stroke_server_applied: (),
 });
 
 pub fn register_intents(reg: &mut IntentRegistry) {
@@ -33,25 +36,49 @@ pub fn terrain_intent_client_system() -> SystemGroup {
             .excl(stroke_client_applied())
             .spawned()
             .to_system(|q, world, qs, _| {
-                let mut strokes =
-                    q.collect_cloned(world, qs).into_iter().filter(|(_, (stroke, _ts))| stroke.cells_exist(world)).collect_vec();
-                if !strokes.is_empty() {
-                    for (id, _) in &strokes {
-                        world.add_component(*id, stroke_client_applied(), ()).unwrap();
-                    }
-                    let session_start = *world.resource(session_start());
-                    strokes.retain(|(_, (_, ts))| *ts > session_start);
-                    let async_run = world.resource(async_run()).clone();
-                    let assets = world.resource(asset_cache()).clone();
-                    world.resource(runtime()).spawn(async move {
-                        let brush = TerrainBrushKey.get(&assets).await;
-                        async_run.run(move |world| {
-                            for (_, (stroke, _)) in strokes {
-                                brush.apply(world, stroke);
-                            }
-                        });
-                    });
+                let strokes = q.collect_cloned(world, qs);
+                apply_strokes(world, strokes, stroke_client_applied());
+            })],
+    )
+}
+
+/// Applies the strokes on the server with the CPU brushes, when the server has no GPU to apply them with.
+pub fn terrain_intent_server_system() -> SystemGroup {
+    SystemGroup::new(
+        "dims/terrain/intent/server",
+        vec![query((intent_terrain_stroke(), intent_timestamp()))
+            .incl(intent_applied())
+            .excl(intent_reverted())
+            .excl(stroke_server_applied())
+            .spawned()
+            .to_system(|q, world, qs, _| {
+                if GpuKey.exists(world.resource(asset_cache())) {
+                    return;
                 }
+                let strokes = q.collect_cloned(world, qs);
+                apply_strokes(world, strokes, stroke_server_applied());
             })],
     )
 }
+
+fn apply_strokes(world: &mut World, strokes: Vec<(EntityId, (TerrainBrushStroke, DateTime<Utc>))>, applied: Component<()>) {
+    let mut strokes = strokes.into_iter().filter(|(_, (stroke, _ts))| stroke.cells_exist(world)).collect_vec();
+    if !strokes.is_empty() {
+        for (id, _) in &strokes {
+            world.add_component(*id, applied, ()).unwrap();
+        }
+        let session_start = *world.resource(session_start());
+        strokes.retain(|(_, (_, ts))| *ts > session_start);
+        let async_run = world.resource(async_run()).clone();
+        let assets = world.resource(asset_cache()).clone();
+        world.resource(runtime()).spawn(async move {
+            let brush = TerrainBrushKey.get(&assets).await;
+            brush.prepare(&strokes.iter().map(|(_, (stroke, _))| stroke.clone()).collect_vec()).await;
+            async_run.run(move |world| {
+                for (_, (stroke, _)) in strokes {
+                    brush.apply(world, stroke);
+                }
+            });
+        });
+    }
+}
@@ -13,7 +13,10 @@ use ambient_core::{
     transform::{local_to_parent, local_to_world, mesh_to_world, rotation, scale, translation},
     FixedTimestepSystem,
 };
-use ambient_ecs::{components, query, Commands, Entity, EntityId, FnSystem, SystemGroup, World};
+use ambient_ecs::{
+    components, generated::components::core::terrain::terrain_cells_from_url, query, Commands, Entity, EntityId, FnSystem, SystemGroup,
+    World,
+};
 use ambient_editor_derive::ElementEditor;
 use ambient_element::{element_tree, render_parented_with_component, Element, ElementComponent, ElementComponentExt, Group, Hooks};
 use ambient_gpu::{
@@ -27,6 +30,7 @@ use ambient_gpu::{
 use ambient_meshes::{GridMesh, GridMeshKey};
 use ambient_physics::{
     collider::{collider_type, ColliderType},
+    helpers::update_shape_layers,
     main_physics_scene,
     physx::{character_controller, physics, physics_shape, rigid_static, Physics},
     PxActorUserData, PxShapeUserData,
@@ -35,11 +39,14 @@ use ambient_renderer::{
     cast_shadows, color, gpu_primitives_lod, gpu_primitives_mesh, lod::cpu_lod, material, primitives, renderer_shader, SharedMaterial,
 };
 use ambient_std::{
-    asset_cache::{Asset, AssetCache, AsyncAssetKeyExt, SyncAssetKey, SyncAssetKeyExt},
+    asset_cache::{Asset, AssetCache, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKey, SyncAssetKeyExt},
     asset_url::AbsAssetUrl,
-    cb, friendly_id, log_result,
+    cb,
+    download_asset::{AssetError, BytesFromUrl},
+    friendly_id, log_result,
     shapes::{Sphere, AABB},
 };
+use async_trait::async_trait;
 use glam::{vec2, vec3, vec4, IVec2, Mat4, Quat, UVec2, Vec2, Vec3, Vec3Swizzles, Vec4};
 use itertools::Itertools;
 use ndarray::{s, Array3, ArrayView3, Axis};
@@ -55,6 +62,7 @@ use crate::terrain_shader::{TerrainMaterial, TerrainMaterialParams};
 
 pub mod brushes;
 mod gather_spread;
+pub mod heightmap;
 pub mod intents;
 mod terrain_shader;
 use ambient_network::ServerWorldExt;
@@ -109,6 +117,31 @@ pub fn spawn_terrain(world: &mut World, terrain_compressed: Arc<TerrainStateCpu>
         .spawn(world)
 }
 
+/// Returns the state of all of the terrain cells in the world.
+pub fn get_terrain_cells(world: &World) -> Vec<(IVec2, Arc<TerrainStateCpu>)> {
+    query((terrain_world_cell(), terrain_state_cpu())).iter(world, None).map(|(_, (cell, state))| (*cell, state.clone())).collect()
+}
+
+/// Spawns the cells of `terrain`, or replaces the state of the cells which already exist.
+pub fn spawn_terrain_cells(world: &mut World, terrain: &TerrainCells) -> Vec<EntityId> {
+    terrain
+        .cells
+        .iter()
+        .map(|(cell, state)| {
+            let state = Arc::new(state.clone());
+            match get_terrain_cell(world, *cell) {
+                Some(id) => {
+                    world.set(id, terrain_state_cpu(), state).unwrap();
+                    // Uploaded to the GPU again by the client systems
+                    world.remove_component(id, terrain_state()).ok();
+                    id
+                }
+                None => spawn_terrain(world, state, *cell),
+            }
+        })
+        .collect()
+}
+
 fn create_terrain_physics(world: &World, terrain_state: Arc<TerrainStateCpu>, position: Vec3, _cell: IVec2) -> PxRigidStaticRef {
     let scene = world.resource(main_physics_scene());
     let physics = world.resource(physics());
@@ -168,6 +201,28 @@ pub fn server_systems() -> SystemGroup {
     SystemGroup::new(
         "dims/terrain/server_systems",
         vec![
+            Box::new(intents::terrain_intent_server_system()),
+            query(terrain_cells_from_url().changed()).to_system_with_name("terrain_cells_from_url", |q, world, qs, _| {
+                for (_, url) in q.collect_cloned(world, qs) {
+                    let url = match AbsAssetUrl::parse(url) {
+                        Ok(url) => url,
+                        Err(err) => {
+                            tracing::warn!("Invalid terrain url: {err:?}");
+                            continue;
+                        }
+                    };
+                    let assets = world.resource(asset_cache()).clone();
+                    let async_run = world.resource(async_run()).clone();
+                    world.resource(runtime()).spawn(async move {
+                        match TerrainCellsFromUrl(url.clone()).get(&assets).await {
+                            Ok(terrain) => async_run.run(move |world| {
+                                spawn_terrain_cells(world, &terrain);
+                            }),
+                            Err(err) => tracing::warn!("Failed to load terrain {url}: {err:?}"),
+                        }
+                    });
+                }
+            }),
             query((terrain_state_cpu().changed(), translation(), terrain_world_cell())).to_system_with_name(
                 "terrain",
                 |q, world, qs, _| {
@@ -199,6 +254,8 @@ pub fn server_systems() -> SystemGroup {
                                     .with(collider_type(), ColliderType::Static),
                             )
                             .unwrap();
+                        // Puts the shape in the layers of the entity, which is the first layer for terrain cells
+                        update_shape_layers(world, id);
                     }
                 },
             ),
@@ -394,6 +451,7 @@ impl TerrainSize {
 }
 
 pub const TERRAIN_LAYERS: u32 = 7;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(usize)]
 pub enum TerrainLayers {
     Rock,
@@ -605,6 +663,30 @@ impl TerrainStateCpu {
     }
 }
 
+/// A set of terrain cells, as produced by the terrain asset pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TerrainCells {
+    pub cells: Vec<(IVec2, TerrainStateCpu)>,
+}
+impl TerrainCells {
+    pub fn from_slice(data: &[u8]) -> anyhow::Result<Self> {
+        Ok(bincode::deserialize(data)?)
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TerrainCellsFromUrl(pub AbsAssetUrl);
+#[async_trait]
+impl AsyncAssetKey<Result<Arc<TerrainCells>, AssetError>> for TerrainCellsFromUrl {
+    async fn load(self, assets: AssetCache) -> Result<Arc<TerrainCells>, AssetError> {
+        let data = BytesFromUrl::new(self.0.clone(), true).get(&assets).await?;
+        Ok(Arc::new(TerrainCells::from_slice(&data)?))
+    }
+}
+
 // #[derive(Clone, Serialize, Deserialize, PartialEq)]
 // pub struct TerrainStateCompressed {
 //     pub size: TerrainSize,
@@ -0,0 +1,261 @@
+//! Checks the CPU implementations of the terrain brushes against known values, and compares them with their compute
+//! shaders where there's a GPU. The erosion brushes aren't compared, as their shaders race between texels, and neither
+//! is the raise brush, whose noise is downloaded.
+
+use std::sync::Arc;
+
+use ambient_gpu::{
+    gpu::{Gpu, GpuKey},
+    texture::Texture,
+};
+use ambient_std::asset_cache::{AssetCache, SyncAssetKeyExt};
+use ambient_terrain::{
+    brushes::{
+        flatten_cpu, hydraulic_erosion_cpu, init_ground_cpu, normalmap_cpu, raise_cpu, thermal_erosion_cpu, water_sim_cpu, BrushShape,
+        BrushWGSL, FlattenBrush, FlattenBrushParams, HydraulicErosionConfig, InitGroundBrush, InitGroundConfig, InitGroundParams,
+        NormalmapFromHeightmapCompute, RaiseBrushConfig, RaiseBrushParams, ThermalErosionParams, WaterSimConfig,
+    },
+    TerrainLayers, TerrainSize, TerrainState, TerrainStateCpu, TERRAIN_LAYERS,
+};
+use glam::{ivec2, uvec2, vec2, IVec2, UVec2, Vec2};
+use image::RgbaImage;
+use ndarray::{s, Array3, ArrayView3, Axis};
+
+/// An asset cache with a GPU, or `None` where there's no GPU to compare with.
+async fn gpu_assets() -> Option<AssetCache> {
+    let instance = wgpu::Instance::new(wgpu::Backends::PRIMARY);
+    instance.request_adapter(&Default::default()).await?;
+    let assets = AssetCache::new(tokio::runtime::Handle::current());
+    GpuKey.insert(&assets, Arc::new(Gpu::new(None).await));
+    Some(assets)
+}
+
+fn test_heightmap() -> Array3<f32> {
+    let size = TerrainSize::new().texture_size();
+    Array3::from_shape_fn((TERRAIN_LAYERS as usize, size, size), |(layer, y, x)| {
+        ((x as f32 * 0.1 + layer as f32).sin() + (y as f32 * 0.07).cos()) * 10. + 20.
+    })
+}
+
+/// A heightmap with `rock` meters of rock everywhere, no soil or water, and no hardness.
+fn flat_heightmap(rock: f32) -> Array3<f32> {
+    let size = TerrainSize::new().texture_size();
+    let mut heightmap = Array3::zeros((TERRAIN_LAYERS as usize, size, size));
+    heightmap.index_axis_mut(Axis(0), TerrainLayers::Rock as usize).fill(rock);
+    heightmap.index_axis_mut(Axis(0), TerrainLayers::HardnessStrataWavelength as usize).fill(100.);
+    heightmap
+}
+
+fn rock(heightmap: &Array3<f32>, texel: IVec2) -> f32 {
+    heightmap[(TerrainLayers::Rock as usize, texel.y as usize, texel.x as usize)]
+}
+
+fn upload(assets: &AssetCache, heightmap: &Array3<f32>) -> TerrainState {
+    let size = TerrainSize::new();
+    let normalmap = Array3::zeros((size.texture_size(), size.texture_size(), 4));
+    TerrainStateCpu { size, heightmap: heightmap.clone(), normalmap }.to_gpu(assets.clone())
+}
+
+async fn read_heightmap(texture: &Texture) -> Array3<f32> {
+    texture.reader().read_array_f32().await.unwrap().remove_axis(Axis(3))
+}
+
+fn assert_close(gpu: ArrayView3<f32>, cpu: ArrayView3<f32>, tolerance: f32) {
+    assert_eq!(gpu.dim(), cpu.dim());
+    for ((index, a), b) in gpu.indexed_iter().zip(cpu.iter()) {
+        assert!((a - b).abs() <= tolerance, "{index:?}: gpu {a} cpu {b}");
+    }
+}
+
+#[tokio::test]
+async fn test_flatten() {
+    let Some(assets) = gpu_assets().await else { return };
+    let gpu = GpuKey.get(&assets);
+    let heightmap = test_heightmap();
+    let start_heightmap = heightmap.mapv(|h| h * 2.);
+    let params = FlattenBrushParams {
+        brush: BrushWGSL {
+            center: vec2(30., 20.),
+            radius: 25.,
+            shape: BrushShape::Circle,
+            smoothness: 0.6,
+            amplitude: 10.,
+            ..Default::default()
+        },
+        start_texel: uvec2(3, 40),
+        heightmap_world_position: Vec2::ZERO,
+        heightmap_world_texel_size: 1.,
+        _padding: Default::default(),
+    };
+
+    let state = upload(&assets, &heightmap);
+    let start_state = upload(&assets, &start_heightmap);
+    let mut encoder = gpu.device.create_command_encoder(&Default::default());
+    let size = UVec2::splat(TerrainSize::new().texture_size() as u32);
+    FlattenBrush::new(&gpu).run(
+        &gpu,
+        &mut encoder,
+        &state.heightmap.create_view(&Default::default()),
+        &start_state.heightmap.create_view(&Default::default()),
+        size,
+        &params,
+    );
+    gpu.queue.submit(Some(encoder.finish()));
+
+    let mut cpu = heightmap;
+    flatten_cpu(&mut cpu, &start_heightmap, &params);
+    assert_close(read_heightmap(&state.heightmap).await.view(), cpu.view(), 1e-3);
+}
+
+#[tokio::test]
+async fn test_init_ground() {
+    let Some(assets) = gpu_assets().await else { return };
+    let gpu = GpuKey.get(&assets);
+    let config = InitGroundConfig {
+        params: InitGroundParams { heightmap_world_position: vec2(-64., 128.), heightmap_world_size: Vec2::splat(64.) },
+        seed: 42,
+    };
+
+    let state = upload(&assets, &test_heightmap());
+    let mut encoder = gpu.device.create_command_encoder(&Default::default());
+    InitGroundBrush::new(&gpu).run(&gpu, &mut encoder, &state.heightmap, &config);
+    gpu.queue.submit(Some(encoder.finish()));
+
+    let mut cpu = test_heightmap();
+    init_ground_cpu(&mut cpu, &config);
+    assert_close(read_heightmap(&state.heightmap).await.view(), cpu.view(), 1e-3);
+}
+
+#[tokio::test]
+async fn test_normalmap() {
+    let Some(assets) = gpu_assets().await else { return };
+    let gpu = GpuKey.get(&assets);
+    let heightmap = test_heightmap();
+    let size = TerrainSize::new().texture_size() as u32;
+
+    let state = upload(&assets, &heightmap);
+    let normalmap = Texture::new(
+        gpu.clone(),
+        &wgpu::TextureDescriptor {
+            label: Some("Test normalmap"),
+            size: wgpu::Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::STORAGE_BINDING,
+        },
+    );
+    let mut encoder = gpu.device.create_command_encoder(&Default::default());
+    NormalmapFromHeightmapCompute::new(&gpu).run(
+        &gpu,
+        &mut encoder,
+        &state.heightmap.create_view(&Default::default()),
+        &normalmap.create_view(&Default::default()),
+        UVec2::splat(size),
+    );
+    gpu.queue.submit(Some(encoder.finish()));
+
+    let gpu_normalmap = normalmap.reader().read_array_f32().await.unwrap().remove_axis(Axis(0));
+    let cpu_normalmap = normalmap_cpu(&heightmap);
+    assert_close(gpu_normalmap.slice(s![.., .., ..3]), cpu_normalmap.slice(s![.., .., ..3]), 1e-4);
+}
+
+#[test]
+fn test_raise_cpu() {
+    let mut heightmap = flat_heightmap(10.);
+    let brush =
+        BrushWGSL { center: vec2(30., 20.), radius: 8., shape: BrushShape::Circle, smoothness: 0.5, amplitude: 5., ..Default::default() };
+    let params = RaiseBrushParams { brush, heightmap_world_texel_size: 1., ..Default::default() };
+    // A white noise image scales the brush by exactly 1
+    let noise = RgbaImage::from_pixel(1, 1, image::Rgba([255; 4]));
+    raise_cpu(&mut heightmap, &noise, &RaiseBrushConfig { params, seed: 1 });
+
+    assert!((rock(&heightmap, ivec2(30, 20)) - 15.).abs() < 1e-4);
+    for (index, &height) in heightmap.index_axis(Axis(0), TerrainLayers::Rock as usize).indexed_iter() {
+        let distance = (vec2(index.1 as f32, index.0 as f32) - brush.center).length();
+        if distance < brush.radius {
+            assert!(height > 10., "{index:?} at {distance}m wasn't raised");
+        } else {
+            assert_eq!(height, 10., "{index:?} at {distance}m was changed");
+        }
+    }
+}
+
+#[test]
+fn test_flat_map_stays_flat() {
+    let size = TerrainSize::new().texture_size() as i32;
+    let mut heightmap = flat_heightmap(10.);
+    let mut config = HydraulicErosionConfig { drops_per_m2: 0.1, brush_position: vec2(20., 20.), brush_radius: 10., ..Default::default() };
+    config.params.heightmap_size = IVec2::splat(size);
+    hydraulic_erosion_cpu(&mut heightmap, &config);
+    for frame in 0..4 {
+        let params = ThermalErosionParams {
+            heightmap_world_size: Vec2::splat(size as f32),
+            heightmap_texture_size: IVec2::splat(size),
+            brush_position: Vec2::splat(32.),
+            brush_radius: 30.,
+            frame,
+            ..Default::default()
+        };
+        thermal_erosion_cpu(&mut heightmap, &params);
+    }
+    assert_close(heightmap.view(), flat_heightmap(10.).view(), 1e-3);
+}
+
+#[test]
+fn test_hydraulic_erosion_cpu() {
+    let size = TerrainSize::new().texture_size() as i32;
+    let mut heightmap = flat_heightmap(0.);
+    heightmap.index_axis_mut(Axis(0), TerrainLayers::Rock as usize).indexed_iter_mut().for_each(|((_, x), h)| *h = 50. - x as f32 * 0.5);
+    let start = heightmap.clone();
+    let mut config = HydraulicErosionConfig { drops_per_m2: 0.5, brush_position: vec2(20., 32.), brush_radius: 10., ..Default::default() };
+    config.params.heightmap_size = IVec2::splat(size);
+    hydraulic_erosion_cpu(&mut heightmap, &config);
+
+    // The drops erode the rock as they run down the slope, and only ever deposit soil
+    assert!(heightmap.iter().all(|h| h.is_finite()));
+    let rock_layer = |heightmap: &Array3<f32>| heightmap.index_axis(Axis(0), TerrainLayers::Rock as usize).to_owned();
+    let (rock, start_rock) = (rock_layer(&heightmap), rock_layer(&start));
+    assert!(rock.iter().zip(&start_rock).all(|(a, b)| a <= b));
+    assert!(rock.iter().zip(&start_rock).any(|(a, b)| a < b));
+}
+
+#[test]
+fn test_thermal_erosion_cpu() {
+    let size = TerrainSize::new().texture_size() as i32;
+    let mut heightmap = flat_heightmap(10.);
+    heightmap[(TerrainLayers::Rock as usize, 32, 32)] = 110.;
+    let params = ThermalErosionParams {
+        heightmap_world_size: Vec2::splat(size as f32),
+        heightmap_texture_size: IVec2::splat(size),
+        // Frame 0 moves material between each texel and its left neighbour
+        frame: 0,
+        ..Default::default()
+    };
+    thermal_erosion_cpu(&mut heightmap, &params);
+
+    // A quarter of the height difference moves, scaled by the brush, which fades in over 50 texels from the border
+    let moved = |border_distance: f32| {
+        let t = (border_distance - 1.) / 50.;
+        t * t * (3. - 2. * t) * 100. / 4.
+    };
+    assert!((rock(&heightmap, ivec2(32, 32)) - (110. - moved(32.))).abs() < 1e-3);
+    assert!((rock(&heightmap, ivec2(33, 32)) - (10. + moved(31.))).abs() < 1e-3);
+    assert_eq!(rock(&heightmap, ivec2(31, 32)), 10.);
+    assert_eq!(rock(&heightmap, ivec2(32, 33)), 10.);
+}
+
+#[test]
+fn test_water_sim_cpu() {
+    let mut heightmap = flat_heightmap(10.);
+    water_sim_cpu(&mut heightmap, &WaterSimConfig::default());
+
+    // Away from the edges of the map, which water flows out of, it rains evenly, and some of it evaporates
+    let water = 0.02 * 0.012 * (1. - 0.015 * 0.02);
+    let size = TerrainSize::new().texture_size();
+    let inner = |layer: TerrainLayers| heightmap.slice(s![layer as usize, 2..size - 2, 2..size - 2]).to_owned();
+    assert!(inner(TerrainLayers::Water).iter().all(|&w| (w - water).abs() < 1e-7));
+    assert!(inner(TerrainLayers::Rock).iter().all(|&rock| rock == 10.));
+    assert!(inner(TerrainLayers::Sediment).iter().all(|&sediment| sediment == 0.));
+}
@@ -78,7 +78,49 @@ impl wit::audio::Host for Bindings {
         crate::shared::implementation::audio::load(self.world_mut(), url)
     }
 
-    fn play(&mut self, name: String, looping: bool, amp: f32) -> anyhow::Result<()> {
-        crate::shared::implementation::audio::play(self.world_mut(), name, looping, amp)
+    fn play(
+        &mut self,
+        name: String,
+        looping: bool,
+        amp: f32,
+        bus: wit::audio::AudioBus,
+    ) -> anyhow::Result<u64> {
+        crate::shared::implementation::audio::play(
+            unsafe { self.world_ref.world() },
+            &mut self.base.sounds,
+            name,
+            looping,
+            amp,
+            bus.from_bindgen(),
+        )
+    }
+
+    fn stop(&mut self, sound: u64) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::stop(&mut self.base.sounds, sound)
+    }
+
+    fn set_paused(&mut self, sound: u64, paused: bool) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::set_paused(&self.base.sounds, sound, paused)
+    }
+
+    fn set_volume(&mut self, sound: u64, volume: f32) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::set_volume(&self.base.sounds, sound, volume)
     }
-}
\ No newline at end of file
+
+    fn fade(&mut self, sound: u64, volume: f32, duration: f32) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::fade(&self.base.sounds, sound, volume, duration)
+    }
+
+    fn set_pitch(&mut self, sound: u64, pitch: f32) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::set_pitch(&self.base.sounds, sound, pitch)
+    }
+
+    fn attach(&mut self, sound: u64, entity: wit::types::EntityId) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::attach(
+            unsafe { self.world_ref.world_mut() },
+            &self.base.sounds,
+            sound,
+            entity.from_bindgen(),
+        )
+    }
+}
@@ -130,6 +130,58 @@ impl wit::entity::Host for Bindings {
         )
     }
 
+    fn set_animation_state_machine(
+        &mut self,
+        entity: wit::types::EntityId,
+        state_machine: wit::entity::AnimationStateMachine,
+    ) -> anyhow::Result<()> {
+        shared::implementation::entity::set_animation_state_machine(
+            self.world_mut(),
+            entity,
+            state_machine,
+        )
+    }
+
+    fn set_animation_parameter(
+        &mut self,
+        entity: wit::types::EntityId,
+        name: String,
+        value: f32,
+    ) -> anyhow::Result<()> {
+        shared::implementation::entity::set_animation_parameter(
+            self.world_mut(),
+            entity,
+            name,
+            value,
+        )
+    }
+
+    fn get_animation_state(
+        &mut self,
+        entity: wit::types::EntityId,
+    ) -> anyhow::Result<Option<String>> {
+        shared::implementation::entity::get_animation_state(self.world(), entity)
+    }
+
+    fn set_animation_root_motion(
+        &mut self,
+        entity: wit::types::EntityId,
+        root_motion: Option<wit::entity::AnimationRootMotion>,
+    ) -> anyhow::Result<()> {
+        shared::implementation::entity::set_animation_root_motion(
+            self.world_mut(),
+            entity,
+            root_motion,
+        )
+    }
+
+    fn get_animation_root_motion_delta(
+        &mut self,
+        entity: wit::types::EntityId,
+    ) -> anyhow::Result<Option<wit::types::Vec3>> {
+        shared::implementation::entity::get_animation_root_motion_delta(self.world(), entity)
+    }
+
     fn exists(&mut self, entity: wit::types::EntityId) -> anyhow::Result<bool> {
         shared::implementation::entity::exists(self.world(), entity)
     }
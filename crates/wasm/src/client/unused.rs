@@ -90,6 +90,38 @@ impl wit::server_physics::Host for Bindings {
         unsupported()
     }
 
+    fn raycast_filtered(
+        &mut self,
+        _origin: wit::types::Vec3,
+        _direction: wit::types::Vec3,
+        _max_distance: f32,
+        _layers: u32,
+    ) -> anyhow::Result<Vec<(wit::types::EntityId, f32)>> {
+        unsupported()
+    }
+
+    fn sweep(
+        &mut self,
+        _shape: wit::server_physics::Shape,
+        _position: wit::types::Vec3,
+        _rotation: wit::types::Quat,
+        _direction: wit::types::Vec3,
+        _max_distance: f32,
+        _layers: u32,
+    ) -> anyhow::Result<Vec<wit::server_physics::SweepHit>> {
+        unsupported()
+    }
+
+    fn overlap(
+        &mut self,
+        _shape: wit::server_physics::Shape,
+        _position: wit::types::Vec3,
+        _rotation: wit::types::Quat,
+        _layers: u32,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        unsupported()
+    }
+
     fn move_character(
         &mut self,
         _entity: wit::types::EntityId,
@@ -7,5 +7,18 @@ pub(crate) static WASMTIME_ENGINE: once_cell::sync::Lazy<wasmtime::Engine> =
         let mut config = wasmtime::Config::new();
         config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
         config.wasm_component_model(true);
-        wasmtime::Engine::new(&config).unwrap()
+        config.epoch_interruption(true);
+        let engine = wasmtime::Engine::new(&config).unwrap();
+
+        // Advance the epoch at a fixed rate, so that modules exceeding their execution time limit
+        // can be interrupted
+        std::thread::spawn({
+            let engine = engine.clone();
+            move || loop {
+                std::thread::sleep(shared::EPOCH_TICK);
+                engine.increment_epoch();
+            }
+        });
+
+        engine
     });
@@ -4,7 +4,10 @@ use ambient_network::server::player_connection;
 use ambient_physics::physx::character_controller;
 use ambient_std::{shapes::Ray, asset_url::AbsAssetUrl};
 use anyhow::Context;
-use physxx::{PxControllerCollisionFlag, PxControllerFilters};
+use physxx::{
+    PxBoxGeometry, PxCapsuleGeometry, PxControllerCollisionFlag, PxControllerFilters, PxGeometry,
+    PxSphereGeometry, PxTransform,
+};
 
 use super::Bindings;
 use crate::shared::{
@@ -183,6 +186,73 @@ impl wit::server_physics::Host for Bindings {
         Ok(result)
     }
 
+    fn raycast_filtered(
+        &mut self,
+        origin: wit::types::Vec3,
+        direction: wit::types::Vec3,
+        max_distance: f32,
+        layers: u32,
+    ) -> anyhow::Result<Vec<(wit::types::EntityId, f32)>> {
+        let result = ambient_physics::intersection::raycast_layers(
+            self.world(),
+            Ray::new(origin.from_bindgen(), direction.from_bindgen()),
+            max_distance,
+            layers,
+        )
+        .into_iter()
+        .map(|t| (t.0.into_bindgen(), t.1.into_bindgen()))
+        .collect();
+
+        Ok(result)
+    }
+
+    fn sweep(
+        &mut self,
+        shape: wit::server_physics::Shape,
+        position: wit::types::Vec3,
+        rotation: wit::types::Quat,
+        direction: wit::types::Vec3,
+        max_distance: f32,
+        layers: u32,
+    ) -> anyhow::Result<Vec<wit::server_physics::SweepHit>> {
+        let (geometry, pose) = shape_to_geometry(shape, position, rotation);
+        let result = ambient_physics::intersection::sweep(
+            self.world(),
+            geometry.as_ref(),
+            &pose,
+            direction.from_bindgen(),
+            max_distance,
+            layers,
+        )
+        .into_iter()
+        .map(|hit| wit::server_physics::SweepHit {
+            entity: hit.entity.into_bindgen(),
+            distance: hit.distance,
+            position: hit.position.into_bindgen(),
+            normal: hit.normal.into_bindgen(),
+        })
+        .collect();
+
+        Ok(result)
+    }
+
+    fn overlap(
+        &mut self,
+        shape: wit::server_physics::Shape,
+        position: wit::types::Vec3,
+        rotation: wit::types::Quat,
+        layers: u32,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        let (geometry, pose) = shape_to_geometry(shape, position, rotation);
+        let result =
+            ambient_physics::intersection::overlap(self.world(), geometry.as_ref(), pose, layers)
+                .into_iter()
+                .map(|id| id.into_bindgen())
+                .collect();
+
+        Ok(result)
+    }
+
     fn move_character(
         &mut self,
         entity: wit::types::EntityId,
@@ -217,6 +287,43 @@ impl wit::server_physics::Host for Bindings {
     }
 }
 
+/// Converts a query shape from the guest into PhysX geometry, and the pose to query it at.
+fn shape_to_geometry(
+    shape: wit::server_physics::Shape,
+    position: wit::types::Vec3,
+    rotation: wit::types::Quat,
+) -> (Box<dyn PxGeometry>, PxTransform) {
+    use wit::server_physics::Shape;
+
+    let position = position.from_bindgen();
+    let rotation = rotation.from_bindgen();
+    match shape {
+        Shape::Sphere(radius) => (
+            Box::new(PxSphereGeometry::new(radius)),
+            PxTransform::new(position, rotation),
+        ),
+        // PhysX capsules extend along their X axis, while guests expect them to extend along Z
+        Shape::Capsule(capsule) => (
+            Box::new(PxCapsuleGeometry::new(capsule.radius, capsule.half_height)),
+            PxTransform::new(
+                position,
+                rotation * glam::Quat::from_rotation_y(-std::f32::consts::FRAC_PI_2),
+            ),
+        ),
+        Shape::Cuboid(size) => {
+            let half_extents = size.from_bindgen() / 2.;
+            (
+                Box::new(PxBoxGeometry::new(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                )),
+                PxTransform::new(position, rotation),
+            )
+        }
+    }
+}
+
 impl wit::server_message::Host for Bindings {
     fn send(
         &mut self,
@@ -287,7 +394,49 @@ impl wit::audio::Host for Bindings {
         crate::shared::implementation::audio::load(self.world_mut(), url)
     }
 
-    fn play(&mut self, name: String, looping: bool, amp: f32) -> anyhow::Result<()> {
-        crate::shared::implementation::audio::play(self.world_mut(), name, looping, amp)
+    fn play(
+        &mut self,
+        name: String,
+        looping: bool,
+        amp: f32,
+        bus: wit::audio::AudioBus,
+    ) -> anyhow::Result<u64> {
+        crate::shared::implementation::audio::play(
+            unsafe { self.world_ref.world() },
+            &mut self.base.sounds,
+            name,
+            looping,
+            amp,
+            bus.from_bindgen(),
+        )
+    }
+
+    fn stop(&mut self, sound: u64) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::stop(&mut self.base.sounds, sound)
+    }
+
+    fn set_paused(&mut self, sound: u64, paused: bool) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::set_paused(&self.base.sounds, sound, paused)
+    }
+
+    fn set_volume(&mut self, sound: u64, volume: f32) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::set_volume(&self.base.sounds, sound, volume)
+    }
+
+    fn fade(&mut self, sound: u64, volume: f32, duration: f32) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::fade(&self.base.sounds, sound, volume, duration)
     }
-}
\ No newline at end of file
+
+    fn set_pitch(&mut self, sound: u64, pitch: f32) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::set_pitch(&self.base.sounds, sound, pitch)
+    }
+
+    fn attach(&mut self, sound: u64, entity: wit::types::EntityId) -> anyhow::Result<()> {
+        crate::shared::implementation::audio::attach(
+            unsafe { self.world_ref.world_mut() },
+            &self.base.sounds,
+            sound,
+            entity.from_bindgen(),
+        )
+    }
+}
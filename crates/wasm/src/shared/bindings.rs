@@ -1,17 +1,20 @@
 use std::collections::HashSet;
 
+use ambient_audio::SoundControl;
 use ambient_ecs::{EntityId, PrimitiveComponent, Query, QueryState, World};
 
 use super::wit;
 
 pub type QueryStateMap =
     slotmap::SlotMap<slotmap::DefaultKey, (Query, QueryState, Vec<PrimitiveComponent>)>;
+pub type SoundMap = slotmap::SlotMap<slotmap::DefaultKey, SoundControl>;
 
 #[derive(Clone, Default)]
 pub struct BindingsBase {
     pub spawned_entities: HashSet<EntityId>,
     pub subscribed_messages: HashSet<String>,
     pub query_states: QueryStateMap,
+    pub sounds: SoundMap,
 }
 
 pub trait BindingsBound:
@@ -1,7 +1,7 @@
 use ambient_sys::time::SystemTime;
 
 use ambient_animation as animation;
-use ambient_ecs::EntityId;
+use ambient_ecs::{EntityId, StructuredValue};
 use ambient_std::asset_url::TypedAssetUrl;
 use glam::{Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
 
@@ -205,6 +205,15 @@ bindgen_passthrough!(u8);
 bindgen_passthrough!(u32);
 bindgen_passthrough!(u64);
 
+/// Structured values are passed through in their serialized form; the other direction is
+/// just `StructuredValue(bytes)`, as `Vec<u8>` is already converted generically.
+impl IntoBindgen for StructuredValue {
+    type Item = Vec<u8>;
+    fn into_bindgen(self) -> Self::Item {
+        self.0
+    }
+}
+
 impl<'a> FromBindgen for &'a str {
     type Item = String;
     fn from_bindgen(self) -> Self::Item {
@@ -274,6 +283,19 @@ impl FromBindgen for wit::entity::AnimationAction {
             },
             looping: self.looping,
             weight: self.weight,
+            mask: self.mask,
+            blend_mode: self.blend_mode.from_bindgen(),
+        }
+    }
+}
+
+impl FromBindgen for wit::entity::AnimationBlendMode {
+    type Item = animation::AnimationBlendMode;
+    fn from_bindgen(self) -> Self::Item {
+        match self {
+            wit::entity::AnimationBlendMode::Blend => animation::AnimationBlendMode::Blend,
+            wit::entity::AnimationBlendMode::Override => animation::AnimationBlendMode::Override,
+            wit::entity::AnimationBlendMode::Additive => animation::AnimationBlendMode::Additive,
         }
     }
 }
@@ -287,3 +309,99 @@ impl FromBindgen for wit::entity::AnimationController {
         }
     }
 }
+
+impl FromBindgen for wit::entity::AnimationStateMachine {
+    type Item = animation::AnimationStateMachine;
+    fn from_bindgen(self) -> Self::Item {
+        animation::AnimationStateMachine {
+            states: self.states.from_bindgen(),
+            transitions: self.transitions.from_bindgen(),
+            initial_state: self.initial_state,
+            apply_base_pose: self.apply_base_pose,
+        }
+    }
+}
+
+impl FromBindgen for wit::entity::AnimationState {
+    type Item = animation::AnimationState;
+    fn from_bindgen(self) -> Self::Item {
+        animation::AnimationState {
+            name: self.name,
+            points: self.points.from_bindgen(),
+            x_parameter: self.x_parameter,
+            y_parameter: self.y_parameter,
+            looping: self.looping,
+            speed: self.speed,
+        }
+    }
+}
+
+impl FromBindgen for wit::entity::AnimationBlendPoint {
+    type Item = animation::AnimationBlendPoint;
+    fn from_bindgen(self) -> Self::Item {
+        animation::AnimationBlendPoint {
+            clip: TypedAssetUrl::parse(self.clip_url).unwrap(),
+            position: Vec2::new(self.x, self.y),
+        }
+    }
+}
+
+impl FromBindgen for wit::entity::AnimationRootMotion {
+    type Item = animation::AnimationRootMotion;
+    fn from_bindgen(self) -> Self::Item {
+        animation::AnimationRootMotion {
+            bind_id: self.bind_id,
+            axes: self.axes.from_bindgen(),
+        }
+    }
+}
+
+impl FromBindgen for wit::entity::AnimationTransition {
+    type Item = animation::AnimationTransition;
+    fn from_bindgen(self) -> Self::Item {
+        animation::AnimationTransition {
+            from: self.from_state,
+            to: self.to_state,
+            conditions: self.conditions.from_bindgen(),
+            duration: self.duration,
+        }
+    }
+}
+
+impl FromBindgen for wit::entity::AnimationCondition {
+    type Item = animation::AnimationCondition;
+    fn from_bindgen(self) -> Self::Item {
+        animation::AnimationCondition {
+            parameter: self.parameter,
+            comparison: match self.comparison {
+                wit::entity::AnimationComparison::Less => animation::AnimationComparison::Less,
+                wit::entity::AnimationComparison::LessOrEqual => {
+                    animation::AnimationComparison::LessOrEqual
+                }
+                wit::entity::AnimationComparison::Equal => animation::AnimationComparison::Equal,
+                wit::entity::AnimationComparison::NotEqual => {
+                    animation::AnimationComparison::NotEqual
+                }
+                wit::entity::AnimationComparison::GreaterOrEqual => {
+                    animation::AnimationComparison::GreaterOrEqual
+                }
+                wit::entity::AnimationComparison::Greater => {
+                    animation::AnimationComparison::Greater
+                }
+            },
+            value: self.value,
+        }
+    }
+}
+
+impl FromBindgen for wit::audio::AudioBus {
+    type Item = ambient_world_audio::AudioBus;
+    fn from_bindgen(self) -> Self::Item {
+        match self {
+            wit::audio::AudioBus::Music => ambient_world_audio::AudioBus::Music,
+            wit::audio::AudioBus::Sfx => ambient_world_audio::AudioBus::Sfx,
+            wit::audio::AudioBus::Voice => ambient_world_audio::AudioBus::Voice,
+            wit::audio::AudioBus::Ui => ambient_world_audio::AudioBus::Ui,
+        }
+    }
+}
@@ -1,10 +1,15 @@
+use std::time::Duration;
+
 use ambient_std::asset_url::{AbsAssetUrl}; // AssetUrl
 use ambient_std::asset_cache::{AsyncAssetKeyExt};
-use ambient_audio::{AudioFromUrl}; //  track::TrackDecodeStream, Source
-use ambient_ecs::{World};
+use ambient_audio::{AudioFromUrl, SoundControl}; //  track::TrackDecodeStream, Source
+use ambient_ecs::{EntityId, World};
 use ambient_core::{asset_cache, async_ecs::async_run, runtime};
-use ambient_world_audio::{audio_sender, AudioMessage}; // audio_tracks,
+use ambient_world_audio::{attach_sound_to_entity, audio_sender, mixer_bus, AudioBus, AudioMessage}; // audio_tracks,
 use anyhow::Context;
+use slotmap::{DefaultKey, Key, KeyData};
+
+use crate::shared::bindings::SoundMap;
 
 pub(crate) fn load(world: &World, url: String) -> anyhow::Result<()> {
     let assets = world.resource(asset_cache()).clone();
@@ -14,10 +19,22 @@ pub(crate) fn load(world: &World, url: String) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub(crate) fn play(world: &World, url: String, looping: bool, amp: f32) -> anyhow::Result<()> {
+pub(crate) fn play(
+    world: &World,
+    sounds: &mut SoundMap,
+    url: String,
+    looping: bool,
+    amp: f32,
+    bus: AudioBus,
+) -> anyhow::Result<u64> {
     let assets = world.resource(asset_cache()).clone();
     let asset_url = AbsAssetUrl::from_asset_key(url).to_string();
     let audio_url = AudioFromUrl { url: AbsAssetUrl::parse(asset_url).context("Failed to parse audio url")? };
+    // Sounds which have ended can no longer be controlled
+    sounds.retain(|_, control| !control.is_stopped());
+    let control = SoundControl::default();
+    let sound = sounds.insert(control.clone()).data().as_ffi();
+
     let runtime = world.resource(runtime()).clone();
     let async_run = world.resource(async_run()).clone();
     runtime.spawn(async move {
@@ -26,11 +43,70 @@ pub(crate) fn play(world: &World, url: String, looping: bool, amp: f32) -> anyho
             match track {
                 Ok(track) => {
                     let sender = world.resource(audio_sender());
-                    sender.send(AudioMessage::Track(track, looping, amp)).unwrap();
+                    sender.send(AudioMessage::Track(track, looping, amp, control, mixer_bus(world, bus))).unwrap();
                 },
-                Err(e) => log::error!("{e:?}")
+                Err(e) => {
+                    control.stop();
+                    log::error!("{e:?}")
+                }
             };
         });
     });
+    Ok(sound)
+}
+
+fn get_sound(sounds: &SoundMap, sound: u64) -> Option<&SoundControl> {
+    sounds.get(DefaultKey::from(KeyData::from_ffi(sound)))
+}
+
+pub(crate) fn stop(sounds: &mut SoundMap, sound: u64) -> anyhow::Result<()> {
+    if let Some(control) = sounds.remove(DefaultKey::from(KeyData::from_ffi(sound))) {
+        control.stop();
+    }
+    Ok(())
+}
+
+pub(crate) fn set_paused(sounds: &SoundMap, sound: u64, paused: bool) -> anyhow::Result<()> {
+    if let Some(control) = get_sound(sounds, sound) {
+        control.set_paused(paused);
+    }
+    Ok(())
+}
+
+pub(crate) fn set_volume(sounds: &SoundMap, sound: u64, volume: f32) -> anyhow::Result<()> {
+    if let Some(control) = get_sound(sounds, sound) {
+        control.set_volume(volume);
+    }
+    Ok(())
+}
+
+pub(crate) fn fade(
+    sounds: &SoundMap,
+    sound: u64,
+    volume: f32,
+    duration: f32,
+) -> anyhow::Result<()> {
+    if let Some(control) = get_sound(sounds, sound) {
+        control.fade(volume, Duration::from_secs_f32(duration.max(0.0)));
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+pub(crate) fn set_pitch(sounds: &SoundMap, sound: u64, pitch: f32) -> anyhow::Result<()> {
+    if let Some(control) = get_sound(sounds, sound) {
+        control.set_pitch(pitch);
+    }
+    Ok(())
+}
+
+pub(crate) fn attach(
+    world: &mut World,
+    sounds: &SoundMap,
+    sound: u64,
+    entity: EntityId,
+) -> anyhow::Result<()> {
+    match get_sound(sounds, sound) {
+        Some(control) => attach_sound_to_entity(world, control, entity),
+        None => Ok(()),
+    }
+}
@@ -1,6 +1,7 @@
 use ambient_ecs::{
     with_component_registry, Component, ComponentDesc, ComponentEntry, ComponentSet,
-    ComponentValue, Entity, EntityId, PrimitiveComponentType as PCT, QueryEvent, QueryState, World,
+    ComponentValue, Entity, EntityId, PrimitiveComponentType as PCT, QueryEvent, QueryState,
+    StructuredValue, World,
 };
 use ambient_shared_types::primitive_component_definitions;
 use anyhow::Context;
@@ -53,6 +54,7 @@ macro_rules! define_component_types {
                 PCT::[<Vec $value>]    => V::TypeVec(VV::[<Type $value>](get::<Vec<$type>>(world, entity_id, c)?),),
                 PCT::[<Option $value>] => V::TypeOption(OV::[<Type $value>](get::<Option<$type>>(world, entity_id, c)?),),
                 )*
+                PCT::Structured => V::TypeStructured(get::<StructuredValue>(world, entity_id, c)?),
             })
         }
 
@@ -79,6 +81,7 @@ macro_rules! define_component_types {
                 PCT::[<Vec $value>]    => V::TypeVec(VV::[<Type $value>](get::<Vec<$type>>(world, entity_accessor, c).clone()),),
                 PCT::[<Option $value>] => V::TypeOption(OV::[<Type $value>](get::<Option<$type>>(world, entity_accessor, c).clone()),),
                 )*
+                PCT::Structured => V::TypeStructured(get::<StructuredValue>(world, entity_accessor, c)),
             })
         }
 
@@ -116,6 +119,7 @@ macro_rules! define_component_types {
                             PCT::[<Vec $value>]    => V::TypeVec(VV::[<Type $value>](get::<Vec<$type>>(cu)?),),
                             PCT::[<Option $value>] => V::TypeOption(OV::[<Type $value>](get::<Option<$type>>(cu)?),),
                             )*
+                            PCT::Structured => V::TypeStructured(get::<StructuredValue>(cu)?),
                         };
 
                         Some((index, value))
@@ -142,6 +146,7 @@ macro_rules! define_component_types {
                             (PCT::[<Vec $value>], V::TypeVec(VV::[<Type $value>](v)))      => Some(ComponentEntry::from_raw_parts(c, v.from_bindgen())),
                             (PCT::[<Option $value>], V::TypeOption(OV::[<Type $value>](v))) => Some(ComponentEntry::from_raw_parts(c, v.from_bindgen()))
                             ),*,
+                            (PCT::Structured, V::TypeStructured(v)) => Some(ComponentEntry::from_raw_parts(c, StructuredValue(v))),
                             _ => None,
                         }
                     })
@@ -176,6 +181,11 @@ macro_rules! define_component_types {
                     }
                 }
                 ) *
+                V::TypeStructured(value) => {
+                    if let Some(component) = get_component_type::<StructuredValue>(index) {
+                        world.add_component(entity_id, component, StructuredValue(value))?;
+                    }
+                }
             }
 
             Ok(())
@@ -208,6 +218,11 @@ macro_rules! define_component_types {
                     }
                 }
                 ) *
+                V::TypeStructured(value) => {
+                    if let Some(component) = get_component_type::<StructuredValue>(index) {
+                        world.set(entity_id, component, StructuredValue(value))?;
+                    }
+                }
             }
 
             Ok(())
@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use ambient_animation::animation_controller;
+use ambient_animation::{
+    animation_controller, animation_parameters, animation_root_motion, animation_root_motion_delta,
+    animation_state, animation_state_machine,
+};
 use ambient_core::transform::translation;
 use ambient_ecs::{query as ecs_query, with_component_registry, EntityId, World};
 use ambient_network::ServerWorldExt;
@@ -46,6 +49,76 @@ pub fn set_animation_controller(
     )?)
 }
 
+pub fn set_animation_state_machine(
+    world: &mut World,
+    entity: wit::types::EntityId,
+    state_machine: wit::entity::AnimationStateMachine,
+) -> anyhow::Result<()> {
+    Ok(world.add_component(
+        entity.from_bindgen(),
+        animation_state_machine(),
+        state_machine.from_bindgen(),
+    )?)
+}
+
+pub fn set_animation_parameter(
+    world: &mut World,
+    entity: wit::types::EntityId,
+    name: String,
+    value: f32,
+) -> anyhow::Result<()> {
+    let entity = entity.from_bindgen();
+    match world.get_mut(entity, animation_parameters()) {
+        Ok(parameters) => {
+            parameters.insert(name, value);
+        }
+        Err(_) => world.add_component(
+            entity,
+            animation_parameters(),
+            [(name, value)].into_iter().collect(),
+        )?,
+    }
+    Ok(())
+}
+
+pub fn get_animation_state(
+    world: &World,
+    entity: wit::types::EntityId,
+) -> anyhow::Result<Option<String>> {
+    Ok(world
+        .get_ref(entity.from_bindgen(), animation_state())
+        .ok()
+        .map(|playback| playback.state.clone()))
+}
+
+pub fn set_animation_root_motion(
+    world: &mut World,
+    entity: wit::types::EntityId,
+    root_motion: Option<wit::entity::AnimationRootMotion>,
+) -> anyhow::Result<()> {
+    let entity = entity.from_bindgen();
+    match root_motion {
+        Some(root_motion) => {
+            world.add_component(entity, animation_root_motion(), root_motion.from_bindgen())?
+        }
+        None => {
+            world.remove_component(entity, animation_root_motion())?;
+            world.remove_component(entity, animation_root_motion_delta())?;
+        }
+    }
+    Ok(())
+}
+
+pub fn get_animation_root_motion_delta(
+    world: &World,
+    entity: wit::types::EntityId,
+) -> anyhow::Result<Option<wit::types::Vec3>> {
+    Ok(world
+        .get(entity.from_bindgen(), animation_root_motion_delta())
+        .ok()
+        .map(|delta| delta.into_bindgen()))
+}
+
 pub fn exists(world: &World, entity: wit::types::EntityId) -> anyhow::Result<bool> {
     Ok(world.exists(entity.from_bindgen()))
 }
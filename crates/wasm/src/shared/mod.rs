@@ -11,12 +11,13 @@ pub mod wit;
 
 use std::sync::Arc;
 
+use ambient_animation::animation_events;
 use ambient_core::async_ecs::async_run;
 use ambient_ecs::{
     dont_despawn_on_unload, generated::messages, query, world_events, Entity, EntityId, FnSystem,
     Message, SystemGroup, World, WorldEventReader,
 };
-use ambient_physics::{collider_loads, collisions};
+use ambient_physics::{collider_loads, collisions, contact_events, trigger_events, ContactPhase};
 use ambient_project::Identifier;
 use itertools::Itertools;
 pub use module::*;
@@ -28,7 +29,9 @@ mod internal {
         components, Debuggable, Description, EntityId, Networked, Resource, Store, World,
     };
 
-    use super::{MessageType, ModuleBytecode, ModuleErrors, ModuleState, ModuleStateArgs};
+    use super::{
+        MessageType, ModuleBytecode, ModuleErrors, ModuleLimits, ModuleState, ModuleStateArgs,
+    };
 
     components!("wasm::shared", {
         @[Networked, Store, Debuggable]
@@ -42,6 +45,8 @@ mod internal {
         module_enabled: bool,
         @[Networked, Store, Debuggable]
         module_errors: ModuleErrors,
+        @[Networked, Store, Debuggable, Description["The resource limits of this module. Uses the default limits if not attached."]]
+        module_limits: ModuleLimits,
         @[Networked, Debuggable, Description["The ID of the module on the \"other side\" of this module, if available. (e.g. serverside module to clientside module)."]]
         remote_paired_id: EntityId,
 
@@ -53,7 +58,7 @@ mod internal {
 }
 pub use internal::{
     client_bytecode_from_url, messenger, module, module_bytecode, module_enabled, module_errors,
-    module_state, module_state_maker, remote_paired_id,
+    module_limits, module_state, module_state_maker, remote_paired_id,
 };
 
 use self::message::Source;
@@ -98,6 +103,20 @@ pub fn systems() -> SystemGroup {
                     }
                 },
             ),
+            query(module_bytecode().changed()).to_system(move |q, world, qs, _| {
+                profiling::scope!("WASM module bytecode updates");
+                // Running modules whose bytecode has been replaced (e.g. by hot-reloading) are restarted with it
+                let modules = q
+                    .iter(world, qs)
+                    .filter(|(id, _)| world.has_component(*id, module_state()))
+                    .filter(|(id, _)| world.get(*id, module_enabled()).unwrap_or(false))
+                    .map(|(id, bytecode)| (id, Some(bytecode.clone())))
+                    .collect_vec();
+
+                for (id, bytecode) in modules {
+                    reload(world, id, bytecode);
+                }
+            }),
             Box::new(FnSystem::new(move |world, _| {
                 profiling::scope!("WASM module app events");
                 let events = app_events_reader
@@ -137,6 +156,69 @@ pub fn systems() -> SystemGroup {
                         .unwrap();
                 }
             })),
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("WASM module contact events");
+                let events = match world.resource_opt(contact_events()) {
+                    Some(events) => events.lock().clone(),
+                    None => return,
+                };
+                for event in events {
+                    let ids = event.entities.to_vec();
+                    let positions = event.points.iter().map(|p| p.position).collect_vec();
+                    let normals = event.points.iter().map(|p| p.normal).collect_vec();
+                    let impulses = event.points.iter().map(|p| p.impulse).collect_vec();
+                    match event.phase {
+                        ContactPhase::Begin => {
+                            messages::ContactBegin::new(ids, impulses, normals, positions)
+                                .run(world, None)
+                                .unwrap()
+                        }
+                        ContactPhase::Persist => {
+                            messages::ContactPersist::new(ids, impulses, normals, positions)
+                                .run(world, None)
+                                .unwrap()
+                        }
+                        ContactPhase::End => {
+                            messages::ContactEnd::new(ids).run(world, None).unwrap()
+                        }
+                    }
+                }
+            })),
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("WASM module trigger events");
+                let events = match world.resource_opt(trigger_events()) {
+                    Some(events) => events.lock().clone(),
+                    None => return,
+                };
+                for event in events {
+                    if event.entered {
+                        messages::TriggerEnter::new(event.other, event.trigger)
+                            .run(world, None)
+                            .unwrap();
+                    } else {
+                        messages::TriggerExit::new(event.other, event.trigger)
+                            .run(world, None)
+                            .unwrap();
+                    }
+                }
+            })),
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("WASM module animation events");
+                let events = match world.resource_opt(animation_events()) {
+                    Some(events) => events.clone(),
+                    None => return,
+                };
+                for event in events {
+                    messages::AnimationEvent::new(
+                        event.clip_url,
+                        event.entity,
+                        event.name,
+                        event.weight,
+                    )
+                    .run(world, None)
+                    .unwrap();
+                }
+            })),
             Box::new(FnSystem::new(move |world, _| {
                 profiling::scope!("WASM module collider loads");
                 // trigger collider loads
@@ -209,6 +291,9 @@ fn load(world: &mut World, module_id: EntityId, component_bytecode: &[u8]) {
 
     let async_run = world.resource(async_run()).clone();
     let component_bytecode = component_bytecode.to_vec();
+    let limits = world
+        .get_cloned(module_id, module_limits())
+        .unwrap_or_default();
 
     // Spawn the module on another thread to ensure that it does not block the main thread during compilation.
     std::thread::spawn(move || {
@@ -225,6 +310,7 @@ fn load(world: &mut World, module_id: EntityId, component_bytecode: &[u8]) {
                     messenger(world, module_id, MessageType::Stderr, msg);
                 }),
                 id: module_id,
+                limits,
             })
         });
 
@@ -290,10 +376,20 @@ fn run(
         return;
     }
 
-    let result =
-        run_and_catch_panics(|| state.run(world, message_source, message_name, message_data));
+    let mut limit_exceeded = false;
+    let result = run_and_catch_panics(|| {
+        let result = state.run(world, message_source, message_name, message_data);
+        limit_exceeded = matches!(&result, Err(err) if err.is::<ModuleLimitExceeded>());
+        result
+    });
 
     if let Err(message) = result {
+        // The module can't be trusted to handle any further messages, including its unload message,
+        // so stop it immediately
+        if limit_exceeded {
+            remove_state(world, id, "exceeded its resource limits");
+        }
+
         update_errors(world, &[(id, message)]);
     }
 }
@@ -307,6 +403,16 @@ pub(crate) fn unload(world: &mut World, module_id: EntityId, reason: &str) {
         .run(world, Some(module_id))
         .unwrap();
 
+    remove_state(world, module_id, reason);
+}
+
+/// Stops the module without notifying it, removing its state and the entities it spawned
+fn remove_state(world: &mut World, module_id: EntityId, reason: &str) {
+    // The module may have been stopped while handling its unload message
+    if !world.has_component(module_id, module_state()) {
+        return;
+    }
+
     let spawned_entities = world
         .get_mut(module_id, module_state())
         .map(|sms| sms.drain_spawned_entities())
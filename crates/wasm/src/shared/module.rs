@@ -1,4 +1,4 @@
-use std::{any::Any, collections::HashSet, sync::Arc};
+use std::{any::Any, collections::HashSet, sync::Arc, time::Duration};
 
 use ambient_ecs::{EntityId, World};
 use data_encoding::BASE64;
@@ -64,9 +64,100 @@ impl<'de> Deserialize<'de> for ModuleBytecode {
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct ModuleErrors(pub Vec<String>);
 
+/// How often the execution time of modules is checked
+pub(crate) const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Limits the resources a module can use, so that a misbehaving module can't stall or exhaust the host.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleLimits {
+    /// How long a single call into the module can run for before it is interrupted
+    pub execution_time: Duration,
+    /// The maximum size of the module's memory, in bytes
+    pub memory_size: usize,
+}
+impl Default for ModuleLimits {
+    fn default() -> Self {
+        Self {
+            execution_time: Duration::from_secs(1),
+            memory_size: 512 * 1024 * 1024,
+        }
+    }
+}
+impl ModuleLimits {
+    /// The limits set in the `[limits]` section of the project manifest, using the defaults for
+    /// the limits that aren't set
+    pub fn from_manifest(limits: &ambient_project::Limits) -> Self {
+        let default = Self::default();
+        Self {
+            execution_time: limits
+                .execution_time_ms
+                .map_or(default.execution_time, Duration::from_millis),
+            memory_size: limits
+                .memory_mb
+                .map_or(default.memory_size, |mb| mb as usize * 1024 * 1024),
+        }
+    }
+}
+
+/// Returned when a module is stopped for exceeding its [`ModuleLimits`]
+#[derive(Debug, thiserror::Error)]
+pub enum ModuleLimitExceeded {
+    #[error("Module exceeded its execution time limit of {0:?}")]
+    ExecutionTime(Duration),
+    #[error("Module exceeded its memory limit of {0} bytes")]
+    Memory(usize),
+}
+
 struct WasmContext<Bindings: BindingsBound> {
     wasi: wasmtime_wasi::WasiCtx,
     bindings: Bindings,
+    limiter: ModuleLimiter,
+}
+
+impl<Bindings: BindingsBound> AsRef<ModuleLimiter> for WasmContext<Bindings> {
+    fn as_ref(&self) -> &ModuleLimiter {
+        &self.limiter
+    }
+}
+impl<Bindings: BindingsBound> AsMut<ModuleLimiter> for WasmContext<Bindings> {
+    fn as_mut(&mut self) -> &mut ModuleLimiter {
+        &mut self.limiter
+    }
+}
+
+/// Enforces the memory limit of a module, and remembers whether it was hit
+struct ModuleLimiter {
+    memory_size: usize,
+    memory_exceeded: bool,
+}
+impl ModuleLimiter {
+    fn new(limits: &ModuleLimits) -> Self {
+        Self {
+            memory_size: limits.memory_size,
+            memory_exceeded: false,
+        }
+    }
+}
+impl wasmtime::ResourceLimiter for ModuleLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        let allowed = desired <= self.memory_size;
+        self.memory_exceeded |= !allowed;
+        Ok(allowed)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: u32,
+        _desired: u32,
+        _maximum: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        Ok(true)
+    }
 }
 
 pub trait ModuleStateBehavior: Sync + Send {
@@ -89,6 +180,7 @@ pub struct ModuleStateArgs<'a> {
     pub stdout_output: Messenger,
     pub stderr_output: Messenger,
     pub id: EntityId,
+    pub limits: ModuleLimits,
 }
 
 #[derive(Clone)]
@@ -107,6 +199,7 @@ impl ModuleState {
             stdout_output,
             stderr_output,
             id,
+            limits,
         } = args;
 
         Ok(Self {
@@ -115,6 +208,7 @@ impl ModuleState {
                 stdout_output,
                 stderr_output,
                 bindings(id),
+                limits,
             )?)),
         })
     }
@@ -159,6 +253,8 @@ struct ModuleStateInnerImpl<Bindings: BindingsBound> {
 
     stdout_consumer: WasiOutputStreamConsumer,
     stderr_consumer: WasiOutputStreamConsumer,
+
+    limits: ModuleLimits,
 }
 
 impl<Bindings: BindingsBound> std::fmt::Debug for ModuleStateInnerImpl<Bindings> {
@@ -172,6 +268,7 @@ impl<Bindings: BindingsBound> ModuleStateInnerImpl<Bindings> {
         stdout_output: Box<dyn Fn(&World, &str) + Sync + Send>,
         stderr_output: Box<dyn Fn(&World, &str) + Sync + Send>,
         bindings: Bindings,
+        limits: ModuleLimits,
     ) -> anyhow::Result<Self> {
         let engine = &*crate::WASMTIME_ENGINE;
 
@@ -185,8 +282,11 @@ impl<Bindings: BindingsBound> ModuleStateInnerImpl<Bindings> {
                     .stderr(stderr_output)
                     .build(),
                 bindings,
+                limiter: ModuleLimiter::new(&limits),
             },
         );
+        store.limiter(|x| &mut x.limiter);
+        reset_limits(&mut store, &limits);
 
         let mut linker = wasmtime::component::Linker::<WasmContext<Bindings>>::new(engine);
         wasmtime_wasi::command::add_to_linker(&mut linker, |x| &mut x.wasi)?;
@@ -198,7 +298,11 @@ impl<Bindings: BindingsBound> ModuleStateInnerImpl<Bindings> {
             wit::Bindings::instantiate(&mut store, &component, &linker)?;
 
         // Initialise the runtime.
-        guest_bindings.guest().call_init(&mut store)?;
+        reset_limits(&mut store, &limits);
+        guest_bindings
+            .guest()
+            .call_init(&mut store)
+            .map_err(|err| check_limits(&store, &limits, err))?;
 
         Ok(Self {
             store,
@@ -207,9 +311,35 @@ impl<Bindings: BindingsBound> ModuleStateInnerImpl<Bindings> {
 
             stdout_consumer,
             stderr_consumer,
+
+            limits,
         })
     }
 }
+
+/// Allows the next call into the module to run for the module's execution time limit
+fn reset_limits<T: AsMut<ModuleLimiter>>(store: &mut wasmtime::Store<T>, limits: &ModuleLimits) {
+    let ticks = limits.execution_time.as_nanos() / EPOCH_TICK.as_nanos();
+    store.set_epoch_deadline((ticks as u64).max(1));
+    store.data_mut().as_mut().memory_exceeded = false;
+}
+
+/// Replaces the error of a failed call with [`ModuleLimitExceeded`] if the module hit its limits
+fn check_limits<T: AsRef<ModuleLimiter>>(
+    store: &wasmtime::Store<T>,
+    limits: &ModuleLimits,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    if err.downcast_ref::<wasmtime::Trap>() == Some(&wasmtime::Trap::Interrupt) {
+        ModuleLimitExceeded::ExecutionTime(limits.execution_time).into()
+    } else if store.data().as_ref().memory_exceeded {
+        // The guest is likely to trap when it fails to allocate, so attribute the error to the limit
+        ModuleLimitExceeded::Memory(limits.memory_size).into()
+    } else {
+        err
+    }
+}
+
 impl<Bindings: BindingsBound> ModuleStateBehavior for ModuleStateInnerImpl<Bindings> {
     fn run(
         &mut self,
@@ -222,6 +352,7 @@ impl<Bindings: BindingsBound> ModuleStateBehavior for ModuleStateInnerImpl<Bindi
 
         let time = ambient_app::get_time_since_app_start(world).as_secs_f32();
 
+        reset_limits(&mut self.store, &self.limits);
         let result = self.guest_bindings.guest().call_exec(
             &mut self.store,
             time,
@@ -234,6 +365,7 @@ impl<Bindings: BindingsBound> ModuleStateBehavior for ModuleStateInnerImpl<Bindi
             message_name,
             message_data,
         );
+        let result = result.map_err(|err| check_limits(&self.store, &self.limits, err));
 
         self.store.data_mut().bindings.clear_world();
 
@@ -321,3 +453,73 @@ impl WasiOutputStreamConsumer {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    impl AsRef<ModuleLimiter> for ModuleLimiter {
+        fn as_ref(&self) -> &ModuleLimiter {
+            self
+        }
+    }
+    impl AsMut<ModuleLimiter> for ModuleLimiter {
+        fn as_mut(&mut self) -> &mut ModuleLimiter {
+            self
+        }
+    }
+
+    /// Calls the `run` export of the module, within the limits
+    fn run(module: &str, limits: &ModuleLimits) -> anyhow::Result<()> {
+        let engine = &*crate::WASMTIME_ENGINE;
+        let mut store = wasmtime::Store::new(engine, ModuleLimiter::new(limits));
+        store.limiter(|x| x);
+        reset_limits(&mut store, limits);
+
+        let module = wasmtime::Module::new(engine, module)?;
+        let instance = wasmtime::Instance::new(&mut store, &module, &[])?;
+        let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+
+        reset_limits(&mut store, limits);
+        run.call(&mut store, ())
+            .map_err(|err| check_limits(&store, limits, err))
+    }
+
+    #[test]
+    fn execution_time_limit() {
+        let limits = ModuleLimits {
+            execution_time: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let err = run(r#"(module (func (export "run") (loop br 0)))"#, &limits).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(ModuleLimitExceeded::ExecutionTime(_))
+        ));
+    }
+
+    #[test]
+    fn memory_limit() {
+        // Grows the memory by `pages` of 64 KiB, and traps if that fails
+        let module = |pages: u32| {
+            format!(
+                r#"(module
+                    (memory 1)
+                    (func (export "run")
+                        (if (i32.eq (memory.grow (i32.const {pages})) (i32.const -1))
+                            (then unreachable))))"#
+            )
+        };
+        let limits = ModuleLimits {
+            memory_size: 4 * 64 * 1024,
+            ..Default::default()
+        };
+
+        run(&module(3), &limits).unwrap();
+        let err = run(&module(4), &limits).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(ModuleLimitExceeded::Memory(_))
+        ));
+    }
+}
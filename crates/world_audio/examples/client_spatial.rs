@@ -13,7 +13,7 @@ use ambient_primitives::Cube;
 use ambient_renderer::{cast_shadows, color};
 use ambient_std::math::SphericalCoords;
 use ambient_ui::World;
-use ambient_world_audio::{audio_emitter, audio_listener, play_sound_on_entity, systems::setup_audio};
+use ambient_world_audio::{audio_emitter, audio_listener, play_sound_on_entity, systems::setup_audio, AudioBus};
 use glam::{vec3, vec4, Mat4, Vec3};
 use parking_lot::Mutex;
 
@@ -29,6 +29,7 @@ fn spawn_emitters(world: &mut World) {
             amplitude: 5.0,
             attenuation: Attenuation::InversePoly { quad: 0.1, lin: 0.0, constant: 1.0 },
             pos,
+            ..Default::default()
         }));
 
         let id = Cube
@@ -40,7 +41,7 @@ fn spawn_emitters(world: &mut World) {
             .with(audio_emitter(), emitter)
             .spawn_static(world);
 
-        play_sound_on_entity(world, id, track.decode().repeat()).expect("Failed to play sound");
+        play_sound_on_entity(world, id, AudioBus::Sfx, track.decode().repeat()).expect("Failed to play sound");
     }
 }
 
@@ -10,6 +10,10 @@ pub enum Error {
     MissingEffect(String),
     #[error("There are no more available sinks")]
     NoAvailableSink,
+    #[error("The sound has a spatial node, but is not played on an audio emitter")]
+    NoSpatialContext,
+    #[error("Delays can be at most 1023 frames, but got {0} frames")]
+    DelayTooLong(f32),
     #[error(transparent)]
     AudioError(#[from] Arc<ambient_audio::Error>),
 }
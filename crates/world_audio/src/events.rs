@@ -1,7 +1,7 @@
 use ambient_core::asset_cache;
 use ambient_ecs::{EntityId, World};
 
-use crate::{play_sound_on_entity, AudioNode, AudioSeed};
+use crate::{audio_mixer, mixer_bus, play_sound_on_entity, spatial_context, AudioBus, AudioNode, AudioSeed};
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 /// Plays a sound on an entity. Requires that the entity has an AudioEmitterDef on the server.
 /// Otherwise, the audio is played on a temporary emitter
@@ -11,11 +11,17 @@ pub struct PlayLocalSound {
     /// A human readable label describing what this sound is
     pub label: String,
     pub seed: AudioSeed,
+    /// The bus the sound is mixed on
+    #[serde(default)]
+    pub bus: AudioBus,
 }
 
 pub fn play_local_sound(world: &mut World, event: PlayLocalSound) -> anyhow::Result<()> {
     let assets = world.resource(asset_cache());
-    let source = match event.source.try_build(assets, event.seed).transpose() {
+    let spatial = spatial_context(world, event.id)?;
+    // Graphs with spatial nodes position their sources themselves
+    let is_spatial = event.source.is_spatial();
+    let source = match event.source.try_build(assets, Some(&spatial), event.seed).transpose() {
         Some(source) => source?,
         None => {
             tracing::warn!("Sound {} is not yet loaded", event.label);
@@ -23,7 +29,11 @@ pub fn play_local_sound(world: &mut World, event: PlayLocalSound) -> anyhow::Res
         }
     };
 
-    play_sound_on_entity(world, event.id, source)?;
+    if is_spatial {
+        world.resource(audio_mixer()).play_on(mixer_bus(world, event.bus), source);
+    } else {
+        play_sound_on_entity(world, event.id, event.bus, source)?;
+    }
     Ok(())
 }
 
@@ -1,15 +1,23 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc, time::Duration};
 
-use ambient_audio::{Source, VorbisFromUrl};
+use ambient_audio::{
+    blt::Bpf,
+    hrtf::HrtfLib,
+    source::dynamic_delay::{DynamicDelay, MAX_DELAY},
+    value::Constant,
+    AudioEmitter, AudioListener, BufferedSource, Frame, SampleRate, Source, VorbisFromUrl,
+};
 use ambient_std::{
     self,
     asset_cache::{AssetCache, AsyncAssetKeyExt},
     asset_url::AbsAssetUrl,
 };
+use glam::vec2;
+use parking_lot::Mutex;
 use rand::{thread_rng, Rng, SeedableRng};
 use rand_chacha::ChaCha12Rng;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 /// Textual representation of a node in the audio graph which specifies how to construct a Sound.
@@ -21,6 +29,38 @@ pub enum AudioNode {
         /// Url asset
         url: String,
     },
+    /// Multiplies the amplitude of the input
+    Gain { input: Box<AudioNode>, gain: f32 },
+    /// Plays all inputs at the same time, until the shortest of them ends
+    Mix { inputs: Vec<AudioNode> },
+    /// Plays `left`, and fades over to `right` during the last `duration` of `left`
+    Crossfade { left: Box<AudioNode>, right: Box<AudioNode>, duration: Duration },
+    /// Plays the input over and over again.
+    ///
+    /// With a `crossfade`, the next iteration fades in during the last `crossfade` of the previous one
+    Repeat { input: Box<AudioNode>, crossfade: Option<Duration> },
+    /// Plays the part of the input between `start` and `end`, or until the input ends if there is no `end`
+    Slice { input: Box<AudioNode>, start: Duration, end: Option<Duration> },
+    /// Delays the left and right channels of the input, by at most 1023 frames each; longer delays fail to build
+    Delay { input: Box<AudioNode>, left: Duration, right: Duration },
+    /// Positions the input at the emitter the sound is played on, relative to the listener
+    Spatial { input: Box<AudioNode> },
+    /// Filters out frequencies above `freq`
+    LowPass {
+        input: Box<AudioNode>,
+        freq: f32,
+        /// In dB/octave
+        bandwidth: f32,
+    },
+    /// Filters out frequencies below `freq`
+    HighPass {
+        input: Box<AudioNode>,
+        freq: f32,
+        /// In dB/octave
+        bandwidth: f32,
+    },
+    /// Filters out frequencies outside of `bandwidth` around `freq`
+    BandPass { input: Box<AudioNode>, freq: f32, bandwidth: f32 },
 }
 
 impl Default for AudioNode {
@@ -29,23 +69,202 @@ impl Default for AudioNode {
     }
 }
 
+/// The listener and emitter which [AudioNode::Spatial] nodes are positioned with
+#[derive(Clone)]
+pub struct SpatialContext {
+    pub hrtf_lib: Arc<HrtfLib>,
+    pub listener: Arc<Mutex<AudioListener>>,
+    pub emitter: Arc<Mutex<AudioEmitter>>,
+}
+
 impl AudioNode {
     /// Builds the adapter into a proper source.
     /// If the graph can not immediately be built, it returns None
-    pub fn try_build(self, assets: &AssetCache, _seed: AudioSeed) -> Result<Option<Box<dyn Source>>> {
-        match self {
-            AudioNode::Vorbis { url } => {
-                let track = VorbisFromUrl { url: AbsAssetUrl::parse(url).unwrap() }.peek(assets).transpose()?;
-                match track {
-                    Some(track) => Ok(Some(Box::new(track.decode()))),
-                    None => Ok(None),
+    ///
+    /// `spatial` is required for graphs with [AudioNode::Spatial] nodes
+    pub fn try_build(self, assets: &AssetCache, spatial: Option<&SpatialContext>, seed: AudioSeed) -> Result<Option<Box<dyn Source>>> {
+        let assets = assets.clone();
+        let vorbis = move |url: &str| -> Result<Option<Box<dyn Source>>> {
+            let track = VorbisFromUrl { url: AbsAssetUrl::parse(url).unwrap() }.peek(&assets).transpose()?;
+            Ok(track.map(|track| Box::new(track.decode()) as Box<dyn Source>))
+        };
+        self.build(&vorbis, spatial, seed)
+    }
+
+    /// Like [Self::try_build], with `vorbis` building the sources of the [AudioNode::Vorbis] nodes
+    fn build<F>(self, vorbis: &F, spatial: Option<&SpatialContext>, seed: AudioSeed) -> Result<Option<Box<dyn Source>>>
+    where
+        F: Fn(&str) -> Result<Option<Box<dyn Source>>> + Clone + Send + 'static,
+    {
+        let source: Box<dyn Source> = match self {
+            AudioNode::Identity => Box::new(BufferedSource::new([0.0f32; 0], 1, 44100)),
+            AudioNode::Vorbis { url } => match vorbis(&url)? {
+                Some(track) => track,
+                None => return Ok(None),
+            },
+            AudioNode::Gain { input, gain } => match input.build(vorbis, spatial, seed)? {
+                Some(input) => Box::new(input.gain(gain)),
+                None => return Ok(None),
+            },
+            AudioNode::Mix { inputs } => {
+                let mut mix: Option<Box<dyn Source>> = None;
+                for input in inputs {
+                    let Some(input) = input.build(vorbis, spatial, seed.clone())? else { return Ok(None) };
+                    mix = Some(match mix {
+                        Some(mix) => Box::new(mix.mix(input)),
+                        None => input,
+                    });
                 }
+                match mix {
+                    Some(mix) => mix,
+                    None => return AudioNode::Identity.build(vorbis, spatial, seed),
+                }
+            }
+            AudioNode::Crossfade { left, right, duration } => {
+                match (left.build(vorbis, spatial, seed.clone())?, right.build(vorbis, spatial, seed)?) {
+                    (Some(left), Some(right)) => Box::new(left.crossfade(right, duration)),
+                    _ => return Ok(None),
+                }
+            }
+            AudioNode::Repeat { input, crossfade } => {
+                let Some(first) = input.clone().build(vorbis, spatial, seed.clone())? else { return Ok(None) };
+                let (vorbis, spatial) = (vorbis.clone(), spatial.cloned());
+                // The sources are not `Clone`, so each iteration is built anew
+                let rebuild = move || input.clone().build(&vorbis, spatial.as_ref(), seed.clone()).ok().flatten();
+                Box::new(Looping::new(first, Box::new(rebuild), crossfade.unwrap_or_default()))
             }
-            _ => unimplemented!(),
+            AudioNode::Slice { input, start, end } => match input.build(vorbis, spatial, seed)? {
+                Some(input) => match end {
+                    Some(end) => Box::new(input.slice(start..end)),
+                    None => Box::new(input.slice(start..)),
+                },
+                None => return Ok(None),
+            },
+            AudioNode::Delay { input, left, right } => match input.build(vorbis, spatial, seed)? {
+                Some(input) => {
+                    let frames = vec2(left.as_secs_f32(), right.as_secs_f32()) * input.sample_rate() as f32;
+                    if frames.max_element() > MAX_DELAY {
+                        return Err(Error::DelayTooLong(frames.max_element()));
+                    }
+                    let mut delay = DynamicDelay::new(input);
+                    delay.delay = frames;
+                    Box::new(delay)
+                }
+                None => return Ok(None),
+            },
+            AudioNode::Spatial { input } => {
+                let spatial = spatial.ok_or(Error::NoSpatialContext)?;
+                match input.build(vorbis, Some(spatial), seed)? {
+                    Some(input) => Box::new(input.spatial(&spatial.hrtf_lib, spatial.listener.clone(), spatial.emitter.clone())),
+                    None => return Ok(None),
+                }
+            }
+            AudioNode::LowPass { input, freq, bandwidth } => match input.build(vorbis, spatial, seed)? {
+                Some(input) => Box::new(input.low_pass(freq, bandwidth)),
+                None => return Ok(None),
+            },
+            AudioNode::HighPass { input, freq, bandwidth } => match input.build(vorbis, spatial, seed)? {
+                Some(input) => Box::new(input.high_pass(freq, bandwidth)),
+                None => return Ok(None),
+            },
+            AudioNode::BandPass { input, freq, bandwidth } => match input.build(vorbis, spatial, seed)? {
+                Some(input) => Box::new(input.blt(Constant(Bpf { freq, bandwidth }))),
+                None => return Ok(None),
+            },
+        };
+
+        Ok(Some(source))
+    }
+
+    /// Returns true if the graph positions any of its sources with an [AudioNode::Spatial] node
+    pub fn is_spatial(&self) -> bool {
+        match self {
+            AudioNode::Identity | AudioNode::Vorbis { .. } => false,
+            AudioNode::Spatial { .. } => true,
+            AudioNode::Mix { inputs } => inputs.iter().any(|input| input.is_spatial()),
+            AudioNode::Crossfade { left, right, .. } => left.is_spatial() || right.is_spatial(),
+            AudioNode::Gain { input, .. }
+            | AudioNode::Repeat { input, .. }
+            | AudioNode::Slice { input, .. }
+            | AudioNode::Delay { input, .. }
+            | AudioNode::LowPass { input, .. }
+            | AudioNode::HighPass { input, .. }
+            | AudioNode::BandPass { input, .. } => input.is_spatial(),
         }
     }
 }
 
+/// Plays the iterations returned by `rebuild` one after another, fading in the next iteration during the last `fade` frames
+/// of the current one
+struct Looping {
+    rebuild: Box<dyn FnMut() -> Option<Box<dyn Source>> + Send>,
+    current: Box<dyn Source>,
+    /// The number of frames in the current iteration, and how many of them have been played
+    count: Option<u64>,
+    pos: u64,
+    /// The iteration fading in, and how many of its frames have been played
+    next: Option<Box<dyn Source>>,
+    next_pos: u64,
+    fade: u64,
+}
+
+impl Looping {
+    fn new(first: Box<dyn Source>, rebuild: Box<dyn FnMut() -> Option<Box<dyn Source>> + Send>, fade: Duration) -> Self {
+        let fade = (first.sample_rate() * fade.as_nanos() as u64) / 1_000_000_000;
+        Self { rebuild, count: first.sample_count(), current: first, pos: 0, next: None, next_pos: 0, fade }
+    }
+}
+
+impl Source for Looping {
+    fn next_sample(&mut self) -> Option<Frame> {
+        let sample = match self.current.next_sample() {
+            Some(sample) => sample,
+            None => {
+                // Continue with the iteration which is fading in, or start a new one
+                self.pos = std::mem::take(&mut self.next_pos);
+                self.current = match self.next.take() {
+                    Some(next) => next,
+                    None => (self.rebuild)()?,
+                };
+                self.count = self.current.sample_count();
+                // An iteration without any frames would never end the loop
+                self.current.next_sample()?
+            }
+        };
+
+        let start = self.count.map(|count| count.saturating_sub(self.fade));
+        let sample = match (start, self.count) {
+            (Some(start), Some(count)) if self.fade > 0 && self.pos >= start => {
+                if self.next.is_none() {
+                    self.next = (self.rebuild)();
+                }
+                let next = self.next.as_mut().and_then(|next| next.next_sample());
+                if next.is_some() {
+                    self.next_pos += 1;
+                }
+                let t = (self.pos - start + 1) as f32 / (count - start + 1) as f32;
+                sample.lerp(next.unwrap_or_default(), t)
+            }
+            _ => sample,
+        };
+
+        self.pos += 1;
+        Some(sample)
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.current.sample_rate()
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 /// The seed for deterministic random state for replicating a sound effect on many clients
 pub struct AudioSeed {
@@ -63,3 +282,76 @@ impl Default for AudioSeed {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the [AudioNode::Vorbis] nodes from mono buffers with a sample rate of 4, so each frame is 250 ms
+    fn buffer(url: &str) -> Result<Option<Box<dyn Source>>> {
+        let samples: &'static [f32] = match url {
+            "short" => &[1.0, 2.0],
+            "long" => &[10.0, 20.0, 30.0, 40.0],
+            _ => return Ok(None),
+        };
+        Ok(Some(Box::new(BufferedSource::new(samples, 1, 4))))
+    }
+
+    fn vorbis(url: &str) -> Box<AudioNode> {
+        Box::new(AudioNode::Vorbis { url: url.to_string() })
+    }
+
+    fn frames(node: AudioNode, max: usize) -> Vec<f32> {
+        let mut source = node.build(&buffer, None, AudioSeed::new()).unwrap().unwrap();
+        std::iter::from_fn(|| source.next_sample()).take(max).map(|frame| frame.x).collect()
+    }
+
+    fn assert_frames(actual: Vec<f32>, expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        assert!(actual.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-4), "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn repeat() {
+        assert_frames(frames(AudioNode::Repeat { input: vorbis("short"), crossfade: None }, 5), &[1.0, 2.0, 1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn repeat_crossfade() {
+        // The last two frames of each iteration fade over to the first two of the next one
+        let node = AudioNode::Repeat { input: vorbis("long"), crossfade: Some(Duration::from_millis(500)) };
+        let fade_in = [30.0 + (10.0 - 30.0) / 3.0, 40.0 + (20.0 - 40.0) * 2.0 / 3.0];
+        assert_frames(frames(node, 6), &[10.0, 20.0, fade_in[0], fade_in[1], fade_in[0], fade_in[1]]);
+    }
+
+    #[test]
+    fn mix_unequal_lengths() {
+        assert_frames(frames(AudioNode::Mix { inputs: vec![*vorbis("long"), *vorbis("short")] }, 10), &[11.0, 22.0]);
+    }
+
+    #[test]
+    fn slice() {
+        let node = |end| AudioNode::Slice { input: vorbis("long"), start: Duration::from_millis(250), end };
+        assert_frames(frames(node(Some(Duration::from_millis(750))), 10), &[20.0, 30.0]);
+        assert_frames(frames(node(None), 10), &[20.0, 30.0, 40.0]);
+    }
+
+    #[test]
+    fn pending_input() {
+        let node = AudioNode::Gain { input: vorbis("not loaded yet"), gain: 2.0 };
+        assert!(node.build(&buffer, None, AudioSeed::new()).unwrap().is_none());
+    }
+
+    #[test]
+    fn spatial_without_emitter() {
+        let node = AudioNode::Spatial { input: vorbis("short") };
+        assert!(matches!(node.build(&buffer, None, AudioSeed::new()), Err(Error::NoSpatialContext)));
+    }
+
+    #[test]
+    fn delay_too_long() {
+        let node = |secs| AudioNode::Delay { input: vorbis("long"), left: Duration::from_secs(secs), right: Duration::ZERO };
+        assert!(node(255).build(&buffer, None, AudioSeed::new()).is_ok());
+        assert!(matches!(node(256).build(&buffer, None, AudioSeed::new()), Err(Error::DelayTooLong(_))));
+    }
+}
@@ -1,7 +1,10 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use ambient_audio::{hrtf::HrtfLib, Attenuation, AudioEmitter, AudioListener, AudioMixer, Source, Sound};
-use ambient_ecs::{components, query, EntityId, Resource, World};
+use ambient_audio::{
+    hrtf::HrtfLib, Attenuation, AudioEmitter, AudioListener, AudioMixer, BusId, Ducking, OfflineStream, Sound, SoundControl, Source,
+};
+use ambient_core::{camera::get_active_camera, main_scene, player::local_user_id, transform::local_to_world};
+use ambient_ecs::{components, query, Debuggable, Description, EntityId, Name, Networked, Resource, World};
 use ambient_element::ElementComponentExt;
 use ambient_std::{cb, Cb};
 use ambient_ui::{
@@ -10,11 +13,13 @@ use ambient_ui::{
 };
 use anyhow::Context;
 use derive_more::{Deref, DerefMut, From, Into};
-use glam::{vec2, vec4};
+use glam::{vec2, vec4, Vec3};
 use itertools::Itertools;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
+use crate::{systems::Y_UP_LHS, SpatialContext};
+
 components!("audio", {
     @[Resource]
     hrtf_lib: Arc<HrtfLib>,
@@ -24,10 +29,102 @@ components!("audio", {
     audio_sender: Arc<flume::Sender<AudioMessage>>,
     @[Resource]
     audio_mixer: AudioMixer,
+    @[Resource]
+    audio_buses: AudioBuses,
+    /// Mixes the audio by simulated time when there is no audio device, such as on headless clients
+    @[Resource]
+    offline_audio_stream: Arc<Mutex<OfflineStream>>,
+    /// The simulated time the offline audio stream is advanced by every frame
+    @[Resource, Debuggable]
+    offline_audio_step: Duration,
+
+    @[Resource, Debuggable]
+    master_volume: f32,
+    @[Resource, Debuggable]
+    music_volume: f32,
+    @[Resource, Debuggable]
+    sfx_volume: f32,
+    @[Resource, Debuggable]
+    voice_volume: f32,
+    @[Resource, Debuggable]
+    ui_volume: f32,
+    /// The fraction of a sound which passes through each physics collider between its emitter and the listener.
+    ///
+    /// Set on the server, which simulates the physics. Sounds are only occluded while this is set
+    @[Resource, Debuggable]
+    audio_occlusion_transmission: f32,
+    @[Debuggable, Networked, Name["Audio occlusion"], Description["How much the sounds of this entity are occluded by the physics colliders between it and the nearest camera, from 0 to 1. Computed by the server while the `audio_occlusion_transmission` resource is set; add it to the entities whose sounds should be occluded."]]
+    audio_occlusion: f32,
 });
 
+/// The buses of the [AudioMixer] which sounds are routed into. Their volumes are set from the `*_volume` resources
+#[derive(Debug, Clone, Copy)]
+pub struct AudioBuses {
+    pub master: BusId,
+    pub music: BusId,
+    pub sfx: BusId,
+    pub voice: BusId,
+    pub ui: BusId,
+}
+
+impl AudioBuses {
+    /// Adds the buses to the mixer, with the music ducked while voices are playing
+    pub fn new(mixer: &AudioMixer) -> Self {
+        let master = mixer.master_bus();
+        let buses = Self {
+            master,
+            music: mixer.add_bus("music", master),
+            sfx: mixer.add_bus("sfx", master),
+            voice: mixer.add_bus("voice", master),
+            ui: mixer.add_bus("ui", master),
+        };
+        mixer.set_bus_ducking(
+            buses.music,
+            Some(Ducking {
+                sidechain: buses.voice,
+                threshold: 0.05,
+                volume: 0.3,
+                attack: Duration::from_millis(100),
+                release: Duration::from_millis(500),
+            }),
+        );
+        buses
+    }
+
+    /// The bus which sounds of the kind are mixed on
+    pub fn get(&self, bus: AudioBus) -> BusId {
+        match bus {
+            AudioBus::Music => self.music,
+            AudioBus::Sfx => self.sfx,
+            AudioBus::Voice => self.voice,
+            AudioBus::Ui => self.ui,
+        }
+    }
+}
+
+/// The kinds of sounds, each of which is mixed on its own bus of the [AudioBuses]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AudioBus {
+    Music,
+    #[default]
+    Sfx,
+    Voice,
+    Ui,
+}
+
+/// The bus sounds of the kind are played on. Without [AudioBuses] this is a null bus, so they are played on the master bus
+pub fn mixer_bus(world: &World, bus: AudioBus) -> BusId {
+    world.resource_opt(audio_buses()).map(|buses| buses.get(bus)).unwrap_or_default()
+}
+
+/// The bus sound effects are played on
+pub fn sfx_bus(world: &World) -> BusId {
+    mixer_bus(world, AudioBus::Sfx)
+}
+
 pub enum AudioMessage {
-    Track(Arc<ambient_audio::track::Track>, bool, f32)
+    /// Plays the track, looping or not, with an amplitude, controlled by the [SoundControl], on the bus
+    Track(Arc<ambient_audio::track::Track>, bool, f32, SoundControl, BusId),
 }
 
 /// TODO: hook this into the Attenuation inside ambient_audio
@@ -74,13 +171,44 @@ fn get_audio_listener(world: &World) -> anyhow::Result<&Arc<Mutex<AudioListener>
     Ok(listener)
 }
 
+/// Returns what [crate::AudioNode::Spatial] nodes need to emit from the entity
+pub fn spatial_context(world: &World, id: EntityId) -> anyhow::Result<SpatialContext> {
+    let hrtf_lib = world.resource(hrtf_lib()).clone();
+    let emitter = world.get_ref(id, audio_emitter()).context("No audio emitter on entity")?.clone();
+    let listener = get_audio_listener(world)?.clone();
+
+    Ok(SpatialContext { hrtf_lib, listener, emitter })
+}
+
 /// Makes a sound source emit from the entity
-pub fn play_sound_on_entity<S: 'static + Source>(world: &World, id: EntityId, source: S) -> anyhow::Result<Sound> {
-    let hrtf_lib = world.resource(hrtf_lib());
+pub fn play_sound_on_entity<S: 'static + Source>(world: &World, id: EntityId, bus: AudioBus, source: S) -> anyhow::Result<Sound> {
     let mixer = world.resource(audio_mixer());
-    let emitter = world.get_ref(id, audio_emitter()).context("No audio emitter on entity")?;
+    let SpatialContext { hrtf_lib, listener, emitter } = spatial_context(world, id)?;
+
+    Ok(mixer.play_on(mixer_bus(world, bus), source.spatial(&hrtf_lib, listener, emitter)))
+}
+
+/// Makes an already playing sound emit from the entity, from now on.
+///
+/// Adds an audio emitter to the entity if it has none, and an audio listener to the active camera if there is no listener
+pub fn attach_sound_to_entity(world: &mut World, control: &SoundControl, id: EntityId) -> anyhow::Result<()> {
+    if world.resource_opt(hrtf_lib()).is_none() {
+        anyhow::bail!("Spatial audio is not available");
+    }
 
-    let listener = get_audio_listener(world)?;
+    if !world.has_component(id, audio_emitter()) {
+        let pos = world.get(id, local_to_world()).map(|ltw| ltw.to_scale_rotation_translation().2).unwrap_or_default();
+        world.add_component(id, audio_emitter(), Arc::new(Mutex::new(AudioEmitter { pos, ..Default::default() })))?;
+    }
 
-    Ok(mixer.play(source.spatial(hrtf_lib, listener.clone(), emitter.clone())))
-}
\ No newline at end of file
+    if query(audio_listener()).iter(world, None).next().is_none() {
+        let camera =
+            get_active_camera(world, main_scene(), world.resource_opt(local_user_id())).context("No audio listener or active camera")?;
+        let transform = Y_UP_LHS * world.get(camera, local_to_world()).unwrap_or_default();
+        world.add_component(camera, audio_listener(), Arc::new(Mutex::new(AudioListener::new(transform, Vec3::X * 0.3))))?;
+    }
+
+    let SpatialContext { hrtf_lib, listener, emitter } = spatial_context(world, id)?;
+    control.attach(hrtf_lib, listener, emitter);
+    Ok(())
+}
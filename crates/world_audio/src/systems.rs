@@ -1,19 +1,58 @@
-use std::{io::Cursor, sync::Arc};
+use std::{io::Cursor, sync::Arc, time::Duration};
 
-use ambient_audio::{hrtf::HrtfLib, AudioMixer};
-use ambient_core::transform::local_to_world;
-use ambient_ecs::{query, SystemGroup, World};
-use glam::{vec4, Mat4};
+use ambient_audio::{hrtf::HrtfLib, AudioMixer, BusEffect, BusId, CustomEffect, ImpulseResponseFromUrl, OfflineStream, SampleRate, Source};
+use ambient_core::{asset_cache, async_ecs::async_run, camera::active_camera, runtime, transform::local_to_world};
+use ambient_ecs::{
+    generated::components::core::audio::{reverb_zone, reverb_zone_radius, reverb_zone_wet},
+    query, FnSystem, SystemGroup, World,
+};
+use ambient_physics::{
+    intersection::{raycast_layers, ALL_COLLISION_LAYERS},
+    main_physics_scene,
+};
+use ambient_std::{asset_cache::AsyncAssetKeyExt, asset_url::AbsAssetUrl, shapes::Ray};
+use glam::{vec4, Mat4, Vec3};
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+use parking_lot::Mutex;
 
-use crate::{audio_emitter, audio_listener, audio_mixer, hrtf_lib};
+use crate::{
+    audio_buses, audio_emitter, audio_listener, audio_mixer, audio_occlusion, audio_occlusion_transmission, hrtf_lib, master_volume,
+    music_volume, offline_audio_step, offline_audio_stream, sfx_volume, ui_volume, voice_volume, AudioBuses,
+};
 
-/// Initializes the HRTF sphere and adds the appropriate resources
+/// The wet mix of reverb zones without [reverb_zone_wet]
+pub const DEFAULT_REVERB_WET: f32 = 0.5;
+
+/// Initializes the HRTF sphere and adds the appropriate resources, including the buses of the mixer and their volumes
 ///
 /// TODO: customizer IR sphere selection
 pub fn setup_audio(world: &mut World, mixer: AudioMixer) -> anyhow::Result<()> {
+    setup_hrtf(world)?;
+    world.add_resource(audio_buses(), AudioBuses::new(&mixer));
+    world.add_resource(audio_mixer(), mixer);
+    for volume in [master_volume(), music_volume(), sfx_volume(), voice_volume(), ui_volume()] {
+        world.add_resource(volume, 1.0);
+    }
+    Ok(())
+}
+
+/// Like [setup_audio], but mixes the audio offline instead of playing it, advanced by `step` every frame.
+///
+/// The step does not follow the frame time, so that the mix is the same on every run. Returns the mixer of the offline stream
+pub fn setup_offline_audio(world: &mut World, sample_rate: SampleRate, step: Duration) -> anyhow::Result<AudioMixer> {
+    let stream = OfflineStream::new(sample_rate);
+    let mixer = stream.mixer().clone();
+    setup_audio(world, mixer.clone())?;
+    world.add_resource(offline_audio_stream(), Arc::new(Mutex::new(stream)));
+    world.add_resource(offline_audio_step(), step);
+    Ok(mixer)
+}
+
+/// Initializes the HRTF sphere, for when the sounds are played on a mixer that is not in the world
+pub fn setup_hrtf(world: &mut World) -> anyhow::Result<()> {
     let hrtf = Arc::new(HrtfLib::load(Cursor::new(include_bytes!("../IRC_1002_C.bin")))?);
     world.add_resource(hrtf_lib(), hrtf);
-    world.add_resource(audio_mixer(), mixer);
     Ok(())
 }
 
@@ -44,6 +83,259 @@ pub fn spatial_audio_systems() -> SystemGroup {
     )
 }
 
+/// Applies the volume resources to the buses of the mixer
+pub fn bus_volume_system() -> FnSystem {
+    FnSystem::new(|world, _| {
+        let (Some(mixer), Some(buses)) = (world.resource_opt(audio_mixer()), world.resource_opt(audio_buses())) else { return };
+        for (bus, volume) in [
+            (buses.master, master_volume()),
+            (buses.music, music_volume()),
+            (buses.sfx, sfx_volume()),
+            (buses.voice, voice_volume()),
+            (buses.ui, ui_volume()),
+        ] {
+            if let Some(&volume) = world.resource_opt(volume) {
+                mixer.set_bus_volume(bus, volume);
+            }
+        }
+    })
+}
+
+/// Advances the offline audio stream, if any, by the [offline_audio_step]
+pub fn offline_audio_system() -> FnSystem {
+    FnSystem::new(|world, _| {
+        if let Some(stream) = world.resource_opt(offline_audio_stream()) {
+            stream.lock().advance(*world.resource(offline_audio_step()));
+        }
+    })
+}
+
+fn listener_position(world: &World) -> Option<Vec3> {
+    let (_, (_, ltw)) = query((audio_listener(), local_to_world())).iter(world, None).next()?;
+    Some(ltw.to_scale_rotation_translation().2)
+}
+
+/// The reverb of the [reverb_zone] the listener is in
+#[derive(Default)]
+struct ActiveReverb {
+    /// The url and wet mix of the zone
+    zone: Option<(String, f32)>,
+    /// The effect of the zone on the sound effects bus, once its impulse response has loaded
+    effect: Option<Arc<CustomEffect>>,
+}
+
+/// Replaces the `current` effect of the bus with `effect`, keeping the other effects of the bus
+fn replace_bus_effect(mixer: &AudioMixer, bus: BusId, current: &mut Option<Arc<CustomEffect>>, effect: Option<Arc<CustomEffect>>) {
+    if current.is_none() && effect.is_none() {
+        return;
+    }
+    let mut effects = mixer.bus_effects(bus);
+    if let Some(current) = current.take() {
+        let current = Arc::as_ptr(&current) as *const ();
+        effects.retain(|e| !matches!(e, BusEffect::Custom(e) if std::ptr::eq(Arc::as_ptr(e) as *const (), current)));
+    }
+    if let Some(effect) = &effect {
+        effects.push(BusEffect::Custom(effect.clone()));
+    }
+    *current = effect;
+    mixer.set_bus_effects(bus, effects);
+}
+
+/// Convolves the sound effects with the impulse response of the nearest [reverb_zone] the listener is within.
+///
+/// The reverb is added to the other effects of the sound effects bus once the impulse response has loaded
+pub fn reverb_zone_system() -> FnSystem {
+    let active = Arc::new(Mutex::new(ActiveReverb::default()));
+    FnSystem::new(move |world, _| {
+        let (Some(mixer), Some(buses)) = (world.resource_opt(audio_mixer()), world.resource_opt(audio_buses())) else { return };
+        let Some(listener_pos) = listener_position(world) else { return };
+
+        let zone = query((reverb_zone(), reverb_zone_radius(), local_to_world()))
+            .iter(world, None)
+            .filter_map(|(id, (url, &radius, ltw))| {
+                let distance = ltw.to_scale_rotation_translation().2.distance(listener_pos);
+                let wet = world.get(id, reverb_zone_wet()).unwrap_or(DEFAULT_REVERB_WET);
+                (distance <= radius).then(|| (distance, (url.clone(), wet)))
+            })
+            .min_by_key(|(distance, _)| OrderedFloat(*distance))
+            .map(|(_, zone)| zone);
+
+        let mut state = active.lock();
+        if zone == state.zone {
+            return;
+        }
+        state.zone = zone.clone();
+        replace_bus_effect(mixer, buses.sfx, &mut state.effect, None);
+
+        let Some((url, wet)) = zone else { return };
+        let key = match AbsAssetUrl::parse(&url) {
+            Ok(url) => ImpulseResponseFromUrl { url, sample_rate: mixer.sample_rate() },
+            Err(err) => {
+                log::error!("Invalid reverb zone url {url:?}: {err:?}");
+                return;
+            }
+        };
+        let assets = world.resource(asset_cache()).clone();
+        let async_run = world.resource(async_run()).clone();
+        let active = active.clone();
+        world.resource(runtime()).spawn(async move {
+            let ir = key.get(&assets).await;
+            async_run.run(move |world| {
+                let ir = match ir {
+                    Ok(ir) => ir,
+                    Err(err) => {
+                        log::error!("Failed to load impulse response {}: {err:?}", key.url);
+                        return;
+                    }
+                };
+                let mut state = active.lock();
+                // The listener may have left the zone while the impulse response was loading
+                if state.zone != Some((url, wet)) {
+                    return;
+                }
+                let (Some(mixer), Some(buses)) = (world.resource_opt(audio_mixer()), world.resource_opt(audio_buses())) else { return };
+                let effect: Arc<CustomEffect> =
+                    Arc::new(move |source: Box<dyn Source>| -> Box<dyn Source> { Box::new(source.convolve(&ir, wet)) });
+                replace_bus_effect(mixer, buses.sfx, &mut state.effect, Some(effect));
+            });
+        });
+    })
+}
+
+/// Occludes the sounds of the entities with [audio_occlusion] by the physics colliders between them and the nearest
+/// camera, while [audio_occlusion_transmission] is set.
+///
+/// Runs on the server, as only worlds which simulate physics can be raycast, and the occlusion is replicated to the clients
+pub fn audio_occlusion_system() -> FnSystem {
+    FnSystem::new(|world, _| {
+        let Some(&transmission) = world.resource_opt(audio_occlusion_transmission()) else { return };
+        if world.resource_opt(main_physics_scene()).is_none() {
+            return;
+        }
+        let cameras = query((active_camera(), local_to_world()))
+            .iter(world, None)
+            .map(|(_, (_, ltw))| ltw.to_scale_rotation_translation().2)
+            .collect_vec();
+
+        let mut changed = Vec::new();
+        for (id, (&old, ltw)) in query((audio_occlusion(), local_to_world())).iter(world, None) {
+            let pos = ltw.to_scale_rotation_translation().2;
+            let Some(listener_pos) = cameras.iter().copied().min_by_key(|camera| OrderedFloat(camera.distance(pos))) else { return };
+            let to_emitter = pos - listener_pos;
+            let distance = to_emitter.length();
+            let occluders = if distance > f32::EPSILON {
+                raycast_layers(world, Ray::new(listener_pos, to_emitter / distance), distance, ALL_COLLISION_LAYERS)
+                    .into_iter()
+                    .filter(|&(hit, _)| hit != id)
+                    .count()
+            } else {
+                0
+            };
+            let occlusion = 1.0 - transmission.clamp(0.0, 1.0).powi(occluders as i32);
+            if occlusion != old {
+                changed.push((id, occlusion));
+            }
+        }
+        for (id, occlusion) in changed {
+            world.set(id, audio_occlusion(), occlusion).ok();
+        }
+    })
+}
+
+/// Applies the [audio_occlusion] computed by the server to the audio emitters
+pub fn apply_audio_occlusion_system() -> SystemGroup {
+    SystemGroup::new(
+        "apply_audio_occlusion",
+        vec![query((audio_emitter(), audio_occlusion())).to_system(|q, world, qs, _| {
+            for (_, (emitter, &occlusion)) in q.iter(world, qs) {
+                emitter.lock().occlusion = occlusion;
+            }
+        })],
+    )
+}
+
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new("Audio occlusion", vec![Box::new(audio_occlusion_system())])
+}
+
 pub fn client_systems() -> SystemGroup {
-    SystemGroup::new("Spatial audio", vec![Box::new(spatial_audio_systems())])
+    SystemGroup::new(
+        "Spatial audio",
+        vec![
+            Box::new(spatial_audio_systems()),
+            Box::new(bus_volume_system()),
+            Box::new(reverb_zone_system()),
+            Box::new(apply_audio_occlusion_system()),
+            Box::new(offline_audio_system()),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ambient_ecs::{Entity, FrameEvent, System};
+    use ambient_physics::{
+        create_server_resources,
+        helpers::update_shape_layers,
+        physx::{physics, physics_shape},
+        wood_physics_material, PxShapeUserData,
+    };
+    use ambient_std::asset_cache::AssetCache;
+    use glam::vec3;
+    use physxx::{AsPxRigidActor, PxBoxGeometry, PxRigidStaticRef, PxShape, PxTransform, PxUserData};
+
+    use super::*;
+
+    #[test]
+    fn occlusion_behind_collider() {
+        ambient_ecs::init_components();
+        ambient_core::init_all_components();
+        ambient_physics::init_all_components();
+        crate::init_components();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut resources = Entity::new();
+        create_server_resources(&AssetCache::new(runtime.handle().clone()), &mut resources);
+        let mut world = World::new("test");
+        world.add_components(world.resource_entity(), resources).unwrap();
+        world.add_resource(audio_occlusion_transmission(), 0.25);
+
+        // A wall between the camera at the origin and the emitter behind it
+        let physics = world.resource(physics()).clone();
+        let material = world.resource(wood_physics_material()).clone();
+        let wall = Entity::new().spawn(&mut world);
+        let shape = PxShape::new(physics.physics, &PxBoxGeometry::new(0.1, 1., 1.), &[&material], Some(true), None);
+        shape.set_user_data(PxShapeUserData { entity: wall, ..Default::default() });
+        let actor = PxRigidStaticRef::new(physics.physics, &PxTransform::from_translation(vec3(2., 0., 0.)));
+        actor.as_rigid_actor().attach_shape(&shape);
+        world.resource(main_physics_scene()).add_actor(&actor);
+        world.add_component(wall, physics_shape(), shape).unwrap();
+        update_shape_layers(&world, wall);
+
+        Entity::new().with(active_camera(), 0.).with(local_to_world(), Mat4::IDENTITY).spawn(&mut world);
+        let emitter = |world: &mut World, pos| {
+            Entity::new().with(audio_occlusion(), 0.).with(local_to_world(), Mat4::from_translation(pos)).spawn(world)
+        };
+        let behind = emitter(&mut world, vec3(4., 0., 0.));
+        let beside = emitter(&mut world, vec3(0., 4., 0.));
+
+        audio_occlusion_system().run(&mut world, &FrameEvent);
+        assert_eq!(world.get(behind, audio_occlusion()).unwrap(), 0.75);
+        assert_eq!(world.get(beside, audio_occlusion()).unwrap(), 0.);
+    }
+
+    #[test]
+    fn reverb_keeps_other_effects() {
+        let mixer = AudioMixer::new(44_100);
+        let bus = mixer.add_bus("sfx", mixer.master_bus());
+        mixer.set_bus_effects(bus, vec![BusEffect::LowPass { freq: 1000., bandwidth: 1. }]);
+
+        let reverb: Arc<CustomEffect> = Arc::new(|source: Box<dyn Source>| source);
+        let mut current = None;
+        replace_bus_effect(&mixer, bus, &mut current, Some(reverb));
+        assert_eq!(mixer.bus_effects(bus).len(), 2);
+        replace_bus_effect(&mixer, bus, &mut current, None);
+        let effects = mixer.bus_effects(bus);
+        assert!(matches!(effects[..], [BusEffect::LowPass { .. }]));
+        assert!(current.is_none());
+    }
 }
@@ -13,4 +13,24 @@ pub enum AudioNode {
         /// Url asset
         url: String,
     },
+    /// Multiplies the amplitude of the input
+    Gain { input: Box<AudioNode>, gain: f32 },
+    /// Plays all inputs at the same time, until the shortest of them ends
+    Mix { inputs: Vec<AudioNode> },
+    /// Plays `left`, and fades over to `right` during the last `duration` of `left`
+    Crossfade { left: Box<AudioNode>, right: Box<AudioNode>, duration: Duration },
+    /// Plays the input over and over again, fading in the next iteration during the last `crossfade` of the previous one
+    Repeat { input: Box<AudioNode>, crossfade: Option<Duration> },
+    /// Plays the part of the input between `start` and `end`
+    Slice { input: Box<AudioNode>, start: Duration, end: Option<Duration> },
+    /// Delays the left and right channels of the input
+    Delay { input: Box<AudioNode>, left: Duration, right: Duration },
+    /// Positions the input at the emitter the sound is played on
+    Spatial { input: Box<AudioNode> },
+    /// Filters out frequencies above `freq`
+    LowPass { input: Box<AudioNode>, freq: f32, bandwidth: f32 },
+    /// Filters out frequencies below `freq`
+    HighPass { input: Box<AudioNode>, freq: f32, bandwidth: f32 },
+    /// Filters out frequencies outside of `bandwidth` around `freq`
+    BandPass { input: Box<AudioNode>, freq: f32, bandwidth: f32 },
 }
@@ -2,6 +2,7 @@
 export type u32 = number;
 export type f32 = number;
 export type Vec2 = [number, number];
+export type IVec2 = [number, number];
 export type Vec3 = [number, number, number];
 export type Vec4 = [number, number, number, number];
 export type Entity = {[component_id: string]: any};
@@ -75,6 +76,11 @@ export type Pipeline = {
       /// Cap this model's textures to SIZE x SIZE.
       /// It is strongly recommended that this is a power of two.
       {"Custom": u32},
+    /// If specified, the textures of this model are compressed for the GPU, which makes them smaller to download and in video memory.
+    texture_compression?: 
+      /// Compress opaque textures to BC1 and textures with transparency to BC3, in KTX2 files with mipmaps.
+      /// These are supported by desktop GPUs; on other GPUs, they are decompressed when they are loaded.
+      "Bc",
     /// Treats all assets in the pipeline as variations, and outputs a single asset which is a collection of all assets.
     /// Most useful for grass and other entities whose individual identity is not important.
     collection_of_variants?: boolean,
@@ -187,6 +193,27 @@ export type Pipeline = {
       /// Re-center this mesh such that the root is located at the origin.
       type: "Center",
     })[],
+    /// If specified, events to add to the animations of this model, in addition to the events that are read from the model files.
+    animation_events?: {
+      /// The animation to add the events to, by the name of its `.anim` file in the `animations` directory of the output.
+      /// If not specified, the events are added to all of the model's animations.
+      animation?: string,
+      /// The events to add. Their times are in seconds from the start of the animation.
+      events: {
+        name: string,
+        /// Seconds from the start of the clip
+        time: f32,
+      }[],
+    }[],
+    /// If specified, simplified levels of detail to generate for the meshes of this model, from the most to the least detailed.
+    /// A collider created from the model uses the least detailed level.
+    lods?: {
+      /// The fraction of the triangles of the original mesh to keep, e.g. `0.5` for half of them.
+      triangle_ratio: f32,
+      /// This level of detail is used once the radius of the model's bounding sphere on screen is smaller than this,
+      /// where `1` is half the height of the screen.
+      screen_size: f32,
+    }[],
   } | {
     /// The materials asset pipeline.
     /// Will import specific materials without needing to be part of a model.
@@ -234,10 +261,52 @@ export type Pipeline = {
     },
     /// Whether or not decal prefabs should be created for each of these materials.
     output_decals?: boolean,
+    /// If specified, the textures of the materials are compressed for the GPU, which makes them smaller to download
+    /// and in video memory.
+    texture_compression?: 
+      /// Compress opaque textures to BC1 and textures with transparency to BC3, in KTX2 files with mipmaps.
+      /// These are supported by desktop GPUs; on other GPUs, they are decompressed when they are loaded.
+      "Bc",
   } | {
     /// The audio asset pipeline.
     /// Will import supported audio file formats and produce Ogg Vorbis files to be used by the runtime.
     type: "Audio",
+  } | {
+    /// The terrain asset pipeline.
+    /// Will import 16-bit heightmaps, and maps of the other terrain layers, and split them into terrain cells.
+    type: "Terrain",
+    /// The height, in meters, that the lowest value of the heightmaps corresponds to. Defaults to 0.
+    min_height?: f32,
+    /// The height, in meters, that the highest value of the heightmaps corresponds to. Defaults to 100.
+    max_height?: f32,
+    /// Maps of other terrain layers, such as soil or hardness, that accompany the heightmaps.
+    layers?: {
+      /// The layer the map is imported to.
+      layer: 
+        "Rock" | 
+        "Soil" | 
+        "Sediment" | 
+        "Hardness" | 
+        "HardnessStrataAmount" | 
+        "HardnessStrataWavelength" | 
+        "Water" | 
+        "WaterOutflowL" | 
+        "WaterOutflowR" | 
+        "WaterOutflowT" | 
+        "WaterOutflowB" | 
+        "WaterVelocityX" | 
+        "WaterVelocityY",
+      /// The map of a heightmap is found by appending this to its file name; for example, a suffix of `_hardness` means
+      /// that `island_hardness.png` accompanies `island.png`. Heightmaps without the map leave the layer at zero.
+      suffix: string,
+      /// The value that the lowest value of the map corresponds to. Defaults to 0.
+      min?: f32,
+      /// The value that the highest value of the map corresponds to. Defaults to 1.
+      max?: f32,
+    }[],
+    /// The cell at which the top left corner of the heightmaps is placed. Each cell is 64 meters wide,
+    /// and each texel of the heightmaps is one meter.
+    origin_cell?: IVec2,
   },
   /// Filter the sources used to feed this pipeline.
   /// This is a list of glob patterns for accepted files.
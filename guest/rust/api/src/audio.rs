@@ -1,4 +1,9 @@
-use crate::internal::wit;
+use crate::{
+    global::EntityId,
+    internal::{conversion::IntoBindgen, wit},
+};
+
+pub use wit::audio::AudioBus;
 
 /// Load an audio file from `url`, and return an [AudioTrack] that can be used to play the audio.
 pub fn load(url: String) -> AudioTrack {
@@ -7,6 +12,7 @@ pub fn load(url: String) -> AudioTrack {
         name: url,
         looping: false,
         volume: 1.0,
+        bus: AudioBus::Sfx,
     }
 }
 
@@ -19,6 +25,8 @@ pub struct AudioTrack {
     pub looping: bool,
     /// The volume of the audio
     pub volume: f32,
+    /// The bus the audio is mixed on
+    pub bus: AudioBus,
 }
 
 impl AudioTrack {
@@ -38,8 +46,66 @@ impl AudioTrack {
         }
     }
 
-    /// Play the track.
-    pub fn play(&self) {
-        wit::audio::play(&self.name, self.looping, self.volume);
+    /// Set the bus the track is mixed on. Tracks play on [AudioBus::Sfx] by default.
+    pub fn bus(&self, bus: AudioBus) -> Self {
+        Self {
+            bus,
+            ..self.clone()
+        }
+    }
+
+    /// Play the track, returning a [Sound] that can be used to control it while it plays.
+    pub fn play(&self) -> Sound {
+        Sound(wit::audio::play(
+            &self.name,
+            self.looping,
+            self.volume,
+            self.bus,
+        ))
+    }
+}
+
+/// A playing sound, returned by [AudioTrack::play].
+///
+/// Controlling a sound which has stopped does nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sound(u64);
+
+impl Sound {
+    /// Stop the sound. It can not be resumed afterwards.
+    pub fn stop(&self) {
+        wit::audio::stop(self.0);
+    }
+
+    /// Pause the sound until it is [resumed](Self::resume).
+    pub fn pause(&self) {
+        wit::audio::set_paused(self.0, true);
+    }
+
+    /// Resume the sound after it was [paused](Self::pause).
+    pub fn resume(&self) {
+        wit::audio::set_paused(self.0, false);
+    }
+
+    /// Set the volume of the sound, on top of the volume of its track.
+    pub fn set_volume(&self, volume: f32) {
+        wit::audio::set_volume(self.0, volume);
+    }
+
+    /// Linearly change the volume of the sound to `volume` over `duration` seconds.
+    pub fn fade(&self, volume: f32, duration: f32) {
+        wit::audio::fade(self.0, volume, duration);
+    }
+
+    /// Play the sound faster or slower, which raises or lowers its pitch. `2.0` plays it twice as fast, an octave higher.
+    pub fn set_pitch(&self, pitch: f32) {
+        wit::audio::set_pitch(self.0, pitch);
+    }
+
+    /// Emit the sound from the `entity` from now on, following its transform.
+    ///
+    /// The sound is spatialized relative to the active camera, and the entity is given an audio emitter if it has none.
+    pub fn attach(&self, entity: EntityId) {
+        wit::audio::attach(self.0, entity.into_bindgen());
     }
 }
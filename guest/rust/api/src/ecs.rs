@@ -1,7 +1,8 @@
 pub use crate::internal::component::{
+    __internal_get_component,
     query::{
         change_query, despawn_query, query, spawn_query, ChangeQuery, EventQuery, GeneralQuery,
         GeneralQueryBuilder, QueryEvent, UntrackedChangeQuery,
     },
-    Component, ComponentsTuple, Entity, SupportedValue, UntypedComponent, __internal_get_component,
+    Component, ComponentsTuple, Entity, Structured, SupportedValue, UntypedComponent,
 };
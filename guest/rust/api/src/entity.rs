@@ -8,7 +8,11 @@ use crate::{
     prelude::block_until,
 };
 
-pub use wit::entity::{AnimationAction, AnimationController};
+pub use wit::entity::{
+    AnimationAction, AnimationBlendMode, AnimationBlendPoint, AnimationComparison,
+    AnimationCondition, AnimationController, AnimationState, AnimationStateMachine,
+    AnimationTransition,
+};
 
 /// Spawns an entity containing the `components`.
 ///
@@ -39,6 +43,46 @@ pub fn despawn(entity: EntityId) -> bool {
 pub fn set_animation_controller(entity: EntityId, controller: AnimationController) {
     wit::entity::set_animation_controller(entity.into_bindgen(), controller)
 }
+/// Set the animation state machine for `entity`. It starts in its initial state, and takes precedence
+/// over the animation controller of `entity`.
+pub fn set_animation_state_machine(entity: EntityId, state_machine: AnimationStateMachine) {
+    wit::entity::set_animation_state_machine(entity.into_bindgen(), state_machine)
+}
+/// Sets the parameter `name` of the animation state machine of `entity` to `value`.
+/// Parameters drive its transitions and blend spaces, and are `0` until they are set.
+pub fn set_animation_parameter(entity: EntityId, name: &str, value: f32) {
+    wit::entity::set_animation_parameter(entity.into_bindgen(), name, value)
+}
+/// Gets the name of the state the animation state machine of `entity` is in.
+///
+/// Animations are played on the client, so this is `None` on the server.
+pub fn get_animation_state(entity: EntityId) -> Option<String> {
+    wit::entity::get_animation_state(entity.into_bindgen())
+}
+/// Extracts the motion of the root bone `bind_id` (e.g. `Hips`) from the animations of `entity`, instead of
+/// playing it on the bone. Only the `axes` that are `1` are extracted; for instance, `vec3(1., 0., 1.)` extracts the
+/// horizontal motion of a Y-up skeleton. The motion can be read with [get_animation_root_motion_delta].
+pub fn set_animation_root_motion(entity: EntityId, bind_id: &str, axes: Vec3) {
+    wit::entity::set_animation_root_motion(
+        entity.into_bindgen(),
+        Some(wit::entity::AnimationRootMotion {
+            bind_id,
+            axes: axes.into_bindgen(),
+        }),
+    )
+}
+/// Stops extracting the motion of the root bone from the animations of `entity`.
+pub fn remove_animation_root_motion(entity: EntityId) {
+    wit::entity::set_animation_root_motion(entity.into_bindgen(), None)
+}
+/// Gets how far the root motion of `entity` moved in the last frame, in world space. Apply it to the character
+/// to move it along with its animations.
+///
+/// Animations are played on the client, so this is `None` on the server. To move the character with
+/// `physics::move_character`, send the delta to the server with a message.
+pub fn get_animation_root_motion_delta(entity: EntityId) -> Option<Vec3> {
+    wit::entity::get_animation_root_motion_delta(entity.into_bindgen()).from_bindgen()
+}
 
 /// Checks if the `entity` exists.
 pub fn exists(entity: EntityId) -> bool {
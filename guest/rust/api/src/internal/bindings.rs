@@ -127,6 +127,38 @@ pub mod audio{
   #[cfg(target_arch = "wasm32")]
   static __FORCE_SECTION_REF: fn() = super::__link_section;
   
+  pub type EntityId = super::types::EntityId;
+  /// The kind of a sound, which decides the bus it is mixed on, along with the volume of that bus.
+  #[repr(u8)]
+  #[derive(Clone, Copy, PartialEq, Eq)]
+  pub enum AudioBus {
+    /// Music, which is ducked while voices are playing.
+    Music,
+    /// Sound effects.
+    Sfx,
+    /// Voices and dialogue.
+    Voice,
+    /// Sounds of the user interface.
+    Ui,
+  }
+  impl core::fmt::Debug for AudioBus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+      match self {
+        AudioBus::Music => {
+          f.debug_tuple("AudioBus::Music").finish()
+        }
+        AudioBus::Sfx => {
+          f.debug_tuple("AudioBus::Sfx").finish()
+        }
+        AudioBus::Voice => {
+          f.debug_tuple("AudioBus::Voice").finish()
+        }
+        AudioBus::Ui => {
+          f.debug_tuple("AudioBus::Ui").finish()
+        }
+      }
+    }
+  }
   #[allow(clippy::all)]
   pub fn load(url: &str,){
     
@@ -148,7 +180,7 @@ pub mod audio{
     }
   }
   #[allow(clippy::all)]
-  pub fn play(name: &str,looping: bool,amp: f32,){
+  pub fn play(name: &str,looping: bool,amp: f32,bus: AudioBus,) -> u64{
     
     #[allow(unused_imports)]
     use wit_bindgen::rt::{alloc, vec::Vec, string::String};
@@ -162,9 +194,113 @@ pub mod audio{
         #[cfg_attr(target_arch = "wasm32", link_name = "play")]
         #[cfg_attr(not(target_arch = "wasm32"), link_name = "audio_play")]
         fn wit_import(
-        _: i32, _: i32, _: i32, _: f32, );
+        _: i32, _: i32, _: i32, _: f32, _: i32, ) -> i64;
+      }
+      let ret = wit_import(ptr0, len0, match looping { true => 1, false => 0 }, wit_bindgen::rt::as_f32(amp), bus.clone() as i32);
+      ret as u64
+    }
+  }
+  #[allow(clippy::all)]
+  pub fn stop(sound: u64,){
+    
+    #[allow(unused_imports)]
+    use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+    unsafe {
+      
+      #[link(wasm_import_module = "audio")]
+      extern "C" {
+        #[cfg_attr(target_arch = "wasm32", link_name = "stop")]
+        #[cfg_attr(not(target_arch = "wasm32"), link_name = "audio_stop")]
+        fn wit_import(
+        _: i64, );
+      }
+      wit_import(wit_bindgen::rt::as_i64(sound));
+    }
+  }
+  #[allow(clippy::all)]
+  pub fn set_paused(sound: u64,paused: bool,){
+    
+    #[allow(unused_imports)]
+    use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+    unsafe {
+      
+      #[link(wasm_import_module = "audio")]
+      extern "C" {
+        #[cfg_attr(target_arch = "wasm32", link_name = "set-paused")]
+        #[cfg_attr(not(target_arch = "wasm32"), link_name = "audio_set-paused")]
+        fn wit_import(
+        _: i64, _: i32, );
+      }
+      wit_import(wit_bindgen::rt::as_i64(sound), match paused { true => 1, false => 0 });
+    }
+  }
+  #[allow(clippy::all)]
+  pub fn set_volume(sound: u64,volume: f32,){
+    
+    #[allow(unused_imports)]
+    use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+    unsafe {
+      
+      #[link(wasm_import_module = "audio")]
+      extern "C" {
+        #[cfg_attr(target_arch = "wasm32", link_name = "set-volume")]
+        #[cfg_attr(not(target_arch = "wasm32"), link_name = "audio_set-volume")]
+        fn wit_import(
+        _: i64, _: f32, );
+      }
+      wit_import(wit_bindgen::rt::as_i64(sound), wit_bindgen::rt::as_f32(volume));
+    }
+  }
+  #[allow(clippy::all)]
+  pub fn fade(sound: u64,volume: f32,duration: f32,){
+    
+    #[allow(unused_imports)]
+    use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+    unsafe {
+      
+      #[link(wasm_import_module = "audio")]
+      extern "C" {
+        #[cfg_attr(target_arch = "wasm32", link_name = "fade")]
+        #[cfg_attr(not(target_arch = "wasm32"), link_name = "audio_fade")]
+        fn wit_import(
+        _: i64, _: f32, _: f32, );
+      }
+      wit_import(wit_bindgen::rt::as_i64(sound), wit_bindgen::rt::as_f32(volume), wit_bindgen::rt::as_f32(duration));
+    }
+  }
+  #[allow(clippy::all)]
+  pub fn set_pitch(sound: u64,pitch: f32,){
+    
+    #[allow(unused_imports)]
+    use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+    unsafe {
+      
+      #[link(wasm_import_module = "audio")]
+      extern "C" {
+        #[cfg_attr(target_arch = "wasm32", link_name = "set-pitch")]
+        #[cfg_attr(not(target_arch = "wasm32"), link_name = "audio_set-pitch")]
+        fn wit_import(
+        _: i64, _: f32, );
+      }
+      wit_import(wit_bindgen::rt::as_i64(sound), wit_bindgen::rt::as_f32(pitch));
+    }
+  }
+  #[allow(clippy::all)]
+  pub fn attach(sound: u64,entity: EntityId,){
+    
+    #[allow(unused_imports)]
+    use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+    unsafe {
+      let super::types::EntityId{ id0:id00, id1:id10, } = entity;
+      
+      #[link(wasm_import_module = "audio")]
+      extern "C" {
+        #[cfg_attr(target_arch = "wasm32", link_name = "attach")]
+        #[cfg_attr(not(target_arch = "wasm32"), link_name = "audio_attach")]
+        fn wit_import(
+        _: i64, _: i64, _: i64, );
       }
-      wit_import(ptr0, len0, match looping { true => 1, false => 0 }, wit_bindgen::rt::as_f32(amp));
+      wit_import(wit_bindgen::rt::as_i64(sound), wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10));
     }
   }
   
@@ -452,6 +588,7 @@ pub mod component{
     TypeUvec4(Uvec4),
     TypeVec(VecValue),
     TypeOption(OptionValue),
+    TypeStructured(wit_bindgen::rt::vec::Vec<u8>),
   }
   impl core::fmt::Debug for Value {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -516,6 +653,9 @@ pub mod component{
         Value::TypeOption(e) => {
           f.debug_tuple("Value::TypeOption").field(e).finish()
         }
+        Value::TypeStructured(e) => {
+          f.debug_tuple("Value::TypeStructured").field(e).finish()
+        }
       }
     }
   }
@@ -735,177 +875,7 @@ pub mod component{
             }),
             #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
           }}}),
-          #[cfg(debug_assertions)]19 => Value::TypeOption({{match i32::from(*((ptr1 + 16) as *const u8)) {
-            0 => OptionValue::TypeEmpty(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(()),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            1 => OptionValue::TypeBool(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some({
-                #[cfg(not(debug_assertions))]
-                { core::mem::transmute::<u8, bool>(i32::from(*((ptr1 + 25) as *const u8)) as u8) }
-                #[cfg(debug_assertions)]
-                {
-                  match i32::from(*((ptr1 + 25) as *const u8)) {
-                    0 => false,
-                    1 => true,
-                    _ => panic!("invalid bool discriminant"),
-                  }
-                }
-              }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            2 => OptionValue::TypeEntityId(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::EntityId{id0:*((ptr1 + 32) as *const i64) as u64, id1:*((ptr1 + 40) as *const i64) as u64, }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            3 => OptionValue::TypeF32(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(*((ptr1 + 28) as *const f32)),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            4 => OptionValue::TypeF64(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(*((ptr1 + 32) as *const f64)),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            5 => OptionValue::TypeMat4(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Mat4{x:super::types::Vec4{x:*((ptr1 + 28) as *const f32), y:*((ptr1 + 32) as *const f32), z:*((ptr1 + 36) as *const f32), w:*((ptr1 + 40) as *const f32), }, y:super::types::Vec4{x:*((ptr1 + 44) as *const f32), y:*((ptr1 + 48) as *const f32), z:*((ptr1 + 52) as *const f32), w:*((ptr1 + 56) as *const f32), }, z:super::types::Vec4{x:*((ptr1 + 60) as *const f32), y:*((ptr1 + 64) as *const f32), z:*((ptr1 + 68) as *const f32), w:*((ptr1 + 72) as *const f32), }, w:super::types::Vec4{x:*((ptr1 + 76) as *const f32), y:*((ptr1 + 80) as *const f32), z:*((ptr1 + 84) as *const f32), w:*((ptr1 + 88) as *const f32), }, }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            6 => OptionValue::TypeI32(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(*((ptr1 + 28) as *const i32)),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            7 => OptionValue::TypeQuat(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Quat{x:*((ptr1 + 28) as *const f32), y:*((ptr1 + 32) as *const f32), z:*((ptr1 + 36) as *const f32), w:*((ptr1 + 40) as *const f32), }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            8 => OptionValue::TypeString(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some({
-                let len22 = *((ptr1 + 32) as *const i32) as usize;
-                
-                {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((ptr1 + 28) as *const i32) as *mut _, len22, len22))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((ptr1 + 28) as *const i32) as *mut _, len22, len22)).unwrap()}}
-              }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            9 => OptionValue::TypeU8(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(i32::from(*((ptr1 + 25) as *const u8)) as u8),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            10 => OptionValue::TypeU32(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(*((ptr1 + 28) as *const i32) as u32),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            11 => OptionValue::TypeU64(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(*((ptr1 + 32) as *const i64) as u64),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            12 => OptionValue::TypeVec2(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Vec2{x:*((ptr1 + 28) as *const f32), y:*((ptr1 + 32) as *const f32), }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            13 => OptionValue::TypeVec3(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Vec3{x:*((ptr1 + 28) as *const f32), y:*((ptr1 + 32) as *const f32), z:*((ptr1 + 36) as *const f32), }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            14 => OptionValue::TypeVec4(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Vec4{x:*((ptr1 + 28) as *const f32), y:*((ptr1 + 32) as *const f32), z:*((ptr1 + 36) as *const f32), w:*((ptr1 + 40) as *const f32), }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            15 => OptionValue::TypeUvec2(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Uvec2{x:*((ptr1 + 28) as *const i32) as u32, y:*((ptr1 + 32) as *const i32) as u32, }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            16 => OptionValue::TypeUvec3(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Uvec3{x:*((ptr1 + 28) as *const i32) as u32, y:*((ptr1 + 32) as *const i32) as u32, z:*((ptr1 + 36) as *const i32) as u32, }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            #[cfg(debug_assertions)]17 => OptionValue::TypeUvec4(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Uvec4{x:*((ptr1 + 28) as *const i32) as u32, y:*((ptr1 + 32) as *const i32) as u32, z:*((ptr1 + 36) as *const i32) as u32, w:*((ptr1 + 40) as *const i32) as u32, }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            #[cfg(not(debug_assertions))]_ => OptionValue::TypeUvec4(match i32::from(*((ptr1 + 24) as *const u8)) {
-              0 => None,
-              1 => Some(super::types::Uvec4{x:*((ptr1 + 28) as *const i32) as u32, y:*((ptr1 + 32) as *const i32) as u32, z:*((ptr1 + 36) as *const i32) as u32, w:*((ptr1 + 40) as *const i32) as u32, }),
-              #[cfg(not(debug_assertions))]
-              _ => core::hint::unreachable_unchecked(),
-              #[cfg(debug_assertions)]
-              _ => panic!("invalid enum discriminant"),
-            }),
-            #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
-          }}}),
-          #[cfg(not(debug_assertions))]_ => Value::TypeOption({{match i32::from(*((ptr1 + 16) as *const u8)) {
+          19 => Value::TypeOption({{match i32::from(*((ptr1 + 16) as *const u8)) {
             0 => OptionValue::TypeEmpty(match i32::from(*((ptr1 + 24) as *const u8)) {
               0 => None,
               1 => Some(()),
@@ -1075,6 +1045,16 @@ pub mod component{
             }),
             #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
           }}}),
+          #[cfg(debug_assertions)]20 => Value::TypeStructured({
+            let len23 = *((ptr1 + 20) as *const i32) as usize;
+            
+            Vec::from_raw_parts(*((ptr1 + 16) as *const i32) as *mut _, len23, len23)
+          }),
+          #[cfg(not(debug_assertions))]_ => Value::TypeStructured({
+            let len23 = *((ptr1 + 20) as *const i32) as usize;
+            
+            Vec::from_raw_parts(*((ptr1 + 16) as *const i32) as *mut _, len23, len23)
+          }),
           #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
         }}}),
         #[cfg(not(debug_assertions))]
@@ -1788,6 +1768,15 @@ pub mod component{
               };
               
             },
+            Value::TypeStructured(e) => {
+              *((ptr0 + 24) as *mut u8) = (20i32) as u8;
+              let vec45 = e;
+              let ptr45 = vec45.as_ptr() as i32;
+              let len45 = vec45.len() as i32;
+              *((ptr0 + 36) as *mut i32) = len45;
+              *((ptr0 + 32) as *mut i32) = ptr45;
+              
+            },
           };
           
           #[link(wasm_import_module = "component")]
@@ -2525,6 +2514,15 @@ pub mod component{
                       };
                       
                     },
+                    Value::TypeStructured(e) => {
+                      *((base + 8) as *mut u8) = (20i32) as u8;
+                      let vec45 = e;
+                      let ptr45 = vec45.as_ptr() as i32;
+                      let len45 = vec45.len() as i32;
+                      *((base + 20) as *mut i32) = len45;
+                      *((base + 16) as *mut i32) = ptr45;
+                      
+                    },
                   };
                   
                 }}
@@ -3255,6 +3253,15 @@ pub mod component{
                         };
                         
                       },
+                      Value::TypeStructured(e) => {
+                        *((ptr0 + 24) as *mut u8) = (20i32) as u8;
+                        let vec45 = e;
+                        let ptr45 = vec45.as_ptr() as i32;
+                        let len45 = vec45.len() as i32;
+                        *((ptr0 + 36) as *mut i32) = len45;
+                        *((ptr0 + 32) as *mut i32) = ptr45;
+                        
+                      },
                     };
                     
                     #[link(wasm_import_module = "component")]
@@ -3992,6 +3999,15 @@ pub mod component{
                                 };
                                 
                               },
+                              Value::TypeStructured(e) => {
+                                *((base + 8) as *mut u8) = (20i32) as u8;
+                                let vec45 = e;
+                                let ptr45 = vec45.as_ptr() as i32;
+                                let len45 = vec45.len() as i32;
+                                *((base + 20) as *mut i32) = len45;
+                                *((base + 16) as *mut i32) = ptr45;
+                                
+                              },
                             };
                             
                           }}
@@ -4346,177 +4362,7 @@ pub mod component{
                                     }),
                                     #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
                                   }}}),
-                                  #[cfg(debug_assertions)]19 => Value::TypeOption({{match i32::from(*((base + 8) as *const u8)) {
-                                    0 => OptionValue::TypeEmpty(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(()),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    1 => OptionValue::TypeBool(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some({
-                                        #[cfg(not(debug_assertions))]
-                                        { core::mem::transmute::<u8, bool>(i32::from(*((base + 17) as *const u8)) as u8) }
-                                        #[cfg(debug_assertions)]
-                                        {
-                                          match i32::from(*((base + 17) as *const u8)) {
-                                            0 => false,
-                                            1 => true,
-                                            _ => panic!("invalid bool discriminant"),
-                                          }
-                                        }
-                                      }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    2 => OptionValue::TypeEntityId(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::EntityId{id0:*((base + 24) as *const i64) as u64, id1:*((base + 32) as *const i64) as u64, }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    3 => OptionValue::TypeF32(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(*((base + 20) as *const f32)),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    4 => OptionValue::TypeF64(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(*((base + 24) as *const f64)),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    5 => OptionValue::TypeMat4(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Mat4{x:super::types::Vec4{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }, y:super::types::Vec4{x:*((base + 36) as *const f32), y:*((base + 40) as *const f32), z:*((base + 44) as *const f32), w:*((base + 48) as *const f32), }, z:super::types::Vec4{x:*((base + 52) as *const f32), y:*((base + 56) as *const f32), z:*((base + 60) as *const f32), w:*((base + 64) as *const f32), }, w:super::types::Vec4{x:*((base + 68) as *const f32), y:*((base + 72) as *const f32), z:*((base + 76) as *const f32), w:*((base + 80) as *const f32), }, }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    6 => OptionValue::TypeI32(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(*((base + 20) as *const i32)),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    7 => OptionValue::TypeQuat(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Quat{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    8 => OptionValue::TypeString(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some({
-                                        let len21 = *((base + 24) as *const i32) as usize;
-                                        
-                                        {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((base + 20) as *const i32) as *mut _, len21, len21))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((base + 20) as *const i32) as *mut _, len21, len21)).unwrap()}}
-                                      }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    9 => OptionValue::TypeU8(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(i32::from(*((base + 17) as *const u8)) as u8),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    10 => OptionValue::TypeU32(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(*((base + 20) as *const i32) as u32),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    11 => OptionValue::TypeU64(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(*((base + 24) as *const i64) as u64),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    12 => OptionValue::TypeVec2(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Vec2{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    13 => OptionValue::TypeVec3(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Vec3{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    14 => OptionValue::TypeVec4(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Vec4{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    15 => OptionValue::TypeUvec2(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Uvec2{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    16 => OptionValue::TypeUvec3(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Uvec3{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    #[cfg(debug_assertions)]17 => OptionValue::TypeUvec4(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Uvec4{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, w:*((base + 32) as *const i32) as u32, }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    #[cfg(not(debug_assertions))]_ => OptionValue::TypeUvec4(match i32::from(*((base + 16) as *const u8)) {
-                                      0 => None,
-                                      1 => Some(super::types::Uvec4{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, w:*((base + 32) as *const i32) as u32, }),
-                                      #[cfg(not(debug_assertions))]
-                                      _ => core::hint::unreachable_unchecked(),
-                                      #[cfg(debug_assertions)]
-                                      _ => panic!("invalid enum discriminant"),
-                                    }),
-                                    #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
-                                  }}}),
-                                  #[cfg(not(debug_assertions))]_ => Value::TypeOption({{match i32::from(*((base + 8) as *const u8)) {
+                                  19 => Value::TypeOption({{match i32::from(*((base + 8) as *const u8)) {
                                     0 => OptionValue::TypeEmpty(match i32::from(*((base + 16) as *const u8)) {
                                       0 => None,
                                       1 => Some(()),
@@ -4686,6 +4532,16 @@ pub mod component{
                                     }),
                                     #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
                                   }}}),
+                                  #[cfg(debug_assertions)]20 => Value::TypeStructured({
+                                    let len22 = *((base + 12) as *const i32) as usize;
+                                    
+                                    Vec::from_raw_parts(*((base + 8) as *const i32) as *mut _, len22, len22)
+                                  }),
+                                  #[cfg(not(debug_assertions))]_ => Value::TypeStructured({
+                                    let len22 = *((base + 12) as *const i32) as usize;
+                                    
+                                    Vec::from_raw_parts(*((base + 8) as *const i32) as *mut _, len22, len22)
+                                  }),
                                   #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
                                 }}});
                               }
@@ -4712,6 +4568,32 @@ pub mod component{
                       pub type EntityId = super::types::EntityId;
                       pub type Vec3 = super::types::Vec3;
                       pub type EntityData<'a,> = super::component::Entity<'a,>;
+                      /// How an animation action is combined with the other actions.
+                      #[repr(u8)]
+                      #[derive(Clone, Copy, PartialEq, Eq)]
+                      pub enum AnimationBlendMode {
+                        /// Blended with the other `blend` actions, by their weights.
+                        Blend,
+                        /// Layered over the blended actions, replacing their pose by its weight.
+                        Override,
+                        /// Layered over the blended actions, adding how far its clip has moved from its first frame, scaled by its weight.
+                        Additive,
+                      }
+                      impl core::fmt::Debug for AnimationBlendMode {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          match self {
+                            AnimationBlendMode::Blend => {
+                              f.debug_tuple("AnimationBlendMode::Blend").finish()
+                            }
+                            AnimationBlendMode::Override => {
+                              f.debug_tuple("AnimationBlendMode::Override").finish()
+                            }
+                            AnimationBlendMode::Additive => {
+                              f.debug_tuple("AnimationBlendMode::Additive").finish()
+                            }
+                          }
+                        }
+                      }
                       /// An action in an animation.
                       #[derive(Clone)]
                       pub struct AnimationAction<'a,> {
@@ -4721,10 +4603,14 @@ pub mod component{
                         pub looping: bool,
                         /// How strongly this action applies to the final blend [0-1]
                         pub weight: f32,
+                        /// The bind IDs of the bones this action plays on, along with their descendants. If empty, it plays on all of them.
+                        pub mask: &'a [&'a str],
+                        /// How this action is combined with the other actions. `override` and `additive` actions are layered in the order they're listed.
+                        pub blend_mode: AnimationBlendMode,
                       }
                       impl<'a,> core::fmt::Debug for AnimationAction<'a,> {
                         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                          f.debug_struct("AnimationAction").field("clip-url", &self.clip_url).field("looping", &self.looping).field("weight", &self.weight).finish()
+                          f.debug_struct("AnimationAction").field("clip-url", &self.clip_url).field("looping", &self.looping).field("weight", &self.weight).field("mask", &self.mask).field("blend-mode", &self.blend_mode).finish()
                         }
                       }
                       /// Controls the animations for an entity.
@@ -4741,6 +4627,130 @@ pub mod component{
                           f.debug_struct("AnimationController").field("actions", &self.actions).field("apply-base-pose", &self.apply_base_pose).finish()
                         }
                       }
+                      /// A clip in the blend space of an animation state.
+                      #[derive(Clone)]
+                      pub struct AnimationBlendPoint<'a,> {
+                        /// The animation clip URL to play.
+                        pub clip_url: &'a str,
+                        /// Where the clip is in the blend space. Only `x` is used in 1D blend spaces.
+                        pub x: f32,
+                        pub y: f32,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationBlendPoint<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationBlendPoint").field("clip-url", &self.clip_url).field("x", &self.x).field("y", &self.y).finish()
+                        }
+                      }
+                      /// A state of an animation state machine. Blends its clips based on how close their points are to its parameters.
+                      #[derive(Clone)]
+                      pub struct AnimationState<'a,> {
+                        pub name: &'a str,
+                        pub points: &'a [AnimationBlendPoint<'a,>],
+                        /// The parameter the x coordinate of the blend space is read from.
+                        pub x_parameter: Option<&'a str>,
+                        /// The parameter the y coordinate of the blend space is read from. If not set, the blend space is 1D.
+                        pub y_parameter: Option<&'a str>,
+                        /// Whether or not the clips of this state should loop
+                        pub looping: bool,
+                        /// How fast the clips of this state are played
+                        pub speed: f32,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationState<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationState").field("name", &self.name).field("points", &self.points).field("x-parameter", &self.x_parameter).field("y-parameter", &self.y_parameter).field("looping", &self.looping).field("speed", &self.speed).finish()
+                        }
+                      }
+                      /// How the value of a parameter is compared in an animation condition.
+                      #[repr(u8)]
+                      #[derive(Clone, Copy, PartialEq, Eq)]
+                      pub enum AnimationComparison {
+                        Less,
+                        LessOrEqual,
+                        Equal,
+                        NotEqual,
+                        GreaterOrEqual,
+                        Greater,
+                      }
+                      impl core::fmt::Debug for AnimationComparison {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          match self {
+                            AnimationComparison::Less => {
+                              f.debug_tuple("AnimationComparison::Less").finish()
+                            }
+                            AnimationComparison::LessOrEqual => {
+                              f.debug_tuple("AnimationComparison::LessOrEqual").finish()
+                            }
+                            AnimationComparison::Equal => {
+                              f.debug_tuple("AnimationComparison::Equal").finish()
+                            }
+                            AnimationComparison::NotEqual => {
+                              f.debug_tuple("AnimationComparison::NotEqual").finish()
+                            }
+                            AnimationComparison::GreaterOrEqual => {
+                              f.debug_tuple("AnimationComparison::GreaterOrEqual").finish()
+                            }
+                            AnimationComparison::Greater => {
+                              f.debug_tuple("AnimationComparison::Greater").finish()
+                            }
+                          }
+                        }
+                      }
+                      /// A condition on a parameter of an animation state machine.
+                      #[derive(Clone)]
+                      pub struct AnimationCondition<'a,> {
+                        pub parameter: &'a str,
+                        pub comparison: AnimationComparison,
+                        pub value: f32,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationCondition<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationCondition").field("parameter", &self.parameter).field("comparison", &self.comparison).field("value", &self.value).finish()
+                        }
+                      }
+                      /// A transition between the states of an animation state machine.
+                      #[derive(Clone)]
+                      pub struct AnimationTransition<'a,> {
+                        /// The state to transition from. If not set, the transition can be taken from any state.
+                        pub from_state: Option<&'a str>,
+                        pub to_state: &'a str,
+                        /// All of these must hold for the transition to be taken.
+                        pub conditions: &'a [AnimationCondition<'a,>],
+                        /// How long, in seconds, the previous state is crossfaded into the next one.
+                        pub duration: f32,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationTransition<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationTransition").field("from-state", &self.from_state).field("to-state", &self.to_state).field("conditions", &self.conditions).field("duration", &self.duration).finish()
+                        }
+                      }
+                      /// Plays the animations for an entity, transitioning between states based on its parameters.
+                      #[derive(Clone)]
+                      pub struct AnimationStateMachine<'a,> {
+                        pub states: &'a [AnimationState<'a,>],
+                        /// Evaluated in order; the first transition whose conditions hold is taken.
+                        pub transitions: &'a [AnimationTransition<'a,>],
+                        pub initial_state: &'a str,
+                        /// Whether or not the pose of the first clip of the initial state should be used as a base pose.
+                        pub apply_base_pose: bool,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationStateMachine<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationStateMachine").field("states", &self.states).field("transitions", &self.transitions).field("initial-state", &self.initial_state).field("apply-base-pose", &self.apply_base_pose).finish()
+                        }
+                      }
+                      /// Extracts the motion of the root bone from the animations of an entity.
+                      #[derive(Clone)]
+                      pub struct AnimationRootMotion<'a,> {
+                        /// The bind ID of the root bone (e.g. `Hips`).
+                        pub bind_id: &'a str,
+                        /// The axes of the root bone's translation to extract; 1 extracts the axis, and 0 keeps playing it on the bone.
+                        pub axes: Vec3,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationRootMotion<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationRootMotion").field("bind-id", &self.bind_id).field("axes", &self.axes).finish()
+                        }
+                      }
                       #[allow(clippy::all)]
                       pub fn spawn(data: EntityData<'_,>,) -> EntityId{
                         
@@ -5460,6 +5470,15 @@ pub mod component{
                                       };
                                       
                                     },
+                                    super::component::Value::TypeStructured(e) => {
+                                      *((base + 8) as *mut u8) = (20i32) as u8;
+                                      let vec44 = e;
+                                      let ptr44 = vec44.as_ptr() as i32;
+                                      let len44 = vec44.len() as i32;
+                                      *((base + 20) as *mut i32) = len44;
+                                      *((base + 16) as *mut i32) = ptr44;
+                                      
+                                    },
                                   };
                                   
                                 }}
@@ -5523,26 +5542,27 @@ pub mod component{
                               #[allow(unused_imports)]
                               use wit_bindgen::rt::{alloc, vec::Vec, string::String};
                               unsafe {
+                                let mut cleanup_list = Vec::new();
                                 let super::types::EntityId{ id0:id00, id1:id10, } = entity;
                                 let AnimationController{ actions:actions1, apply_base_pose:apply_base_pose1, } = animation_controller;
-                                let vec4 = actions1;
-                                let len4 = vec4.len() as i32;
-                                let layout4 = alloc::Layout::from_size_align_unchecked(vec4.len() * 16, 4);
-                                let result4 = if layout4.size() != 0
+                                let vec6 = actions1;
+                                let len6 = vec6.len() as i32;
+                                let layout6 = alloc::Layout::from_size_align_unchecked(vec6.len() * 28, 4);
+                                let result6 = if layout6.size() != 0
                                 {
-                                  let ptr = alloc::alloc(layout4);
+                                  let ptr = alloc::alloc(layout6);
                                   if ptr.is_null()
                                   {
-                                    alloc::handle_alloc_error(layout4);
+                                    alloc::handle_alloc_error(layout6);
                                   }
                                   ptr
                                 }else {
                                   core::ptr::null_mut()
                                 };
-                                for (i, e) in vec4.into_iter().enumerate() {
-                                  let base = result4 as i32 + (i as i32) * 16;
+                                for (i, e) in vec6.into_iter().enumerate() {
+                                  let base = result6 as i32 + (i as i32) * 28;
                                   {
-                                    let AnimationAction{ clip_url:clip_url2, looping:looping2, weight:weight2, } = e;
+                                    let AnimationAction{ clip_url:clip_url2, looping:looping2, weight:weight2, mask:mask2, blend_mode:blend_mode2, } = e;
                                     let vec3 = clip_url2;
                                     let ptr3 = vec3.as_ptr() as i32;
                                     let len3 = vec3.len() as i32;
@@ -5550,22 +5570,376 @@ pub mod component{
                                     *((base + 0) as *mut i32) = ptr3;
                                     *((base + 8) as *mut u8) = (match looping2 { true => 1, false => 0 }) as u8;
                                     *((base + 12) as *mut f32) = wit_bindgen::rt::as_f32(weight2);
+                                    let vec5 = mask2;
+                                    let len5 = vec5.len() as i32;
+                                    let layout5 = alloc::Layout::from_size_align_unchecked(vec5.len() * 8, 4);
+                                    let result5 = if layout5.size() != 0
+                                    {
+                                      let ptr = alloc::alloc(layout5);
+                                      if ptr.is_null()
+                                      {
+                                        alloc::handle_alloc_error(layout5);
+                                      }
+                                      ptr
+                                    }else {
+                                      core::ptr::null_mut()
+                                    };
+                                    for (i, e) in vec5.into_iter().enumerate() {
+                                      let base = result5 as i32 + (i as i32) * 8;
+                                      {
+                                        let vec4 = e;
+                                        let ptr4 = vec4.as_ptr() as i32;
+                                        let len4 = vec4.len() as i32;
+                                        *((base + 4) as *mut i32) = len4;
+                                        *((base + 0) as *mut i32) = ptr4;
+                                      }}
+                                      *((base + 20) as *mut i32) = len5;
+                                      *((base + 16) as *mut i32) = result5 as i32;
+                                      *((base + 24) as *mut u8) = (blend_mode2.clone() as i32) as u8;
+                                      cleanup_list.extend_from_slice(&[(result5, layout5),]);
+                                      
+                                    }}
                                     
-                                  }}
-                                  
-                                  #[link(wasm_import_module = "entity")]
-                                  extern "C" {
-                                    #[cfg_attr(target_arch = "wasm32", link_name = "set-animation-controller")]
-                                    #[cfg_attr(not(target_arch = "wasm32"), link_name = "entity_set-animation-controller")]
-                                    fn wit_import(
-                                    _: i64, _: i64, _: i32, _: i32, _: i32, );
-                                  }
-                                  wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), result4 as i32, len4, match apply_base_pose1 { true => 1, false => 0 });
-                                  if layout4.size() != 0 {
-                                    alloc::dealloc(result4, layout4);
+                                    #[link(wasm_import_module = "entity")]
+                                    extern "C" {
+                                      #[cfg_attr(target_arch = "wasm32", link_name = "set-animation-controller")]
+                                      #[cfg_attr(not(target_arch = "wasm32"), link_name = "entity_set-animation-controller")]
+                                      fn wit_import(
+                                      _: i64, _: i64, _: i32, _: i32, _: i32, );
+                                    }
+                                    wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), result6 as i32, len6, match apply_base_pose1 { true => 1, false => 0 });
+                                    if layout6.size() != 0 {
+                                      alloc::dealloc(result6, layout6);
+                                    }
+                                    for (ptr, layout) in cleanup_list {
+                                      
+                                      if layout.size() != 0 {
+                                        
+                                        alloc::dealloc(ptr, layout);
+                                        
+                                      }
+                                      
+                                    }
                                   }
                                 }
-                              }
+                              #[allow(clippy::all)]
+                              pub fn set_animation_state_machine(entity: EntityId,state_machine: AnimationStateMachine<'_,>,){
+                                
+                                #[allow(unused_imports)]
+                                use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                unsafe {
+                                  let mut cleanup_list = Vec::new();
+                                  let super::types::EntityId{ id0:id00, id1:id10, } = entity;
+                                  let AnimationStateMachine{ states:states1, transitions:transitions1, initial_state:initial_state1, apply_base_pose:apply_base_pose1, } = state_machine;
+                                  let vec9 = states1;
+                                  let len9 = vec9.len() as i32;
+                                  let layout9 = alloc::Layout::from_size_align_unchecked(vec9.len() * 48, 4);
+                                  let result9 = if layout9.size() != 0
+                                  {
+                                    let ptr = alloc::alloc(layout9);
+                                    if ptr.is_null()
+                                    {
+                                      alloc::handle_alloc_error(layout9);
+                                    }
+                                    ptr
+                                  }else {
+                                    core::ptr::null_mut()
+                                  };
+                                  for (i, e) in vec9.into_iter().enumerate() {
+                                    let base = result9 as i32 + (i as i32) * 48;
+                                    {
+                                      let AnimationState{ name:name2, points:points2, x_parameter:x_parameter2, y_parameter:y_parameter2, looping:looping2, speed:speed2, } = e;
+                                      let vec3 = name2;
+                                      let ptr3 = vec3.as_ptr() as i32;
+                                      let len3 = vec3.len() as i32;
+                                      *((base + 4) as *mut i32) = len3;
+                                      *((base + 0) as *mut i32) = ptr3;
+                                      let vec6 = points2;
+                                      let len6 = vec6.len() as i32;
+                                      let layout6 = alloc::Layout::from_size_align_unchecked(vec6.len() * 16, 4);
+                                      let result6 = if layout6.size() != 0
+                                      {
+                                        let ptr = alloc::alloc(layout6);
+                                        if ptr.is_null()
+                                        {
+                                          alloc::handle_alloc_error(layout6);
+                                        }
+                                        ptr
+                                      }else {
+                                        core::ptr::null_mut()
+                                      };
+                                      for (i, e) in vec6.into_iter().enumerate() {
+                                        let base = result6 as i32 + (i as i32) * 16;
+                                        {
+                                          let AnimationBlendPoint{ clip_url:clip_url4, x:x4, y:y4, } = e;
+                                          let vec5 = clip_url4;
+                                          let ptr5 = vec5.as_ptr() as i32;
+                                          let len5 = vec5.len() as i32;
+                                          *((base + 4) as *mut i32) = len5;
+                                          *((base + 0) as *mut i32) = ptr5;
+                                          *((base + 8) as *mut f32) = wit_bindgen::rt::as_f32(x4);
+                                          *((base + 12) as *mut f32) = wit_bindgen::rt::as_f32(y4);
+                                          
+                                        }}
+                                        *((base + 12) as *mut i32) = len6;
+                                        *((base + 8) as *mut i32) = result6 as i32;
+                                        match x_parameter2 {
+                                          Some(e) => {
+                                            *((base + 16) as *mut u8) = (1i32) as u8;
+                                            let vec7 = e;
+                                            let ptr7 = vec7.as_ptr() as i32;
+                                            let len7 = vec7.len() as i32;
+                                            *((base + 24) as *mut i32) = len7;
+                                            *((base + 20) as *mut i32) = ptr7;
+                                            
+                                          },
+                                          None => {
+                                            {
+                                              *((base + 16) as *mut u8) = (0i32) as u8;
+                                              
+                                            }
+                                          },
+                                        };
+                                        match y_parameter2 {
+                                          Some(e) => {
+                                            *((base + 28) as *mut u8) = (1i32) as u8;
+                                            let vec8 = e;
+                                            let ptr8 = vec8.as_ptr() as i32;
+                                            let len8 = vec8.len() as i32;
+                                            *((base + 36) as *mut i32) = len8;
+                                            *((base + 32) as *mut i32) = ptr8;
+                                            
+                                          },
+                                          None => {
+                                            {
+                                              *((base + 28) as *mut u8) = (0i32) as u8;
+                                              
+                                            }
+                                          },
+                                        };
+                                        *((base + 40) as *mut u8) = (match looping2 { true => 1, false => 0 }) as u8;
+                                        *((base + 44) as *mut f32) = wit_bindgen::rt::as_f32(speed2);
+                                        cleanup_list.extend_from_slice(&[(result6, layout6),]);
+                                        
+                                      }}
+                                      let vec16 = transitions1;
+                                      let len16 = vec16.len() as i32;
+                                      let layout16 = alloc::Layout::from_size_align_unchecked(vec16.len() * 32, 4);
+                                      let result16 = if layout16.size() != 0
+                                      {
+                                        let ptr = alloc::alloc(layout16);
+                                        if ptr.is_null()
+                                        {
+                                          alloc::handle_alloc_error(layout16);
+                                        }
+                                        ptr
+                                      }else {
+                                        core::ptr::null_mut()
+                                      };
+                                      for (i, e) in vec16.into_iter().enumerate() {
+                                        let base = result16 as i32 + (i as i32) * 32;
+                                        {
+                                          let AnimationTransition{ from_state:from_state10, to_state:to_state10, conditions:conditions10, duration:duration10, } = e;
+                                          match from_state10 {
+                                            Some(e) => {
+                                              *((base + 0) as *mut u8) = (1i32) as u8;
+                                              let vec11 = e;
+                                              let ptr11 = vec11.as_ptr() as i32;
+                                              let len11 = vec11.len() as i32;
+                                              *((base + 8) as *mut i32) = len11;
+                                              *((base + 4) as *mut i32) = ptr11;
+                                              
+                                            },
+                                            None => {
+                                              {
+                                                *((base + 0) as *mut u8) = (0i32) as u8;
+                                                
+                                              }
+                                            },
+                                          };
+                                          let vec12 = to_state10;
+                                          let ptr12 = vec12.as_ptr() as i32;
+                                          let len12 = vec12.len() as i32;
+                                          *((base + 16) as *mut i32) = len12;
+                                          *((base + 12) as *mut i32) = ptr12;
+                                          let vec15 = conditions10;
+                                          let len15 = vec15.len() as i32;
+                                          let layout15 = alloc::Layout::from_size_align_unchecked(vec15.len() * 16, 4);
+                                          let result15 = if layout15.size() != 0
+                                          {
+                                            let ptr = alloc::alloc(layout15);
+                                            if ptr.is_null()
+                                            {
+                                              alloc::handle_alloc_error(layout15);
+                                            }
+                                            ptr
+                                          }else {
+                                            core::ptr::null_mut()
+                                          };
+                                          for (i, e) in vec15.into_iter().enumerate() {
+                                            let base = result15 as i32 + (i as i32) * 16;
+                                            {
+                                              let AnimationCondition{ parameter:parameter13, comparison:comparison13, value:value13, } = e;
+                                              let vec14 = parameter13;
+                                              let ptr14 = vec14.as_ptr() as i32;
+                                              let len14 = vec14.len() as i32;
+                                              *((base + 4) as *mut i32) = len14;
+                                              *((base + 0) as *mut i32) = ptr14;
+                                              *((base + 8) as *mut u8) = (comparison13.clone() as i32) as u8;
+                                              *((base + 12) as *mut f32) = wit_bindgen::rt::as_f32(value13);
+                                              
+                                            }}
+                                            *((base + 24) as *mut i32) = len15;
+                                            *((base + 20) as *mut i32) = result15 as i32;
+                                            *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(duration10);
+                                            cleanup_list.extend_from_slice(&[(result15, layout15),]);
+                                            
+                                          }}
+                                          let vec17 = initial_state1;
+                                          let ptr17 = vec17.as_ptr() as i32;
+                                          let len17 = vec17.len() as i32;
+                                          
+                                          #[link(wasm_import_module = "entity")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "set-animation-state-machine")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "entity_set-animation-state-machine")]
+                                            fn wit_import(
+                                            _: i64, _: i64, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, );
+                                          }
+                                          wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), result9 as i32, len9, result16 as i32, len16, ptr17, len17, match apply_base_pose1 { true => 1, false => 0 });
+                                          if layout9.size() != 0 {
+                                            alloc::dealloc(result9, layout9);
+                                          }
+                                          if layout16.size() != 0 {
+                                            alloc::dealloc(result16, layout16);
+                                          }
+                                          for (ptr, layout) in cleanup_list {
+                                            
+                                            if layout.size() != 0 {
+                                              
+                                              alloc::dealloc(ptr, layout);
+                                              
+                                            }
+                                            
+                                          }
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      pub fn set_animation_parameter(entity: EntityId,name: &str,value: f32,){
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                        unsafe {
+                                          let super::types::EntityId{ id0:id00, id1:id10, } = entity;
+                                          let vec1 = name;
+                                          let ptr1 = vec1.as_ptr() as i32;
+                                          let len1 = vec1.len() as i32;
+                                          
+                                          #[link(wasm_import_module = "entity")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "set-animation-parameter")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "entity_set-animation-parameter")]
+                                            fn wit_import(
+                                            _: i64, _: i64, _: i32, _: i32, _: f32, );
+                                          }
+                                          wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), ptr1, len1, wit_bindgen::rt::as_f32(value));
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      pub fn get_animation_state(entity: EntityId,) -> Option<wit_bindgen::rt::string::String>{
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                        unsafe {
+                                          
+                                          #[repr(align(4))]
+                                          struct RetArea([u8; 12]);
+                                          let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                          let super::types::EntityId{ id0:id00, id1:id10, } = entity;
+                                          let ptr1 = ret_area.as_mut_ptr() as i32;
+                                          #[link(wasm_import_module = "entity")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "get-animation-state")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "entity_get-animation-state")]
+                                            fn wit_import(
+                                            _: i64, _: i64, _: i32, );
+                                          }
+                                          wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), ptr1);
+                                          match i32::from(*((ptr1 + 0) as *const u8)) {
+                                            0 => None,
+                                            1 => Some({
+                                              let len2 = *((ptr1 + 8) as *const i32) as usize;
+                                              
+                                              {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((ptr1 + 4) as *const i32) as *mut _, len2, len2))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((ptr1 + 4) as *const i32) as *mut _, len2, len2)).unwrap()}}
+                                            }),
+                                            #[cfg(not(debug_assertions))]
+                                            _ => core::hint::unreachable_unchecked(),
+                                            #[cfg(debug_assertions)]
+                                            _ => panic!("invalid enum discriminant"),
+                                          }
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      pub fn set_animation_root_motion(entity: EntityId,root_motion: Option<AnimationRootMotion<'_,>>,){
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                        unsafe {
+                                          let super::types::EntityId{ id0:id00, id1:id10, } = entity;
+                                          let (result3_0,result3_1,result3_2,result3_3,result3_4,result3_5,) = match root_motion {
+                                            Some(e) => {
+                                              let AnimationRootMotion{ bind_id:bind_id1, axes:axes1, } = e;
+                                              let vec2 = bind_id1;
+                                              let ptr2 = vec2.as_ptr() as i32;
+                                              let len2 = vec2.len() as i32;
+                                              let super::types::Vec3{ x:x3, y:y3, z:z3, } = axes1;
+                                              
+                                              (1i32, ptr2, len2, wit_bindgen::rt::as_f32(x3), wit_bindgen::rt::as_f32(y3), wit_bindgen::rt::as_f32(z3))
+                                            },
+                                            None => {
+                                              (0i32, 0i32, 0i32, 0.0f32, 0.0f32, 0.0f32)
+                                            },
+                                          };
+                                          #[link(wasm_import_module = "entity")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "set-animation-root-motion")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "entity_set-animation-root-motion")]
+                                            fn wit_import(
+                                            _: i64, _: i64, _: i32, _: i32, _: i32, _: f32, _: f32, _: f32, );
+                                          }
+                                          wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), result3_0, result3_1, result3_2, result3_3, result3_4, result3_5);
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      pub fn get_animation_root_motion_delta(entity: EntityId,) -> Option<Vec3>{
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                        unsafe {
+                                          
+                                          #[repr(align(4))]
+                                          struct RetArea([u8; 16]);
+                                          let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                          let super::types::EntityId{ id0:id00, id1:id10, } = entity;
+                                          let ptr1 = ret_area.as_mut_ptr() as i32;
+                                          #[link(wasm_import_module = "entity")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "get-animation-root-motion-delta")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "entity_get-animation-root-motion-delta")]
+                                            fn wit_import(
+                                            _: i64, _: i64, _: i32, );
+                                          }
+                                          wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), ptr1);
+                                          match i32::from(*((ptr1 + 0) as *const u8)) {
+                                            0 => None,
+                                            1 => Some(super::types::Vec3{x:*((ptr1 + 4) as *const f32), y:*((ptr1 + 8) as *const f32), z:*((ptr1 + 12) as *const f32), }),
+                                            #[cfg(not(debug_assertions))]
+                                            _ => core::hint::unreachable_unchecked(),
+                                            #[cfg(debug_assertions)]
+                                            _ => panic!("invalid enum discriminant"),
+                                          }
+                                        }
+                                      }
                               #[allow(clippy::all)]
                               pub fn in_area(position: Vec3,radius: f32,) -> wit_bindgen::rt::vec::Vec::<EntityId>{
                                 
@@ -7024,6 +7398,7 @@ pub mod component{
                               
                               pub type EntityId = super::types::EntityId;
                               pub type Vec3 = super::types::Vec3;
+                              pub type Quat = super::types::Quat;
                               #[repr(C)]
                               #[derive(Copy, Clone)]
                               pub struct CharacterCollision {
@@ -7036,6 +7411,51 @@ pub mod component{
                                   f.debug_struct("CharacterCollision").field("side", &self.side).field("up", &self.up).field("down", &self.down).finish()
                                 }
                               }
+                              #[repr(C)]
+                              #[derive(Copy, Clone)]
+                              pub struct CapsuleShape {
+                                pub radius: f32,
+                                pub half_height: f32,
+                              }
+                              impl core::fmt::Debug for CapsuleShape {
+                                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                                  f.debug_struct("CapsuleShape").field("radius", &self.radius).field("half-height", &self.half_height).finish()
+                                }
+                              }
+                              #[derive(Clone, Copy)]
+                              pub enum Shape{
+                                Sphere(f32),
+                                Capsule(CapsuleShape),
+                                Cuboid(Vec3),
+                              }
+                              impl core::fmt::Debug for Shape {
+                                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                                  match self {
+                                    Shape::Sphere(e) => {
+                                      f.debug_tuple("Shape::Sphere").field(e).finish()
+                                    }
+                                    Shape::Capsule(e) => {
+                                      f.debug_tuple("Shape::Capsule").field(e).finish()
+                                    }
+                                    Shape::Cuboid(e) => {
+                                      f.debug_tuple("Shape::Cuboid").field(e).finish()
+                                    }
+                                  }
+                                }
+                              }
+                              #[repr(C)]
+                              #[derive(Copy, Clone)]
+                              pub struct SweepHit {
+                                pub entity: EntityId,
+                                pub distance: f32,
+                                pub position: Vec3,
+                                pub normal: Vec3,
+                              }
+                              impl core::fmt::Debug for SweepHit {
+                                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                                  f.debug_struct("SweepHit").field("entity", &self.entity).field("distance", &self.distance).field("position", &self.position).field("normal", &self.normal).finish()
+                                }
+                              }
                               #[allow(clippy::all)]
                               pub fn add_force(entity: EntityId,force: Vec3,){
                                 
@@ -7308,6 +7728,114 @@ pub mod component{
                                 }
                               }
                               #[allow(clippy::all)]
+                              pub fn raycast_filtered(origin: Vec3,direction: Vec3,max_distance: f32,layers: u32,) -> wit_bindgen::rt::vec::Vec::<(EntityId,f32,)>{
+                                
+                                #[allow(unused_imports)]
+                                use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                unsafe {
+                                  
+                                  #[repr(align(4))]
+                                  struct RetArea([u8; 8]);
+                                  let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                  let super::types::Vec3{ x:x0, y:y0, z:z0, } = origin;
+                                  let super::types::Vec3{ x:x1, y:y1, z:z1, } = direction;
+                                  let ptr2 = ret_area.as_mut_ptr() as i32;
+                                  #[link(wasm_import_module = "server-physics")]
+                                  extern "C" {
+                                    #[cfg_attr(target_arch = "wasm32", link_name = "raycast-filtered")]
+                                    #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-physics_raycast-filtered")]
+                                    fn wit_import(
+                                    _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: i32, _: i32, );
+                                  }
+                                  wit_import(wit_bindgen::rt::as_f32(x0), wit_bindgen::rt::as_f32(y0), wit_bindgen::rt::as_f32(z0), wit_bindgen::rt::as_f32(x1), wit_bindgen::rt::as_f32(y1), wit_bindgen::rt::as_f32(z1), wit_bindgen::rt::as_f32(max_distance), wit_bindgen::rt::as_i32(layers), ptr2);
+                                  let len3 = *((ptr2 + 4) as *const i32) as usize;
+                                  Vec::from_raw_parts(*((ptr2 + 0) as *const i32) as *mut _, len3, len3)
+                                }
+                              }
+                              #[allow(clippy::all)]
+                              pub fn sweep(shape: Shape,position: Vec3,rotation: Quat,direction: Vec3,max_distance: f32,layers: u32,) -> wit_bindgen::rt::vec::Vec::<SweepHit>{
+                                
+                                #[allow(unused_imports)]
+                                use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                unsafe {
+                                  
+                                  #[repr(align(4))]
+                                  struct RetArea([u8; 8]);
+                                  let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                  let (result2_0,result2_1,result2_2,result2_3,) = match shape {
+                                    Shape::Sphere(e) => {
+                                      
+                                      (0i32, wit_bindgen::rt::as_f32(e), 0.0f32, 0.0f32)
+                                    },
+                                    Shape::Capsule(e) => {
+                                      let CapsuleShape{ radius:radius0, half_height:half_height0, } = e;
+                                      
+                                      (1i32, wit_bindgen::rt::as_f32(radius0), wit_bindgen::rt::as_f32(half_height0), 0.0f32)
+                                    },
+                                    Shape::Cuboid(e) => {
+                                      let super::types::Vec3{ x:x1, y:y1, z:z1, } = e;
+                                      
+                                      (2i32, wit_bindgen::rt::as_f32(x1), wit_bindgen::rt::as_f32(y1), wit_bindgen::rt::as_f32(z1))
+                                    },
+                                  };
+                                  let super::types::Vec3{ x:x3, y:y3, z:z3, } = position;
+                                  let super::types::Quat{ x:x4, y:y4, z:z4, w:w4, } = rotation;
+                                  let super::types::Vec3{ x:x5, y:y5, z:z5, } = direction;
+                                  let ptr6 = ret_area.as_mut_ptr() as i32;
+                                  #[link(wasm_import_module = "server-physics")]
+                                  extern "C" {
+                                    #[cfg_attr(target_arch = "wasm32", link_name = "sweep")]
+                                    #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-physics_sweep")]
+                                    fn wit_import(
+                                    _: i32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: i32, _: i32, );
+                                  }
+                                  wit_import(result2_0, result2_1, result2_2, result2_3, wit_bindgen::rt::as_f32(x3), wit_bindgen::rt::as_f32(y3), wit_bindgen::rt::as_f32(z3), wit_bindgen::rt::as_f32(x4), wit_bindgen::rt::as_f32(y4), wit_bindgen::rt::as_f32(z4), wit_bindgen::rt::as_f32(w4), wit_bindgen::rt::as_f32(x5), wit_bindgen::rt::as_f32(y5), wit_bindgen::rt::as_f32(z5), wit_bindgen::rt::as_f32(max_distance), wit_bindgen::rt::as_i32(layers), ptr6);
+                                  let len7 = *((ptr6 + 4) as *const i32) as usize;
+                                  Vec::from_raw_parts(*((ptr6 + 0) as *const i32) as *mut _, len7, len7)
+                                }
+                              }
+                              #[allow(clippy::all)]
+                              pub fn overlap(shape: Shape,position: Vec3,rotation: Quat,layers: u32,) -> wit_bindgen::rt::vec::Vec::<EntityId>{
+                                
+                                #[allow(unused_imports)]
+                                use wit_bindgen::rt::{alloc, vec::Vec, string::String};
+                                unsafe {
+                                  
+                                  #[repr(align(4))]
+                                  struct RetArea([u8; 8]);
+                                  let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                  let (result2_0,result2_1,result2_2,result2_3,) = match shape {
+                                    Shape::Sphere(e) => {
+                                      
+                                      (0i32, wit_bindgen::rt::as_f32(e), 0.0f32, 0.0f32)
+                                    },
+                                    Shape::Capsule(e) => {
+                                      let CapsuleShape{ radius:radius0, half_height:half_height0, } = e;
+                                      
+                                      (1i32, wit_bindgen::rt::as_f32(radius0), wit_bindgen::rt::as_f32(half_height0), 0.0f32)
+                                    },
+                                    Shape::Cuboid(e) => {
+                                      let super::types::Vec3{ x:x1, y:y1, z:z1, } = e;
+                                      
+                                      (2i32, wit_bindgen::rt::as_f32(x1), wit_bindgen::rt::as_f32(y1), wit_bindgen::rt::as_f32(z1))
+                                    },
+                                  };
+                                  let super::types::Vec3{ x:x3, y:y3, z:z3, } = position;
+                                  let super::types::Quat{ x:x4, y:y4, z:z4, w:w4, } = rotation;
+                                  let ptr5 = ret_area.as_mut_ptr() as i32;
+                                  #[link(wasm_import_module = "server-physics")]
+                                  extern "C" {
+                                    #[cfg_attr(target_arch = "wasm32", link_name = "overlap")]
+                                    #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-physics_overlap")]
+                                    fn wit_import(
+                                    _: i32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: i32, _: i32, );
+                                  }
+                                  wit_import(result2_0, result2_1, result2_2, result2_3, wit_bindgen::rt::as_f32(x3), wit_bindgen::rt::as_f32(y3), wit_bindgen::rt::as_f32(z3), wit_bindgen::rt::as_f32(x4), wit_bindgen::rt::as_f32(y4), wit_bindgen::rt::as_f32(z4), wit_bindgen::rt::as_f32(w4), wit_bindgen::rt::as_i32(layers), ptr5);
+                                  let len6 = *((ptr5 + 4) as *const i32) as usize;
+                                  Vec::from_raw_parts(*((ptr5 + 0) as *const i32) as *mut _, len6, len6)
+                                }
+                              }
+                              #[allow(clippy::all)]
                               pub fn move_character(entity: EntityId,displacement: Vec3,min_dist: f32,elapsed_time: f32,) -> CharacterCollision{
                                 
                                 #[allow(unused_imports)]
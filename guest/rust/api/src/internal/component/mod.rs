@@ -4,7 +4,7 @@ use std::{collections::HashMap, marker::PhantomData};
 pub(crate) mod query;
 pub(crate) mod traits;
 
-pub use traits::{get_component as __internal_get_component, SupportedValue};
+pub use traits::{get_component as __internal_get_component, Structured, SupportedValue};
 
 /// Implemented by all [Component]s.
 pub trait UntypedComponent {
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::{
     global::{EntityId, Mat4, Quat, Vec2, Vec3, Vec4},
     internal::{
@@ -5,6 +7,7 @@ use crate::{
         conversion::{FromBindgen, IntoBindgen},
         wit,
     },
+    message::MessageSerde,
 };
 use ambient_shared_types::primitive_component_definitions;
 use glam::{UVec2, UVec3, UVec4};
@@ -70,3 +73,27 @@ macro_rules! define_component_types {
 }
 
 primitive_component_definitions!(define_component_types);
+
+/// Implemented by the map, enum and struct types generated from `ambient.toml`, so that they can be used as values in components.
+///
+/// These values are stored in their serialized form, and are only deserialized by the guest.
+pub trait Structured: MessageSerde {}
+impl<T: MessageSerde> Structured for BTreeMap<String, T> {}
+
+impl<T: Structured> SupportedValue for T {
+    fn from_result(result: wit::component::Value) -> Option<Self> {
+        match result {
+            wit::component::Value::TypeStructured(v) => {
+                T::deserialize_message_part(&mut v.as_slice()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    fn into_result(self) -> wit::component::Value {
+        let mut output = vec![];
+        self.serialize_message_part(&mut output)
+            .expect("structured value could not be serialized");
+        wit::component::Value::TypeStructured(output)
+    }
+}
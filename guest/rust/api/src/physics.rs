@@ -1,5 +1,5 @@
 use crate::{
-    global::{EntityId, Vec3},
+    global::{EntityId, Quat, Vec3},
     internal::{
         conversion::{FromBindgen, IntoBindgen},
         wit,
@@ -166,6 +166,128 @@ fn raycast_result_to_hit(
     }
 }
 
+/// A collision layer mask that matches every layer. See [collision_layers](crate::components::core::physics::collision_layers).
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+/// Casts a ray from `origin` in `direction`, and returns the [RaycastHit]s within `max_distance` of the `origin`,
+/// sorted by distance.
+///
+/// Only entities in at least one of the `layers` (a bitmask) are hit. Use [ALL_LAYERS] to hit every entity.
+///
+/// `direction` must be normalized.
+pub fn raycast_filtered(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    layers: u32,
+) -> Vec<RaycastHit> {
+    wit::server_physics::raycast_filtered(
+        origin.into_bindgen(),
+        direction.into_bindgen(),
+        max_distance,
+        layers,
+    )
+    .into_iter()
+    .map(|(entity, distance)| raycast_result_to_hit(origin, direction, entity, distance))
+    .collect()
+}
+
+/// The shape used by [sweep] and [overlap].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    /// A sphere with the given `radius`.
+    Sphere {
+        /// The radius of the sphere.
+        radius: f32,
+    },
+    /// A capsule that extends along its Z axis.
+    Capsule {
+        /// The radius of the capsule.
+        radius: f32,
+        /// The distance from the center of the capsule to the center of either of its hemispheres.
+        half_height: f32,
+    },
+    /// A box of the given `size`.
+    Cuboid {
+        /// The size of the box along each axis.
+        size: Vec3,
+    },
+}
+impl IntoBindgen for Shape {
+    type Item = wit::server_physics::Shape;
+    fn into_bindgen(self) -> Self::Item {
+        match self {
+            Shape::Sphere { radius } => wit::server_physics::Shape::Sphere(radius),
+            Shape::Capsule {
+                radius,
+                half_height,
+            } => wit::server_physics::Shape::Capsule(wit::server_physics::CapsuleShape {
+                radius,
+                half_height,
+            }),
+            Shape::Cuboid { size } => wit::server_physics::Shape::Cuboid(size.into_bindgen()),
+        }
+    }
+}
+
+/// Where a [sweep] hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    /// The entity that was hit.
+    pub entity: EntityId,
+    /// The distance the shape travelled before it hit.
+    pub distance: f32,
+    /// The position of the contact.
+    pub position: Vec3,
+    /// The normal of the surface that was hit at the contact.
+    pub normal: Vec3,
+}
+
+/// Moves `shape` from `position`, rotated by `rotation`, in `direction`, and returns the [SweepHit]s within
+/// `max_distance`, sorted by distance.
+///
+/// Only entities in at least one of the `layers` (a bitmask) are hit. Use [ALL_LAYERS] to hit every entity.
+///
+/// `direction` must be normalized.
+pub fn sweep(
+    shape: Shape,
+    position: Vec3,
+    rotation: Quat,
+    direction: Vec3,
+    max_distance: f32,
+    layers: u32,
+) -> Vec<SweepHit> {
+    wit::server_physics::sweep(
+        shape.into_bindgen(),
+        position.into_bindgen(),
+        rotation.into_bindgen(),
+        direction.into_bindgen(),
+        max_distance,
+        layers,
+    )
+    .into_iter()
+    .map(|hit| SweepHit {
+        entity: hit.entity.from_bindgen(),
+        distance: hit.distance,
+        position: hit.position.from_bindgen(),
+        normal: hit.normal.from_bindgen(),
+    })
+    .collect()
+}
+
+/// Returns the entities that overlap `shape` at `position`, rotated by `rotation`.
+///
+/// Only entities in at least one of the `layers` (a bitmask) are returned. Use [ALL_LAYERS] to return every entity.
+pub fn overlap(shape: Shape, position: Vec3, rotation: Quat, layers: u32) -> Vec<EntityId> {
+    wit::server_physics::overlap(
+        shape.into_bindgen(),
+        position.into_bindgen(),
+        rotation.into_bindgen(),
+        layers,
+    )
+    .from_bindgen()
+}
+
 /// Collision results when using [move_character].
 pub struct CharacterCollision {
     /// Side
@@ -3,7 +3,7 @@ use ambient_api::{
         camera::aspect_ratio_from_window, prefab::prefab_from_url, primitives::quad,
     },
     concepts::{make_perspective_infinite_reverse_camera, make_transformable},
-    entity::{AnimationAction, AnimationController},
+    entity::{AnimationAction, AnimationBlendMode, AnimationController},
     prelude::*,
 };
 use ambient_ui_components::prelude::*;
@@ -54,11 +54,15 @@ fn App(hooks: &mut Hooks, unit: EntityId) -> Element {
                         clip_url: &asset::url(START.1).unwrap(),
                         looping: true,
                         weight: 1. - *weight,
+                        mask: &[],
+                        blend_mode: AnimationBlendMode::Blend,
                     },
                     AnimationAction {
                         clip_url: &asset::url(END.1).unwrap(),
                         looping: true,
                         weight: *weight,
+                        mask: &[],
+                        blend_mode: AnimationBlendMode::Blend,
                     },
                 ],
                 apply_base_pose: false,
@@ -36,13 +36,16 @@ impl PxSceneDesc {
             physx_sys::enable_custom_filter_shader(&mut self.0, shader, call_default_filter_shader_first as u32);
         }
     }
-    pub fn set_simulation_event_callbacks<C: FnMut(&PxContactPairHeader)>(&mut self, callbacks: PxSimulationEventCallback<C>) {
+    pub fn set_simulation_event_callbacks<C: FnMut(&PxContactPairHeader), T: FnMut(&[PxTriggerPair])>(
+        &mut self,
+        callbacks: PxSimulationEventCallback<C, T>,
+    ) {
         unsafe {
             unsafe extern "C" fn collision_callback_trampoline<C: FnMut(&PxContactPairHeader)>(
                 user_data: *mut std::ffi::c_void,
                 pair_header: *const physx_sys::PxContactPairHeader,
-                _pairs: *const physx_sys::PxContactPair,
-                _nb_pairs: u32,
+                pairs: *const physx_sys::PxContactPair,
+                nb_pairs: u32,
             ) {
                 let mut cb: Box<C> = Box::from_raw(user_data as _);
                 let pair_header_flags = PxContactPairHeaderFlag::from_bits((*pair_header).flags.mBits).unwrap();
@@ -59,14 +62,29 @@ impl PxSceneDesc {
                             PxRigidActorRef::from_ptr((*pair_header).actors[1])
                         },
                     ],
+                    pairs: std::slice::from_raw_parts(pairs, nb_pairs as usize).iter().map(PxContactPair::from_px).collect(),
                 });
                 Box::into_raw(cb);
             }
+            unsafe extern "C" fn trigger_callback_trampoline<T: FnMut(&[PxTriggerPair])>(
+                user_data: *mut std::ffi::c_void,
+                pairs: *const physx_sys::PxTriggerPair,
+                count: u32,
+            ) {
+                let mut cb: Box<T> = Box::from_raw(user_data as _);
+                let pairs = std::slice::from_raw_parts(pairs, count as usize).iter().map(PxTriggerPair::from_px).collect::<Vec<_>>();
+                cb(&pairs);
+                Box::into_raw(cb);
+            }
             let mut cbs = physx_sys::SimulationEventCallbackInfo { ..Default::default() };
             if let Some(cb) = callbacks.collision_callback {
                 cbs.collision_callback = Some(collision_callback_trampoline::<C>);
                 cbs.collision_user_data = Box::into_raw(cb) as _;
             }
+            if let Some(cb) = callbacks.trigger_callback {
+                cbs.trigger_callback = Some(trigger_callback_trampoline::<T>);
+                cbs.trigger_user_data = Box::into_raw(cb) as _;
+            }
             self.0.simulationEventCallback = physx_sys::create_simulation_event_callbacks(&cbs);
         }
     }
@@ -85,10 +103,83 @@ impl PxSceneDesc {
 
 pub struct PxContactPairHeader {
     pub actors: [Option<PxRigidActorRef>; 2],
+    pub pairs: Vec<PxContactPair>,
+}
+
+pub struct PxContactPair {
+    /// The shapes in contact. A shape is `None` if it has been removed from the scene
+    pub shapes: [Option<PxShape>; 2],
+    pub events: PxPairFlag,
+    /// Only available if the pair was reported with [`PxPairFlag::NOTIFY_CONTACT_POINTS`]
+    pub points: Vec<PxContactPairPoint>,
+}
+impl PxContactPair {
+    pub(crate) fn from_px(pair: &physx_sys::PxContactPair) -> Self {
+        let flags = PxContactPairFlag::from_bits_truncate(pair.flags.mBits);
+        let points = unsafe {
+            let mut points = (0..pair.contactCount).map(|_| std::mem::zeroed()).collect::<Vec<physx_sys::PxContactPairPoint>>();
+            let count = physx_sys::PxContactPair_extractContacts(pair, points.as_mut_ptr(), points.len() as u32);
+            points.truncate(count as usize);
+            points
+        };
+        Self {
+            shapes: [
+                if flags.contains(PxContactPairFlag::REMOVED_SHAPE_0) { None } else { Some(PxShape::from_ptr(pair.shapes[0])) },
+                if flags.contains(PxContactPairFlag::REMOVED_SHAPE_1) { None } else { Some(PxShape::from_ptr(pair.shapes[1])) },
+            ],
+            events: PxPairFlag::from_bits_truncate(pair.events.mBits),
+            points: points
+                .iter()
+                .map(|point| PxContactPairPoint {
+                    position: to_glam_vec3(&point.position),
+                    separation: point.separation,
+                    normal: to_glam_vec3(&point.normal),
+                    impulse: to_glam_vec3(&point.impulse),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PxContactPairPoint {
+    pub position: Vec3,
+    pub separation: f32,
+    /// Points from the second shape to the first shape
+    pub normal: Vec3,
+    pub impulse: Vec3,
+}
+
+pub struct PxTriggerPair {
+    /// `None` if the shape has been removed from the scene
+    pub trigger_shape: Option<PxShape>,
+    /// `None` if the shape has been removed from the scene
+    pub other_shape: Option<PxShape>,
+    /// Either [`PxPairFlag::NOTIFY_TOUCH_FOUND`] or [`PxPairFlag::NOTIFY_TOUCH_LOST`]
+    pub status: PxPairFlag,
+}
+impl PxTriggerPair {
+    pub(crate) fn from_px(pair: &physx_sys::PxTriggerPair) -> Self {
+        let flags = PxTriggerPairFlag::from_bits_truncate(pair.flags.mBits);
+        Self {
+            trigger_shape: if flags.contains(PxTriggerPairFlag::REMOVED_SHAPE_TRIGGER) {
+                None
+            } else {
+                Some(PxShape::from_ptr(pair.triggerShape))
+            },
+            other_shape: if flags.contains(PxTriggerPairFlag::REMOVED_SHAPE_OTHER) {
+                None
+            } else {
+                Some(PxShape::from_ptr(pair.otherShape))
+            },
+            status: PxPairFlag::from_bits_truncate(pair.status as u16),
+        }
+    }
 }
 
-pub struct PxSimulationEventCallback<C: FnMut(&PxContactPairHeader)> {
+pub struct PxSimulationEventCallback<C: FnMut(&PxContactPairHeader), T: FnMut(&[PxTriggerPair])> {
     pub collision_callback: Option<Box<C>>,
+    pub trigger_callback: Option<Box<T>>,
 }
 
 bitflags! {
@@ -98,6 +189,34 @@ bitflags! {
     }
 }
 
+bitflags! {
+    pub struct PxContactPairFlag: u16 {
+        const REMOVED_SHAPE_0 = physx_sys::PxContactPairFlag::eREMOVED_SHAPE_0 as u16;
+        const REMOVED_SHAPE_1 = physx_sys::PxContactPairFlag::eREMOVED_SHAPE_1 as u16;
+    }
+}
+
+bitflags! {
+    pub struct PxTriggerPairFlag: u8 {
+        const REMOVED_SHAPE_TRIGGER = physx_sys::PxTriggerPairFlag::eREMOVED_SHAPE_TRIGGER as u8;
+        const REMOVED_SHAPE_OTHER = physx_sys::PxTriggerPairFlag::eREMOVED_SHAPE_OTHER as u8;
+    }
+}
+
+bitflags! {
+    pub struct PxPairFlag: u16 {
+        const SOLVE_CONTACT = physx_sys::PxPairFlag::eSOLVE_CONTACT as u16;
+        const DETECT_DISCRETE_CONTACT = physx_sys::PxPairFlag::eDETECT_DISCRETE_CONTACT as u16;
+        const DETECT_CCD_CONTACT = physx_sys::PxPairFlag::eDETECT_CCD_CONTACT as u16;
+        const NOTIFY_TOUCH_FOUND = physx_sys::PxPairFlag::eNOTIFY_TOUCH_FOUND as u16;
+        const NOTIFY_TOUCH_PERSISTS = physx_sys::PxPairFlag::eNOTIFY_TOUCH_PERSISTS as u16;
+        const NOTIFY_TOUCH_LOST = physx_sys::PxPairFlag::eNOTIFY_TOUCH_LOST as u16;
+        const NOTIFY_CONTACT_POINTS = physx_sys::PxPairFlag::eNOTIFY_CONTACT_POINTS as u16;
+        const CONTACT_DEFAULT = physx_sys::PxPairFlag::eCONTACT_DEFAULT as u16;
+        const TRIGGER_DEFAULT = physx_sys::PxPairFlag::eTRIGGER_DEFAULT as u16;
+    }
+}
+
 bitflags! {
     pub struct PxSceneFlags: u32 {
         const ADAPTIVE_FORCE = physx_sys::PxSceneFlag::eADAPTIVE_FORCE;
@@ -190,6 +309,11 @@ impl PxSceneRef {
             physx_sys::PxScene_removeActor_mut(self.0, actor.as_actor().0, wake_on_lost_touch);
         }
     }
+    /// Marks the filtering of the actor's shapes as dirty, so that their existing pairs are filtered again
+    /// with their current filter data and flags
+    pub fn reset_filtering(&self, actor: &dyn AsPxActor) -> bool {
+        unsafe { physx_sys::PxScene_resetFiltering_mut(self.0, actor.as_actor().0) }
+    }
 
     pub fn add_aggregate(&self, aggregate: &PxAggregateRef) {
         unsafe {
@@ -357,6 +481,10 @@ impl PxQueryFilterData {
     pub fn set_flags(&mut self, flags: PxQueryFlag) {
         self.0.flags.mBits = flags.bits as u16;
     }
+    /// Only shapes whose query filter data shares a bit with this in any word are hit. All shapes are hit if this is zero
+    pub fn set_data(&mut self, data: [u32; 4]) {
+        self.0.data = physx_sys::PxFilterData { word0: data[0], word1: data[1], word2: data[2], word3: data[3] };
+    }
 }
 impl Default for PxQueryFilterData {
     fn default() -> Self {
@@ -103,6 +103,24 @@ impl PxShape {
     pub fn set_rest_offset(&self, offset: f32) {
         unsafe { physx_sys::PxShape_setRestOffset_mut(self.0, offset) }
     }
+    pub fn get_simulation_filter_data(&self) -> [u32; 4] {
+        let data = unsafe { physx_sys::PxShape_getSimulationFilterData(self.0) };
+        [data.word0, data.word1, data.word2, data.word3]
+    }
+    /// The filter data is passed to the scene's filter shader when a pair containing this shape is found
+    pub fn set_simulation_filter_data(&self, data: [u32; 4]) {
+        let data = physx_sys::PxFilterData { word0: data[0], word1: data[1], word2: data[2], word3: data[3] };
+        unsafe { physx_sys::PxShape_setSimulationFilterData_mut(self.0, &data as *const _) }
+    }
+    pub fn get_query_filter_data(&self) -> [u32; 4] {
+        let data = unsafe { physx_sys::PxShape_getQueryFilterData(self.0) };
+        [data.word0, data.word1, data.word2, data.word3]
+    }
+    /// The filter data is compared with the filter data of scene queries, to determine whether they can hit this shape
+    pub fn set_query_filter_data(&self, data: [u32; 4]) {
+        let data = physx_sys::PxFilterData { word0: data[0], word1: data[1], word2: data[2], word3: data[3] };
+        unsafe { physx_sys::PxShape_setQueryFilterData_mut(self.0, &data as *const _) }
+    }
 }
 impl AsPxBase for PxShape {
     fn as_base(&self) -> PxBaseRef {
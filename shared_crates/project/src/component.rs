@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 
+use crate::Identifier;
+
 #[derive(Deserialize, Clone, Debug, PartialEq)]
 pub struct Component {
     pub name: String,
@@ -16,6 +20,7 @@ pub struct Component {
 #[serde(untagged)]
 pub enum ComponentType {
     String(String),
+    Structured(StructuredType),
     ContainerType {
         #[serde(rename = "type")]
         #[serde(alias = "container_type")]
@@ -24,3 +29,18 @@ pub enum ComponentType {
         element_type: Option<String>,
     },
 }
+
+/// A type defined in the manifest itself. The Rust type for it is generated for guests,
+/// and is named after the component, message or field that it is declared in.
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum StructuredType {
+    /// A map from strings to values of `element_type`.
+    Map { element_type: Box<ComponentType> },
+    /// One of `variants`.
+    Enum { variants: Vec<Identifier> },
+    /// A record made up of `fields`, which can themselves be structured.
+    Struct {
+        fields: BTreeMap<Identifier, ComponentType>,
+    },
+}
@@ -10,11 +10,15 @@ pub struct Manifest {
     #[serde(default)]
     pub build: Build,
     #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
     pub components: BTreeMap<IdentifierPathBuf, NamespaceOr<Component>>,
     #[serde(default)]
     pub concepts: BTreeMap<IdentifierPathBuf, NamespaceOr<Concept>>,
     #[serde(default)]
     pub messages: BTreeMap<IdentifierPathBuf, NamespaceOr<Message>>,
+    #[serde(default)]
+    pub migrations: Vec<Migration>,
 }
 impl Manifest {
     pub fn parse(manifest: &str) -> Result<Self, toml::de::Error> {
@@ -61,6 +65,34 @@ impl Default for BuildRust {
     }
 }
 
+/// The resource limits of the project's WASM modules. Unset limits use the runtime's defaults.
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Limits {
+    /// How long a single call into a module can run for, in milliseconds
+    #[serde(rename = "execution-time-ms")]
+    pub execution_time_ms: Option<u64>,
+    /// The maximum size of a module's memory, in mebibytes
+    #[serde(rename = "memory-mb")]
+    pub memory_mb: Option<u64>,
+}
+
+/// Brings the `Store` components of a world saved with the previous schema version up to `version`.
+///
+/// The components are renamed first, then converted, and then given their defaults.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Migration {
+    pub version: u32,
+    /// The components to rename, from their old to their new ID. An entity can't have both.
+    #[serde(default)]
+    pub rename: BTreeMap<IdentifierPathBuf, IdentifierPathBuf>,
+    /// The components whose saved values should be converted to their current type.
+    #[serde(default)]
+    pub convert: Vec<IdentifierPathBuf>,
+    /// The values to add for components that are missing from a saved entity, or whose saved values can't be read as their current type.
+    #[serde(default)]
+    pub default: BTreeMap<IdentifierPathBuf, toml::Value>,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct Namespace {
     pub name: String,
@@ -110,8 +142,8 @@ mod tests {
     use std::collections::BTreeMap;
 
     use crate::{
-        Build, BuildRust, Component, ComponentType, Concept, Identifier, IdentifierPathBuf,
-        Manifest, Namespace, Project, Version, VersionSuffix,
+        Build, BuildRust, Component, ComponentType, Concept, Identifier, IdentifierPathBuf, Limits,
+        Manifest, Migration, Namespace, Project, StructuredType, Version, VersionSuffix,
     };
 
     #[test]
@@ -148,6 +180,7 @@ mod tests {
                         feature_multibuild: vec!["client".to_string(), "server".to_string()]
                     }
                 },
+                limits: Limits::default(),
                 components: BTreeMap::from_iter([(
                     IdentifierPathBuf::new("cell").unwrap(),
                     Component {
@@ -173,6 +206,7 @@ mod tests {
                     .into()
                 )]),
                 messages: BTreeMap::new(),
+                migrations: vec![],
             })
         )
     }
@@ -205,9 +239,11 @@ mod tests {
                         feature_multibuild: vec!["client".to_string()]
                     }
                 },
+                limits: Limits::default(),
                 components: BTreeMap::new(),
                 concepts: BTreeMap::new(),
                 messages: BTreeMap::new(),
+                migrations: vec![],
             })
         )
     }
@@ -243,6 +279,7 @@ mod tests {
                         feature_multibuild: vec!["client".to_string(), "server".to_string()]
                     }
                 },
+                limits: Limits::default(),
                 components: BTreeMap::from_iter([
                     (
                         IdentifierPathBuf::new("core").unwrap(),
@@ -274,6 +311,7 @@ mod tests {
                 ]),
                 concepts: BTreeMap::new(),
                 messages: BTreeMap::new(),
+                migrations: vec![],
             })
         )
     }
@@ -315,6 +353,7 @@ mod tests {
                         feature_multibuild: vec!["client".to_string(), "server".to_string()]
                     }
                 },
+                limits: Limits::default(),
                 components: BTreeMap::from_iter([
                     (
                         IdentifierPathBuf::new("core::transform::rotation").unwrap(),
@@ -408,7 +447,130 @@ mod tests {
                     )
                 ]),
                 messages: BTreeMap::new(),
+                migrations: vec![],
             })
         )
     }
+
+    #[test]
+    fn can_parse_structured_types() {
+        const TOML: &str = r#"
+        [project]
+        id = "my_project"
+        name = "My Project"
+        version = "0.0.1"
+
+        [components]
+        inventory = { name = "Inventory", description = "", type = { type = "Map", element_type = "U32" } }
+        state = { name = "State", description = "", type = { type = "Enum", variants = ["idle", "walking"] } }
+        stats = { name = "Stats", description = "", type = { type = "Struct", fields = { health = "F32", tags = { type = "Vec", element_type = "String" }, resistances = { type = "Map", element_type = "F32" } } } }
+        "#;
+
+        let manifest = Manifest::parse(TOML).unwrap();
+        let type_of = |id: &str| {
+            manifest.components[&IdentifierPathBuf::new(id).unwrap()]
+                .other()
+                .unwrap()
+                .type_
+                .clone()
+        };
+
+        assert_eq!(
+            type_of("inventory"),
+            ComponentType::Structured(StructuredType::Map {
+                element_type: Box::new(ComponentType::String("U32".to_string()))
+            })
+        );
+        assert_eq!(
+            type_of("state"),
+            ComponentType::Structured(StructuredType::Enum {
+                variants: vec![
+                    Identifier::new("idle").unwrap(),
+                    Identifier::new("walking").unwrap()
+                ]
+            })
+        );
+        assert_eq!(
+            type_of("stats"),
+            ComponentType::Structured(StructuredType::Struct {
+                fields: BTreeMap::from_iter([
+                    (
+                        Identifier::new("health").unwrap(),
+                        ComponentType::String("F32".to_string())
+                    ),
+                    (
+                        Identifier::new("resistances").unwrap(),
+                        ComponentType::Structured(StructuredType::Map {
+                            element_type: Box::new(ComponentType::String("F32".to_string()))
+                        })
+                    ),
+                    (
+                        Identifier::new("tags").unwrap(),
+                        ComponentType::ContainerType {
+                            type_: "Vec".to_string(),
+                            element_type: Some("String".to_string())
+                        }
+                    ),
+                ])
+            })
+        );
+    }
+
+    #[test]
+    fn can_parse_migrations() {
+        const TOML: &str = r#"
+        [project]
+        id = "my_project"
+        name = "My Project"
+        version = "0.0.1"
+
+        [[migrations]]
+        version = 1
+        rename = { health = "hit_points" }
+
+        [[migrations]]
+        version = 2
+        convert = ["hit_points"]
+        default = { hit_points = 100 }
+        "#;
+
+        let path = |id: &str| IdentifierPathBuf::new(id).unwrap();
+        assert_eq!(
+            Manifest::parse(TOML).unwrap().migrations,
+            vec![
+                Migration {
+                    version: 1,
+                    rename: BTreeMap::from_iter([(path("health"), path("hit_points"))]),
+                    convert: vec![],
+                    default: BTreeMap::new(),
+                },
+                Migration {
+                    version: 2,
+                    rename: BTreeMap::new(),
+                    convert: vec![path("hit_points")],
+                    default: BTreeMap::from_iter([(path("hit_points"), toml::Value::Integer(100))]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn can_parse_limits() {
+        const TOML: &str = r#"
+        [project]
+        id = "limited"
+        version = "0.0.1"
+
+        [limits]
+        execution-time-ms = 100
+        "#;
+
+        assert_eq!(
+            Manifest::parse(TOML).unwrap().limits,
+            Limits {
+                execution_time_ms: Some(100),
+                memory_mb: None,
+            }
+        );
+    }
 }
@@ -1,8 +1,11 @@
 use super::{
+    structured,
     tree::{Tree, TreeNode, TreeNodeInner, TreeNodeNamespace},
     util, Context,
 };
-use ambient_project::{Component, ComponentType, IdentifierPath, IdentifierPathBuf};
+use ambient_project::{
+    Component, ComponentType, IdentifierPath, IdentifierPathBuf, StructuredType,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
 use thiserror::Error;
@@ -20,7 +23,7 @@ pub fn tree_to_token_stream(
                 let namespace_path = IdentifierPath(ns.path.split_first().unwrap().1).to_string();
                 quote! {
                     use glam::{Vec2, Vec3, Vec4, UVec2, UVec3, UVec4, Mat4, Quat};
-                    use crate::{EntityId, Debuggable, Networked, Store, Resource, MaybeResource, Name, Description};
+                    use crate::{EntityId, Debuggable, Networked, Store, Resource, MaybeResource, OwnerOnly, Name, Description};
                     crate::components!(#namespace_path, {
                         #ts
                     });
@@ -91,7 +94,12 @@ fn to_token_stream(
         |name, component, context| {
             let name_ident: syn::Path = syn::parse_str(name)?;
             let name_uppercase_ident: syn::Path = syn::parse_str(&name.to_ascii_uppercase())?;
-            let component_ty = type_to_token_stream(&component.type_, context, false)?;
+            let type_name = util::to_pascal_case(name);
+            let component_ty = match (&component.type_, context) {
+                // The host does not know the layout of structured types, so it stores them encoded
+                (ComponentType::Structured(_), Context::Host) => quote! { crate::StructuredValue },
+                (ty, context) => type_to_token_stream(ty, Some(&type_name), context, false)?,
+            };
 
             let mut doc_comment = format!("**{}**", component.name);
 
@@ -127,11 +135,21 @@ fn to_token_stream(
                         #name_ident: #component_ty,
                     })
                 }
-                Context::Guest { .. } => Ok(quote! {
-                    static #name_uppercase_ident: Lazy< Component< #component_ty > > = Lazy::new(|| __internal_get_component(#id));
+                Context::Guest { .. } => {
+                    let type_definitions = structured::type_definitions(
+                        &component.type_,
+                        &type_name,
+                        &format!("the `{name}` component"),
+                        context,
+                    )?;
+
+                    Ok(quote! {
+                        #type_definitions
+                        static #name_uppercase_ident: Lazy< Component< #component_ty > > = Lazy::new(|| __internal_get_component(#id));
                     #[doc = #doc_comment]
-                    pub fn #name_ident() -> Component< #component_ty > { *#name_uppercase_ident }
-                }),
+                        pub fn #name_ident() -> Component< #component_ty > { *#name_uppercase_ident }
+                    })
+                }
             }
         },
     )
@@ -146,16 +164,41 @@ pub enum TypeTokenStreamError {
     InvalidContainerType,
     #[error("invalid element type")]
     InvalidElementType,
+    #[error("structured types can only be used in components and messages")]
+    UnsupportedStructuredType,
 }
 
+/// Converts `ty` to the Rust type that represents it. Structured types are referred to by
+/// `structured_name`; their definitions are generated by [structured::type_definitions].
 pub fn type_to_token_stream(
     ty: &ComponentType,
+    structured_name: Option<&str>,
     context: &Context,
     with_turbofish: bool,
 ) -> Result<proc_macro2::TokenStream, TypeTokenStreamError> {
     match ty {
         ComponentType::String(ty) => convert_primitive_type_to_rust_type(ty, context)
             .ok_or(TypeTokenStreamError::InvalidPrimitiveType),
+        ComponentType::Structured(structured) => {
+            let name = structured_name.ok_or(TypeTokenStreamError::UnsupportedStructuredType)?;
+            match structured {
+                StructuredType::Map { element_type } => {
+                    let element_name = structured::map_element_type_name(name);
+                    let element_ty =
+                        type_to_token_stream(element_type, Some(&element_name), context, false)?;
+
+                    if with_turbofish {
+                        Ok(quote! { std::collections::BTreeMap::<String, #element_ty> })
+                    } else {
+                        Ok(quote! { std::collections::BTreeMap<String, #element_ty> })
+                    }
+                }
+                StructuredType::Enum { .. } | StructuredType::Struct { .. } => {
+                    let ident = syn::Ident::new(name, proc_macro2::Span::call_site());
+                    Ok(quote! { #ident })
+                }
+            }
+        }
         ComponentType::ContainerType {
             type_,
             element_type,
@@ -244,7 +244,7 @@ fn generate_concept(
 
     let fn_ret = components
         .iter()
-        .map(|c| Ok(type_to_token_stream(&c.1.type_, context, false)?))
+        .map(|c| Ok(type_to_token_stream(&c.1.type_, None, context, false)?))
         .collect::<anyhow::Result<Vec<_>>>()?;
 
     Ok(quote! {
@@ -266,6 +266,9 @@ fn toml_value_to_tokens(
 ) -> anyhow::Result<TokenStream> {
     match ty {
         ComponentType::String(ty) => toml_value_to_tokens_primitive(path, ty, value),
+        ComponentType::Structured(_) => {
+            anyhow::bail!("component `{path}` has a structured type, which concepts do not support")
+        }
         ComponentType::ContainerType {
             type_,
             element_type,
@@ -406,7 +409,7 @@ pub fn generate_component_list_doc_comment(
             writeln!(
                 output,
                 "{padding}\"{component_path}\": {} = {},",
-                SemiprettyTokenStream(type_to_token_stream(&ty, context, false)?),
+                SemiprettyTokenStream(type_to_token_stream(&ty, None, context, false)?),
                 SemiprettyTokenStream(toml_value_to_tokens(component_path.as_path(), &ty, value)?)
             )?;
         }
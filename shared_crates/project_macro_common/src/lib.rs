@@ -11,6 +11,7 @@ mod tests;
 mod component;
 mod concept;
 mod message;
+mod structured;
 mod tree;
 mod util;
 
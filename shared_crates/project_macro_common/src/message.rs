@@ -1,9 +1,10 @@
 use super::{
     component::type_to_token_stream,
+    structured,
     tree::{Tree, TreeNode},
     util, Context,
 };
-use ambient_project::Message;
+use ambient_project::{ComponentType, Identifier, Message};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 
@@ -50,25 +51,33 @@ fn to_token_stream(
         |id, message, context| {
             let doc_comment = format!("**{}**: {}", message.name, message.description);
 
-            let struct_name = syn::Ident::new(
-                &id.split('_')
-                    .map(|segment| {
-                        let mut c = segment.chars();
-                        match c.next() {
-                            None => String::new(),
-                            Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
-                        }
-                    })
-                    .collect::<String>(),
-                Span::call_site(),
-            );
+            let struct_name_str = util::to_pascal_case(id);
+            let struct_name = syn::Ident::new(&struct_name_str, Span::call_site());
+
+            let type_definitions = message
+                .fields
+                .iter()
+                .map(|f| {
+                    structured::type_definitions(
+                        f.1,
+                        &structured::field_type_name(&struct_name_str, f.0),
+                        &format!("the `{}` field of [`{struct_name_str}`]", f.0),
+                        context,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let field_type = |(name, ty): (&Identifier, &ComponentType), with_turbofish| {
+                let type_name = structured::field_type_name(&struct_name_str, name);
+                type_to_token_stream(ty, Some(&type_name), context, with_turbofish)
+            };
 
             let fields = message
                 .fields
                 .iter()
                 .map(|f| {
                     let name = f.0;
-                    type_to_token_stream(f.1, context, false).map(|ty| {
+                    field_type(f, false).map(|ty| {
                         quote! { pub #name: #ty }
                     })
                 })
@@ -79,7 +88,7 @@ fn to_token_stream(
                 .iter()
                 .map(|f| {
                     let name = f.0;
-                    type_to_token_stream(f.1, context, false).map(|ty| {
+                    field_type(f, false).map(|ty| {
                         quote! { #name: impl Into<#ty> }
                     })
                 })
@@ -100,7 +109,7 @@ fn to_token_stream(
                 .iter()
                 .map(|f| {
                     let name = f.0;
-                    type_to_token_stream(f.1, context, true).map(|ty| {
+                    field_type(f, true).map(|ty| {
                         quote! { #name: #ty ::deserialize_message_part(&mut input)? }
                     })
                 })
@@ -113,6 +122,7 @@ fn to_token_stream(
             };
 
             Ok(quote! {
+                #(#type_definitions)*
                 #[derive(Clone, Debug)]
                 #[doc = #doc_comment]
                 pub struct #struct_name {
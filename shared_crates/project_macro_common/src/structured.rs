@@ -0,0 +1,127 @@
+use super::{component::type_to_token_stream, util, Context};
+use ambient_project::{ComponentType, StructuredType};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+/// The name of the type generated for `field` of the structured type `name`.
+pub fn field_type_name(name: &str, field: impl AsRef<str>) -> String {
+    format!("{name}{}", util::to_pascal_case(field.as_ref()))
+}
+
+/// The name of the type generated for the values of the map `name`.
+pub fn map_element_type_name(name: &str) -> String {
+    format!("{name}Value")
+}
+
+/// Generates the definitions for `ty` (named `name`) and any structured types nested within it.
+///
+/// `origin` describes where the type was declared, and is used for its documentation.
+/// Nothing is generated if `ty` is not structured.
+pub fn type_definitions(
+    ty: &ComponentType,
+    name: &str,
+    origin: &str,
+    context: &Context,
+) -> anyhow::Result<TokenStream> {
+    let ComponentType::Structured(structured) = ty else {
+        return Ok(TokenStream::new());
+    };
+
+    let ident = syn::Ident::new(name, Span::call_site());
+    let doc_comment = format!("The type of {origin}.");
+    let message_serde = match context {
+        Context::Host => quote! { ambient_project_rt::message_serde },
+        Context::Guest { api_path, .. } => quote! { #api_path::message },
+    };
+    // Guests store structured types in components in their encoded form
+    let structured_impl = match context {
+        Context::Host => quote! {},
+        Context::Guest { api_path, .. } => quote! { impl #api_path::ecs::Structured for #ident {} },
+    };
+
+    match structured {
+        StructuredType::Map { element_type } => type_definitions(
+            element_type,
+            &map_element_type_name(name),
+            &format!("the values of {origin}"),
+            context,
+        ),
+        StructuredType::Enum { variants } => {
+            anyhow::ensure!(
+                !variants.is_empty(),
+                "the enum for {origin} must have at least one variant"
+            );
+
+            let variants = variants
+                .iter()
+                .map(|v| syn::Ident::new(&util::to_pascal_case(v.as_ref()), Span::call_site()))
+                .collect::<Vec<_>>();
+            let (default_variant, other_variants) = variants.split_first().unwrap();
+            let indices = 0..variants.len() as u32;
+
+            Ok(quote! {
+                #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+                #[doc = #doc_comment]
+                pub enum #ident {
+                    #[default]
+                    #default_variant,
+                    #(#other_variants,)*
+                }
+                impl #message_serde::MessageSerde for #ident {
+                    fn serialize_message_part(&self, output: &mut Vec<u8>) -> Result<(), #message_serde::MessageSerdeError> {
+                        #message_serde::MessageSerde::serialize_message_part(&(*self as u32), output)
+                    }
+                    fn deserialize_message_part(input: &mut dyn std::io::Read) -> Result<Self, #message_serde::MessageSerdeError> {
+                        match <u32 as #message_serde::MessageSerde>::deserialize_message_part(input)? {
+                            #(#indices => Ok(Self::#variants),)*
+                            _ => Err(#message_serde::MessageSerdeError::InvalidValue),
+                        }
+                    }
+                }
+                #structured_impl
+            })
+        }
+        StructuredType::Struct { fields } => {
+            let nested_definitions = fields
+                .iter()
+                .map(|(field, ty)| {
+                    type_definitions(
+                        ty,
+                        &field_type_name(name, field),
+                        &format!("the `{field}` field of [`{name}`]"),
+                        context,
+                    )
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let field_names = fields.keys().collect::<Vec<_>>();
+            let field_types = fields
+                .iter()
+                .map(|(field, ty)| {
+                    type_to_token_stream(ty, Some(&field_type_name(name, field)), context, false)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(quote! {
+                #(#nested_definitions)*
+                #[derive(Clone, Debug, Default, PartialEq)]
+                #[doc = #doc_comment]
+                pub struct #ident {
+                    #(pub #field_names: #field_types,)*
+                }
+                impl #message_serde::MessageSerde for #ident {
+                    fn serialize_message_part(&self, output: &mut Vec<u8>) -> Result<(), #message_serde::MessageSerdeError> {
+                        #(#message_serde::MessageSerde::serialize_message_part(&self.#field_names, output)?;)*
+                        Ok(())
+                    }
+                    fn deserialize_message_part(input: &mut dyn std::io::Read) -> Result<Self, #message_serde::MessageSerdeError> {
+                        Ok(Self {
+                            #(#field_names: #message_serde::MessageSerde::deserialize_message_part(input)?,)*
+                        })
+                    }
+                }
+                #structured_impl
+            })
+        }
+    }
+}
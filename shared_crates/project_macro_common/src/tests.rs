@@ -1195,3 +1195,90 @@ fn can_generate_runtime_message() {
 
     assert_eq!(result.to_string(), expected_output.to_string());
 }
+
+#[test]
+fn can_generate_structured_types() {
+    let manifest = indoc::indoc! {r#"
+        [project]
+        id = "my_project"
+        name = "My Project"
+        version = "0.0.1"
+
+        [components.inventory]
+        name = "Inventory"
+        description = ""
+        type = { type = "Map", element_type = { type = "Struct", fields = { count = "U32", rarity = { type = "Enum", variants = ["common", "very_rare"] } } } }
+        "#};
+
+    let expected_output = quote::quote! {
+        const _PROJECT_MANIFEST: &'static str = include_str!("ambient.toml");
+        #[doc = r" Auto-generated component definitions. These come from `ambient.toml` in the root of the project."]
+        pub mod components {
+            use ambient_api2::{once_cell::sync::Lazy, ecs::{Component, __internal_get_component}};
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+            #[doc = "The type of the `rarity` field of [`InventoryValue`]."]
+            pub enum InventoryValueRarity {
+                #[default]
+                Common,
+                VeryRare,
+            }
+            impl ambient_api2::message::MessageSerde for InventoryValueRarity {
+                fn serialize_message_part(&self, output: &mut Vec<u8>) -> Result<(), ambient_api2::message::MessageSerdeError> {
+                    ambient_api2::message::MessageSerde::serialize_message_part(&(*self as u32), output)
+                }
+                fn deserialize_message_part(input: &mut dyn std::io::Read) -> Result<Self, ambient_api2::message::MessageSerdeError> {
+                    match <u32 as ambient_api2::message::MessageSerde>::deserialize_message_part(input)? {
+                        0u32 => Ok(Self::Common),
+                        1u32 => Ok(Self::VeryRare),
+                        _ => Err(ambient_api2::message::MessageSerdeError::InvalidValue),
+                    }
+                }
+            }
+            impl ambient_api2::ecs::Structured for InventoryValueRarity {}
+            #[derive(Clone, Debug, Default, PartialEq)]
+            #[doc = "The type of the values of the `inventory` component."]
+            pub struct InventoryValue {
+                pub count: u32,
+                pub rarity: InventoryValueRarity,
+            }
+            impl ambient_api2::message::MessageSerde for InventoryValue {
+                fn serialize_message_part(&self, output: &mut Vec<u8>) -> Result<(), ambient_api2::message::MessageSerdeError> {
+                    ambient_api2::message::MessageSerde::serialize_message_part(&self.count, output)?;
+                    ambient_api2::message::MessageSerde::serialize_message_part(&self.rarity, output)?;
+                    Ok(())
+                }
+                fn deserialize_message_part(input: &mut dyn std::io::Read) -> Result<Self, ambient_api2::message::MessageSerdeError> {
+                    Ok(Self {
+                        count: ambient_api2::message::MessageSerde::deserialize_message_part(input)?,
+                        rarity: ambient_api2::message::MessageSerde::deserialize_message_part(input)?,
+                    })
+                }
+            }
+            impl ambient_api2::ecs::Structured for InventoryValue {}
+            static INVENTORY: Lazy< Component< std::collections::BTreeMap<String, InventoryValue> > > = Lazy::new(|| __internal_get_component("my_project::inventory"));
+            #[doc = "**Inventory**"]
+            pub fn inventory() -> Component< std::collections::BTreeMap<String, InventoryValue> > {
+                *INVENTORY
+            }
+        }
+        #[doc = r" Auto-generated concept definitions. Concepts are collections of components that describe some form of gameplay concept."]
+        #[doc = r""]
+        #[doc = r" They do not have any runtime representation outside of the components that compose them."]
+        pub mod concepts {
+        }
+        #[doc = r" Auto-generated message definitions. Messages are used to communicate with the runtime, the other side of the network,"]
+        #[doc = r" and with other modules."]
+        pub mod messages {
+        }
+    };
+
+    let result = implementation(
+        (Some("ambient.toml".to_string()), manifest.to_string()),
+        guest_context(),
+        false,
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(result.to_string(), expected_output.to_string());
+}
@@ -94,3 +94,16 @@ pub fn tree_to_token_stream<
         TreeNodeInner::Other(other) => other_call(name, other, context),
     }
 }
+
+/// Converts a snake_case identifier to PascalCase.
+pub fn to_pascal_case(id: &str) -> String {
+    id.split('_')
+        .map(|segment| {
+            let mut c = segment.chars();
+            match c.next() {
+                None => String::new(),
+                Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
+            }
+        })
+        .collect()
+}
@@ -1,4 +1,4 @@
-use std::io::Read;
+use std::{collections::BTreeMap, io::Read};
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use glam::{Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
@@ -251,6 +251,22 @@ impl<T: MessageSerde> MessageSerde for Option<T> {
     }
 }
 
+impl<T: MessageSerde> MessageSerde for BTreeMap<String, T> {
+    fn serialize_message_part(&self, output: &mut Vec<u8>) -> Result<(), MessageSerdeError> {
+        output.write_u32::<BigEndian>(self.len().try_into()?)?;
+        for (key, value) in self {
+            key.serialize_message_part(output)?;
+            value.serialize_message_part(output)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_message_part(input: &mut dyn Read) -> Result<Self, MessageSerdeError> {
+        let length = input.read_u32::<BigEndian>()?;
+        (0..length).map(|_| Ok((String::deserialize_message_part(input)?, T::deserialize_message_part(input)?))).collect()
+    }
+}
+
 fn serialize_array<T: MessageSerde>(output: &mut Vec<u8>, data: &[T]) -> Result<(), MessageSerdeError> {
     output.write_u32::<BigEndian>(data.len().try_into()?)?;
     for value in data {